@@ -159,8 +159,25 @@ pub fn day23(c: &mut Criterion) {
     let mut group = c.benchmark_group("Day 23");
     let input = std::fs::read_to_string("../inputs/23").unwrap();
 
+    group.significance_level(0.1).sample_size(10);
     group.bench_function("part 1", |b| b.iter(|| day23::part1(black_box(&input))));
     group.bench_function("part 2", |b| b.iter(|| day23::part2(black_box(&input))));
+
+    let labels: Vec<u32> = input
+        .trim()
+        .chars()
+        .map(|ch| ch.to_digit(10).unwrap())
+        .collect();
+    for &(cups, pick_up, moves) in &[(1_000_000, 8, 1_000_000), (10_000_000, 3, 1_000_000)] {
+        let name = format!("{} cups, pick up {}, {} moves", cups, pick_up, moves);
+        group.bench_function(&name, |b| {
+            b.iter(|| {
+                let mut game = day23::CrabCups::new(black_box(&labels), cups, pick_up);
+                game.play(moves);
+                game.current()
+            })
+        });
+    }
     group.finish();
 }
 
@@ -1,3 +1,5 @@
+use aoc2020::parsing;
+use aoc2020::puzzle::PuzzleError;
 use std::fs::File;
 use std::io::Read;
 
@@ -44,14 +46,15 @@ fn find_triple_sum(values: &[i32], target: i32) -> Option<(i32, i32, i32)> {
     }
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut file = File::open("../inputs/01")?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
-    let expenses: Vec<i32> = contents
-        .split_whitespace()
-        .map(|str| str.parse().unwrap())
-        .collect();
+    let expenses: Vec<i32> = parsing::lines(&contents)
+        .iter()
+        .map(|line| line.parse(line.text))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(1))?;
 
     if let Some(part1) = part1(&expenses) {
         println!("part 1: {}", part1);
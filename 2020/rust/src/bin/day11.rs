@@ -1,10 +1,10 @@
 use aoc2020::day11::*;
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input: String = std::fs::read_to_string("../inputs/11")?;
 
-    println!("part 1: {}", part1(&input));
-    println!("part 2: {}", part2(&input));
+    println!("part 1: {}", try_part1(&input)?);
+    println!("part 2: {}", try_part2(&input)?);
 
     Ok(())
 }
@@ -1,8 +1,9 @@
 #![warn(clippy::pedantic)]
 
+use aoc2020::parsing::{self, SourceLine};
+use aoc2020::puzzle::PuzzleError;
 use std::fs::File;
 use std::io::Read;
-use std::str::FromStr;
 
 fn part1(password_entries: &[PasswordEntry]) -> usize {
     password_entries
@@ -41,34 +42,43 @@ impl PasswordEntry {
     }
 }
 
-impl FromStr for PasswordEntry {
-    type Err = ();
+impl PasswordEntry {
+    // Positions are 1-based and must fall inside the password.
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let fields = line.fields("{}-{} {}: {}")?;
+        let left: usize = line.parse(fields[0])?;
+        let right: usize = line.parse(fields[1])?;
+        let letter: char = line.parse(fields[2])?;
+        let password = fields[3];
+        let length = password.chars().count();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.splitn(2, ": ").collect();
-        let password: &str = parts[1];
-        let parts: Vec<&str> = parts[0].splitn(2, ' ').collect();
-        let letter: char = parts[1].chars().next().unwrap();
-        let numbers: Vec<usize> = parts[0]
-            .splitn(2, '-')
-            .map(|str| str.parse().unwrap())
-            .collect();
+        for (field, position) in [(fields[0], left), (fields[1], right)] {
+            if position == 0 || position > length {
+                return Err(line.error(
+                    field,
+                    format!("position {position} is outside the password"),
+                ));
+            }
+        }
 
         Ok(PasswordEntry {
             password: password.into(),
             letter,
-            left: numbers[0],
-            right: numbers[1],
+            left,
+            right,
         })
     }
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut file = File::open("../inputs/02")?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
-    let password_entries: Vec<PasswordEntry> =
-        contents.lines().map(|str| str.parse().unwrap()).collect();
+    let password_entries: Vec<PasswordEntry> = parsing::lines(&contents)
+        .iter()
+        .map(PasswordEntry::parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(2))?;
 
     println!("part 1: {}", part1(&password_entries));
     println!("part 2: {}", part2(&password_entries));
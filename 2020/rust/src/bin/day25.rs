@@ -1,9 +1,9 @@
 use aoc2020::day25::*;
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input: String = std::fs::read_to_string("../inputs/25")?;
 
-    println!("part 1: {}", part1(&input));
+    println!("part 1: {}", try_part1(&input)?);
 
     Ok(())
 }
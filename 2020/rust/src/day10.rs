@@ -1,8 +1,30 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 10;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let mut voltages: Vec<usize> = input.lines().map(|line| line.parse().unwrap()).collect();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't an adapter's joltage.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let mut voltages = parse(input)?;
     voltages.sort_unstable();
     let deltas: Vec<usize> = voltages
         .iter()
@@ -14,18 +36,28 @@ pub fn part1(input: &str) -> usize {
     let delta_threes: usize = deltas.iter().filter(|&delta| delta == &3).count();
     let delta_ones: usize = deltas.iter().filter(|&delta| delta == &1).count();
 
-    (delta_threes + 1) * (delta_ones + 1)
+    Ok((delta_threes + 1) * (delta_ones + 1))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut voltages: Vec<usize> = input.lines().map(|line| line.parse().unwrap()).collect();
+/// # Errors
+///
+/// Fails on a line that isn't an adapter's joltage.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut voltages = parse(input)?;
     voltages.sort_unstable();
     voltages.insert(0, 0);
 
     let mut cache: HashMap<usize, usize> = HashMap::new();
 
-    chain_count(&voltages, 0, &mut cache)
+    Ok(chain_count(&voltages, 0, &mut cache))
+}
+
+fn parse(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| line.parse(line.text))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 fn chain_count(voltages: &[usize], index: usize, cache: &mut HashMap<usize, usize>) -> usize {
@@ -1,3 +1,8 @@
+use crate::parsing::{self, ParseError};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 11;
+
 #[derive(Debug, PartialEq)]
 enum Cell {
     Floor,
@@ -256,49 +261,76 @@ impl Grid {
 }
 
 impl std::str::FromStr for Grid {
-    type Err = ();
+    type Err = ParseError;
 
-    fn from_str(string: &str) -> Result<Self, ()> {
+    fn from_str(string: &str) -> parsing::Result<Self> {
         use Cell::{EmptySeat, Floor, OccupiedSeat};
-        let data: Vec<Vec<Cell>> = string
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|cell| match cell {
-                        '.' => Floor,
-                        'L' => EmptySeat,
-                        '#' => OccupiedSeat,
-                        _ => panic!("unexpected input"),
-                    })
-                    .collect()
-            })
-            .collect();
+        let mut data: Vec<Vec<Cell>> = vec![];
+
+        for line in parsing::lines(string) {
+            let row = line
+                .text
+                .char_indices()
+                .map(|(index, cell)| match cell {
+                    '.' => Ok(Floor),
+                    'L' => Ok(EmptySeat),
+                    '#' => Ok(OccupiedSeat),
+                    _ => Err(line.error(&line.text[index..], format!("invalid cell {cell:?}"))),
+                })
+                .collect::<parsing::Result<Vec<Cell>>>()?;
+
+            if row.len() != data.first().map_or(row.len(), Vec::len) {
+                return Err(line.error(line.text, "rows differ in width"));
+            }
+            data.push(row);
+        }
+
         let rows: usize = data.len();
-        let cols: usize = data.get(0).unwrap().len();
+        let cols: usize = data.first().map_or(0, Vec::len);
 
         Ok(Grid { data, rows, cols })
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let mut grid: Grid = input.parse().unwrap();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a cell other than `.`, `L` or `#`, or rows of different widths.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let mut grid: Grid = input.parse().map_err(PuzzleError::on_day(DAY))?;
     let mut occupied_seat_count: usize = grid.occupied_seat_count();
 
     loop {
         grid = grid.step();
         let next_occupied_seat_count = grid.occupied_seat_count();
         if next_occupied_seat_count == occupied_seat_count {
-            break occupied_seat_count;
+            break Ok(occupied_seat_count);
         } else {
             occupied_seat_count = next_occupied_seat_count;
         }
     }
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut grid: Grid = input.parse().unwrap();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut grid: Grid = input.parse().map_err(PuzzleError::on_day(DAY))?;
     let mut occupied_seat_count: usize = grid.occupied_seat_count();
     let mut steps: usize = 0;
 
@@ -308,7 +340,7 @@ pub fn part2(input: &str) -> usize {
 
         let next_occupied_seat_count = grid.occupied_seat_count();
         if next_occupied_seat_count == occupied_seat_count || steps > 100 {
-            break occupied_seat_count;
+            break Ok(occupied_seat_count);
         } else {
             occupied_seat_count = next_occupied_seat_count;
         }
@@ -1,3 +1,8 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 12;
+
 #[derive(Debug)]
 enum Direction {
     North,
@@ -108,46 +113,84 @@ impl Ferry {
     }
 }
 
-impl std::str::FromStr for Instruction {
-    type Err = String;
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
+impl Instruction {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
         use Direction::{East, North, South, West};
         use Instruction::{Forward, Rotate, Shift};
-        match string.split_at(1) {
-            ("N", value) => Ok(Shift(North, value.parse().unwrap())),
-            ("S", value) => Ok(Shift(South, value.parse().unwrap())),
-            ("E", value) => Ok(Shift(East, value.parse().unwrap())),
-            ("W", value) => Ok(Shift(West, value.parse().unwrap())),
-            ("L", value) => Ok(Rotate(
-                360_isize.checked_sub(value.parse().unwrap()).unwrap(),
-            )),
-            ("R", value) => Ok(Rotate(value.parse().unwrap())),
-            ("F", value) => Ok(Forward(value.parse().unwrap())),
-            _ => Err(format!("invalid instruction: {:?}", string)),
+        let text = line.text.trim();
+        let split = text.chars().next().map_or(0, char::len_utf8);
+        let (action, value) = text.split_at(split);
+
+        match action {
+            "N" => Ok(Shift(North, line.parse(value)?)),
+            "S" => Ok(Shift(South, line.parse(value)?)),
+            "E" => Ok(Shift(East, line.parse(value)?)),
+            "W" => Ok(Shift(West, line.parse(value)?)),
+            "L" => Ok(Rotate(360 - Self::angle(line, value)?)),
+            "R" => Ok(Rotate(Self::angle(line, value)?)),
+            "F" => Ok(Forward(line.parse(value)?)),
+            _ => Err(line.error(text, format!("invalid action {action:?}"))),
+        }
+    }
+
+    // Turns are limited to the quarter turns the ferry can make.
+    fn angle(line: &SourceLine, value: &str) -> parsing::Result<isize> {
+        match line.parse(value)? {
+            angle @ (90 | 180 | 270) => Ok(angle),
+            angle => Err(line.error(value, format!("cannot turn {angle} degrees"))),
         }
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> isize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> isize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on an unknown action, a value that isn't a number, or a turn that
+/// isn't a multiple of 90 degrees.
+pub fn try_part1(input: &str) -> Result<isize, PuzzleError> {
     let mut ferry = Ferry::new(Version::One);
 
-    input
-        .lines()
-        .for_each(|line| ferry.execute(&line.parse().unwrap()));
+    for instruction in parse(input)? {
+        ferry.execute(&instruction);
+    }
 
-    ferry.distance()
+    Ok(ferry.distance())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> isize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<isize, PuzzleError> {
     let mut ferry = Ferry::new(Version::Two);
 
-    input
-        .lines()
-        .for_each(|line| ferry.execute(&line.parse().unwrap()));
+    for instruction in parse(input)? {
+        ferry.execute(&instruction);
+    }
+
+    Ok(ferry.distance())
+}
 
-    ferry.distance()
+fn parse(input: &str) -> Result<Vec<Instruction>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(Instruction::parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -163,4 +206,12 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2("F10\nN3\nF7\nR90\nF11\n"), 286)
     }
+
+    #[test]
+    fn only_quarter_turns() {
+        assert_eq!(
+            try_part2("F10\nL45\n").unwrap_err().to_string(),
+            "day 12, line 2: column 2, cannot turn 45 degrees"
+        );
+    }
 }
@@ -1,28 +1,40 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+const DAY: u8 = 14;
+const ADDRESS_SPACE: usize = 1 << 36;
+
 enum Instruction {
     SetMask(Mask),
     SetMemory(usize, usize),
 }
 
-impl FromStr for Instruction {
-    type Err = ();
+impl Instruction {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let (target, value_string) = line.split_once(line.text, " = ")?;
 
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = string.splitn(2, " = ").collect();
+        if target == "mask" {
+            let mask = value_string
+                .parse()
+                .map_err(|message: String| line.error(value_string, message))?;
 
-        match parts.as_slice() {
-            ["mask", mask] => Ok(Instruction::SetMask(mask.parse().unwrap())),
-            [memory_location, value_string] => {
-                let parts: Vec<&str> = memory_location.split(|ch| ch == '[' || ch == ']').collect();
-                let value: usize = value_string.parse().unwrap();
-                let location: usize = parts[1].parse().unwrap();
+            return Ok(Instruction::SetMask(mask));
+        }
 
-                Ok(Instruction::SetMemory(location, value))
-            }
-            _ => Err(()),
+        let location_string = line.strip_prefix(target, "mem[")?;
+        let location_string = location_string
+            .strip_suffix(']')
+            .ok_or_else(|| line.error(target, "expected `mem[<address>]`"))?;
+        let location: usize = line.parse(location_string)?;
+        let value: usize = line.parse(value_string)?;
+
+        if location >= ADDRESS_SPACE || value >= ADDRESS_SPACE {
+            return Err(line.error(target, "values are limited to 36 bits"));
         }
+
+        Ok(Instruction::SetMemory(location, value))
     }
 }
 
@@ -70,9 +82,13 @@ impl Mask {
 }
 
 impl FromStr for Mask {
-    type Err = ();
+    type Err = String;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.len() != 36 || !string.chars().all(|ch| "01X".contains(ch)) {
+            return Err(format!("expected 36 of `0`, `1` or `X`, found `{string}`"));
+        }
+
         let pattern = string.to_owned();
         let and_mask = usize::from_str_radix(&string.replace("X", "1"), 2).unwrap();
         let or_mask = usize::from_str_radix(&string.replace("X", "0"), 2).unwrap();
@@ -85,13 +101,36 @@ impl FromStr for Mask {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't `mask = <mask>` or `mem[<address>] = <value>`,
+/// or on an address or value wider than 36 bits.
+///
+/// # Panics
+///
+/// Never in practice: the starting mask is well-formed.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let mut memory: HashMap<usize, usize> = HashMap::new();
     let mut mask: Mask = "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX".parse().unwrap();
 
-    for line in input.lines() {
-        match line.parse::<Instruction>().unwrap() {
+    for instruction in parse(input)? {
+        match instruction {
             Instruction::SetMask(new_mask) => mask = new_mask,
             Instruction::SetMemory(location, value) => {
                 memory.insert(location, mask.mask(value));
@@ -99,18 +138,24 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    memory.values().sum()
+    Ok(memory.values().sum())
 }
 
 // combinations
 // 18 * 2**4 + 16 * 2**5 + 13 * 2**6 + 19 * 2**7 + 15 * 2**8 + 16 * 2**9 = 16096
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+///
+/// # Panics
+///
+/// Never in practice: the starting mask is well-formed.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut memory: HashMap<usize, usize> = HashMap::new();
     let mut mask: Mask = "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX".parse().unwrap();
 
-    for line in input.lines() {
-        match line.parse::<Instruction>().unwrap() {
+    for instruction in parse(input)? {
+        match instruction {
             Instruction::SetMask(new_mask) => mask = new_mask,
             Instruction::SetMemory(location, value) => {
                 for address in mask.addresses(location) {
@@ -120,7 +165,15 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    memory.values().sum()
+    Ok(memory.values().sum())
+}
+
+fn parse(input: &str) -> Result<Vec<Instruction>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(Instruction::parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
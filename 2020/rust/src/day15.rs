@@ -1,21 +1,44 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::convert::TryFrom;
+
+const DAY: u8 = 15;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> u32 {
-    number_at(input, 2020)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> u32 {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is one line of comma-separated numbers below the
+/// turn being asked for.
+pub fn try_part1(input: &str) -> Result<u32, PuzzleError> {
+    number_at(input, 2020)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<u32, PuzzleError> {
     number_at(input, 30_000_000)
 }
 
 #[allow(clippy::match_on_vec_items)]
 #[allow(clippy::cast_possible_truncation)]
-fn number_at(input: &str, index: u32) -> u32 {
-    let numbers: Vec<u32> = input
-        .trim()
-        .split(',')
-        .map(|c| c.parse().unwrap())
-        .collect();
+fn number_at(input: &str, index: u32) -> Result<u32, PuzzleError> {
+    let numbers = parse(input, index)?;
 
     let mut cache: Vec<Option<u32>> = vec![None; index as usize];
     for (index, &number) in numbers.iter().enumerate() {
@@ -36,7 +59,37 @@ fn number_at(input: &str, index: u32) -> u32 {
         count += 1;
     }
 
-    current
+    Ok(current)
+}
+
+// Every number spoken stays below the number of turns, which keeps the
+// cache in `number_at` the size of the game.
+fn parse(input: &str, turns: u32) -> Result<Vec<u32>, PuzzleError> {
+    let lines = parsing::lines(input.trim());
+    let [line] = &lines[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected one line of starting numbers",
+        ));
+    };
+
+    parse_numbers(line, turns).map_err(PuzzleError::on_day(DAY))
+}
+
+fn parse_numbers(line: &SourceLine, turns: u32) -> parsing::Result<Vec<u32>> {
+    let fields: Vec<&str> = line.text.split(',').collect();
+
+    if u32::try_from(fields.len()).map_or(true, |count| count > turns) {
+        return Err(line.error(line.text, format!("more than {turns} starting numbers")));
+    }
+
+    fields
+        .into_iter()
+        .map(|field| match line.parse(field)? {
+            number if number < turns => Ok(number),
+            number => Err(line.error(field, format!("{number} is not below {turns}"))),
+        })
+        .collect()
 }
 
 #[cfg(test)]
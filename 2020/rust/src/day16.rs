@@ -1,6 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::ops::Range;
-use std::str::FromStr;
+
+const DAY: u8 = 16;
 
 #[derive(Debug)]
 struct Rule {
@@ -14,22 +17,27 @@ impl Rule {
     }
 }
 
-impl FromStr for Rule {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let name_and_ranges: Vec<&str> = input.split(": ").collect();
-        let name = name_and_ranges[0].to_owned();
-        let ranges: Vec<Range<usize>> = name_and_ranges[1]
+impl Rule {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let (name, ranges) = line.key_value()?;
+        let ranges = ranges
             .split(" or ")
             .map(|range| {
-                let parts: Vec<usize> = range.split('-').map(|x| x.parse().unwrap()).collect();
-                Range {
-                    start: parts[0],
-                    end: parts[1] + 1,
-                }
+                let (start, end) = line.split_once(range, "-")?;
+                let start: usize = line.parse(start)?;
+                let end: usize = line.parse(end)?;
+
+                Ok(Range {
+                    start,
+                    end: end + 1,
+                })
             })
-            .collect();
-        Ok(Self { name, ranges })
+            .collect::<parsing::Result<_>>()?;
+
+        Ok(Self {
+            name: name.to_owned(),
+            ranges,
+        })
     }
 }
 
@@ -56,14 +64,19 @@ impl Ticket {
     }
 }
 
-impl FromStr for Ticket {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let values = input
+impl Ticket {
+    fn parse(line: &SourceLine, fields: usize) -> parsing::Result<Self> {
+        let values = line
+            .text
             .split(',')
-            .map(|value| value.parse().unwrap())
-            .collect();
-        Ok(Ticket(values))
+            .map(|value| line.parse(value))
+            .collect::<parsing::Result<Vec<usize>>>()?;
+
+        if values.len() == fields {
+            Ok(Ticket(values))
+        } else {
+            Err(line.error(line.text, format!("expected {fields} values, one per rule")))
+        }
     }
 }
 
@@ -74,20 +87,42 @@ struct Notes {
     nearby_tickets: Vec<Ticket>,
 }
 
-impl FromStr for Notes {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let groups: Vec<&str> = input.trim().split("\n\n").collect();
-        let rules: Vec<Rule> = groups[0]
-            .lines()
-            .map(|rule| rule.parse().unwrap())
-            .collect();
-        let my_ticket: Ticket = groups[1].lines().nth(1).unwrap().parse().unwrap();
-        let nearby_tickets: Vec<Ticket> = groups[2]
-            .lines()
-            .skip(1)
-            .map(|ticket| ticket.parse().unwrap())
-            .collect();
+impl Notes {
+    fn parse(input: &str) -> Result<Self, PuzzleError> {
+        let blocks = parsing::blocks(input);
+        let [rules, mine, nearby] = &blocks[..] else {
+            return Err(PuzzleError::new(
+                DAY,
+                "expected rules, your ticket and nearby tickets separated by blank lines",
+            ));
+        };
+
+        Self::parse_blocks(rules, mine, nearby).map_err(PuzzleError::on_day(DAY))
+    }
+
+    fn parse_blocks(
+        rules: &[SourceLine],
+        mine: &[SourceLine],
+        nearby: &[SourceLine],
+    ) -> parsing::Result<Self> {
+        let rules: Vec<Rule> = rules
+            .iter()
+            .map(Rule::parse)
+            .collect::<parsing::Result<_>>()?;
+        let fields = rules.len();
+        let my_ticket = match mine {
+            [header, ticket] if header.text == "your ticket:" => Ticket::parse(ticket, fields)?,
+            _ => return Err(mine[0].error(mine[0].text, "expected `your ticket:` and one ticket")),
+        };
+        let nearby_tickets = nearby
+            .split_first()
+            .filter(|(header, _)| header.text == "nearby tickets:")
+            .ok_or_else(|| nearby[0].error(nearby[0].text, "expected `nearby tickets:`"))?
+            .1
+            .iter()
+            .map(|ticket| Ticket::parse(ticket, fields))
+            .collect::<parsing::Result<_>>()?;
+
         Ok(Self {
             rules,
             my_ticket,
@@ -102,21 +137,43 @@ enum Mapping {
     Possibilities(Vec<usize>),
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let notes: Notes = input.parse().unwrap();
-    notes
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a malformed rule or ticket, a ticket with a value count other
+/// than the number of rules, or missing sections.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let notes = Notes::parse(input)?;
+    Ok(notes
         .nearby_tickets
         .iter()
         .map(|ticket| ticket.invalid_sum(&notes.rules))
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the valid tickets
+/// don't narrow every rule down to one field.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     use Mapping::{Definitely, Possibilities};
 
-    let notes: Notes = input.parse().unwrap();
+    let notes = Notes::parse(input)?;
     let rule_count: usize = notes.rules.len();
 
     let valid_tickets: Vec<&Ticket> = notes
@@ -149,14 +206,14 @@ pub fn part2(input: &str) -> usize {
     }
 
     loop {
+        let assigned = assigned_indexes.len();
+
         for (_rule_name, mapping) in mappings.iter_mut() {
             match mapping {
                 Definitely(_) => {}
                 Possibilities(possible_indexes) => {
                     possible_indexes.retain(|x| !assigned_indexes.contains(x));
-                    if possible_indexes.len() == 1 {
-                        let index = *possible_indexes.first().unwrap();
-
+                    if let [index] = possible_indexes[..] {
                         assigned_indexes.push(index);
                         *mapping = Definitely(index)
                     }
@@ -166,6 +223,11 @@ pub fn part2(input: &str) -> usize {
 
         if assigned_indexes.len() == mappings.len() {
             break;
+        } else if assigned_indexes.len() == assigned {
+            return Err(PuzzleError::new(
+                DAY,
+                "the rules don't narrow down to one field each",
+            ));
         }
     }
 
@@ -175,12 +237,12 @@ pub fn part2(input: &str) -> usize {
         if rule.starts_with("departure") {
             match mapping {
                 Definitely(index) => answer *= notes.my_ticket.at(index),
-                Possibilities(_) => panic!(),
+                Possibilities(_) => unreachable!("every rule was assigned a field"),
             }
         }
     }
 
-    answer
+    Ok(answer)
 }
 
 #[cfg(test)]
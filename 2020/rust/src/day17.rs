@@ -1,6 +1,10 @@
+use crate::parsing::{self, ParseError};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+const DAY: u8 = 17;
+
 type Location = (isize, isize, isize, isize);
 
 #[derive(Debug)]
@@ -175,17 +179,22 @@ enum Cell {
 }
 
 impl FromStr for Grid {
-    type Err = ();
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
+    type Err = ParseError;
+    fn from_str(string: &str) -> parsing::Result<Self> {
         let mut grid = Grid::new();
         let z: isize = 0;
         let w: isize = 0;
-        for (y, line) in string.trim().lines().enumerate() {
-            for (x, ch) in line.trim().chars().enumerate() {
+        for (y, line) in parsing::lines(string.trim()).into_iter().enumerate() {
+            let line = line.trim();
+            for (x, (index, ch)) in line.text.char_indices().enumerate() {
                 let cell = match ch {
                     '#' => Cell::Active,
                     '.' => Cell::Inactive,
-                    _ => panic!("invalid cell found"),
+                    _ => {
+                        return Err(
+                            line.error(&line.text[index..], format!("invalid cell {ch:?}"))
+                        )
+                    }
                 };
                 grid.insert((x as isize, y as isize, z, w), cell);
             }
@@ -194,37 +203,46 @@ impl FromStr for Grid {
     }
 }
 
-impl FromStr for Cell {
-    type Err = ();
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        match string {
-            "#" => Ok(Cell::Active),
-            "." => Ok(Cell::Inactive),
-            _ => Err(()),
-        }
-    }
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
+#[must_use]
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
-pub fn part1(input: &str) -> usize {
-    let mut grid: Grid = input.parse().unwrap();
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a cell other than `#` or `.`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let mut grid: Grid = input.parse().map_err(PuzzleError::on_day(DAY))?;
 
     for _generation in 0..6 {
         grid.next(false);
     }
 
-    grid.active_count
+    Ok(grid.active_count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut grid: Grid = input.parse().unwrap();
+/// # Errors
+///
+/// Fails on a cell other than `#` or `.`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut grid: Grid = input.parse().map_err(PuzzleError::on_day(DAY))?;
 
     for _generation in 0..6 {
         grid.next(true);
     }
 
-    grid.active_count
+    Ok(grid.active_count)
 }
 
 #[cfg(test)]
@@ -1,157 +1,375 @@
-#[derive(Debug, Clone, Copy)]
-enum Op {
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use std::collections::HashMap;
+use std::fmt;
+
+const DAY: u8 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
     Plus,
+    Minus,
     Times,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+// The "weird math" a homework sheet uses: how tightly each binary operator
+// binds (higher binds tighter) and how unary minus binds.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    binary: HashMap<Op, (u8, Associativity)>,
+    negate: u8,
+}
+
+// Operators keep their position in the source so evaluation errors can
+// point at them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number(i64),
+    Negate(usize, Box<Expr>),
+    Binary(Op, usize, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    pub position: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Token {
-    Number(usize),
+    Number(i64),
     Operator(Op),
     LParen,
     RParen,
 }
 
-fn tokenize(input: &str) -> Vec<Token> {
-    let tokens: Vec<Token> = input
-        .chars()
-        .filter_map(|ch| match ch {
-            '+' => Some(Token::Operator(Op::Plus)),
-            '*' => Some(Token::Operator(Op::Times)),
-            '(' => Some(Token::LParen),
-            ')' => Some(Token::RParen),
-            '0' => Some(Token::Number(0)),
-            '1' => Some(Token::Number(1)),
-            '2' => Some(Token::Number(2)),
-            '3' => Some(Token::Number(3)),
-            '4' => Some(Token::Number(4)),
-            '5' => Some(Token::Number(5)),
-            '6' => Some(Token::Number(6)),
-            '7' => Some(Token::Number(7)),
-            '8' => Some(Token::Number(8)),
-            '9' => Some(Token::Number(9)),
-            _ => None,
-        })
-        .collect();
-
-    tokens
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end: usize,
+    rules: &'a Rules,
 }
 
-fn evaluate_math(tokens: &[Token]) -> usize {
-    let (_index, result) = evaluate_expression(tokens, 0);
-    result
+impl Op {
+    fn symbol(self) -> char {
+        match self {
+            Op::Plus => '+',
+            Op::Minus => '-',
+            Op::Times => '*',
+            Op::Divide => '/',
+        }
+    }
+
+    fn apply(self, lhs: i64, rhs: i64) -> Option<i64> {
+        match self {
+            Op::Plus => lhs.checked_add(rhs),
+            Op::Minus => lhs.checked_sub(rhs),
+            Op::Times => lhs.checked_mul(rhs),
+            Op::Divide => lhs.checked_div(rhs),
+        }
+    }
 }
 
-fn evaluate_expression(tokens: &[Token], starting_index: usize) -> (usize, usize) {
-    let mut index: usize = starting_index;
-    let mut op_stack: Vec<&Op> = Vec::new();
-    let mut result: Option<usize> = None;
+impl Rules {
+    #[must_use]
+    pub fn new(binary: &[(Op, u8, Associativity)], negate: u8) -> Self {
+        Rules {
+            binary: binary
+                .iter()
+                .map(|&(op, precedence, associativity)| (op, (precedence, associativity)))
+                .collect(),
+            negate,
+        }
+    }
+
+    // Part 1: everything is evaluated left to right.
+    #[must_use]
+    pub fn flat() -> Self {
+        Rules::new(
+            &[
+                (Op::Plus, 1, Associativity::Left),
+                (Op::Minus, 1, Associativity::Left),
+                (Op::Times, 1, Associativity::Left),
+                (Op::Divide, 1, Associativity::Left),
+            ],
+            2,
+        )
+    }
+
+    // Part 2: addition and subtraction bind tighter than multiplication.
+    #[must_use]
+    pub fn additive_first() -> Self {
+        Rules::new(
+            &[
+                (Op::Plus, 2, Associativity::Left),
+                (Op::Minus, 2, Associativity::Left),
+                (Op::Times, 1, Associativity::Left),
+                (Op::Divide, 1, Associativity::Left),
+            ],
+            3,
+        )
+    }
+}
 
-    while let Some(token) = &tokens.get(index) {
-        match token {
-            Token::LParen => {
-                let (new_index, inner_result) = evaluate_expression(&tokens, index + 1);
+impl Expr {
+    /// # Errors
+    ///
+    /// Fails on division by zero or when a result doesn't fit in an `i64`.
+    pub fn evaluate(&self) -> Result<i64, EvalError> {
+        let failed = |position, message: &str| EvalError {
+            position,
+            message: message.to_owned(),
+        };
 
-                index = new_index;
-                result = if let Some(op) = op_stack.pop() {
-                    Some(evaluate_op(op, result.unwrap(), inner_result))
-                } else {
-                    Some(inner_result)
-                };
+        match self {
+            Expr::Number(value) => Ok(*value),
+            Expr::Negate(position, inner) => inner
+                .evaluate()?
+                .checked_neg()
+                .ok_or_else(|| failed(*position, "overflow")),
+            Expr::Binary(op, position, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate()?, rhs.evaluate()?);
+                op.apply(lhs, rhs).ok_or_else(|| match (op, rhs) {
+                    (Op::Divide, 0) => failed(*position, "division by zero"),
+                    _ => failed(*position, "overflow"),
+                })
             }
-            Token::RParen => break,
-            Token::Operator(op) => op_stack.push(op),
-            Token::Number(number) => match result {
-                Some(lhs) => {
-                    if let Some(op) = op_stack.pop() {
-                        result = Some(evaluate_op(op, lhs, *number))
-                    } else {
-                        panic!(
-                            "number without an operation on the stack! number={} index={}",
-                            number, index
-                        )
-                    }
-                }
-                None => result = Some(*number),
-            },
         }
-        index += 1;
     }
 
-    (index, result.unwrap())
+    // One node per line, children indented below their operator.
+    #[must_use]
+    pub fn tree(&self) -> String {
+        let mut output = String::new();
+        self.write_tree(&mut output, 0);
+        output
+    }
+
+    fn write_tree(&self, output: &mut String, depth: usize) {
+        output.push_str(&"  ".repeat(depth));
+
+        let (label, children) = match self {
+            Expr::Number(value) => (value.to_string(), vec![]),
+            Expr::Negate(_, inner) => ("neg".to_owned(), vec![inner]),
+            Expr::Binary(op, _, lhs, rhs) => (op.symbol().to_string(), vec![lhs, rhs]),
+        };
+
+        output.push_str(&label);
+        output.push('\n');
+
+        for child in children {
+            child.write_tree(output, depth + 1);
+        }
+    }
+}
+
+// Fully parenthesized, so the grouping the rules chose is visible.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(value) => write!(f, "{value}"),
+            Expr::Negate(_, inner) => write!(f, "-{inner}"),
+            Expr::Binary(op, _, lhs, rhs) => write!(f, "({lhs} {} {rhs})", op.symbol()),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
 }
 
-fn evaluate_op(op: &Op, lhs: usize, rhs: usize) -> usize {
-    match op {
-        Op::Plus => lhs + rhs,
-        Op::Times => lhs * rhs,
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
     }
 }
 
-fn evaluate_advanced_math(tokens: &[Token]) -> usize {
-    let (_index, result) = evaluate_advanced_expression(tokens, 0);
-    result
+impl std::error::Error for EvalError {}
+
+fn error<T>(position: usize, message: &str) -> Result<T, ParseError> {
+    Err(ParseError {
+        position,
+        message: message.to_owned(),
+    })
 }
 
-fn evaluate_advanced_expression(tokens: &[Token], starting_index: usize) -> (usize, usize) {
-    let mut index: usize = starting_index;
-    let mut stack: Vec<Token> = Vec::new();
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
 
-    while let Some(token) = &tokens.get(index) {
-        match token {
-            Token::LParen => {
-                let (new_index, inner_result) = evaluate_advanced_expression(&tokens, index + 1);
+    while let Some((position, ch)) = chars.next() {
+        let token = match ch {
+            '+' => Token::Operator(Op::Plus),
+            '-' => Token::Operator(Op::Minus),
+            '*' => Token::Operator(Op::Times),
+            '/' => Token::Operator(Op::Divide),
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '0'..='9' => {
+                let mut value = i64::from(ch as u8 - b'0');
 
-                stack.push(Token::Number(inner_result));
-                index = new_index;
+                while let Some(&(_, digit @ '0'..='9')) = chars.peek() {
+                    value = match value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(i64::from(digit as u8 - b'0')))
+                    {
+                        Some(value) => value,
+                        None => return error(position, "number too large"),
+                    };
+                    chars.next();
+                }
+
+                Token::Number(value)
             }
-            Token::RParen => break,
-            _ => stack.push(*token.clone()),
-        }
-        index += 1;
-    }
-
-    let mut products: Vec<usize> = Vec::new();
-    while stack.len() > 0 {
-        let token: Token = stack.pop().unwrap();
-        match token {
-            Token::Number(value) => products.push(value),
-            Token::Operator(Op::Times) => {}
-            Token::Operator(Op::Plus) => {
-                if let Token::Number(rhs) = stack.pop().unwrap() {
-                    let lhs = products.pop().unwrap();
-                    products.push(lhs + rhs)
-                } else {
-                    panic!("unexpected non-number")
+            ch if ch.is_whitespace() => continue,
+            _ => return error(position, &format!("unexpected character `{ch}`")),
+        };
+
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<(usize, Token)> {
+        self.tokens.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), ParseError> {
+        let token = self.peek();
+        self.index += 1;
+        token.map_or_else(|| error(self.end, "unexpected end of input"), Ok)
+    }
+
+    // Parses operators that bind at least as tightly as `min_binding`, using
+    // binding powers of 2 * precedence (+ 1 on the side that groups first).
+    // These are u16 so every u8 precedence fits.
+    fn expression(&mut self, min_binding: u16) -> Result<Expr, ParseError> {
+        let mut lhs = match self.next()? {
+            (_, Token::Number(value)) => Expr::Number(value),
+            (position, Token::Operator(Op::Minus)) => Expr::Negate(
+                position,
+                Box::new(self.expression(u16::from(self.rules.negate) * 2)?),
+            ),
+            (_, Token::LParen) => {
+                let inner = self.expression(0)?;
+                match self.next()? {
+                    (_, Token::RParen) => inner,
+                    (position, _) => return error(position, "expected `)`"),
                 }
             }
-            _ => panic!("shouldn't have parens at this point"),
+            (position, _) => return error(position, "expected a number or `(`"),
+        };
+
+        while let Some((position, token)) = self.peek() {
+            let op = match token {
+                Token::Operator(op) => op,
+                Token::RParen => break,
+                _ => return error(position, "expected an operator"),
+            };
+            let (precedence, associativity) = match self.rules.binary.get(&op) {
+                Some(&(precedence, associativity)) => (u16::from(precedence) * 2, associativity),
+                None => return error(position, &format!("operator `{}` not allowed", op.symbol())),
+            };
+            let (left, right) = match associativity {
+                Associativity::Left => (precedence, precedence + 1),
+                Associativity::Right => (precedence + 1, precedence),
+            };
+
+            if left < min_binding {
+                break;
+            }
+
+            self.index += 1;
+            let rhs = self.expression(right)?;
+            lhs = Expr::Binary(op, position, Box::new(lhs), Box::new(rhs));
         }
+
+        Ok(lhs)
     }
+}
 
-    (index, products.iter().product())
+/// # Errors
+///
+/// Fails on an unknown token, a number too large for an `i64`, or an
+/// expression that doesn't follow `rules`.
+pub fn parse(input: &str, rules: &Rules) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+        end: input.len(),
+        rules,
+    };
+    let expr = parser.expression(0)?;
+
+    match parser.peek() {
+        Some((position, _)) => error(position, "unmatched `)`"),
+        None => Ok(expr),
+    }
 }
 
-#[must_use]
-pub fn part1(input: &str) -> usize {
-    input
-        .lines()
+fn sum_with(input: &str, rules: &Rules) -> Result<i64, PuzzleError> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
-            let tokens: Vec<Token> = tokenize(line);
-            evaluate_math(&tokens)
+            parse(line.text, rules)
+                .map_err(|error| error.to_string())
+                .and_then(|expr| expr.evaluate().map_err(|error| error.to_string()))
+                .map_err(|message| PuzzleError::new(DAY, message).at_line(line.number))
+        })
+        .try_fold(0_i64, |sum, value| {
+            sum.checked_add(value?)
+                .ok_or_else(|| PuzzleError::new(DAY, "the sum overflows an i64"))
         })
-        .sum()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
-pub fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let tokens: Vec<Token> = tokenize(line);
-            evaluate_advanced_math(&tokens)
-        })
-        .sum()
+pub fn part1(input: &str) -> i64 {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> i64 {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line [`parse`] rejects, an expression that overflows or
+/// divides by zero, or a sum that overflows.
+pub fn try_part1(input: &str) -> Result<i64, PuzzleError> {
+    sum_with(input, &Rules::flat())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<i64, PuzzleError> {
+    sum_with(input, &Rules::additive_first())
 }
 
 #[cfg(test)]
@@ -231,4 +449,99 @@ mod tests {
             23340
         )
     }
+
+    #[test]
+    fn custom_rules() {
+        let rules = Rules::new(
+            &[
+                (Op::Minus, 1, Associativity::Right),
+                (Op::Divide, 2, Associativity::Left),
+            ],
+            3,
+        );
+        let expr = parse("10 - 4 - -12 / 2 / 3", &rules).unwrap();
+
+        assert_eq!(expr.to_string(), "(10 - (4 - ((-12 / 2) / 3)))");
+        assert_eq!(expr.evaluate(), Ok(4));
+        assert_eq!(
+            parse("-(3 - 1)", &rules).unwrap().tree(),
+            "neg\n  -\n    3\n    1\n"
+        );
+
+        let high = Rules::new(
+            &[
+                (Op::Plus, 200, Associativity::Left),
+                (Op::Times, 255, Associativity::Right),
+            ],
+            255,
+        );
+        let expr = parse("1 + 2 * 3 * -4 + 5", &high).unwrap();
+        assert_eq!(expr.to_string(), "((1 + (2 * (3 * -4))) + 5)");
+        assert_eq!(expr.evaluate(), Ok(-18));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let rules = Rules::additive_first();
+        let message = |input| parse(input, &rules).unwrap_err().to_string();
+
+        assert_eq!(
+            message("1 + (2 * 3"),
+            "unexpected end of input at position 10"
+        );
+        assert_eq!(message("1 + 2)"), "unmatched `)` at position 5");
+        assert_eq!(message("1 2"), "expected an operator at position 2");
+        assert_eq!(message("1 + * 2"), "expected a number or `(` at position 4");
+        assert_eq!(message("1 % 2"), "unexpected character `%` at position 2");
+
+        let additive = Rules::new(&[(Op::Plus, 1, Associativity::Left)], 2);
+        assert_eq!(
+            parse("1 / 2", &additive),
+            Err(ParseError {
+                position: 2,
+                message: "operator `/` not allowed".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        let rules = Rules::flat();
+        let evaluate = |input| parse(input, &rules).unwrap().evaluate();
+
+        assert_eq!(
+            evaluate("1 + 2 / (3 - 3)"),
+            Err(EvalError {
+                position: 6,
+                message: "division by zero".into(),
+            })
+        );
+        assert_eq!(
+            evaluate("(-9223372036854775807 - 1) / -1")
+                .unwrap_err()
+                .to_string(),
+            "overflow at position 27"
+        );
+        assert_eq!(
+            evaluate("-(-9223372036854775807 - 1)")
+                .unwrap_err()
+                .to_string(),
+            "overflow at position 0"
+        );
+        assert_eq!(evaluate("9223372036854775807 + 0"), Ok(i64::MAX));
+        assert_eq!(
+            parse("2 * 12345678901234567890", &rules)
+                .unwrap_err()
+                .to_string(),
+            "number too large at position 4"
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        assert_eq!(
+            try_part1("1 + 2\n3 *").unwrap_err().to_string(),
+            "day 18, line 2: unexpected end of input at position 3"
+        );
+    }
 }
@@ -1,218 +1,372 @@
-use std::collections::HashMap;
+use crate::parsing::{self, ParseError, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum Rule {
-    Sequence(Vec<Rule>),
-    Either(Vec<Rule>, Vec<Rule>),
-    A,
-    B,
+const DAY: u8 = 19;
+
+pub type RuleID = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    Rule(RuleID),
+    Terminal(String),
 }
 
-#[derive(Debug)]
-enum RuleItem {
-    ID(u8),
-    A,
-    B,
+// A context-free grammar: each rule is a list of alternative symbol sequences.
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    rules: HashMap<RuleID, Vec<Vec<Symbol>>>,
 }
 
-type RuleMap = HashMap<u8, Vec<Vec<RuleItem>>>;
+// How a rule matched: which alternative was taken and what each of its
+// symbols matched in turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Derivation {
+    Terminal(String),
+    Rule {
+        id: RuleID,
+        alternative: usize,
+        children: Vec<Derivation>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    rule: RuleID,
+    alternative: usize,
+    dot: usize,
+    origin: usize,
+}
+
+type Span = (RuleID, usize, usize);
 
 #[derive(Debug)]
 struct Input {
-    rule_map: RuleMap,
+    grammar: Grammar,
     strings: Vec<String>,
 }
 
-impl FromStr for Input {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let (rule_lines, string_lines): (&str, &str) =
-            match input.splitn(2, "\n\n").collect::<Vec<&str>>()[..] {
-                [rule_lines, string_lines] => (rule_lines, string_lines),
-                _ => panic!("unexpected input"),
-            };
-
-        let mut rule_map: RuleMap = HashMap::new();
-
-        for line in rule_lines.lines() {
-            match line.splitn(2, ": ").collect::<Vec<&str>>()[..] {
-                [id_string, rule_string] => {
-                    let rule: Vec<Vec<RuleItem>> = rule_string
-                        .split(" | ")
-                        .map(|sequence| {
-                            sequence
-                                .trim_matches('"')
-                                .split(" ")
-                                .map(|ch| match ch {
-                                    "a" => RuleItem::A,
-                                    "b" => RuleItem::B,
-                                    _ => RuleItem::ID(ch.parse().unwrap()),
-                                })
-                                .collect()
-                        })
-                        .collect();
+impl FromStr for Grammar {
+    type Err = ParseError;
+    fn from_str(rule_lines: &str) -> Result<Self, Self::Err> {
+        let mut grammar = Grammar::default();
 
-                    rule_map.insert(id_string.parse().unwrap(), rule);
-                }
-                _ => panic!("invalid rule input"),
-            }
+        for line in parsing::lines(rule_lines) {
+            grammar.add_rule(line)?;
         }
 
-        let strings: Vec<String> = string_lines
-            .lines()
-            .map(|string| string.to_owned())
-            .collect();
-
-        Ok(Input { rule_map, strings })
+        Ok(grammar)
     }
 }
 
-#[must_use]
-pub fn part1(input_string: &str) -> usize {
-    let input: Input = input_string.parse().unwrap();
-    let rule: &Rule = &get_sequence_rule(&input.rule_map, 0);
+fn parse(input: &str) -> Result<Input, PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let (rule_lines, string_blocks) = match blocks.split_first() {
+        Some((rule_lines, string_blocks)) if !string_blocks.is_empty() => {
+            (rule_lines, string_blocks)
+        }
+        _ => {
+            return Err(PuzzleError::new(
+                DAY,
+                "expected a blank line between the rules and the messages",
+            ))
+        }
+    };
+    let mut grammar = Grammar::default();
 
-    input
-        .strings
-        .iter()
-        .filter(|string| {
-            let (matching, consumed) = matches_rule(string.as_str(), rule);
-            matching && consumed == string.len()
-        })
-        .count()
+    for &line in rule_lines {
+        grammar.add_rule(line).map_err(PuzzleError::on_day(DAY))?;
+    }
+
+    Ok(Input {
+        grammar,
+        strings: string_blocks
+            .iter()
+            .flatten()
+            .map(|line| line.text.to_owned())
+            .collect(),
+    })
 }
 
-fn get_sequence_rule(rule_map: &RuleMap, id: u8) -> Rule {
-    let rule_item: &Vec<RuleItem> = &rule_map.get(&id).unwrap()[0];
+impl Grammar {
+    pub fn set_rule(&mut self, id: RuleID, alternatives: Vec<Vec<Symbol>>) {
+        self.rules.insert(id, alternatives);
+    }
+
+    /// Adds or replaces a rule written like `8: 42 | 42 8` or `4: "a"`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the line isn't a well-formed rule.
+    pub fn parse_rule(&mut self, line: &str) -> parsing::Result<()> {
+        match parsing::lines(line).first() {
+            Some(&line) => self.add_rule(line),
+            None => Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected `<id>: <rule>`".into(),
+            }),
+        }
+    }
 
-    Rule::Sequence(map_sequence(rule_item, rule_map))
-}
+    fn add_rule(&mut self, line: SourceLine) -> parsing::Result<()> {
+        let (id_string, rule_string) = line
+            .text
+            .split_once(": ")
+            .ok_or_else(|| line.error(line.text, "expected `<id>: <rule>`"))?;
+        let id = id_string
+            .parse()
+            .map_err(|_| line.error(id_string, format!("invalid rule id {id_string:?}")))?;
+        let alternatives = rule_string
+            .split(" | ")
+            .map(|sequence| {
+                sequence
+                    .split_whitespace()
+                    .map(|symbol| match symbol.strip_prefix('"') {
+                        Some(quoted) => quoted
+                            .strip_suffix('"')
+                            .map(|terminal| Symbol::Terminal(terminal.to_owned()))
+                            .ok_or_else(|| {
+                                line.error(symbol, format!("unterminated string {symbol}"))
+                            }),
+                        None => symbol
+                            .parse()
+                            .map(Symbol::Rule)
+                            .map_err(|_| line.error(symbol, format!("invalid symbol {symbol:?}"))),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.set_rule(id, alternatives);
+        Ok(())
+    }
 
-fn get_either_rule(rule_map: &RuleMap, id: u8) -> Rule {
-    let rule_item: &Vec<Vec<RuleItem>> = &rule_map.get(&id).unwrap();
-    let option_one: &Vec<RuleItem> = &rule_item[0];
-    let option_two: &Vec<RuleItem> = &rule_item[1];
+    #[must_use]
+    pub fn alternatives(&self, id: RuleID) -> &[Vec<Symbol>] {
+        self.rules.get(&id).map_or(&[], Vec::as_slice)
+    }
 
-    Rule::Either(
-        map_sequence(option_one, rule_map),
-        map_sequence(option_two, rule_map),
-    )
-}
+    #[must_use]
+    pub fn recognize(&self, start: RuleID, input: &str) -> bool {
+        self.completed_spans(start, input)
+            .contains(&(start, 0, input.len()))
+    }
 
-fn map_sequence(rule: &Vec<RuleItem>, rule_map: &RuleMap) -> Vec<Rule> {
-    rule.iter()
-        .map(|item| match item {
-            RuleItem::A => Rule::A,
-            RuleItem::B => Rule::B,
-            RuleItem::ID(id) => match &rule_map.get(id).unwrap()[..] {
-                [sequence] => Rule::Sequence(map_sequence(sequence, rule_map)),
-                [sequence_one, sequence_two] => Rule::Either(
-                    map_sequence(sequence_one, rule_map),
-                    map_sequence(sequence_two, rule_map),
-                ),
-                _ => panic!("unexpected structure"),
-            },
-        })
-        .collect()
-}
+    #[must_use]
+    pub fn derive(&self, start: RuleID, input: &str) -> Option<Derivation> {
+        let spans = self.completed_spans(start, input);
 
-fn matches_rule(string: &str, rule: &Rule) -> (bool, usize) {
-    match rule {
-        Rule::A => {
-            if string.chars().nth(0).unwrap() == 'a' {
-                (true, 1)
-            } else {
-                (false, 0)
-            }
-        }
-        Rule::B => {
-            if string.chars().nth(0).unwrap() == 'b' {
-                (true, 1)
-            } else {
-                (false, 0)
+        self.build(&spans, input, (start, 0, input.len()), &mut HashSet::new())
+    }
+
+    fn next_symbol(&self, item: &Item) -> Option<&Symbol> {
+        self.alternatives(item.rule)[item.alternative].get(item.dot)
+    }
+
+    // An Earley parse over the bytes of the input, returning every span
+    // (rule, from, to) that some rule reachable from `start` matched.
+    fn completed_spans(&self, start: RuleID, input: &str) -> HashSet<Span> {
+        let bytes = input.as_bytes();
+        let mut sets: Vec<Vec<Item>> = vec![vec![]; bytes.len() + 1];
+        let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); bytes.len() + 1];
+        let mut completed: HashSet<Span> = HashSet::new();
+
+        let mut add = |sets: &mut Vec<Vec<Item>>, position: usize, item: Item| {
+            if seen[position].insert(item) {
+                sets[position].push(item);
             }
+        };
+
+        for alternative in 0..self.alternatives(start).len() {
+            let item = Item {
+                rule: start,
+                alternative,
+                dot: 0,
+                origin: 0,
+            };
+            add(&mut sets, 0, item);
         }
-        Rule::Sequence(rules) => matches_rules(string, rules),
-        Rule::Either(some_rules, other_rules) => {
-            let (matching, consumed) = matches_rules(string, some_rules);
-            if matching {
-                (matching, consumed)
-            } else {
-                matches_rules(string, other_rules)
+
+        for position in 0..=bytes.len() {
+            let mut index = 0;
+
+            while index < sets[position].len() {
+                let item = sets[position][index];
+                let advanced = Item {
+                    dot: item.dot + 1,
+                    ..item
+                };
+                index += 1;
+
+                match self.next_symbol(&item) {
+                    Some(Symbol::Rule(rule)) => {
+                        for alternative in 0..self.alternatives(*rule).len() {
+                            let predicted = Item {
+                                rule: *rule,
+                                alternative,
+                                dot: 0,
+                                origin: position,
+                            };
+                            add(&mut sets, position, predicted);
+                        }
+
+                        // A rule that matched nothing here has already been
+                        // completed, so move past it straight away.
+                        if completed.contains(&(*rule, position, position)) {
+                            add(&mut sets, position, advanced);
+                        }
+                    }
+                    Some(Symbol::Terminal(terminal)) => {
+                        if bytes[position..].starts_with(terminal.as_bytes()) {
+                            add(&mut sets, position + terminal.len(), advanced);
+                        }
+                    }
+                    None => {
+                        completed.insert((item.rule, item.origin, position));
+
+                        let waiting: Vec<Item> = sets[item.origin]
+                            .iter()
+                            .filter(|other| {
+                                self.next_symbol(other) == Some(&Symbol::Rule(item.rule))
+                            })
+                            .copied()
+                            .collect();
+
+                        for other in waiting {
+                            let advanced = Item {
+                                dot: other.dot + 1,
+                                ..other
+                            };
+                            add(&mut sets, position, advanced);
+                        }
+                    }
+                }
             }
         }
+
+        completed
     }
-}
 
-fn matches_rules(string: &str, rules: &[Rule]) -> (bool, usize) {
-    let mut index: usize = 0;
-    let mut all_match: bool = true;
+    fn build(
+        &self,
+        spans: &HashSet<Span>,
+        input: &str,
+        span: Span,
+        active: &mut HashSet<Span>,
+    ) -> Option<Derivation> {
+        if !spans.contains(&span) || !active.insert(span) {
+            return None;
+        }
 
-    for rule in rules {
-        let (matching, consumed): (bool, usize) = matches_rule(&string[index..], rule);
+        let (id, from, to) = span;
+        let derivation =
+            self.alternatives(id)
+                .iter()
+                .enumerate()
+                .find_map(|(alternative, symbols)| {
+                    self.build_sequence(spans, input, symbols, (from, to), active)
+                        .map(|children| Derivation::Rule {
+                            id,
+                            alternative,
+                            children,
+                        })
+                });
 
-        if matching {
-            index += consumed;
-        } else {
-            all_match = false;
-            break;
-        }
+        active.remove(&span);
+        derivation
     }
 
-    (all_match, index)
+    fn build_sequence(
+        &self,
+        spans: &HashSet<Span>,
+        input: &str,
+        symbols: &[Symbol],
+        (from, to): (usize, usize),
+        active: &mut HashSet<Span>,
+    ) -> Option<Vec<Derivation>> {
+        let (first, rest) = match symbols.split_first() {
+            Some(split) => split,
+            None if from == to => return Some(vec![]),
+            None => return None,
+        };
+
+        let (child, middle) = match first {
+            Symbol::Terminal(terminal) => {
+                if !input.as_bytes()[from..to].starts_with(terminal.as_bytes()) {
+                    return None;
+                }
+                let mut children =
+                    self.build_sequence(spans, input, rest, (from + terminal.len(), to), active)?;
+                children.insert(0, Derivation::Terminal(terminal.clone()));
+                return Some(children);
+            }
+            Symbol::Rule(rule) => (*rule, from..=to),
+        };
+
+        for end in middle {
+            if let Some(derivation) = self.build(spans, input, (child, from, end), active) {
+                if let Some(mut children) =
+                    self.build_sequence(spans, input, rest, (end, to), active)
+                {
+                    children.insert(0, derivation);
+                    return Some(children);
+                }
+            }
+        }
+
+        None
+    }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
-pub fn part2(input_string: &str) -> usize {
-    let input: Input = input_string.parse().unwrap();
-    let rule_forty_two: &Rule = &get_either_rule(&input.rule_map, 42);
-    let rule_thirty_one: &Rule = &get_either_rule(&input.rule_map, 31);
-    let mut matching_count: usize = 0;
-
-    for initial_string in input.strings {
-        let mut string: &str = initial_string.as_str();
-        let mut rule_forty_two_count: usize = 0;
-        let mut rule_thirty_one_count: usize = 0;
-
-        loop {
-            if string == "" {
-                break;
-            }
-            let (matching, consumed): (bool, usize) = matches_rule(string, rule_forty_two);
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
 
-            if matching {
-                rule_forty_two_count += 1;
-                string = &string[consumed..];
-            } else {
-                break;
-            }
-        }
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
 
-        loop {
-            if string == "" {
-                break;
-            }
-            let (matching, consumed): (bool, usize) = matches_rule(string, rule_thirty_one);
+/// # Errors
+///
+/// Fails when the rules or messages can't be parsed.
+pub fn try_part1(input_string: &str) -> Result<usize, PuzzleError> {
+    let input = parse(input_string)?;
 
-            if matching {
-                rule_thirty_one_count += 1;
-                string = &string[consumed..];
-            } else {
-                break;
-            }
-        }
+    Ok(input
+        .strings
+        .iter()
+        .filter(|string| input.grammar.recognize(0, string))
+        .count())
+}
 
-        let matching: bool = string == ""
-            && rule_forty_two_count > rule_thirty_one_count
-            && rule_thirty_one_count > 0;
+/// # Errors
+///
+/// Fails when the rules or messages can't be parsed.
+///
+/// # Panics
+///
+/// Never in practice: the replacement rules are fixed and well-formed.
+pub fn try_part2(input_string: &str) -> Result<usize, PuzzleError> {
+    let mut input = parse(input_string)?;
 
-        if matching {
-            matching_count += 1;
-        }
-    }
+    input.grammar.parse_rule("8: 42 | 42 8").unwrap();
+    input.grammar.parse_rule("11: 42 31 | 42 11 31").unwrap();
 
-    matching_count
+    Ok(input
+        .strings
+        .iter()
+        .filter(|string| input.grammar.recognize(0, string))
+        .count())
 }
 
 #[cfg(test)]
@@ -297,4 +451,84 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba\n"
             12
         )
     }
+
+    #[test]
+    fn derivation() {
+        let grammar: Grammar = "\
+0: 1 0 | 2
+1: \"ab\"
+2: \"c\" | \"\""
+            .parse()
+            .unwrap();
+
+        assert!(grammar.recognize(0, "ababc"));
+        assert!(grammar.recognize(0, "ab"));
+        assert!(!grammar.recognize(0, "abca"));
+        assert_eq!(
+            grammar.derive(0, "abc"),
+            Some(Derivation::Rule {
+                id: 0,
+                alternative: 0,
+                children: vec![
+                    Derivation::Rule {
+                        id: 1,
+                        alternative: 0,
+                        children: vec![Derivation::Terminal("ab".into())],
+                    },
+                    Derivation::Rule {
+                        id: 0,
+                        alternative: 1,
+                        children: vec![Derivation::Rule {
+                            id: 2,
+                            alternative: 0,
+                            children: vec![Derivation::Terminal("c".into())],
+                        }],
+                    },
+                ],
+            })
+        );
+        assert_eq!(grammar.derive(0, "abab c"), None);
+    }
+
+    #[test]
+    fn puzzle_errors() {
+        assert_eq!(
+            try_part1("0: 1\n1: a\n\nab\n").unwrap_err().to_string(),
+            "day 19, line 2: column 4, invalid symbol \"a\""
+        );
+        assert_eq!(
+            try_part2("0: 1\n1: \"a\"\n").unwrap_err().to_string(),
+            "day 19: expected a blank line between the rules and the messages"
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut grammar = Grammar::default();
+
+        assert_eq!(
+            grammar.parse_rule("8 42 | 42 8").unwrap_err().to_string(),
+            "line 1, column 1: expected `<id>: <rule>`"
+        );
+        assert_eq!(
+            grammar.parse_rule("x: 1").unwrap_err(),
+            ParseError {
+                line: 1,
+                column: 1,
+                message: "invalid rule id \"x\"".into(),
+            }
+        );
+        assert_eq!(
+            grammar.parse_rule("4: \"a").unwrap_err().message,
+            "unterminated string \"a"
+        );
+        assert_eq!(
+            "0: 1\n2: 1 y".parse::<Grammar>().unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 6,
+                message: "invalid symbol \"y\"".into(),
+            }
+        );
+    }
 }
@@ -1,7 +1,11 @@
-use std::collections::HashMap;
+use crate::parsing::{self, ParseError, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Copy, Clone, PartialEq)]
-enum Cell {
+const DAY: u8 = 20;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Cell {
     On,
     Off,
 }
@@ -18,55 +22,29 @@ impl std::fmt::Debug for Cell {
     }
 }
 
-type TileID = usize;
-type Discriminant = String;
+pub type TileID = usize;
 
-#[derive(Debug)]
-struct Tile {
-    id: TileID,
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub id: TileID,
     cells: Vec<Vec<Cell>>,
-    side_discriminants: HashMap<Discriminant, (Side, Orientation)>,
 }
 
-struct LockedTile {
-    id: TileID,
-    cells: Vec<Vec<Cell>>,
+// A tile fixed in one orientation, with its four edges read left to right
+// and top to bottom so that neighbouring edges compare equal.
+#[derive(Debug, Clone)]
+pub struct LockedTile {
+    pub id: TileID,
+    pub transform: Transform,
     cells_with_borders: Vec<Vec<Cell>>,
-    transform: Transform,
-    side_discriminants: HashMap<Side, Discriminant>,
-}
-
-impl std::fmt::Debug for LockedTile {
-    fn fmt(
-        &self,
-        formatter: &mut std::fmt::Formatter<'_>,
-    ) -> std::result::Result<(), std::fmt::Error> {
-        formatter.write_str(
-            format!(
-                "LockedTile: ID={} Transform={:?} SideDiscriminants={:?}",
-                self.id, self.transform, self.side_discriminants
-            )
-            .as_str(),
-        )
-    }
+    top: Vec<Cell>,
+    bottom: Vec<Cell>,
+    left: Vec<Cell>,
+    right: Vec<Cell>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-enum Side {
-    Top,
-    Bottom,
-    Left,
-    Right,
-}
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum Orientation {
-    Normal,
-    Reversed,
-}
-
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum Transform {
+pub enum Transform {
     Nothing,
     FlipVertically,
     FlipHorizontally,
@@ -78,372 +56,355 @@ enum Transform {
 }
 
 #[derive(Debug)]
-struct Connections {
-    map: HashMap<TileID, Vec<TileID>>,
-    max: usize,
+pub struct Grid {
+    pub tiles: Vec<Vec<LockedTile>>,
 }
 
-#[derive(Debug)]
-struct Grid<'a> {
-    tiles: Vec<Vec<&'a LockedTile>>,
-}
-
-#[derive(Debug)]
-struct Image {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
     cells: Vec<Vec<Cell>>,
 }
 
-impl std::str::FromStr for Tile {
-    type Err = ();
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let lines: Vec<&str> = string.trim().lines().collect();
-        let id: TileID = TileID::from_str_radix(&lines[0][5..9], 10).unwrap();
-        let cells: Vec<Vec<Cell>> = lines[1..]
-            .iter()
-            .map(|line| {
-                line.chars()
-                    .map(|ch| match ch {
-                        '#' => Cell::On,
-                        '.' => Cell::Off,
-                        _ => panic!("invalid cell"),
-                    })
-                    .collect()
-            })
-            .collect();
-
-        use Orientation::{Normal, Reversed};
-        use Side::{Bottom, Left, Right, Top};
-
-        let mut side_discriminants: HashMap<Discriminant, (Side, Orientation)> = HashMap::new();
-
-        let mut top_row: Vec<Cell> = cells[0].clone();
-        side_discriminants.insert(Tile::side_discriminant(&top_row), (Top, Normal));
-        top_row.reverse();
-        side_discriminants.insert(Tile::side_discriminant(&top_row), (Top, Reversed));
-
-        let mut bottom_row: Vec<Cell> = cells[9].clone();
-        side_discriminants.insert(Tile::side_discriminant(&bottom_row), (Bottom, Normal));
-        bottom_row.reverse();
-        side_discriminants.insert(Tile::side_discriminant(&bottom_row), (Bottom, Reversed));
-
-        let mut left_row: Vec<Cell> = cells.iter().map(|row| row[0]).collect();
-        side_discriminants.insert(Tile::side_discriminant(&left_row), (Left, Normal));
-        left_row.reverse();
-        side_discriminants.insert(Tile::side_discriminant(&left_row), (Left, Reversed));
+// The lit cells of a shape to search for, relative to its top-left corner.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    offsets: Vec<(usize, usize)>,
+    height: usize,
+    width: usize,
+}
 
-        let mut right_row: Vec<Cell> = cells.iter().map(|row| row[9]).collect();
-        side_discriminants.insert(Tile::side_discriminant(&right_row), (Right, Normal));
-        right_row.reverse();
-        side_discriminants.insert(Tile::side_discriminant(&right_row), (Right, Reversed));
+pub const SEA_MONSTER: &str = "                  # \n#    ##    ##    ###\n #  #  #  #  #  #   ";
 
-        Ok(Tile {
-            id,
-            cells,
-            side_discriminants,
-        })
+impl std::str::FromStr for Tile {
+    type Err = ParseError;
+    fn from_str(string: &str) -> parsing::Result<Self> {
+        match &parsing::blocks(string)[..] {
+            [lines] => Tile::from_lines(lines),
+            _ => Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected one tile".into(),
+            }),
+        }
     }
 }
 
 impl Tile {
-    fn parse(input: &str) -> Vec<Self> {
-        input
-            .trim()
-            .split("\n\n")
-            .map(|tile_lines| tile_lines.parse().unwrap())
+    // Tiles must be square; any size works.
+    #[must_use]
+    pub fn new(id: TileID, cells: Vec<Vec<Cell>>) -> Option<Self> {
+        if cells.is_empty() || cells.iter().any(|row| row.len() != cells.len()) {
+            return None;
+        }
+
+        Some(Self { id, cells })
+    }
+
+    /// # Errors
+    ///
+    /// Fails on a tile without a `Tile <id>:` header, a cell other than `#`
+    /// or `.`, or a tile that isn't square.
+    pub fn parse(input: &str) -> parsing::Result<Vec<Self>> {
+        parsing::blocks(input)
+            .iter()
+            .map(|lines| Self::from_lines(lines))
             .collect()
     }
 
-    fn side_discriminant(cells: &Vec<Cell>) -> Discriminant {
-        cells
+    fn from_lines(lines: &[SourceLine]) -> parsing::Result<Self> {
+        let (header, rows) = lines.split_first().expect("blocks are never empty");
+        let header = header.trim();
+        let id = header.strip_prefix(header.text, "Tile ")?;
+        let id = id
+            .strip_suffix(':')
+            .ok_or_else(|| header.error(&id[id.len()..], "expected `:`"))?;
+        let id: TileID = header.parse(id)?;
+        let cells = rows
             .iter()
-            .map(|cell| match cell {
-                Cell::On => "#",
-                Cell::Off => ".",
+            .map(|row| {
+                row.text
+                    .char_indices()
+                    .map(|(index, ch)| match ch {
+                        '#' => Ok(Cell::On),
+                        '.' => Ok(Cell::Off),
+                        _ => Err(row.error(&row.text[index..], format!("invalid cell {ch:?}"))),
+                    })
+                    .collect()
             })
-            .collect::<Vec<&str>>()
-            .join("")
+            .collect::<parsing::Result<_>>()?;
+
+        Tile::new(id, cells).ok_or_else(|| header.error(header.text, "tile isn't square"))
     }
 
-    fn print_cells(cells: &Vec<Vec<Cell>>) {
-        for row in cells {
-            for cell in row {
-                print!("{:?} ", cell);
-            }
-            println!();
-        }
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.cells.len()
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        println!(
-            "ID: {} side discriminants: {:?}",
-            self.id, self.side_discriminants
-        );
-        Self::print_cells(&self.cells);
+    #[must_use]
+    pub fn lock(&self, transform: Transform) -> LockedTile {
+        LockedTile::from_tile(self, transform)
     }
 }
 
 impl LockedTile {
     fn from_tile(tile: &Tile, transform: Transform) -> Self {
-        let cells: Vec<Vec<Cell>> = transform.apply(tile.cells.clone());
-        let mut side_discriminants: HashMap<Side, Discriminant> = HashMap::new();
-
-        side_discriminants.insert(Side::Top, Tile::side_discriminant(&cells[0]));
-        side_discriminants.insert(Side::Bottom, Tile::side_discriminant(&cells[9]));
-        side_discriminants.insert(
-            Side::Left,
-            Tile::side_discriminant(&cells.iter().map(|row| row[0]).collect()),
-        );
-        side_discriminants.insert(
-            Side::Right,
-            Tile::side_discriminant(&cells.iter().map(|row| row[9]).collect()),
-        );
+        let cells: Vec<Vec<Cell>> = transform.apply(&tile.cells);
+        let last = cells.len() - 1;
 
         Self {
             id: tile.id,
-            cells: Self::remove_borders(&cells),
-            cells_with_borders: cells,
             transform,
-            side_discriminants,
+            top: cells[0].clone(),
+            bottom: cells[last].clone(),
+            left: cells.iter().map(|row| row[0]).collect(),
+            right: cells.iter().map(|row| row[last]).collect(),
+            cells_with_borders: cells,
         }
     }
 
-    fn remove_borders(cells: &Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
-        cells
-            .iter()
-            .skip(1)
-            .take(cells.len() - 2)
-            .map(|row| row.iter().skip(1).take(row.len() - 2).copied().collect())
-            .collect()
-    }
+    fn cells(&self) -> impl Iterator<Item = &[Cell]> {
+        let length = self.cells_with_borders.len();
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        println!("\n# Locked Tile ID {} ({:?})", self.id, self.transform);
-        println!();
-        Tile::print_cells(&self.cells_with_borders);
-        println!();
+        self.cells_with_borders[1..length - 1]
+            .iter()
+            .map(move |row| &row[1..length - 1])
     }
 }
 
 impl Transform {
-    fn from_adjacent_tiles(
-        adjacent_tiles: &HashMap<TileID, ((Side, Side), (Orientation, Orientation))>,
-    ) -> Self {
-        adjacent_tiles
-            .iter()
-            .map(|(_tile_id, (sides, _orientations))| match sides {
-                (Side::Top, Side::Bottom) => Transform::FlipVertically,
-                (Side::Top, Side::Left) => Transform::RotateRight,
-                (Side::Bottom, Side::Top) => Transform::Nothing,
-                (Side::Bottom, Side::Left) => Transform::Nothing,
-                (Side::Bottom, Side::Right) => Transform::Nothing,
-                (Side::Right, Side::Left) => Transform::Nothing,
-                (Side::Right, Side::Right) => Transform::FlipHorizontally,
-                (Side::Right, Side::Bottom) => Transform::RotateRight,
-                (Side::Left, Side::Right) => Transform::FlipHorizontally,
-                _ => panic!(format!("invalid side combination: {:?}", sides)),
-            })
-            .fold(Transform::Nothing, |acc, transform| {
-                match (acc, transform) {
-                    (Transform::Nothing, transform) => transform,
-                    (transform, Transform::Nothing) => transform,
-                    (transform, other_transform) if transform == other_transform => transform,
-                    (Transform::FlipVertically, Transform::FlipHorizontally) => Transform::FlipBoth,
-                    (Transform::FlipHorizontally, Transform::FlipVertically) => Transform::FlipBoth,
-                    (Transform::RotateRight, Transform::FlipHorizontally)
-                    | (Transform::FlipHorizontally, Transform::RotateRight) => {
-                        Transform::RotateRightAndFlipHorizontally
-                    }
-                    pair => panic!(format!("{:?} shouldn't happen", pair)),
-                }
-            })
-    }
-
-    fn apply(&self, cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
-        fn flip_horizontally(cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
+    pub const ALL: [Transform; 8] = [
+        Transform::Nothing,
+        Transform::FlipVertically,
+        Transform::FlipHorizontally,
+        Transform::FlipBoth,
+        Transform::RotateLeft,
+        Transform::RotateRight,
+        Transform::RotateLeftAndFlipHorizontally,
+        Transform::RotateRightAndFlipHorizontally,
+    ];
+
+    #[must_use]
+    pub fn apply<T: Copy>(&self, cells: &[Vec<T>]) -> Vec<Vec<T>> {
+        fn flip_horizontally<T: Copy>(cells: &[Vec<T>]) -> Vec<Vec<T>> {
             cells
                 .iter()
                 .map(|row| row.iter().rev().copied().collect())
                 .collect()
         }
 
-        fn flip_vertically(cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
-            cells.iter().rev().map(|row| row.clone()).collect()
+        fn flip_vertically<T: Copy>(cells: &[Vec<T>]) -> Vec<Vec<T>> {
+            cells.iter().rev().cloned().collect()
         }
 
-        fn rotate_left(cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
-            let mut new_cells: Vec<Vec<Cell>> = (0..cells.len()).map(|_| Vec::new()).collect();
-            let mut index: usize;
-
-            for row in &cells {
-                index = cells.len();
-                for &cell in row {
-                    index -= 1;
-                    new_cells[index].push(cell);
-                }
-            }
+        fn rotate_left<T: Copy>(cells: &[Vec<T>]) -> Vec<Vec<T>> {
+            let width = cells.first().map_or(0, Vec::len);
 
-            new_cells
+            (0..width)
+                .rev()
+                .map(|col| cells.iter().map(|row| row[col]).collect())
+                .collect()
         }
 
-        fn rotate_right(cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
-            let mut new_cells: Vec<Vec<Cell>> = (0..cells.len()).map(|_| Vec::new()).collect();
-            let mut index: usize;
-
-            for row in &cells {
-                index = 0;
-                for &cell in row {
-                    new_cells[index].push(cell);
-                    index += 1;
-                }
-            }
+        fn rotate_right<T: Copy>(cells: &[Vec<T>]) -> Vec<Vec<T>> {
+            let width = cells.first().map_or(0, Vec::len);
 
-            new_cells
+            (0..width)
+                .map(|col| cells.iter().rev().map(|row| row[col]).collect())
+                .collect()
         }
 
         match self {
-            Transform::Nothing => cells,
+            Transform::Nothing => cells.to_vec(),
             Transform::FlipVertically => flip_vertically(cells),
             Transform::FlipHorizontally => flip_horizontally(cells),
-            Transform::FlipBoth => flip_vertically(flip_horizontally(cells)),
+            Transform::FlipBoth => flip_vertically(&flip_horizontally(cells)),
             Transform::RotateLeft => rotate_left(cells),
-            Transform::RotateLeftAndFlipHorizontally => flip_horizontally(rotate_left(cells)),
+            Transform::RotateLeftAndFlipHorizontally => flip_horizontally(&rotate_left(cells)),
             Transform::RotateRight => rotate_right(cells),
-            Transform::RotateRightAndFlipHorizontally => flip_horizontally(rotate_right(cells)),
+            Transform::RotateRightAndFlipHorizontally => flip_horizontally(&rotate_right(cells)),
         }
     }
 }
 
-impl Connections {
-    fn new(tiles: &[Tile]) -> Self {
-        let mut map: HashMap<TileID, Vec<TileID>> = HashMap::new();
-        let mut max: usize = 0;
-
-        fn add_connection(
-            map: &mut HashMap<TileID, Vec<TileID>>,
-            max: &mut usize,
-            id: TileID,
-            other_id: TileID,
-        ) {
-            if let Some(existing_ids) = map.get_mut(&id) {
-                existing_ids.push(other_id);
-                if existing_ids.len() > *max {
-                    *max = existing_ids.len();
-                }
-            } else {
-                map.insert(id, vec![other_id]);
-            }
+impl Grid {
+    // Places tiles row by row, backtracking whenever an edge matches more than
+    // one candidate and the choice later leads to a dead end.
+    #[must_use]
+    pub fn assemble(tiles: &[Tile]) -> Option<Self> {
+        let length = (0..=tiles.len()).find(|length| length * length >= tiles.len())?;
+        let size = tiles.first()?.size();
+
+        if length * length != tiles.len() || tiles.iter().any(|tile| tile.size() != size) {
+            return None;
         }
 
-        for (index, tile) in tiles.iter().enumerate() {
-            for other_tile in tiles.iter().skip(index + 1) {
-                if tile.side_discriminants.keys().any(|side_discriminant| {
-                    other_tile
-                        .side_discriminants
-                        .contains_key(side_discriminant)
-                }) {
-                    add_connection(&mut map, &mut max, tile.id, other_tile.id);
-                    add_connection(&mut map, &mut max, other_tile.id, tile.id);
-                }
-            }
+        let candidates: Vec<LockedTile> = tiles
+            .iter()
+            .flat_map(|tile| {
+                Transform::ALL
+                    .iter()
+                    .map(move |&transform| tile.lock(transform))
+            })
+            .collect();
+        let mut by_top: HashMap<&[Cell], Vec<usize>> = HashMap::new();
+        let mut by_left: HashMap<&[Cell], Vec<usize>> = HashMap::new();
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            by_top.entry(&candidate.top).or_default().push(index);
+            by_left.entry(&candidate.left).or_default().push(index);
         }
 
-        Self { map, max }
+        let mut assembler = Assembler {
+            candidates: &candidates,
+            by_top,
+            by_left,
+            length,
+            placed: Vec::with_capacity(tiles.len()),
+            used: HashSet::new(),
+        };
+
+        if !assembler.place() {
+            return None;
+        }
+
+        let tiles = assembler
+            .placed
+            .chunks(length)
+            .map(|row| row.iter().map(|&index| candidates[index].clone()).collect())
+            .collect();
+
+        Some(Grid { tiles })
     }
-}
 
-impl<'a> Grid<'a> {
-    fn export(&self) -> Image {
-        let tile_length = self
+    #[must_use]
+    pub fn corner_ids(&self) -> [TileID; 4] {
+        let last = self.tiles.len() - 1;
+
+        [
+            self.tiles[0][0].id,
+            self.tiles[0][last].id,
+            self.tiles[last][0].id,
+            self.tiles[last][last].id,
+        ]
+    }
+
+    // Stitches the tiles together with their borders removed.
+    #[must_use]
+    pub fn image(&self) -> Image {
+        let cells = self
             .tiles
             .iter()
-            .nth(0)
-            .unwrap()
-            .iter()
-            .nth(0)
-            .unwrap()
-            .cells
-            .len();
-        let length: usize = tile_length * self.tiles.len();
-        let mut cells: Vec<Vec<Cell>> = (0..length).map(|_| vec![]).collect();
-        let mut output_row: usize = 0;
-
-        for grid_row in &self.tiles {
-            for input_row in 0..tile_length {
-                for tile in grid_row {
-                    for cell in tile.cells.iter().nth(input_row).unwrap() {
-                        cells[output_row].push(cell.clone())
-                    }
-                }
-                output_row += 1;
-            }
-        }
+            .flat_map(|grid_row| {
+                let rows: Vec<Vec<&[Cell]>> =
+                    grid_row.iter().map(|tile| tile.cells().collect()).collect();
+
+                (0..rows[0].len()).map(move |row| {
+                    rows.iter()
+                        .flat_map(|tile_rows| tile_rows[row].iter().copied())
+                        .collect()
+                })
+            })
+            .collect();
 
         Image { cells }
     }
+}
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        let tile_length = self
-            .tiles
-            .iter()
-            .nth(0)
-            .unwrap()
-            .iter()
-            .nth(0)
-            .unwrap()
-            .cells_with_borders
-            .len();
-        for grid_row in &self.tiles {
-            for tile in grid_row {
-                print!("[  {}  ] ", tile.id);
+struct Assembler<'a> {
+    candidates: &'a [LockedTile],
+    by_top: HashMap<&'a [Cell], Vec<usize>>,
+    by_left: HashMap<&'a [Cell], Vec<usize>>,
+    length: usize,
+    placed: Vec<usize>,
+    used: HashSet<TileID>,
+}
+
+impl Assembler<'_> {
+    fn place(&mut self) -> bool {
+        let position = self.placed.len();
+
+        if position == self.length * self.length {
+            return true;
+        }
+
+        let above = position
+            .checked_sub(self.length)
+            .map(|index| &self.candidates[self.placed[index]]);
+        let left = if position.is_multiple_of(self.length) {
+            None
+        } else {
+            Some(&self.candidates[self.placed[position - 1]])
+        };
+
+        let options: Vec<usize> = match (left, above) {
+            (Some(left), _) => self
+                .by_left
+                .get(&left.right[..])
+                .cloned()
+                .unwrap_or_default(),
+            (None, Some(above)) => self
+                .by_top
+                .get(&above.bottom[..])
+                .cloned()
+                .unwrap_or_default(),
+            (None, None) => (0..self.candidates.len()).collect(),
+        };
+
+        for index in options {
+            let candidate = &self.candidates[index];
+
+            if self.used.contains(&candidate.id)
+                || above.is_some_and(|above| above.bottom != candidate.top)
+            {
+                continue;
             }
-            println!();
-
-            for input_row in 0..tile_length {
-                for tile in grid_row {
-                    for cell in tile.cells_with_borders.iter().nth(input_row).unwrap() {
-                        print!("{:?}", cell);
-                    }
-                    print!(" ");
-                }
-                println!();
+
+            self.placed.push(index);
+            self.used.insert(candidate.id);
+
+            if self.place() {
+                return true;
             }
+
+            self.placed.pop();
+            self.used.remove(&candidate.id);
         }
+
+        false
     }
 }
 
 impl Image {
-    fn find_sea_monsters(&self) -> Vec<(usize, usize)> {
-        //                   #
-        // #    ##    ##    ###
-        //  #  #  #  #  #  #
-        let sea_monster: Vec<(usize, usize)> = vec![
-            (0, 18),
-            (1, 0),
-            (1, 5),
-            (1, 6),
-            (1, 11),
-            (1, 12),
-            (1, 17),
-            (1, 18),
-            (1, 19),
-            (2, 1),
-            (2, 4),
-            (2, 7),
-            (2, 10),
-            (2, 13),
-            (2, 16),
-        ];
+    #[must_use]
+    pub fn new(cells: Vec<Vec<Cell>>) -> Self {
+        Self { cells }
+    }
+
+    #[must_use]
+    pub fn on_count(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|&&cell| cell == Cell::On)
+            .count()
+    }
+
+    // Top-left corners of every place the pattern matches in this orientation.
+    #[must_use]
+    pub fn matches(&self, pattern: &Pattern) -> Vec<(usize, usize)> {
+        let height = self.cells.len();
+        let width = self.cells.first().map_or(0, Vec::len);
         let mut found_locations: Vec<(usize, usize)> = vec![];
 
-        for row_offset in 0..(self.cells.len() - 3) {
-            for col_offset in 0..(self.cells.len() - 20) {
-                if sea_monster
-                    .iter()
-                    .all(|(row, col)| self.is_on(row + row_offset, col + col_offset))
-                {
+        for row_offset in 0..=height.saturating_sub(pattern.height) {
+            for col_offset in 0..=width.saturating_sub(pattern.width) {
+                if pattern.offsets.iter().all(|(row, col)| {
+                    self.cells
+                        .get(row + row_offset)
+                        .and_then(|cells| cells.get(col + col_offset))
+                        == Some(&Cell::On)
+                }) {
                     found_locations.push((row_offset, col_offset));
                 }
             }
@@ -452,265 +413,151 @@ impl Image {
         found_locations
     }
 
-    fn is_on(&self, row: usize, col: usize) -> bool {
-        self.cells[row][col] == Cell::On
+    // Tries all eight orientations, returning the first one in which the
+    // pattern appears along with where it matched.
+    #[must_use]
+    pub fn find(&self, pattern: &Pattern) -> Option<(Transform, Vec<(usize, usize)>)> {
+        Transform::ALL.iter().find_map(|&transform| {
+            let locations = self.transform(transform).matches(pattern);
+
+            if locations.is_empty() {
+                None
+            } else {
+                Some((transform, locations))
+            }
+        })
     }
 
-    fn flip(&mut self) {
-        for row in self.cells.iter_mut() {
-            *row = row.iter().rev().copied().collect::<Vec<Cell>>();
+    #[must_use]
+    pub fn transform(&self, transform: Transform) -> Self {
+        Self {
+            cells: transform.apply(&self.cells),
         }
     }
 
-    fn rotate(&mut self) {
-        let mut new_cells: Vec<Vec<Cell>> = (0..self.cells.len()).map(|_| vec![]).collect();
-        let mut index: usize;
-
-        for row in &self.cells {
-            index = 0;
-            for &cell in row {
-                new_cells[index].push(cell);
-                index += 1;
-            }
-        }
+    // Lit cells that aren't part of any match of the pattern.
+    #[must_use]
+    pub fn roughness(&self, pattern: &Pattern) -> usize {
+        let Some((transform, locations)) = self.find(pattern) else {
+            return self.on_count();
+        };
+        let covered: HashSet<(usize, usize)> = locations
+            .iter()
+            .flat_map(|&(row, col)| {
+                pattern
+                    .offsets
+                    .iter()
+                    .map(move |(dr, dc)| (row + dr, col + dc))
+            })
+            .collect();
 
-        self.cells = new_cells;
+        self.transform(transform).on_count() - covered.len()
     }
+}
 
-    fn on_count(&self) -> usize {
-        let mut count: usize = 0;
-
+impl std::fmt::Display for Image {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for row in &self.cells {
             for cell in row {
-                if cell == &Cell::On {
-                    count += 1
-                }
+                write!(formatter, "{cell:?}")?;
             }
+            writeln!(formatter)?;
         }
 
-        count
+        Ok(())
     }
+}
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for row in &self.cells {
-            for cell in row {
-                match cell {
-                    Cell::On => print!("#"),
-                    Cell::Off => print!("."),
-                }
-            }
-            println!();
+impl std::str::FromStr for Pattern {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let offsets: Vec<(usize, usize)> = string
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|&(_col, ch)| ch == '#')
+                    .map(move |(col, _ch)| (row, col))
+            })
+            .collect();
+
+        if offsets.is_empty() {
+            return Err("a pattern needs at least one `#`".into());
         }
+
+        Ok(Pattern {
+            height: offsets.iter().map(|(row, _col)| row + 1).max().unwrap_or(0),
+            width: offsets.iter().map(|(_row, col)| col + 1).max().unwrap_or(0),
+            offsets,
+        })
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let tiles: Vec<Tile> = Tile::parse(input);
-    let connections: Connections = Connections::new(&tiles);
-    let corner_tiles: Vec<&Tile> = find_corner_tiles(&tiles, &connections);
-
-    corner_tiles.iter().map(|tile| tile.id).product()
-}
-
-fn find_corner_tiles<'a>(tiles: &'a [Tile], connections: &Connections) -> Vec<&'a Tile> {
-    let mut excluded_ids: Vec<TileID> = Vec::new();
-
-    for (id, other_ids) in &connections.map {
-        if other_ids.len() == connections.max {
-            excluded_ids.push(*id);
-            for id in other_ids {
-                excluded_ids.push(*id);
-            }
-        }
-    }
-
-    tiles
-        .iter()
-        .filter(|tile| !excluded_ids.contains(&&tile.id))
-        .collect()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let tiles: Vec<Tile> = Tile::parse(input);
-
-    let mut all_locked_tiles: Vec<LockedTile> = vec![];
-
-    for tile in &tiles {
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::Nothing));
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::RotateRight));
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::RotateLeft));
-        all_locked_tiles.push(LockedTile::from_tile(
-            tile,
-            Transform::RotateLeftAndFlipHorizontally,
-        ));
-        all_locked_tiles.push(LockedTile::from_tile(
-            tile,
-            Transform::RotateRightAndFlipHorizontally,
-        ));
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::FlipVertically));
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::FlipHorizontally));
-        all_locked_tiles.push(LockedTile::from_tile(tile, Transform::FlipBoth));
-    }
-
-    let grid: Grid = assemble_grid(&tiles, &all_locked_tiles);
-    //    grid.print();
-
-    let mut image: Image = grid.export();
-    //    image.print();
-
-    for _rotations in 0..3 {
-        image.rotate();
-        for _flips in 0..2 {
-            image.flip();
-            let sea_monsters = image.find_sea_monsters();
-
-            if sea_monsters.len() > 0 {
-                return image.on_count() - sea_monsters.len() * 15;
-            }
-        }
-    }
-
-    panic!("if we got here, we failed :'(");
+    try_part2(input).unwrap()
 }
 
-fn assemble_grid<'a>(
-    all_tiles: &Vec<Tile>,
-    possible_locked_tiles: &'a Vec<LockedTile>,
-) -> Grid<'a> {
-    let length: usize = (all_tiles.len() as f32).sqrt() as usize;
-    let mut tiles: Vec<Vec<&LockedTile>> = (0..length).map(|_| vec![]).collect();
-    let mut used_tile_ids: Vec<TileID> = vec![];
-
-    let connections: Connections = Connections::new(&all_tiles);
-    let corner_tile: &Tile = find_corner_tiles(&all_tiles, &connections)[0];
-
-    let adjacent_tiles = find_adjacent_tiles(&corner_tile, &all_tiles);
-    let transform = Transform::from_adjacent_tiles(&adjacent_tiles);
-    let corner_locked_tile = possible_locked_tiles
-        .iter()
-        .find(|tile| tile.id == corner_tile.id && tile.transform == transform)
-        .unwrap();
-
-    used_tile_ids.push(corner_tile.id);
-    tiles[0].push(&corner_locked_tile);
-
-    #[allow(dead_code)]
-    fn print_tiles(tiles: &Vec<Vec<LockedTile>>) {
-        for row in tiles {
-            for tile in row {
-                println!("{} ", tile.id);
-                Tile::print_cells(&tile.cells_with_borders);
-            }
-            println!();
-        }
-        println!();
-    }
-
-    for col in 0..length {
-        for row in 1..length {
-            //            print_tiles(&tiles);
-            let previous_locked_tile = &tiles[row - 1][col];
-            let next_locked_tile = next_locked_tile(
-                &possible_locked_tiles,
-                &used_tile_ids,
-                Side::Top,
-                previous_locked_tile.side_discriminants[&Side::Bottom].clone(),
-            );
-
-            //            next_locked_tile.print();
-            used_tile_ids.push(next_locked_tile.id);
-            tiles[row].push(next_locked_tile);
-        }
+/// # Errors
+///
+/// Fails on input [`Tile::parse`] rejects, or tiles that don't fit together.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let grid = assemble(input)?;
 
-        if col < length - 1 {
-            let next_column_locked_tile = next_locked_tile(
-                &possible_locked_tiles,
-                &used_tile_ids,
-                Side::Left,
-                tiles[0][col].side_discriminants[&Side::Right].clone(),
-            );
-            //            next_column_locked_tile.print();
-            used_tile_ids.push(next_column_locked_tile.id);
-            tiles[0].push(next_column_locked_tile);
-        }
-    }
-
-    Grid { tiles }
+    Ok(grid.corner_ids().iter().product())
 }
 
-fn next_locked_tile<'a>(
-    possible_locked_tiles: &'a Vec<LockedTile>,
-    used_tile_ids: &Vec<TileID>,
-    target_side: Side,
-    discriminant: Discriminant,
-) -> &'a LockedTile {
-    possible_locked_tiles
-        .iter()
-        .filter(|tile| !used_tile_ids.contains(&tile.id))
-        .find(|tile| tile.side_discriminants[&target_side] == discriminant)
-        .unwrap()
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+///
+/// # Panics
+///
+/// Never in practice: [`SEA_MONSTER`] is a valid pattern.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let grid = assemble(input)?;
+    let sea_monster: Pattern = SEA_MONSTER.parse().unwrap();
+
+    Ok(grid.image().roughness(&sea_monster))
 }
 
-fn find_adjacent_tiles(
-    tile: &Tile,
-    tiles: &Vec<Tile>,
-) -> HashMap<TileID, ((Side, Side), (Orientation, Orientation))> {
-    let mut adjacent_tiles: HashMap<TileID, ((Side, Side), (Orientation, Orientation))> =
-        HashMap::new();
-
-    for (side_discriminant, &(side, orientation)) in &tile.side_discriminants {
-        if let Some(matching_tile) = tiles
-            .iter()
-            .filter(|other_tile| other_tile.id != tile.id)
-            .find(|tile| {
-                tile.side_discriminants
-                    .keys()
-                    .collect::<Vec<&Discriminant>>()
-                    .contains(&side_discriminant)
-            })
-        {
-            let existing = adjacent_tiles.get(&matching_tile.id);
-            match existing {
-                // TODO: figure out how to not repeat this...
-                None => {
-                    let (matching_tile_side, matching_tile_orientation) =
-                        matching_tile.side_discriminants[side_discriminant];
-
-                    adjacent_tiles.insert(
-                        matching_tile.id,
-                        (
-                            (side, matching_tile_side),
-                            (orientation, matching_tile_orientation),
-                        ),
-                    );
-                }
-                Some((_sides, (Orientation::Reversed, Orientation::Reversed))) => {
-                    let (matching_tile_side, matching_tile_orientation) =
-                        matching_tile.side_discriminants[side_discriminant];
-
-                    adjacent_tiles.insert(
-                        matching_tile.id,
-                        (
-                            (side, matching_tile_side),
-                            (orientation, matching_tile_orientation),
-                        ),
-                    );
-                }
-                _ => {}
-            }
-        }
-    }
+fn assemble(input: &str) -> Result<Grid, PuzzleError> {
+    let tiles: Vec<Tile> = Tile::parse(input).map_err(PuzzleError::on_day(DAY))?;
 
-    adjacent_tiles
+    Grid::assemble(&tiles).ok_or_else(|| PuzzleError::new(DAY, "the tiles don't fit together"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse_cells(lines: &[&str]) -> Option<Vec<Vec<Cell>>> {
+        lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| match ch {
+                        '#' => Some(Cell::On),
+                        '.' => Some(Cell::Off),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     #[test]
     fn part1_example() {
         assert_eq!(part1(input()), 20899048083289)
@@ -721,6 +568,51 @@ mod tests {
         assert_eq!(part2(input()), 273)
     }
 
+    #[test]
+    fn assemble_transformed_tiles() {
+        let tiles: Vec<Tile> = Tile::parse(input())
+            .unwrap()
+            .iter()
+            .zip(Transform::ALL.iter().cycle())
+            .map(|(tile, transform)| Tile::new(tile.id, transform.apply(&tile.cells)).unwrap())
+            .collect();
+        let grid = Grid::assemble(&tiles).unwrap();
+        let mut corners = grid.corner_ids();
+        corners.sort_unstable();
+
+        assert_eq!(corners, [1171, 1951, 2971, 3079]);
+        assert_eq!(grid.image().on_count(), 303);
+    }
+
+    #[test]
+    fn rejects_invalid_tiles() {
+        assert!("Tile 7:\n#.\n.x".parse::<Tile>().is_err());
+        assert!("Tile 7:\n#.\n.#".parse::<Tile>().is_ok());
+        assert_eq!(
+            Tile::parse("Tile 1:\n#\n\nTile 2:\n?")
+                .unwrap_err()
+                .to_string(),
+            "line 5, column 1: invalid cell '?'"
+        );
+    }
+
+    #[test]
+    fn find_pattern_in_any_orientation() {
+        let arrow: Pattern = "#.\n##\n#.".parse().unwrap();
+        let image = Image::new(parse_cells(&["....", ".###", "..#.", "...."]).unwrap());
+
+        assert_eq!(image.matches(&arrow), vec![]);
+        assert_eq!(
+            image.find(&arrow),
+            Some((Transform::RotateLeft, vec![(0, 1)]))
+        );
+        assert_eq!(image.roughness(&arrow), 0);
+        assert_eq!(
+            Image::new(parse_cells(&["#."]).unwrap()).roughness(&arrow),
+            1
+        );
+    }
+
     fn input() -> &'static str {
         "\
 Tile 2311:
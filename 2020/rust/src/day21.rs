@@ -1,7 +1,11 @@
+use crate::parsing::{self, ParseError};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::str::FromStr;
 
+const DAY: u8 = 21;
+
 #[derive(Debug)]
 struct Food {
     ingredients: HashSet<String>,
@@ -16,18 +20,18 @@ struct Input {
 }
 
 impl FromStr for Input {
-    type Err = ();
+    type Err = ParseError;
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
+    fn from_str(input: &str) -> parsing::Result<Self> {
         let mut ingredients: HashMap<String, usize> = HashMap::new();
         let mut allergens: HashSet<String> = HashSet::new();
         let mut foods: Vec<Food> = Vec::new();
 
-        for line in input.lines() {
-            let mut parts = line.split(" (contains ");
+        for line in parsing::lines(input) {
+            let fields = line.trim().fields("{} (contains {})")?;
             let mut food_ingredients: HashSet<String> = HashSet::new();
 
-            for ingredient in parts.next().unwrap().split(' ') {
+            for ingredient in fields[0].split(' ') {
                 *ingredients.entry(ingredient.to_owned()).or_insert(0) += 1;
 
                 food_ingredients.insert(ingredient.to_owned());
@@ -35,7 +39,7 @@ impl FromStr for Input {
 
             let mut food_allergens: HashSet<String> = HashSet::new();
 
-            for allergen in parts.next().unwrap().strip_suffix(")").unwrap().split(", ") {
+            for allergen in fields[1].split(", ") {
                 allergens.insert(allergen.to_owned());
                 food_allergens.insert(allergen.to_owned());
             }
@@ -54,12 +58,31 @@ impl FromStr for Input {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
-pub fn part1(input_string: &str) -> usize {
-    let input: Input = input_string.parse().unwrap();
-    let allergen_map: HashMap<&String, &String> = map_ingredients_to_allergens(&input);
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
 
-    input
+/// # Errors
+///
+/// Fails on a food that isn't `<ingredients> (contains <allergens>)`, or
+/// when the allergens can't each be pinned to one ingredient.
+pub fn try_part1(input_string: &str) -> Result<usize, PuzzleError> {
+    let input: Input = input_string.parse().map_err(PuzzleError::on_day(DAY))?;
+    let allergen_map: HashMap<&String, &String> = map_ingredients_to_allergens(&input)?;
+
+    Ok(input
         .ingredients
         .iter()
         .filter_map(|(ingredient, count)| {
@@ -69,10 +92,10 @@ pub fn part1(input_string: &str) -> usize {
                 Some(count)
             }
         })
-        .sum()
+        .sum())
 }
 
-fn map_ingredients_to_allergens(input: &Input) -> HashMap<&String, &String> {
+fn map_ingredients_to_allergens(input: &Input) -> Result<HashMap<&String, &String>, PuzzleError> {
     let mut possibles: HashMap<&String, Vec<&String>> = HashMap::new();
     let mut definites: HashMap<&String, &String> = HashMap::new();
 
@@ -86,6 +109,7 @@ fn map_ingredients_to_allergens(input: &Input) -> HashMap<&String, &String> {
         }
 
         let unmatched_allergens: Vec<&String> = possibles.keys().copied().collect();
+        let matched = definites.len();
 
         for allergen in &unmatched_allergens {
             if let Some(ingredients) = possibles.get(allergen) {
@@ -98,9 +122,16 @@ fn map_ingredients_to_allergens(input: &Input) -> HashMap<&String, &String> {
                 }
             }
         }
+
+        if definites.len() == matched {
+            return Err(PuzzleError::new(
+                DAY,
+                "the allergens can't each be pinned to one ingredient",
+            ));
+        }
     }
 
-    definites
+    Ok(definites)
 }
 
 fn find_overlap<'a>(allergen: &'a str, foods: &'a [Food]) -> Vec<&'a String> {
@@ -120,19 +151,21 @@ fn find_overlap<'a>(allergen: &'a str, foods: &'a [Food]) -> Vec<&'a String> {
     common.into_iter().collect()
 }
 
-#[must_use]
-pub fn part2(input_string: &str) -> String {
-    let input: Input = input_string.parse().unwrap();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input_string: &str) -> Result<String, PuzzleError> {
+    let input: Input = input_string.parse().map_err(PuzzleError::on_day(DAY))?;
     let mut allergen_list: Vec<(&String, &String)> =
-        map_ingredients_to_allergens(&input).into_iter().collect();
+        map_ingredients_to_allergens(&input)?.into_iter().collect();
 
     allergen_list.sort_by(|a, b| a.1.cmp(b.1));
 
-    allergen_list
+    Ok(allergen_list
         .into_iter()
         .map(|(ingredient, _allergen)| ingredient.as_str())
         .collect::<Vec<&str>>()
-        .join(",")
+        .join(","))
 }
 
 #[cfg(test)]
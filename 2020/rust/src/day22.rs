@@ -1,6 +1,9 @@
-use std::collections::HashSet;
-use std::collections::VecDeque;
-use std::str::FromStr;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+const DAY: u8 = 22;
 
 #[derive(Debug)]
 struct Player {
@@ -8,169 +11,297 @@ struct Player {
     deck: VecDeque<u8>,
 }
 
-impl FromStr for Player {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut lines = input.trim().lines();
-        let id: u8 = lines
-            .next()
-            .and_then(|line| line.strip_prefix("Player "))
-            .and_then(|line| line.strip_suffix(":"))
-            .and_then(|line| line.parse().ok())
-            .unwrap();
-        let deck: VecDeque<u8> = lines.map(|line| line.parse().unwrap()).collect();
+impl Player {
+    fn parse(lines: &[SourceLine]) -> parsing::Result<Self> {
+        let (header, cards) = lines.split_first().expect("blocks are never empty");
+        let header = header.trim();
+        let id = header.fields("Player {}:")?[0];
+        let id: u8 = header.parse(id)?;
+        let deck = cards
+            .iter()
+            .map(|card| card.parse(card.text))
+            .collect::<parsing::Result<_>>()?;
 
         Ok(Self { id, deck })
     }
 }
 
-fn play_round(deck1: &mut VecDeque<u8>, deck2: &mut VecDeque<u8>) {
-    //    println!("Player 1's deck: {:?}", &deck1);
-    //    println!("Player 2's deck: {:?}", &deck2);
-
-    let card1 = deck1.pop_front().unwrap();
-    let card2 = deck2.pop_front().unwrap();
-
-    //    println!("Player 1 plays: {}", &card1);
-    //    println!("Player 2 plays: {}", &card2);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winner {
+    Player1,
+    Player2,
+}
 
-    if card1 > card2 {
-        //        println!("Player 1 wins the round!");
-        deck1.push_back(card1);
-        deck1.push_back(card2);
-    } else {
-        //        println!("Player 2 wins the round!");
-        deck2.push_back(card2);
-        deck2.push_back(card1);
+impl fmt::Display for Winner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Winner::Player1 => write!(f, "1"),
+            Winner::Player2 => write!(f, "2"),
+        }
     }
 }
 
-fn play_recursive_round(
-    deck1: &mut VecDeque<u8>,
-    deck2: &mut VecDeque<u8>,
-    game: usize,
-    _round: usize,
-) {
-    //    println!("Player 1's deck: {:?}", &deck1);
-    //    println!("Player 2's deck: {:?}", &deck2);
-
-    let card1 = deck1.pop_front().unwrap();
-    let card2 = deck2.pop_front().unwrap();
-
-    //    println!("Player 1 plays: {}", &card1);
-    //    println!("Player 2 plays: {}", &card2);
+// Counters for one top-level game and everything played beneath it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub rounds: usize,
+    pub games_played: usize,
+    pub sub_games: usize,
+    pub cache_hits: usize,
+    pub repeated_states: usize,
+    pub max_depth: usize,
+}
 
-    if deck1.len() >= card1 as usize && deck2.len() >= card2 as usize {
-        //        println!("Playing a sub-game to determine the winner...");
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    pub winner: Winner,
+    pub deck: Vec<u8>,
+    pub stats: Stats,
+    pub log: Option<String>,
+}
 
-        let mut deck1_copy = deck1.iter().take(card1 as usize).copied().collect();
-        let mut deck2_copy = deck2.iter().take(card2 as usize).copied().collect();
+impl Outcome {
+    #[must_use]
+    pub fn score(&self) -> usize {
+        let count = self.deck.len();
+        self.deck
+            .iter()
+            .enumerate()
+            .map(|(index, &card)| card as usize * (count - index))
+            .sum()
+    }
+}
 
-        play_game(&mut deck1_copy, &mut deck2_copy, true, game + 1);
+// Plays Combat, optionally the recursive variant. Sub-games only ever need
+// their winner, so those are cached by their starting decks and survive
+// between calls to `play`.
+pub struct Combat {
+    recursive: bool,
+    cache: Option<HashMap<Vec<u8>, Winner>>,
+    log: Option<String>,
+    stats: Stats,
+}
 
-        if deck1_copy.len() == 0 {
-            //            println!("Player 2 wins game {} and therefore game {}, round {}!", game, game - 1, round);
-            deck2.push_back(card2);
-            deck2.push_back(card1);
-        } else {
-            //            println!("Player 1 wins game {} and therefore game {}, round {}!", game, game - 1, round);
-            deck1.push_back(card1);
-            deck1.push_back(card2);
-        }
-    } else {
-        if card1 > card2 {
-            //            println!("Player 1 wins the round!");
-            deck1.push_back(card1);
-            deck1.push_back(card2);
-        } else {
-            //            println!("Player 2 wins the round!");
-            deck2.push_back(card2);
-            deck2.push_back(card1);
+impl Combat {
+    #[must_use]
+    pub fn classic() -> Self {
+        Self {
+            recursive: false,
+            cache: None,
+            log: None,
+            stats: Stats::default(),
         }
     }
-}
 
-fn play_game(
-    mut deck1: &mut VecDeque<u8>,
-    mut deck2: &mut VecDeque<u8>,
-    recursive: bool,
-    game: usize,
-) {
-    //    println!("\n=== Game {} ===", game);
+    #[must_use]
+    pub fn recursive() -> Self {
+        Self {
+            recursive: true,
+            cache: Some(HashMap::new()),
+            ..Self::classic()
+        }
+    }
 
-    let mut states_seen: HashSet<Vec<u8>> = HashSet::new();
-    let mut round: usize = 1;
+    #[must_use]
+    pub fn without_cache(self) -> Self {
+        Self {
+            cache: None,
+            ..self
+        }
+    }
 
-    loop {
-        let state: Vec<u8> = deck1
-            .iter()
-            .chain(vec![0].iter())
-            .chain(deck2.iter())
-            .copied()
-            .collect();
+    // Records every game in the same format as the puzzle's walkthrough.
+    #[must_use]
+    pub fn with_log(self) -> Self {
+        Self {
+            log: Some(String::new()),
+            ..self
+        }
+    }
 
-        if deck1.len() == 0 || deck2.len() == 0 {
-            break;
+    pub fn play(&mut self, deck1: &[u8], deck2: &[u8]) -> Outcome {
+        self.stats = Stats::default();
+        if let Some(log) = &mut self.log {
+            log.clear();
         }
-        if states_seen.contains(&state) {
-            deck2.clear();
-            break;
+
+        let mut deck1: VecDeque<u8> = deck1.iter().copied().collect();
+        let mut deck2: VecDeque<u8> = deck2.iter().copied().collect();
+        let winner = self.game(&mut deck1, &mut deck2, 1);
+
+        self.note(|| "== Post-game results ==".to_string());
+        self.note(|| format!("Player 1's deck: {}", cards(&deck1)));
+        self.note(|| format!("Player 2's deck: {}", cards(&deck2)));
+
+        Outcome {
+            winner,
+            deck: match winner {
+                Winner::Player1 => deck1.into(),
+                Winner::Player2 => deck2.into(),
+            },
+            stats: self.stats.clone(),
+            log: self.log.clone(),
         }
+    }
 
-        states_seen.insert(state);
+    fn game(&mut self, deck1: &mut VecDeque<u8>, deck2: &mut VecDeque<u8>, depth: usize) -> Winner {
+        self.stats.games_played += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        let game = self.stats.games_played;
+        self.note(|| format!("=== Game {game} ===\n"));
+
+        let mut states_seen: HashSet<Vec<u8>> = HashSet::new();
+        let mut round = 1;
+
+        let winner = loop {
+            if deck1.is_empty() {
+                break Winner::Player2;
+            }
+            if deck2.is_empty() {
+                break Winner::Player1;
+            }
+            // Only Recursive Combat ends a game on repeated decks.
+            if self.recursive && !states_seen.insert(state(deck1, deck2)) {
+                self.stats.repeated_states += 1;
+                self.note(|| format!("Decks repeated, so player 1 wins game {game}!"));
+                break Winner::Player1;
+            }
+
+            self.stats.rounds += 1;
+            self.note(|| format!("-- Round {round} (Game {game}) --"));
+            self.note(|| format!("Player 1's deck: {}", cards(deck1)));
+            self.note(|| format!("Player 2's deck: {}", cards(deck2)));
+
+            let card1 = deck1.pop_front().unwrap();
+            let card2 = deck2.pop_front().unwrap();
+            self.note(|| format!("Player 1 plays: {card1}"));
+            self.note(|| format!("Player 2 plays: {card2}"));
+
+            let round_winner =
+                if self.recursive && deck1.len() >= card1 as usize && deck2.len() >= card2 as usize
+                {
+                    let sub1: VecDeque<u8> = deck1.iter().take(card1 as usize).copied().collect();
+                    let sub2: VecDeque<u8> = deck2.iter().take(card2 as usize).copied().collect();
+                    self.note(|| "Playing a sub-game to determine the winner...\n".to_string());
+                    let winner = self.sub_game(sub1, sub2, depth + 1);
+                    self.note(|| format!("...anyway, back to game {game}."));
+                    winner
+                } else if card1 > card2 {
+                    Winner::Player1
+                } else {
+                    Winner::Player2
+                };
+
+            self.note(|| format!("Player {round_winner} wins round {round} of game {game}!\n"));
+            match round_winner {
+                Winner::Player1 => deck1.extend([card1, card2].iter()),
+                Winner::Player2 => deck2.extend([card2, card1].iter()),
+            }
+            round += 1;
+        };
+
+        self.note(|| format!("The winner of game {game} is player {winner}!\n"));
+        winner
+    }
 
-        //        println!("\n-- Round {} (Game {}) --", round, game);
+    fn sub_game(
+        &mut self,
+        mut deck1: VecDeque<u8>,
+        mut deck2: VecDeque<u8>,
+        depth: usize,
+    ) -> Winner {
+        self.stats.sub_games += 1;
+        let key = state(&deck1, &deck2);
+
+        if let Some(&winner) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            self.stats.cache_hits += 1;
+            self.note(|| format!("(cached) The winner of that sub-game is player {winner}!\n"));
+            return winner;
+        }
 
-        if recursive {
-            play_recursive_round(&mut deck1, &mut deck2, game, round);
-        } else {
-            play_round(&mut deck1, &mut deck2);
+        let winner = self.game(&mut deck1, &mut deck2, depth);
+        if let Some(cache) = &mut self.cache {
+            cache.insert(key, winner);
         }
+        winner
+    }
 
-        round += 1;
+    // Only formats the line when logging, which keeps the plain game fast.
+    fn note(&mut self, line: impl FnOnce() -> String) {
+        if let Some(log) = &mut self.log {
+            log.push_str(&line());
+            log.push('\n');
+        }
     }
 }
 
-fn compute_score(deck: &VecDeque<u8>) -> usize {
-    let count = deck.len();
-    deck.iter().enumerate().fold(0, |sum, (index, &card)| {
-        sum + (card as usize) * (count - index)
-    })
+// Both decks with a separator no card uses.
+fn state(deck1: &VecDeque<u8>, deck2: &VecDeque<u8>) -> Vec<u8> {
+    deck1.iter().chain(&[0]).chain(deck2).copied().collect()
 }
 
-#[must_use]
-pub fn part1(input: &str) -> usize {
-    let player_inputs: Vec<&str> = input.split("\n\n").collect();
-    let mut player1: Player = player_inputs.get(0).unwrap().parse().unwrap();
-    let mut player2: Player = player_inputs.get(1).unwrap().parse().unwrap();
-
-    play_game(&mut player1.deck, &mut player2.deck, false, 1);
+fn cards(deck: &VecDeque<u8>) -> String {
+    deck.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    if player1.deck.len() == 0 {
-        compute_score(&player2.deck)
-    } else {
-        compute_score(&player1.deck)
+fn parse(input: &str) -> Result<(Vec<u8>, Vec<u8>), PuzzleError> {
+    let mut players: Vec<Player> = parsing::blocks(input)
+        .iter()
+        .map(|lines| Player::parse(lines))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    players.sort_by_key(|player| player.id);
+
+    match &players[..] {
+        [first, second] if first.id == 1 && second.id == 2 => {
+            Ok((first.deck.clone().into(), second.deck.clone().into()))
+        }
+        _ => Err(PuzzleError::new(DAY, "expected decks for players 1 and 2")),
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
+#[must_use]
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let player_inputs: Vec<&str> = input.split("\n\n").collect();
-    let mut player1: Player = player_inputs.get(0).unwrap().parse().unwrap();
-    let mut player2: Player = player_inputs.get(1).unwrap().parse().unwrap();
+    try_part2(input).unwrap()
+}
 
-    play_game(&mut player1.deck, &mut player2.deck, true, 1);
+/// # Errors
+///
+/// Fails unless the input is a `Player 1:` deck and a `Player 2:` deck of
+/// card numbers.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (deck1, deck2) = parse(input)?;
 
-    if player1.deck.len() == 0 {
-        compute_score(&player2.deck)
-    } else {
-        compute_score(&player1.deck)
-    }
+    Ok(Combat::classic().play(&deck1, &deck2).score())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (deck1, deck2) = parse(input)?;
+
+    Ok(Combat::recursive().play(&deck1, &deck2).score())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Rng;
 
     fn input() -> &'static str {
         "\
@@ -217,4 +348,94 @@ Player 2:
             105
         );
     }
+
+    #[test]
+    fn classic_stats() {
+        let (deck1, deck2) = parse(input()).unwrap();
+        let outcome = Combat::classic().play(&deck1, &deck2);
+
+        assert_eq!(
+            outcome.stats,
+            Stats {
+                rounds: 29,
+                games_played: 1,
+                max_depth: 1,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn stats_and_log() {
+        let (deck1, deck2) = parse(input()).unwrap();
+        let outcome = Combat::recursive().with_log().play(&deck1, &deck2);
+
+        assert_eq!(outcome.winner, Winner::Player2);
+        assert_eq!(outcome.deck, vec![7, 5, 6, 2, 4, 1, 10, 8, 9, 3]);
+        // The last sub-game repeats an earlier one, so the cache skips it.
+        assert_eq!(
+            outcome.stats,
+            Stats {
+                rounds: 28,
+                games_played: 4,
+                sub_games: 4,
+                cache_hits: 1,
+                repeated_states: 0,
+                max_depth: 3,
+            }
+        );
+        assert_eq!(
+            Combat::recursive()
+                .without_cache()
+                .play(&deck1, &deck2)
+                .stats,
+            Stats {
+                rounds: 29,
+                games_played: 5,
+                cache_hits: 0,
+                ..outcome.stats.clone()
+            }
+        );
+
+        let log = outcome.log.unwrap();
+        assert!(log.starts_with(
+            "\
+=== Game 1 ===
+
+-- Round 1 (Game 1) --
+Player 1's deck: 9, 2, 6, 3, 1
+Player 2's deck: 5, 8, 4, 7, 10
+Player 1 plays: 9
+Player 2 plays: 5
+Player 1 wins round 1 of game 1!
+"
+        ));
+        assert!(log.contains("...anyway, back to game 1.\nPlayer 2 wins round 9 of game 1!"));
+        assert!(log.ends_with("Player 2's deck: 7, 5, 6, 2, 4, 1, 10, 8, 9, 3\n"));
+    }
+
+    #[test]
+    fn cache_gives_the_same_results() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        let mut cached = Combat::recursive();
+
+        for _ in 0..20 {
+            let mut cards: Vec<u8> = (1..=20).collect();
+            for index in (1..cards.len()).rev() {
+                cards.swap(index, rng.below(index + 1));
+            }
+            let (deck1, deck2) = cards.split_at(10);
+
+            let fresh = Combat::recursive().without_cache().play(deck1, deck2);
+            let outcome = cached.play(deck1, deck2);
+
+            assert_eq!(
+                (outcome.winner, outcome.score()),
+                (fresh.winner, fresh.score())
+            );
+            assert_eq!(fresh.stats.cache_hits, 0);
+            assert_eq!(outcome.stats.sub_games, fresh.stats.sub_games);
+            assert!(outcome.stats.games_played <= fresh.stats.games_played);
+        }
+    }
 }
@@ -1,164 +1,237 @@
-type Cup = usize;
-
-#[derive(Debug)]
-struct Cups {
-    data: Vec<Cup>,
-    next: Vec<Cup>,
-    current_cup: usize,
-    current_move: usize,
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use std::convert::TryFrom;
+
+const DAY: u8 = 23;
+
+// The crab's game as a linked ring: `next[cup]` is the label of the cup
+// clockwise of `cup`. Labels run 1..=len and slot 0 is unused, so a million
+// cups take 4MB and each move only rewrites three links.
+#[derive(Clone, Debug)]
+pub struct CrabCups {
+    next: Vec<u32>,
+    current: u32,
+    highest: u32,
+    held: Vec<u32>,
 }
 
-impl Cups {
-    fn new(input: &str, size: usize) -> Self {
-        let mut data: Vec<Cup> = Vec::with_capacity(size);
-
-        for ch in input.trim().chars() {
-            let cup = ch.to_digit(10).unwrap() as Cup;
-            data.push(cup);
-        }
-
-        for cup in (*data.iter().max().unwrap() + 1)..(size + 1) {
-            data.push(cup);
+impl CrabCups {
+    /// Starts with `labels` in clockwise order followed by the remaining
+    /// labels up to `cups` in increasing order; the first label is current.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `labels` is a non-empty permutation of `1..=n` for some
+    /// `n <= cups`, and there are more than `pick_up + 1` cups.
+    #[must_use]
+    pub fn new(labels: &[u32], cups: usize, pick_up: usize) -> Self {
+        assert!(!labels.is_empty(), "no labels");
+        let mut seen = vec![false; labels.len() + 1];
+        for &label in labels {
+            let slot = seen.get_mut(label as usize).filter(|_| label > 0);
+            assert!(
+                slot.is_some_and(|seen| !std::mem::replace(seen, true)),
+                "labels must be a permutation of 1..={}",
+                labels.len()
+            );
         }
-
-        let mut next: Vec<Cup> = vec![0; data.len() + 1];
-        for index in 0..(data.len() - 1) {
-            next[data[index]] = data[index + 1];
+        assert!(labels.len() <= cups, "more labels than cups");
+        assert!(pick_up + 1 < cups, "too few cups to pick up {}", pick_up);
+        let cups = u32::try_from(cups).expect("too many cups");
+
+        let first_extra = labels.iter().max().unwrap() + 1;
+        let order = labels.iter().copied().chain(first_extra..=cups);
+        let mut next = vec![0; cups as usize + 1];
+        let mut previous = labels[0];
+        for cup in order.skip(1) {
+            next[previous as usize] = cup;
+            previous = cup;
         }
-        next[data[data.len() - 1]] = data[0];
+        next[previous as usize] = labels[0];
 
-        let current_cup = data[0];
-
-        Cups {
-            data,
+        Self {
             next,
-            current_cup,
-            current_move: 0,
+            current: labels[0],
+            highest: cups,
+            held: vec![0; pick_up],
         }
     }
 
-    fn stepn(&mut self, count: usize) {
-        for _index in 0..count {
-            self.step();
-        }
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.next.len() - 1
     }
 
-    fn step(&mut self) {
-        self.current_move += 1;
-
-        let mut moving_cups: Vec<Cup> = Vec::with_capacity(3);
-        let mut cup: Cup = self.current_cup;
-        for _ in 0..3 {
-            cup = self.next[cup];
-            moving_cups.push(cup);
-        }
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-        let destination_cup: Cup = self.destination(&moving_cups);
+    #[must_use]
+    pub fn current(&self) -> u32 {
+        self.current
+    }
 
-        //        self.print_step(&moving_cups, destination_cup);
-        self.move_cups(&moving_cups, destination_cup);
+    // Every other cup, clockwise starting just after `cup`.
+    pub fn after(&self, cup: u32) -> impl Iterator<Item = u32> + '_ {
+        let mut cup = cup;
+        (1..self.len()).map(move |_| {
+            cup = self.next[cup as usize];
+            cup
+        })
+    }
 
-        self.current_cup = self.next[self.current_cup];
+    pub fn play(&mut self, moves: usize) {
+        for _ in 0..moves {
+            self.step();
+        }
     }
 
-    fn destination(&self, moving_cups: &[Cup]) -> Cup {
-        let mut destination_cup: Cup = self.previous_cup(self.current_cup);
+    pub fn step(&mut self) {
+        let next = &mut self.next;
 
+        let mut last = self.current;
+        for held in &mut self.held {
+            last = next[last as usize];
+            *held = last;
+        }
+
+        let mut destination = self.current;
         loop {
-            if moving_cups.contains(&destination_cup) {
-                destination_cup = self.previous_cup(destination_cup)
+            destination = if destination == 1 {
+                self.highest
             } else {
+                destination - 1
+            };
+            if !self.held.contains(&destination) {
                 break;
             }
         }
 
-        destination_cup
-    }
-
-    fn previous_cup(&self, cup: Cup) -> Cup {
-        let mut previous_cup: Cup = cup - 1;
-
-        if previous_cup <= 0 {
-            previous_cup += self.data.len() as Cup;
+        if let Some(&first) = self.held.first() {
+            next[self.current as usize] = next[last as usize];
+            next[last as usize] = next[destination as usize];
+            next[destination as usize] = first;
         }
-
-        previous_cup
-    }
-
-    fn move_cups(&mut self, cups: &[Cup], destination_cup: Cup) {
-        self.next.swap(destination_cup, self.current_cup);
-        self.next.swap(self.current_cup, cups[2]);
+        self.current = next[self.current as usize];
     }
+}
 
-    fn output(&mut self) -> String {
-        let mut cup: Cup = self.next[1];
-        let mut cups: Vec<Cup> = vec![cup];
-
-        for _ in 0..7 {
-            cup = self.next[cup];
-            cups.push(cup);
-        }
-
-        cups.iter().map(|&cup| (48 + (cup as u8)) as char).collect()
+// The cups' labels, which must be the digits 1 to n in some order for at
+// least five cups so there's always somewhere to put the three picked up.
+fn labels(input: &str) -> Result<Vec<u32>, PuzzleError> {
+    let lines = parsing::lines(input.trim());
+    let [line] = &lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected one line of cup labels"));
+    };
+    let labels: Vec<u32> = line
+        .text
+        .char_indices()
+        .map(|(index, ch)| {
+            ch.to_digit(10)
+                .ok_or_else(|| line.error(&line.text[index..], format!("invalid label {ch:?}")))
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    let mut sorted = labels.clone();
+    sorted.sort_unstable();
+    if labels.len() < 5 || !sorted.into_iter().eq((1..).take(labels.len())) {
+        return Err(PuzzleError::new(
+            DAY,
+            "labels must be 1 to n in some order, with n at least 5",
+        )
+        .at_line(line.number));
     }
 
-    #[allow(dead_code)]
-    fn print_step(&self, moving_cups: &[Cup], destination_cup: Cup) {
-        println!("-- move {} --", self.current_move);
-        print!("cups: ");
-        let mut cups = vec![0; 9];
-        let mut cup = 7;
-
-        for index in 0..9 {
-            cup = self.next[cup];
-            cups[index] = cup;
-        }
-
-        for cup in cups {
-            if cup == self.current_cup {
-                print!("({}) ", cup);
-            } else {
-                print!("{} ", cup);
-            }
-        }
-        println!();
-        println!("pick up: {:?}", moving_cups);
-        println!("destination: {}\n", destination_cup);
-    }
+    Ok(labels)
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> String {
-    let mut cups: Cups = Cups::new(input, 9);
-
-    cups.stepn(100);
-
-    cups.output()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let mut cups: Cups = Cups::new(input, 1_000_000);
+    try_part2(input).unwrap()
+}
 
-    cups.stepn(10_000_000);
+/// # Errors
+///
+/// Fails unless the input is one line holding the labels 1 to n in some
+/// order, with n from 5 to 9.
+pub fn try_part1(input: &str) -> Result<String, PuzzleError> {
+    let labels = labels(input)?;
+    let mut cups = CrabCups::new(&labels, labels.len(), 3);
 
-    let one = cups.next[1];
-    let two = cups.next[one];
+    cups.play(100);
 
-    one * two
+    Ok(cups.after(1).map(|cup| cup.to_string()).collect())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut cups = CrabCups::new(&labels(input)?, 1_000_000, 3);
+
+    cups.play(10_000_000);
+
+    Ok(cups.after(1).take(2).map(|cup| cup as usize).product())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // A plain `Vec` simulation of the rules to check the linked ring.
+    fn naive(labels: &[u32], cups: u32, pick_up: usize, moves: usize) -> Vec<u32> {
+        let mut circle: Vec<u32> = labels.to_vec();
+        circle.extend(labels.iter().max().unwrap() + 1..=cups);
+
+        for _ in 0..moves {
+            let current = circle[0];
+            let picked: Vec<u32> = circle.drain(1..=pick_up).collect();
+            let mut destination = current;
+            loop {
+                destination = if destination == 1 {
+                    cups
+                } else {
+                    destination - 1
+                };
+                if !picked.contains(&destination) {
+                    break;
+                }
+            }
+            let index = circle.iter().position(|&cup| cup == destination).unwrap();
+            let at = index + 1;
+            circle.splice(at..at, picked);
+            circle.rotate_left(1);
+        }
+
+        let one = circle.iter().position(|&cup| cup == 1).unwrap();
+        circle.rotate_left(one);
+        circle[1..].to_vec()
+    }
+
     #[test]
     fn part1_short_example() {
-        let mut cups: Cups = Cups::new("389125467\n", 9);
+        let mut cups = CrabCups::new(&labels("389125467\n").unwrap(), 9, 3);
 
-        cups.stepn(10);
+        cups.play(10);
 
-        assert_eq!(cups.output(), "92658374");
+        assert_eq!(
+            cups.after(1).collect::<Vec<_>>(),
+            vec![9, 2, 6, 5, 8, 3, 7, 4]
+        );
+        assert_eq!(cups.current(), 8);
     }
 
     #[test]
@@ -170,4 +243,42 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2("389125467\n"), 149245887792)
     }
+
+    #[test]
+    fn other_sizes_match_naive_simulation() {
+        for &(cups, pick_up, moves) in &[
+            (9, 1, 50),
+            (9, 5, 50),
+            (20, 3, 200),
+            (30, 7, 500),
+            (12, 0, 5),
+        ] {
+            let labels = labels("389125467").unwrap();
+            let mut engine = CrabCups::new(&labels, cups as usize, pick_up);
+            engine.play(moves);
+
+            assert_eq!(
+                engine.after(1).collect::<Vec<_>>(),
+                naive(&labels, cups, pick_up, moves)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "permutation")]
+    fn rejects_missing_labels() {
+        let _ = CrabCups::new(&[1, 2, 4], 10, 3);
+    }
+
+    #[test]
+    fn labels_must_be_a_permutation() {
+        assert_eq!(
+            try_part1("38912546").unwrap_err().to_string(),
+            "day 23, line 1: labels must be 1 to n in some order, with n at least 5"
+        );
+        assert_eq!(
+            try_part1("3891x5467").unwrap_err().to_string(),
+            "day 23, line 1: column 5, invalid label 'x'"
+        );
+    }
 }
@@ -1,6 +1,10 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use core::cmp;
 use std::collections::HashMap;
 
+const DAY: u8 = 24;
+
 #[derive(Debug, PartialEq)]
 enum Direction {
     Northeast,
@@ -62,28 +66,25 @@ impl Directions {
     }
 }
 
-impl std::str::FromStr for Directions {
-    type Err = ();
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut chars = input.chars();
+impl Directions {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let text = line.text.trim();
+        let mut rest = text;
         let mut directions: Vec<Direction> = Vec::new();
 
-        while let Some(char) = chars.next() {
-            match char {
-                'e' => directions.push(Direction::East),
-                's' => match chars.next().unwrap() {
-                    'e' => directions.push(Direction::Southeast),
-                    'w' => directions.push(Direction::Southwest),
-                    _ => panic!("unexpected char"),
-                },
-                'w' => directions.push(Direction::West),
-                'n' => match chars.next().unwrap() {
-                    'e' => directions.push(Direction::Northeast),
-                    'w' => directions.push(Direction::Northwest),
-                    _ => panic!("unexpected char"),
-                },
-                _ => panic!("unexpected char"),
-            }
+        while !rest.is_empty() {
+            let (direction, length) = match rest.as_bytes() {
+                [b'e', ..] => (Direction::East, 1),
+                [b'w', ..] => (Direction::West, 1),
+                [b's', b'e', ..] => (Direction::Southeast, 2),
+                [b's', b'w', ..] => (Direction::Southwest, 2),
+                [b'n', b'e', ..] => (Direction::Northeast, 2),
+                [b'n', b'w', ..] => (Direction::Northwest, 2),
+                _ => return Err(line.error(rest, "expected `e`, `se`, `sw`, `w`, `nw` or `ne`")),
+            };
+
+            directions.push(direction);
+            rest = &rest[length..];
         }
 
         Ok(Directions(directions))
@@ -264,19 +265,37 @@ impl Map {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let tile_directions: Vec<Directions> =
-        input.lines().map(|line| line.parse().unwrap()).collect();
-    let map: Map = Map::from_directions(&tile_directions);
-
-    map.black_count()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let tile_directions: Vec<Directions> =
-        input.lines().map(|line| line.parse().unwrap()).collect();
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a step other than `e`, `se`, `sw`, `w`, `nw` or `ne`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let tile_directions = parse(input)?;
+    let map: Map = Map::from_directions(&tile_directions);
+
+    Ok(map.black_count())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let tile_directions = parse(input)?;
     let mut map: Map = Map::from_directions(&tile_directions);
 
     for _iteration in 1..101 {
@@ -285,7 +304,15 @@ pub fn part2(input: &str) -> usize {
     }
 
     //    map.print();
-    map.black_count()
+    Ok(map.black_count())
+}
+
+fn parse(input: &str) -> Result<Vec<Directions>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(Directions::parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -295,7 +322,7 @@ mod tests {
     #[test]
     fn parse_example() {
         use Direction::{East, Northeast, Southeast};
-        let directions: Directions = "esenee".parse().unwrap();
+        let directions = Directions::parse(&parsing::lines("esenee")[0]).unwrap();
         assert_eq!(directions.0, vec![East, Southeast, Northeast, East]);
     }
 
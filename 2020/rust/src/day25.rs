@@ -1,39 +1,61 @@
+use crate::math;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::convert::TryFrom;
+
+const DAY: u8 = 25;
 const MODULO: usize = 20201227;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let public_keys: Vec<usize> = input
-        .lines()
-        .map(|line| line.trim().parse().unwrap())
-        .collect();
-    let loop_size: usize = find_loop_size(public_keys[0]);
-
-    transform(public_keys[1], loop_size)
+    try_part1(input).unwrap()
 }
 
-fn find_loop_size(public_key: usize) -> usize {
-    let mut loop_size: usize = 0;
-    let mut value: usize = 1;
-    let subject_number: usize = 7;
+/// # Errors
+///
+/// Fails unless the input is two public keys below the modulus, or when
+/// the card's key isn't a power of 7.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let public_keys = parse(input)?;
+    let loop_size: usize = find_loop_size(public_keys[0])
+        .ok_or_else(|| PuzzleError::new(DAY, "the card's public key has no loop size"))?;
 
-    loop {
-        if value == public_key {
-            break;
-        }
+    Ok(transform(public_keys[1], loop_size))
+}
 
-        value = transform_step(value, subject_number);
-        loop_size += 1;
-    }
+fn find_loop_size(public_key: usize) -> Option<usize> {
+    let loop_size = math::discrete_log(7, public_key as u64, MODULO as u64)?;
 
-    loop_size
+    usize::try_from(loop_size).ok()
 }
 
 fn transform(subject_number: usize, loop_size: usize) -> usize {
-    (0..loop_size).fold(1, |acc, _iteration| transform_step(acc, subject_number))
+    let value = math::mod_pow(subject_number as u64, loop_size as u64, MODULO as u64);
+
+    usize::try_from(value).unwrap()
 }
 
-fn transform_step(value: usize, subject_number: usize) -> usize {
-    value * subject_number % MODULO
+fn parse(input: &str) -> Result<[usize; 2], PuzzleError> {
+    let lines = parsing::lines(input);
+    let [card, door] = &lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected two public keys"));
+    };
+
+    Ok([public_key(card)?, public_key(door)?])
+}
+
+fn public_key(line: &SourceLine) -> Result<usize, PuzzleError> {
+    let text = line.text.trim();
+
+    match line.parse(text).map_err(PuzzleError::on_day(DAY))? {
+        key if key < MODULO => Ok(key),
+        _ => Err(
+            PuzzleError::new(DAY, format!("public keys are below {MODULO}")).at_line(line.number),
+        ),
+    }
 }
 
 #[cfg(test)]
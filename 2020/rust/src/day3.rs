@@ -1,3 +1,8 @@
+use crate::parsing::{self, ParseError};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 3;
+
 #[derive(Debug, PartialEq)]
 enum Cell {
     Open,
@@ -18,23 +23,39 @@ impl Map {
 }
 
 impl std::str::FromStr for Map {
-    type Err = ();
-
-    fn from_str(string: &str) -> Result<Self, ()> {
-        let data: Vec<Vec<Cell>> = string
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|ch| match ch {
-                        '#' => Cell::Tree,
-                        '.' => Cell::Open,
-                        _ => panic!("invalid character found: {:?}", ch),
-                    })
-                    .collect()
-            })
-            .collect();
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> parsing::Result<Self> {
+        let lines = parsing::lines(string);
+        let mut data: Vec<Vec<Cell>> = vec![];
+
+        for line in &lines {
+            let row = line
+                .text
+                .char_indices()
+                .map(|(index, ch)| match ch {
+                    '#' => Ok(Cell::Tree),
+                    '.' => Ok(Cell::Open),
+                    _ => Err(line.error(&line.text[index..], format!("invalid square {ch:?}"))),
+                })
+                .collect::<parsing::Result<Vec<Cell>>>()?;
+
+            if row.len() != data.first().map_or(row.len(), Vec::len) {
+                return Err(line.error(line.text, "rows differ in width"));
+            }
+            data.push(row);
+        }
+
         let rows = data.len();
-        let columns = data.get(0).unwrap().len();
+        let columns = data.first().map_or(0, Vec::len);
+
+        if columns == 0 {
+            return Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected a map".into(),
+            });
+        }
 
         Ok(Map {
             data,
@@ -44,20 +65,41 @@ impl std::str::FromStr for Map {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let map: Map = input.parse().unwrap();
-    collisions(&map, (0, 0), (3, 1), 0)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let map: Map = input.parse().unwrap();
-    collisions(&map, (0, 0), (1, 1), 0)
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a square other than `.` or `#`, rows of different widths, or
+/// an empty map.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let map: Map = input.parse().map_err(PuzzleError::on_day(DAY))?;
+    Ok(collisions(&map, (0, 0), (3, 1), 0))
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let map: Map = input.parse().map_err(PuzzleError::on_day(DAY))?;
+    Ok(collisions(&map, (0, 0), (1, 1), 0)
         * collisions(&map, (0, 0), (3, 1), 0)
         * collisions(&map, (0, 0), (5, 1), 0)
         * collisions(&map, (0, 0), (7, 1), 0)
-        * collisions(&map, (0, 0), (1, 2), 0)
+        * collisions(&map, (0, 0), (1, 2), 0))
 }
 
 fn collisions(map: &Map, (x, y): (usize, usize), (vx, vy): (usize, usize), trees: usize) -> usize {
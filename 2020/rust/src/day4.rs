@@ -1,15 +1,31 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
-use std::str::FromStr;
+
+const DAY: u8 = 4;
 
 #[derive(Debug)]
 pub struct Passport(HashMap<String, String>);
 
 impl Passport {
-    fn load(input: &str) -> Vec<Passport> {
-        input
-            .split("\n\n")
-            .map(|lines| lines.parse().unwrap())
-            .collect()
+    fn load(input: &str) -> Result<Vec<Passport>, PuzzleError> {
+        parsing::blocks(input)
+            .iter()
+            .map(|lines| Self::parse(lines))
+            .collect::<parsing::Result<_>>()
+            .map_err(PuzzleError::on_day(DAY))
+    }
+
+    fn parse(lines: &[SourceLine]) -> parsing::Result<Self> {
+        let mut map: HashMap<String, String> = HashMap::new();
+
+        for line in lines {
+            for field in line.text.split_whitespace() {
+                let (key, value) = line.split_once(field, ":")?;
+                map.insert(key.into(), value.into());
+            }
+        }
+        Ok(Passport(map))
     }
 
     fn required_fields_present(&self) -> bool {
@@ -35,8 +51,9 @@ impl Passport {
 }
 
 fn between(string: &str, from: i32, to: i32) -> bool {
-    let value: i32 = string.parse().unwrap();
-    from <= value && value <= to
+    string
+        .parse::<i32>()
+        .is_ok_and(|value| from <= value && value <= to)
 }
 
 #[allow(clippy::option_if_let_else)]
@@ -64,38 +81,42 @@ fn valid_passport_id(string: &str) -> bool {
     string.len() == 9 && string.parse::<i32>().is_ok()
 }
 
-impl FromStr for Passport {
-    type Err = ();
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let mut map: HashMap<String, String> = HashMap::new();
-
-        for group in string.trim().split(|c| c == ' ' || c == '\n') {
-            let pair: Vec<&str> = group.split(':').collect();
-            map.insert(
-                (*pair.get(0).unwrap()).into(),
-                (*pair.get(1).unwrap()).into(),
-            );
-        }
-        Ok(Passport(map))
-    }
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
+#[must_use]
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
-pub fn part1(input: &str) -> usize {
-    let passports: Vec<Passport> = Passport::load(input);
-    passports
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a passport field that isn't `key:value`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let passports: Vec<Passport> = Passport::load(input)?;
+    Ok(passports
         .iter()
         .filter(|passport| passport.required_fields_present())
-        .count()
+        .count())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let passports: Vec<Passport> = Passport::load(input);
-    passports
+/// # Errors
+///
+/// Fails on a passport field that isn't `key:value`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let passports: Vec<Passport> = Passport::load(input)?;
+    Ok(passports
         .iter()
         .filter(|passport| passport.is_valid())
-        .count()
+        .count())
 }
 
 #[cfg(test)]
@@ -1,39 +1,80 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 5;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    input.lines().map(|line| seat_id(line)).max().unwrap()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let mut seat_ids: Vec<usize> = input.lines().map(|line| seat_id(line)).collect();
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a boarding pass that isn't `F`/`B` rows then `L`/`R` columns,
+/// or when there are no boarding passes.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    seat_ids(input)?
+        .into_iter()
+        .max()
+        .ok_or_else(|| PuzzleError::new(DAY, "no boarding passes"))
+}
+
+/// # Errors
+///
+/// Fails on a malformed boarding pass, or when no seat is missing between
+/// two occupied ones.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut seat_ids = seat_ids(input)?;
     let mut last: usize = 0;
 
     seat_ids.sort_unstable();
     for current in seat_ids {
         if current - last == 2 {
-            return current - 1;
+            return Ok(current - 1);
         } else {
             last = current
         }
     }
 
-    panic!("something went terribly wrong")
+    Err(PuzzleError::new(
+        DAY,
+        "no empty seat between two occupied ones",
+    ))
 }
 
-fn seat_id(string: &str) -> usize {
-    usize::from_str_radix(
-        string
-            .chars()
-            .map(|c| match c {
-                'F' | 'L' => '0',
-                'B' | 'R' => '1',
-                _ => panic!("found unexpected character {:?}", c),
+fn seat_ids(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| {
+            seat_id(line.text).ok_or_else(|| {
+                line.error(line.text, format!("invalid boarding pass `{}`", line.text))
             })
-            .collect::<String>()
-            .as_str(),
-        2,
-    )
-    .unwrap()
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn seat_id(string: &str) -> Option<usize> {
+    if string.is_empty() || string.len() > 10 {
+        return None;
+    }
+
+    string.chars().try_fold(0, |id, c| match c {
+        'F' | 'L' => Some(id * 2),
+        'B' | 'R' => Some(id * 2 + 1),
+        _ => None,
+    })
 }
 
 #[cfg(test)]
@@ -42,10 +83,10 @@ mod tests {
 
     #[test]
     fn seat_id_examples() {
-        assert_eq!(seat_id("FBFBBFFRLR"), 357);
-        assert_eq!(seat_id("BFFFBBFRRR"), 567);
-        assert_eq!(seat_id("FFFBBBFRRR"), 119);
-        assert_eq!(seat_id("BBFFBBFRLL"), 820);
+        assert_eq!(seat_id("FBFBBFFRLR"), Some(357));
+        assert_eq!(seat_id("BFFFBBFRRR"), Some(567));
+        assert_eq!(seat_id("FFFBBBFRRR"), Some(119));
+        assert_eq!(seat_id("BBFFBBFRLL"), Some(820));
     }
 
     #[test]
@@ -55,4 +96,14 @@ mod tests {
 
     #[test]
     fn part2_example() {}
+
+    #[test]
+    fn invalid_boarding_pass() {
+        assert_eq!(
+            try_part1("FBFBBFFRLR\nFBFXBFFRLR\n")
+                .unwrap_err()
+                .to_string(),
+            "day 05, line 2: column 1, invalid boarding pass `FBFXBFFRLR`"
+        );
+    }
 }
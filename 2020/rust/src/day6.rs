@@ -1,7 +1,31 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 6;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on an answer that isn't a question from `a` to `z`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    validate(input)?;
+
     let mut set: HashSet<char> = HashSet::new();
     let mut total: usize = 0;
 
@@ -16,11 +40,15 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    total + set.len()
+    Ok(total + set.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on an answer that isn't a question from `a` to `z`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    validate(input)?;
+
     let mut map: HashMap<char, usize> = HashMap::new();
     let mut total: usize = 0;
     let mut group_size: usize = 0;
@@ -39,7 +67,20 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    total + common_count(&map, group_size)
+    Ok(total + common_count(&map, group_size))
+}
+
+fn validate(input: &str) -> Result<(), PuzzleError> {
+    for line in parsing::lines(input) {
+        let text = line.text.trim();
+
+        if let Some(index) = text.find(|c: char| !c.is_ascii_lowercase()) {
+            let error = line.error(&text[index..], "expected a question from `a` to `z`");
+            return Err(PuzzleError::on_day(DAY)(error));
+        }
+    }
+
+    Ok(())
 }
 
 fn common_count(map: &HashMap<char, usize>, group_size: usize) -> usize {
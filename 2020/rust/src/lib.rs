@@ -20,3 +20,9 @@ pub mod day3;
 pub mod day4;
 pub mod day5;
 pub mod day6;
+pub mod math;
+pub mod parsing;
+pub mod puzzle;
+
+#[cfg(test)]
+mod testing;
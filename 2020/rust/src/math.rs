@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[must_use]
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+// None when the result doesn't fit in a u64.
+#[must_use]
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        Some(0)
+    } else {
+        (a / gcd(a, b)).checked_mul(b)
+    }
+}
+
+pub fn gcd_all<I: IntoIterator<Item = u64>>(values: I) -> u64 {
+    values.into_iter().fold(0, gcd)
+}
+
+pub fn lcm_all<I: IntoIterator<Item = u64>>(values: I) -> Option<u64> {
+    values.into_iter().try_fold(1, lcm)
+}
+
+// Returns (g, x, y) such that a*x + b*y = g = gcd(a, b), with g >= 0.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+        (old_t, t) = (t, old_t - quotient * t);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+#[must_use]
+pub fn mod_inv(a: i64, modulus: i64) -> Option<i64> {
+    if modulus <= 0 {
+        return None;
+    }
+    let (g, x, _y) = egcd(a.rem_euclid(modulus), modulus);
+
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn mod_mul(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
+}
+
+#[must_use]
+pub fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+
+    let mut result = 1;
+    let mut base = base % modulus;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, modulus);
+        }
+        base = mod_mul(base, base, modulus);
+        exp >>= 1;
+    }
+
+    result
+}
+
+// Solves the system x = residue (mod modulus) for every (residue, modulus)
+// pair, returning (x, lcm of the moduli) with 0 <= x < lcm. The moduli need
+// not be pairwise coprime. None means the system has no solution, a modulus
+// isn't positive, or the lcm overflows.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut modulus: i128 = 1;
+
+    for &(residue, n) in congruences {
+        if n <= 0 {
+            return None;
+        }
+        let n = i128::from(n);
+        let residue = i128::from(residue).rem_euclid(n);
+        let (g, p, _q) = egcd128(modulus, n);
+        let difference = residue - x;
+
+        if difference % g != 0 {
+            return None;
+        }
+
+        let step = n / g;
+        let k = (difference / g % step * p).rem_euclid(step);
+        let next_modulus = modulus.checked_mul(step)?;
+        x = (x + modulus * k).rem_euclid(next_modulus);
+        modulus = next_modulus;
+    }
+
+    Some((i64::try_from(x).ok()?, i64::try_from(modulus).ok()?))
+}
+
+#[allow(clippy::many_single_char_names)]
+fn egcd128(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = egcd128(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+// Baby-step giant-step: the smallest exponent e with base^e = target
+// (mod modulus), if one exists.
+#[must_use]
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let target = target % modulus;
+    let steps = modulus.isqrt() + 1;
+    let mut baby_steps = HashMap::with_capacity(usize::try_from(steps).ok()?);
+    let mut value = 1 % modulus;
+
+    for exponent in 0..steps {
+        if value == target {
+            return Some(exponent);
+        }
+        baby_steps.entry(value).or_insert(exponent);
+        value = mod_mul(value, base, modulus);
+    }
+
+    let giant_step = mod_inv(
+        i64::try_from(mod_pow(base, steps, modulus)).ok()?,
+        i64::try_from(modulus).ok()?,
+    )?;
+    let giant_step = u64::try_from(giant_step).ok()?;
+    let mut value = target;
+
+    for i in 0..steps {
+        if let Some(exponent) = baby_steps.get(&value) {
+            return Some(i * steps + exponent);
+        }
+        value = mod_mul(value, giant_step, modulus);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm_properties() {
+        for a in 0..60 {
+            for b in 0..60 {
+                let g = gcd(a, b);
+                let l = lcm(a, b).unwrap();
+
+                assert_eq!(g, gcd(b, a));
+                if g > 0 {
+                    assert_eq!(a % g, 0);
+                    assert_eq!(b % g, 0);
+                    assert_eq!(g * l, a * b);
+                }
+            }
+        }
+
+        assert_eq!(gcd_all([12, 18, 30]), 6);
+        assert_eq!(lcm_all([2, 3, 4, 5]), Some(60));
+        assert_eq!(lcm_all([]), Some(1));
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(lcm_all([4_294_967_291, 4_294_967_279, 3]), None);
+    }
+
+    #[test]
+    fn egcd_properties() {
+        for a in -40..40 {
+            for b in -40..40 {
+                let (g, x, y) = egcd(a, b);
+
+                assert_eq!(a * x + b * y, g);
+                assert_eq!(
+                    u64::try_from(g).unwrap(),
+                    gcd(a.unsigned_abs(), b.unsigned_abs())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mod_inv_properties() {
+        for modulus in 2..50 {
+            for a in -60..60 {
+                match mod_inv(a, modulus) {
+                    Some(inverse) => {
+                        assert!((0..modulus).contains(&inverse));
+                        assert_eq!((a * inverse).rem_euclid(modulus), 1);
+                    }
+                    None => assert_ne!(gcd(a.unsigned_abs(), modulus.unsigned_abs()), 1),
+                }
+            }
+        }
+
+        assert_eq!(mod_inv(3, 0), None);
+        assert_eq!(mod_inv(3, -7), None);
+    }
+
+    #[test]
+    fn mod_pow_properties() {
+        for modulus in 1..30 {
+            for base in 0..30 {
+                let mut expected = 1 % modulus;
+
+                for exp in 0..20 {
+                    assert_eq!(mod_pow(base, exp, modulus), expected);
+                    expected = expected * base % modulus;
+                }
+            }
+        }
+
+        assert_eq!(mod_pow(7, 20_201_226, 20_201_227), 1);
+    }
+
+    #[test]
+    fn crt_properties() {
+        for m1 in 1..16 {
+            for m2 in 1..16 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..m1 * m2).find(|x| x % m1 == r1 && x % m2 == r2);
+
+                        match crt(&[(r1, m1), (r2, m2)]) {
+                            Some((x, modulus)) => {
+                                assert_eq!(Some(x), expected);
+                                assert_eq!(
+                                    u64::try_from(modulus).unwrap(),
+                                    lcm(m1.unsigned_abs(), m2.unsigned_abs()).unwrap()
+                                );
+                            }
+                            None => assert_eq!(expected, None),
+                        }
+                    }
+                }
+            }
+        }
+
+        assert_eq!(crt(&[(6, 7), (4, 5), (1, 3)]), Some((34, 105)));
+        assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(1, 4), (2, 0)]), None);
+        assert_eq!(crt(&[(1, -3)]), None);
+        assert_eq!(
+            crt(&[(1, i64::MAX), (2, i64::MAX - 1), (3, i64::MAX - 2)]),
+            None
+        );
+    }
+
+    #[test]
+    fn discrete_log_properties() {
+        for modulus in [7, 11, 13, 101] {
+            for base in 2..modulus {
+                for exponent in 0..modulus - 1 {
+                    let target = mod_pow(base, exponent, modulus);
+                    let found = discrete_log(base, target, modulus).unwrap();
+
+                    assert!(found <= exponent);
+                    assert_eq!(mod_pow(base, found, modulus), target);
+                }
+            }
+        }
+
+        assert_eq!(discrete_log(7, 5_764_801, 20_201_227), Some(8));
+        assert_eq!(discrete_log(2, 3, 7), None);
+        assert_eq!(discrete_log(2, 3, 0), None);
+    }
+}
@@ -0,0 +1,264 @@
+// Mirrored as src/parsing.rs in each year's crate: the years share no library,
+// so change every copy together.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+// One line of puzzle input along with its 1-based line number. Fields handed
+// back by the helpers below are slices of `raw`, which is how errors recover
+// the column they occurred at.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceLine<'a> {
+    pub number: usize,
+    pub text: &'a str,
+    raw: &'a str,
+}
+
+#[must_use]
+pub fn lines(input: &str) -> Vec<SourceLine<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, text)| SourceLine {
+            number: index + 1,
+            text,
+            raw: text,
+        })
+        .collect()
+}
+
+#[must_use]
+pub fn blocks(input: &str) -> Vec<Vec<SourceLine<'_>>> {
+    let mut blocks = vec![];
+    let mut block = vec![];
+
+    for line in lines(input) {
+        if line.text.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+impl<'a> SourceLine<'a> {
+    #[must_use]
+    pub fn trim(self) -> Self {
+        Self {
+            text: self.text.trim(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn error(&self, at: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.number,
+            column: self.column(at),
+            message: message.into(),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Fails when the trimmed `field` doesn't parse as a `T`.
+    pub fn parse<T: FromStr>(&self, field: &'a str) -> Result<T> {
+        field
+            .trim()
+            .parse()
+            .map_err(|_| self.error(field, format!("invalid value `{field}`")))
+    }
+
+    /// # Errors
+    ///
+    /// Fails when a run of digits doesn't fit in a `T`.
+    pub fn integers<T: FromStr>(&self) -> Result<Vec<T>> {
+        let bytes = self.text.as_bytes();
+        let mut integers = vec![];
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let signed = bytes[index] == b'-'
+                && bytes.get(index + 1).is_some_and(u8::is_ascii_digit)
+                && (index == 0 || !bytes[index - 1].is_ascii_alphanumeric());
+
+            if signed || bytes[index].is_ascii_digit() {
+                let start = index;
+                index += 1;
+                while index < bytes.len() && bytes[index].is_ascii_digit() {
+                    index += 1;
+                }
+                integers.push(self.parse(&self.text[start..index])?);
+            } else {
+                index += 1;
+            }
+        }
+
+        Ok(integers)
+    }
+
+    /// # Errors
+    ///
+    /// Fails when `field` doesn't start with `prefix`.
+    pub fn strip_prefix(&self, field: &'a str, prefix: &str) -> Result<&'a str> {
+        field
+            .strip_prefix(prefix)
+            .ok_or_else(|| self.error(field, format!("expected `{prefix}`")))
+    }
+
+    /// # Errors
+    ///
+    /// Fails when `delimiter` doesn't occur in `field`.
+    pub fn split_once(&self, field: &'a str, delimiter: &str) -> Result<(&'a str, &'a str)> {
+        field
+            .split_once(delimiter)
+            .ok_or_else(|| self.error(field, format!("expected `{delimiter}`")))
+    }
+
+    /// `key: value`, with surrounding whitespace trimmed from both sides
+    ///
+    /// # Errors
+    ///
+    /// Fails when the line has no `:`.
+    pub fn key_value(&self) -> Result<(&'a str, &'a str)> {
+        let (key, value) = self.split_once(self.text, ":")?;
+
+        Ok((key.trim(), value.trim()))
+    }
+
+    /// `a -> b`
+    ///
+    /// # Errors
+    ///
+    /// Fails when the line has no `->`.
+    pub fn arrow(&self) -> Result<(&'a str, &'a str)> {
+        let (from, to) = self.split_once(self.text, "->")?;
+
+        Ok((from.trim(), to.trim()))
+    }
+
+    /// Matches the line against a pattern such as `move {} from {} to {}`,
+    /// returning the text captured by each `{}`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a literal part of `pattern` is missing, or text remains
+    /// after the last one.
+    pub fn fields(&self, pattern: &str) -> Result<Vec<&'a str>> {
+        let mut literals = pattern.split("{}");
+        let mut rest = self.text;
+        let mut fields = vec![];
+
+        if let Some(prefix) = literals.next() {
+            rest = self.strip_prefix(rest, prefix)?;
+        }
+
+        for literal in literals {
+            if literal.is_empty() {
+                fields.push(rest);
+                rest = &rest[rest.len()..];
+            } else {
+                let (field, remainder) = self.split_once(rest, literal)?;
+                fields.push(field);
+                rest = remainder;
+            }
+        }
+
+        if rest.is_empty() {
+            Ok(fields)
+        } else {
+            Err(self.error(rest, format!("unexpected `{rest}`")))
+        }
+    }
+
+    fn column(&self, at: &str) -> usize {
+        let start = self.raw.as_ptr() as usize;
+        let position = at.as_ptr() as usize;
+
+        if (start..=start + self.raw.len()).contains(&position) {
+            self.raw[..position - start].chars().count() + 1
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_with_signs_and_ranges() {
+        let line = lines("Sensor at x=-2, y=18: range 2-4,6-8")[0];
+
+        assert_eq!(line.integers::<isize>().unwrap(), vec![-2, 18, 2, 4, 6, 8]);
+        assert_eq!(
+            lines("p=-3,x ok")[0].integers::<usize>(),
+            Err(ParseError {
+                line: 1,
+                column: 3,
+                message: "invalid value `-3`".into()
+            })
+        );
+    }
+
+    #[test]
+    fn blocks_keep_line_numbers() {
+        let blocks = blocks("a\nb\n\n\nc: 1\nd -> e\n");
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1][0].number, 5);
+        assert_eq!(blocks[1][0].key_value().unwrap(), ("c", "1"));
+        assert_eq!(blocks[1][1].arrow().unwrap(), ("d", "e"));
+    }
+
+    #[test]
+    fn fields_report_position_of_mismatch() {
+        let input = "move 1 from 2 to 3\nmove 4 form 5 to 6";
+        let lines = lines(input);
+        let pattern = "move {} from {} to {}";
+
+        assert_eq!(lines[0].fields(pattern).unwrap(), vec!["1", "2", "3"]);
+        assert_eq!(
+            lines[1].fields(pattern),
+            Err(ParseError {
+                line: 2,
+                column: 6,
+                message: "expected ` from `".into()
+            })
+        );
+        assert_eq!(
+            lines[1].error(&lines[1].text[12..], "bad").to_string(),
+            "line 2, column 13: bad"
+        );
+    }
+}
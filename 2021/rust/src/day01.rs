@@ -1,19 +1,52 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 1;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let depths: Vec<u32> = input.lines().map(|line| line.parse().unwrap()).collect();
-
-    increase_count(depths)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let depths: Vec<u32> = input.lines().map(|line| line.parse().unwrap()).collect();
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a depth measurement.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let depths = parse(input)?;
+
+    Ok(increase_count(depths))
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a depth measurement.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let depths = parse(input)?;
     let three_measurement_depths: Vec<u32> = depths
         .windows(3)
         .map(|values| values.iter().sum())
         .collect();
 
-    increase_count(three_measurement_depths)
+    Ok(increase_count(three_measurement_depths))
+}
+
+fn parse(input: &str) -> Result<Vec<u32>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| line.parse(line.text))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 fn increase_count(depths: Vec<u32>) -> usize {
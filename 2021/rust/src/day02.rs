@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 2;
 
 #[derive(Debug)]
 struct Position {
@@ -24,55 +27,87 @@ enum Command {
     Forward(usize),
 }
 
-impl FromStr for Command {
-    type Err = &'static str;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let (direction, amount_string) = input.split_once(" ").ok_or("invalid command")?;
-        let amount = amount_string.parse().map_err(|_| "invalid amount")?;
+impl Command {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let (direction, amount) = line.split_once(line.text, " ")?;
+        let amount = line.parse(amount)?;
 
         match direction {
             "up" => Ok(Self::Up(amount)),
             "down" => Ok(Self::Down(amount)),
             "forward" => Ok(Self::Forward(amount)),
-            _ => Err("invalid direction"),
+            _ => Err(line.error(direction, format!("invalid direction `{direction}`"))),
         }
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let commands: Vec<Command> = input.lines().map(|line| line.parse().unwrap()).collect();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a command, or when the commands take the
+/// submarine above the surface.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let mut position = Position::default();
 
-    for command in commands {
+    for (line, command) in parse(input)? {
         match command {
             Command::Forward(amount) => position.horizontal += amount,
-            Command::Up(amount) => position.depth -= amount,
+            Command::Up(amount) => position.depth = rise(position.depth, amount, &line)?,
             Command::Down(amount) => position.depth += amount,
         }
     }
 
-    position.horizontal * position.depth
+    Ok(position.horizontal * position.depth)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let commands: Vec<Command> = input.lines().map(|line| line.parse().unwrap()).collect();
+/// # Errors
+///
+/// Fails on a line that isn't a command, or when the aim points the
+/// submarine above the surface.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut position = Position::default();
 
-    for command in commands {
+    for (line, command) in parse(input)? {
         match command {
             Command::Forward(amount) => {
                 position.horizontal += amount;
                 position.depth += position.aim * amount;
             }
-            Command::Up(amount) => position.aim -= amount,
+            Command::Up(amount) => position.aim = rise(position.aim, amount, &line)?,
             Command::Down(amount) => position.aim += amount,
         }
     }
 
-    position.horizontal * position.depth
+    Ok(position.horizontal * position.depth)
+}
+
+fn parse(input: &str) -> Result<Vec<(SourceLine<'_>, Command)>, PuzzleError> {
+    parsing::lines(input)
+        .into_iter()
+        .map(|line| Ok((line, Command::parse(&line)?)))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn rise(value: usize, amount: usize, line: &SourceLine) -> Result<usize, PuzzleError> {
+    value.checked_sub(amount).ok_or_else(|| {
+        PuzzleError::new(DAY, "the submarine rises above the surface").at_line(line.number)
+    })
 }
 
 #[cfg(test)]
@@ -87,6 +122,18 @@ mod tests {
         )
     }
 
+    #[test]
+    fn errors_name_the_line() {
+        assert_eq!(
+            try_part1("forward 5\nup 3\n").unwrap_err().to_string(),
+            "day 02, line 2: the submarine rises above the surface"
+        );
+        assert_eq!(
+            try_part1("forward 5\nback 3\n").unwrap_err().to_string(),
+            "day 02, line 2: column 1, invalid direction `back`"
+        );
+    }
+
     #[test]
     fn part2_example() {
         assert_eq!(
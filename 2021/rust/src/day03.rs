@@ -1,7 +1,38 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 3;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let diagnostics: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the report is binary numbers of one width that fit in a
+/// `usize`, or when a column has as many ones as zeros.
+///
+/// # Panics
+///
+/// Never in practice: [`parse`] rejects anything but short binary numbers.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let diagnostics = parse(input)?;
     let gamma_string: String = most_common_bits(&diagnostics).into_iter().collect();
+    if gamma_string.contains('x') {
+        return Err(PuzzleError::new(DAY, "a column has as many ones as zeros"));
+    }
     let gamma: usize = usize::from_str_radix(&gamma_string, 2).unwrap();
 
     let epsilon_string: String = gamma_string
@@ -10,17 +41,24 @@ pub fn part1(input: &str) -> usize {
         .replace("x", "1");
     let epsilon: usize = usize::from_str_radix(&epsilon_string, 2).unwrap();
 
-    gamma * epsilon
+    Ok(gamma * epsilon)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let diagnostics: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`] apart from evenly split
+/// columns, or when the report repeats a number so a rating isn't unique.
+///
+/// # Panics
+///
+/// Never in practice: [`parse`] rejects anything but short binary numbers.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let diagnostics = parse(input)?;
     let mut index = 0;
     let mut oxygen_generator_rating_candidates = diagnostics.clone();
 
     while oxygen_generator_rating_candidates.len() > 1 {
-        let mut most_common_bit = most_common_bits(&oxygen_generator_rating_candidates)[index];
+        let mut most_common_bit = bit_at(&oxygen_generator_rating_candidates, index)?;
 
         if most_common_bit == 'x' {
             most_common_bit = '1'
@@ -47,7 +85,7 @@ pub fn part2(input: &str) -> usize {
     let mut c02_scrubber_rating_candidates = diagnostics.clone();
 
     while c02_scrubber_rating_candidates.len() > 1 {
-        let mut most_common_bit = most_common_bits(&c02_scrubber_rating_candidates)[index];
+        let mut most_common_bit = bit_at(&c02_scrubber_rating_candidates, index)?;
 
         if most_common_bit == 'x' {
             most_common_bit = '0'
@@ -72,7 +110,44 @@ pub fn part2(input: &str) -> usize {
     )
     .unwrap();
 
-    oxygen_generator_rating * c02_scrubber_rating
+    Ok(oxygen_generator_rating * c02_scrubber_rating)
+}
+
+fn parse(input: &str) -> Result<Vec<Vec<char>>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let width = lines.first().map_or(0, |line| line.text.len());
+
+    if width == 0 {
+        return Err(PuzzleError::new(DAY, "expected a diagnostic report"));
+    }
+    if width > usize::BITS as usize {
+        return Err(PuzzleError::new(
+            DAY,
+            format!("numbers are wider than {} bits", usize::BITS),
+        ));
+    }
+
+    lines
+        .iter()
+        .map(|line| {
+            if let Some(index) = line.text.find(|ch| ch != '0' && ch != '1') {
+                Err(line.error(&line.text[index..], "expected `0` or `1`"))
+            } else if line.text.len() == width {
+                Ok(line.text.chars().collect())
+            } else {
+                Err(line.error(line.text, format!("expected {width} bits")))
+            }
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+// Identical candidates never narrow down to one, and run out of bits.
+fn bit_at(candidates: &Vec<Vec<char>>, index: usize) -> Result<char, PuzzleError> {
+    most_common_bits(candidates)
+        .get(index)
+        .copied()
+        .ok_or_else(|| PuzzleError::new(DAY, "the report repeats a number"))
 }
 
 fn most_common_bits(diagnostics: &Vec<Vec<char>>) -> Vec<char> {
@@ -116,4 +191,23 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2("00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n"), 230)
     }
+
+    #[test]
+    fn invalid_reports() {
+        let error = |input| try_part2(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("0101\n0121\n"),
+            "day 03, line 2: column 3, expected `0` or `1`"
+        );
+        assert_eq!(
+            error("0101\n011\n"),
+            "day 03, line 2: column 1, expected 4 bits"
+        );
+        assert_eq!(error("0101\n0101\n"), "day 03: the report repeats a number");
+        assert_eq!(
+            try_part1("01\n10\n").unwrap_err().to_string(),
+            "day 03: a column has as many ones as zeros"
+        );
+    }
 }
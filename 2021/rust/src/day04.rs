@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 4;
 
 #[derive(Clone, Debug, PartialEq)]
 enum Cell {
@@ -69,19 +72,24 @@ impl Board {
             })
             .sum::<usize>()
     }
-}
 
-impl FromStr for Board {
-    type Err = &'static str;
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let cells: Vec<Vec<Cell>> = input
-            .lines()
+    fn parse(lines: &[SourceLine]) -> parsing::Result<Self> {
+        let cells = lines
+            .iter()
             .map(|line| {
-                line.split_whitespace()
-                    .map(|x| Cell::Unmarked(x.parse().unwrap()))
-                    .collect()
+                let row: Vec<Cell> = line
+                    .text
+                    .split_whitespace()
+                    .map(|field| line.parse(field).map(Cell::Unmarked))
+                    .collect::<parsing::Result<_>>()?;
+
+                if row.len() == lines.len() {
+                    Ok(row)
+                } else {
+                    Err(line.error(line.text, format!("expected {} numbers", lines.len())))
+                }
             })
-            .collect();
+            .collect::<parsing::Result<Vec<Vec<Cell>>>>()?;
         let mut transposed_cells = vec![vec![Cell::Unmarked(0); cells.len()]; cells[0].len()];
 
         for (y, row) in cells.iter().enumerate() {
@@ -97,25 +105,47 @@ impl FromStr for Board {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (numbers, mut boards) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a line of numbers followed by square boards,
+/// or when no board wins.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (numbers, mut boards) = parse(input)?;
 
     for number in numbers {
         for board in boards.iter_mut() {
             board.call(number);
             if board.won() {
-                return number * board.unmarked_sum();
+                return Ok(number * board.unmarked_sum());
             }
         }
     }
 
-    return 0;
+    Err(PuzzleError::new(DAY, "no board wins"))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (numbers, mut boards) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when no single board is
+/// left to win last.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (numbers, mut boards) = parse(input)?;
 
     for number in numbers {
         for board in boards.iter_mut() {
@@ -123,27 +153,35 @@ pub fn part2(input: &str) -> usize {
         }
 
         if boards.len() == 1 && boards[0].won() {
-            return number * boards[0].unmarked_sum();
+            return Ok(number * boards[0].unmarked_sum());
         } else {
             boards = boards.into_iter().filter(|board| !board.won()).collect();
         }
     }
 
-    return 0;
+    Err(PuzzleError::new(DAY, "no single board wins last"))
 }
 
-fn parse(input: &str) -> (Vec<usize>, Vec<Board>) {
-    let (numbers_string, boards_string) = input.split_once("\n\n").unwrap();
-    let numbers: Vec<usize> = numbers_string
-        .split(",")
-        .map(|string| string.parse().unwrap())
-        .collect();
-    let boards: Vec<Board> = boards_string
-        .split("\n\n")
-        .map(|board_string| board_string.parse().unwrap())
-        .collect();
-
-    (numbers, boards)
+fn parse(input: &str) -> Result<(Vec<usize>, Vec<Board>), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let (numbers_block, board_blocks) = match &blocks[..] {
+        [numbers, boards @ ..] if numbers.len() == 1 && !boards.is_empty() => (numbers, boards),
+        _ => return Err(PuzzleError::new(DAY, "expected numbers followed by boards")),
+    };
+    let line = &numbers_block[0];
+    let numbers: Vec<usize> = line
+        .text
+        .split(',')
+        .map(|field| line.parse(field))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    let boards: Vec<Board> = board_blocks
+        .iter()
+        .map(|block| Board::parse(block))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((numbers, boards))
 }
 
 #[cfg(test)]
@@ -182,4 +220,16 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 1924)
     }
+
+    #[test]
+    fn boards_are_square() {
+        assert_eq!(
+            try_part1("1,2\n\n1 2\n3\n").unwrap_err().to_string(),
+            "day 04, line 4: column 1, expected 2 numbers"
+        );
+        assert_eq!(
+            try_part1("1,2\n\n1 2\n3 x\n").unwrap_err().to_string(),
+            "day 04, line 4: column 3, invalid value `x`"
+        );
+    }
 }
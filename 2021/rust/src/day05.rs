@@ -1,8 +1,11 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use core::fmt::Debug;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ops::Range;
-use std::str::FromStr;
+
+const DAY: u8 = 5;
 
 enum Orientation {
     Horizontal,
@@ -40,6 +43,35 @@ struct LineSegment {
 }
 
 impl LineSegment {
+    fn parse(line: &SourceLine) -> parsing::Result<Self> {
+        let (from, to) = line.arrow()?;
+        let from = Point::parse(line, from)?;
+        let to = Point::parse(line, to)?;
+
+        if from.x.abs_diff(to.x) != from.y.abs_diff(to.y) && from.x != to.x && from.y != to.y {
+            return Err(line.error(
+                line.text,
+                "expected a horizontal, vertical or 45 degree line",
+            ));
+        }
+
+        if from.x <= to.x {
+            let orientation = Self::orientation(&from, &to);
+            Ok(LineSegment {
+                from,
+                to,
+                orientation,
+            })
+        } else {
+            let orientation = Self::orientation(&to, &from);
+            Ok(LineSegment {
+                from: to,
+                to: from,
+                orientation,
+            })
+        }
+    }
+
     fn to_points(&self) -> Vec<Point> {
         let mut points: Vec<Point> = vec![];
         let mut x = self.from.x;
@@ -291,58 +323,46 @@ impl LineSegment {
     }
 }
 
-impl FromStr for Point {
-    type Err = &'static str;
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y)) = string.split_once(",") {
-            Ok(Point {
-                x: x.parse().unwrap(),
-                y: y.parse().unwrap(),
-            })
-        } else {
-            Err("invalid point")
-        }
-    }
-}
+impl Point {
+    fn parse(line: &SourceLine, field: &str) -> parsing::Result<Self> {
+        let (x, y) = line.split_once(field, ",")?;
 
-impl FromStr for LineSegment {
-    type Err = &'static str;
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        if let Some((from, to)) = string.split_once(" -> ") {
-            let from: Point = from.parse().unwrap();
-            let to: Point = to.parse().unwrap();
-
-            if from.x <= to.x {
-                let orientation = Self::orientation(&from, &to);
-                Ok(LineSegment {
-                    from,
-                    to,
-                    orientation,
-                })
-            } else {
-                let orientation = Self::orientation(&to, &from);
-                Ok(LineSegment {
-                    from: to,
-                    to: from,
-                    orientation,
-                })
-            }
-        } else {
-            Err("invalid point")
-        }
+        Ok(Point {
+            x: line.parse(x)?,
+            y: line.parse(y)?,
+        })
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let line_segments: Vec<LineSegment> = parse(input)
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a horizontal, vertical or 45 degree vent
+/// line such as `0,9 -> 5,9`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let line_segments: Vec<LineSegment> = parse(input)?
         .into_iter()
         .filter(|line_segment| match line_segment.orientation {
             Orientation::Horizontal | Orientation::Vertical => true,
             _ => false,
         })
         .collect();
-    overlap_count(line_segments)
+    Ok(overlap_count(line_segments))
 }
 
 #[allow(dead_code)]
@@ -375,13 +395,19 @@ fn overlap_count(line_segments: Vec<LineSegment>) -> usize {
         .count()
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    overlap_count(parse(input))
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(overlap_count(parse(input)?))
 }
 
-fn parse(input: &str) -> Vec<LineSegment> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+fn parse(input: &str) -> Result<Vec<LineSegment>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(LineSegment::parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -411,4 +437,14 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 12)
     }
+
+    #[test]
+    fn only_straight_or_diagonal_lines() {
+        assert_eq!(
+            try_part2("0,9 -> 5,9\n0,0 -> 2,1\n")
+                .unwrap_err()
+                .to_string(),
+            "day 05, line 2: column 1, expected a horizontal, vertical or 45 degree line"
+        );
+    }
 }
@@ -1,3 +1,8 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 6;
+
 struct Lanternfish {
     timer: usize,
 }
@@ -8,18 +13,38 @@ impl Default for Lanternfish {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    spawn_count(input, 80)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is one line of comma-separated timers from 0 to 8.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    spawn_count(input, 80)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     spawn_count(input, 256)
 }
 
-fn spawn_count(input: &str, days: usize) -> usize {
-    let lanternfish = parse(input);
+fn spawn_count(input: &str, days: usize) -> Result<usize, PuzzleError> {
+    let lanternfish = parse(input)?;
     let mut lanternfish_by_age = [0usize; 9];
     let mut spawns;
 
@@ -38,12 +63,12 @@ fn spawn_count(input: &str, days: usize) -> usize {
         lanternfish_by_age[8] = spawns;
     }
 
-    lanternfish_by_age.iter().sum()
+    Ok(lanternfish_by_age.iter().sum())
 }
 
 #[allow(dead_code)]
 fn spawn_count_brute_force(input: &str, days: usize) -> usize {
-    let mut lanternfish = parse(input);
+    let mut lanternfish = parse(input).unwrap();
     let mut spawns = 0;
 
     for _day in 0..days {
@@ -66,14 +91,21 @@ fn spawn_count_brute_force(input: &str, days: usize) -> usize {
     lanternfish.len()
 }
 
-fn parse(input: &str) -> Vec<Lanternfish> {
-    input
+fn parse(input: &str) -> Result<Vec<Lanternfish>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let [line] = &lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected one line of timers"));
+    };
+
+    line.text
         .trim_end()
-        .split(",")
-        .map(|string| Lanternfish {
-            timer: string.parse().unwrap(),
+        .split(',')
+        .map(|field| match line.parse(field)? {
+            timer @ 0..=8 => Ok(Lanternfish { timer }),
+            _ => Err(line.error(field, "timers run from 0 to 8")),
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -86,8 +118,21 @@ mod tests {
 
     #[test]
     fn examples() {
-        assert_eq!(spawn_count(input(), 18), 26);
-        assert_eq!(spawn_count(input(), 80), 5934);
-        assert_eq!(spawn_count(input(), 256), 26984457539);
+        assert_eq!(spawn_count(input(), 18).unwrap(), 26);
+        assert_eq!(spawn_count(input(), 80).unwrap(), 5934);
+        assert_eq!(spawn_count(input(), 256).unwrap(), 26984457539);
+    }
+
+    #[test]
+    fn timers_run_from_0_to_8() {
+        assert_eq!(
+            part1_error("3,9,1\n"),
+            "day 06, line 1: column 3, timers run from 0 to 8"
+        );
+        assert_eq!(part1_error("3\n4\n"), "day 06: expected one line of timers");
+    }
+
+    fn part1_error(input: &str) -> String {
+        try_part1(input).unwrap_err().to_string()
     }
 }
@@ -1,34 +1,74 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 7;
 const MAX_STEPS: usize = 1000;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let positions = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is one line of comma-separated positions.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let positions = parse(input)?;
     let median: usize = positions[positions.len() / 2];
     let linear_loss = |a: usize, b: usize| -> usize { (a as isize - b as isize).abs() as usize };
 
-    total_fuel_burn(&positions, median, linear_loss)
+    Ok(total_fuel_burn(&positions, median, linear_loss))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let positions = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the cheapest position
+/// is more than 1000 steps past the median.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let positions = parse(input)?;
     let quadratic_loss = |a: usize, b: usize| -> usize {
         let distance = (a as isize - b as isize).abs() as usize;
         (distance * distance + distance) / 2
     };
-    let min_loss_position = minimize(&positions, quadratic_loss);
+    let min_loss_position = minimize(&positions, quadratic_loss).ok_or_else(|| {
+        PuzzleError::new(
+            DAY,
+            format!("no cheapest position within {MAX_STEPS} steps"),
+        )
+    })?;
 
-    total_fuel_burn(&positions, min_loss_position, quadratic_loss)
+    Ok(total_fuel_burn(
+        &positions,
+        min_loss_position,
+        quadratic_loss,
+    ))
 }
 
-fn parse(input: &str) -> Vec<usize> {
-    let mut positions: Vec<usize> = input
+fn parse(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let [line] = &lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected one line of positions"));
+    };
+    let mut positions: Vec<usize> = line
+        .text
         .trim_end()
-        .split(",")
-        .map(|x| x.parse().unwrap())
-        .collect();
+        .split(',')
+        .map(|field| line.parse(field))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
     positions.sort_unstable();
-    positions
+    Ok(positions)
 }
 
 fn total_fuel_burn<F>(positions: &Vec<usize>, destination: usize, loss_function: F) -> usize
@@ -41,7 +81,7 @@ where
         .sum()
 }
 
-fn minimize<F>(positions: &Vec<usize>, loss_function: F) -> usize
+fn minimize<F>(positions: &Vec<usize>, loss_function: F) -> Option<usize>
 where
     F: Fn(usize, usize) -> usize,
 {
@@ -65,12 +105,12 @@ fn minimize_step<F>(
     current_cost: usize,
     delta: usize,
     steps: usize,
-) -> usize
+) -> Option<usize>
 where
     F: Fn(usize, usize) -> usize,
 {
     if steps > MAX_STEPS {
-        return 0;
+        return None;
     }
 
     let candidate_position: usize = current_position + delta;
@@ -86,7 +126,7 @@ where
             steps + 1,
         )
     } else {
-        current_position
+        Some(current_position)
     }
 }
 
@@ -1,11 +1,36 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 8;
+
+type Entry<'a> = (SourceLine<'a>, Vec<Vec<char>>, Vec<Vec<char>>);
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let pairs = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't ten patterns and four output digits made of
+/// the segments `a` to `g`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input)?;
     let mut count: usize = 0;
 
-    for (_, output) in pairs {
+    for (_, _, output) in pairs {
         for segments in output {
             match segments.len() {
                 2 | 3 | 4 | 7 => count += 1,
@@ -14,19 +39,24 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let pairs = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when a line's patterns
+/// aren't the ten digits of some wiring.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input)?;
     let mut total: usize = 0;
 
-    for (pattern, output) in pairs {
-        total += decode(pattern, output)
+    for (line, pattern, output) in pairs {
+        total += decode(pattern, output).ok_or_else(|| {
+            PuzzleError::new(DAY, "the patterns aren't the ten digits").at_line(line.number)
+        })?;
     }
 
-    total
+    Ok(total)
 }
 
 // segment: digits required for
@@ -45,17 +75,17 @@ pub fn part2(input: &str) -> usize {
 // 5: 2, 3, 5
 // 6: 0, 6, 9
 // 7: 8
-fn decode(pattern: Vec<Vec<char>>, output: Vec<Vec<char>>) -> usize {
+fn decode(pattern: Vec<Vec<char>>, output: Vec<Vec<char>>) -> Option<usize> {
     let mut digit_map: HashMap<&Vec<char>, u8> = HashMap::new();
     let mut segment_map: HashMap<char, char> = HashMap::new();
 
-    let one = &mut find_by_length(&pattern, 2)[0];
+    let mut one = find_by_length(&pattern, 2).into_iter().next()?;
     one.sort();
-    let four = &mut find_by_length(&pattern, 4)[0];
+    let mut four = find_by_length(&pattern, 4).into_iter().next()?;
     four.sort();
-    let seven = &mut find_by_length(&pattern, 3)[0];
+    let mut seven = find_by_length(&pattern, 3).into_iter().next()?;
     seven.sort();
-    let eight = &mut find_by_length(&pattern, 7)[0];
+    let mut eight = find_by_length(&pattern, 7).into_iter().next()?;
     eight.sort();
     let length_fives = find_by_length(&pattern, 5);
 
@@ -64,29 +94,29 @@ fn decode(pattern: Vec<Vec<char>>, output: Vec<Vec<char>>) -> usize {
     digit_map.insert(&four, 4);
     digit_map.insert(&eight, 8);
 
-    let a = minus(&seven, &one)[0];
+    let a = *minus(&seven, &one).first()?;
     segment_map.insert('a', a);
 
-    let f = find_char_missing_in_only_one(&pattern);
+    let f = find_char_missing_in_only_one(&pattern)?;
     segment_map.insert('f', f);
 
-    let c = minus(&one, &vec![f])[0];
+    let c = *minus(&one, &vec![f]).first()?;
     segment_map.insert('c', c);
 
     let be = find_chars_missing_in_all_but_one(&length_fives);
     let bd = minus(&four, &one);
 
-    let b = first_common(&be, &bd);
+    let b = first_common(&be, &bd)?;
     segment_map.insert('b', b);
 
-    let d = minus(&bd, &vec![b])[0];
+    let d = *minus(&bd, &vec![b]).first()?;
     segment_map.insert('d', d);
 
-    let e = minus(&be, &vec![b])[0];
+    let e = *minus(&be, &vec![b]).first()?;
     segment_map.insert('e', e);
 
     let eg = minus(&minus(&eight, &four), &seven);
-    let g = minus(&eg, &vec![e])[0];
+    let g = *minus(&eg, &vec![e]).first()?;
     segment_map.insert('g', g);
 
     let mut zero = vec![a, b, c, e, f, g];
@@ -117,11 +147,11 @@ fn decode(pattern: Vec<Vec<char>>, output: Vec<Vec<char>>) -> usize {
         .into_iter()
         .map(|mut digit| {
             digit.sort();
-            digit_map.get(&digit).unwrap().to_string()
+            Some(digit_map.get(&digit)?.to_string())
         })
-        .collect();
+        .collect::<Option<_>>()?;
 
-    usize::from_str_radix(&numbers.join(""), 10).unwrap()
+    usize::from_str_radix(&numbers.join(""), 10).ok()
 }
 
 fn minus(a: &Vec<char>, b: &Vec<char>) -> Vec<char> {
@@ -131,29 +161,18 @@ fn minus(a: &Vec<char>, b: &Vec<char>) -> Vec<char> {
         .collect()
 }
 
-fn first_common(a: &Vec<char>, b: &Vec<char>) -> char {
-    for &aa in a.iter() {
-        for &bb in b.iter() {
-            if aa == bb {
-                return aa;
-            }
-        }
-    }
-    panic!("would've been nice to find one...")
+fn first_common(a: &Vec<char>, b: &Vec<char>) -> Option<char> {
+    a.iter().copied().find(|aa| b.contains(aa))
 }
 
-fn find_char_missing_in_only_one(pattern: &Vec<Vec<char>>) -> char {
-    for target in "abcdefg".chars() {
-        if pattern
+fn find_char_missing_in_only_one(pattern: &Vec<Vec<char>>) -> Option<char> {
+    "abcdefg".chars().find(|&target| {
+        pattern
             .iter()
             .filter(|chars| chars.iter().find(|&&ch| ch == target).is_some())
             .count()
             == (pattern.len() - 1)
-        {
-            return target;
-        }
-    }
-    panic!("really should've found one...")
+    })
 }
 
 fn find_chars_missing_in_all_but_one(pattern: &Vec<Vec<char>>) -> Vec<char> {
@@ -179,25 +198,32 @@ fn find_by_length(pattern: &Vec<Vec<char>>, target: usize) -> Vec<Vec<char>> {
         .collect()
 }
 
-fn parse(input: &str) -> Vec<(Vec<Vec<char>>, Vec<Vec<char>>)> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Entry<'_>>, PuzzleError> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
-            let (left, right) = line
-                .split_once(" | ")
-                .unwrap_or_else(|| panic!("invalid line: {}", line));
-
-            (
-                left.split_whitespace()
-                    .map(|s| s.chars().collect())
-                    .collect(),
-                right
-                    .split_whitespace()
-                    .map(|s| s.chars().collect())
-                    .collect(),
-            )
+            let (left, right) = line.split_once(line.text, " | ")?;
+
+            Ok((line, digits(&line, left, 10)?, digits(&line, right, 4)?))
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn digits(line: &SourceLine, field: &str, count: usize) -> parsing::Result<Vec<Vec<char>>> {
+    let digits: Vec<Vec<char>> = field
+        .split_whitespace()
+        .map(|s| match s.find(|ch| !('a'..='g').contains(&ch)) {
+            Some(index) => Err(line.error(&s[index..], "segments run from `a` to `g`")),
+            None => Ok(s.chars().collect()),
+        })
+        .collect::<parsing::Result<_>>()?;
+
+    if digits.len() == count {
+        Ok(digits)
+    } else {
+        Err(line.error(field, format!("expected {count} digits")))
+    }
 }
 
 #[cfg(test)]
@@ -228,4 +254,18 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
         assert_eq!(part2("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n"), 5353);
         assert_eq!(part2(input()), 61229)
     }
+
+    #[test]
+    fn invalid_entries() {
+        assert_eq!(
+            try_part2("ab abc abcd abcde abcdf abcdg abcdef abcdeg abcdfg abcdefg | ab ab ab ab\n")
+                .unwrap_err()
+                .to_string(),
+            "day 08, line 1: the patterns aren't the ten digits"
+        );
+        assert_eq!(
+            try_part1("ab cd | ab\n").unwrap_err().to_string(),
+            "day 08, line 1: column 1, expected 10 digits"
+        );
+    }
 }
@@ -1,8 +1,30 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 9;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let height_map: Vec<Vec<u8>> = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a rectangular grid of digits.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let height_map: Vec<Vec<u8>> = parse(input)?;
     let mut low_points: Vec<u8> = vec![];
 
     for (y, row) in height_map.iter().enumerate() {
@@ -16,7 +38,7 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    low_points.iter().map(|value| (value + 1) as usize).sum()
+    Ok(low_points.iter().map(|value| (value + 1) as usize).sum())
 }
 
 fn neighbors<T>(map: &Vec<Vec<T>>, x: usize, y: usize) -> Vec<T>
@@ -48,9 +70,16 @@ enum Cell {
     Basin(usize),
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let height_map: Vec<Vec<u8>> = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+///
+/// # Panics
+///
+/// Never in practice: only the cells left of and above the current one
+/// have been assigned a basin yet.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let height_map: Vec<Vec<u8>> = parse(input)?;
     let mut basin_map: Vec<Vec<Cell>> =
         vec![vec![Cell::Unknown; height_map[0].len()]; height_map.len()];
     let mut basin_count: usize = 0;
@@ -111,7 +140,7 @@ pub fn part2(input: &str) -> usize {
 
     let mut counts: Vec<usize> = basin_counts.into_values().collect();
     counts.sort();
-    counts.iter().rev().take(3).product()
+    Ok(counts.iter().rev().take(3).product())
 }
 
 fn adjacent_basin_numbers(map: &Vec<Vec<Cell>>, x: usize, y: usize) -> Vec<usize> {
@@ -139,12 +168,27 @@ fn print_map(map: &Vec<Vec<Cell>>) {
     }
 }
 
-fn parse(input: &str) -> Vec<Vec<u8>> {
-    input
-        .trim_end()
-        .lines()
-        .map(|line| line.chars().map(|digit| (digit as u8) - 48).collect())
-        .collect()
+fn parse(input: &str) -> Result<Vec<Vec<u8>>, PuzzleError> {
+    let lines = parsing::lines(input.trim_end());
+    let width = lines.first().map_or(0, |line| line.text.len());
+
+    if width == 0 {
+        return Err(PuzzleError::new(DAY, "expected a height map"));
+    }
+
+    lines
+        .iter()
+        .map(|line| {
+            if let Some(index) = line.text.find(|ch: char| !ch.is_ascii_digit()) {
+                Err(line.error(&line.text[index..], "expected a digit"))
+            } else if line.text.len() == width {
+                Ok(line.text.bytes().map(|digit| digit - b'0').collect())
+            } else {
+                Err(line.error(line.text, format!("expected {width} digits")))
+            }
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -169,4 +213,18 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 1134)
     }
+
+    #[test]
+    fn invalid_height_maps() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("219\n39x\n"),
+            "day 09, line 2: column 3, expected a digit"
+        );
+        assert_eq!(
+            error("219\n39\n"),
+            "day 09, line 2: column 1, expected 3 digits"
+        );
+    }
 }
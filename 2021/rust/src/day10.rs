@@ -1,3 +1,8 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 10;
+
 #[derive(Debug)]
 enum ParseResult {
     Valid,
@@ -5,13 +10,34 @@ enum ParseResult {
     Corrupted(char),
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line with anything but brackets.
+///
+/// # Panics
+///
+/// Never in practice: only closing brackets can corrupt a line.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(parse_lines(input)?
+        .into_iter()
+        .map(|parse_result| {
             use ParseResult::*;
-            let parse_result: ParseResult = parse(line);
             match parse_result {
                 Valid | Incomplete(_) => 0,
                 Corrupted(')') => 3,
@@ -21,17 +47,30 @@ pub fn part1(input: &str) -> usize {
                 Corrupted(_) => panic!("invalid character"),
             }
         })
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut scores: Vec<usize> = input
-        .lines()
-        .filter_map(|line| autocomplete_score(parse(line)))
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when no line is incomplete.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut scores: Vec<usize> = parse_lines(input)?
+        .into_iter()
+        .filter_map(autocomplete_score)
         .collect();
     scores.sort_unstable();
-    scores[scores.len() / 2]
+    scores
+        .get(scores.len() / 2)
+        .copied()
+        .ok_or_else(|| PuzzleError::new(DAY, "no line is incomplete"))
+}
+
+fn parse_lines(input: &str) -> Result<Vec<ParseResult>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(parse)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 fn autocomplete_score(parse_result: ParseResult) -> Option<usize> {
@@ -60,28 +99,28 @@ fn value(ch: char) -> usize {
     }
 }
 
-fn parse(line: &str) -> ParseResult {
+fn parse(line: &SourceLine) -> parsing::Result<ParseResult> {
     let mut stack: Vec<char> = vec![];
 
-    for ch in line.chars() {
+    for (index, ch) in line.text.char_indices() {
         match ch {
             '(' | '[' | '{' | '<' => stack.push(ch),
             ')' | ']' | '}' | '>' => match stack.pop() {
                 Some(opening) => {
                     if !matching(opening, ch) {
-                        return ParseResult::Corrupted(ch);
+                        return Ok(ParseResult::Corrupted(ch));
                     }
                 }
-                None => return ParseResult::Incomplete(stack),
+                None => return Ok(ParseResult::Incomplete(stack)),
             },
-            _ => panic!("invalid input"),
+            _ => return Err(line.error(&line.text[index..], format!("invalid character {ch:?}"))),
         }
     }
 
     if stack.len() == 0 {
-        ParseResult::Valid
+        Ok(ParseResult::Valid)
     } else {
-        ParseResult::Incomplete(stack)
+        Ok(ParseResult::Incomplete(stack))
     }
 }
 
@@ -122,4 +161,16 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 288957)
     }
+
+    #[test]
+    fn invalid_lines() {
+        assert_eq!(
+            try_part1("[()]\n[(x)]\n").unwrap_err().to_string(),
+            "day 10, line 2: column 3, invalid character 'x'"
+        );
+        assert_eq!(
+            try_part2("[()]\n").unwrap_err().to_string(),
+            "day 10: no line is incomplete"
+        );
+    }
 }
@@ -1,6 +1,12 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use core::fmt::Debug;
 use std::collections::HashMap;
 
+const DAY: u8 = 11;
+const SIZE: usize = 10;
+const MAX_STEPS: usize = 1000;
+
 #[derive(Clone, Eq, Hash, PartialEq)]
 struct Point {
     x: i8,
@@ -35,13 +41,34 @@ impl Octopus {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a 10 by 10 grid of energy levels.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     flash_count(input, 100)
 }
 
-pub fn flash_count(input: &str, steps: usize) -> usize {
-    let mut octopi = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn flash_count(input: &str, steps: usize) -> Result<usize, PuzzleError> {
+    let mut octopi = parse(input)?;
     let mut flash_count: usize = 0;
 
     //    println!("Before any steps:");
@@ -53,7 +80,7 @@ pub fn flash_count(input: &str, steps: usize) -> usize {
         //        print(&octopi);
     }
 
-    flash_count
+    Ok(flash_count)
 }
 
 fn step(octopi: &mut HashMap<Point, Octopus>) -> usize {
@@ -157,11 +184,27 @@ fn print(octopi: &HashMap<Point, Octopus>) {
     println!();
 }
 
-fn parse(input: &str) -> HashMap<Point, Octopus> {
+fn parse(input: &str) -> Result<HashMap<Point, Octopus>, PuzzleError> {
+    let lines = parsing::lines(input);
     let mut octopi: HashMap<Point, Octopus> = HashMap::new();
+    let error = PuzzleError::on_day(DAY);
+
+    if lines.len() != SIZE {
+        return Err(PuzzleError::new(DAY, format!("expected {SIZE} rows")));
+    }
+
+    for (y, line) in lines.iter().enumerate() {
+        if line.text.len() != SIZE {
+            return Err(error(
+                line.error(line.text, format!("expected {SIZE} energy levels")),
+            ));
+        }
+
+        for (x, chr) in line.text.char_indices() {
+            if !chr.is_ascii_digit() {
+                return Err(error(line.error(&line.text[x..], "expected a digit")));
+            }
 
-    for (y, line) in input.lines().enumerate() {
-        for (x, chr) in line.chars().enumerate() {
             octopi.insert(
                 Point {
                     x: x as i8,
@@ -175,24 +218,31 @@ fn parse(input: &str) -> HashMap<Point, Octopus> {
         }
     }
 
-    octopi
+    Ok(octopi)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut octopi = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the flashes don't
+/// synchronize within 1000 steps.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut octopi = parse(input)?;
 
-    for step_count in 1..=1000 {
+    for step_count in 1..=MAX_STEPS {
         step(&mut octopi);
 
         if octopi
             .iter()
             .all(|(_location, octopus)| octopus.energy == 0)
         {
-            return step_count;
+            return Ok(step_count);
         }
     }
-    panic!("not found")
+
+    Err(PuzzleError::new(
+        DAY,
+        format!("the flashes don't synchronize within {MAX_STEPS} steps"),
+    ))
 }
 
 #[cfg(test)]
@@ -215,12 +265,23 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(flash_count(input(), 10), 204);
-        assert_eq!(flash_count(input(), 100), 1656)
+        assert_eq!(flash_count(input(), 10).unwrap(), 204);
+        assert_eq!(flash_count(input(), 100).unwrap(), 1656)
     }
 
     #[test]
     fn part2_example() {
         assert_eq!(part2(input()), 195)
     }
+
+    #[test]
+    fn grid_is_10_by_10() {
+        let error = |input: &str| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(error("5483143223\n"), "day 11: expected 10 rows");
+        assert_eq!(
+            error(&input().replacen("2745854711", "27458547x1", 1)),
+            "day 11, line 2: column 9, expected a digit"
+        );
+    }
 }
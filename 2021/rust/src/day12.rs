@@ -1,12 +1,35 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+const DAY: u8 = 12;
+
 type Cave = String;
 type Path<'a> = Vec<&'a Cave>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let connections = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't two caves joined by `-`, when two large caves
+/// are connected so paths never end, or when there's no `start` cave.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let connections = parse(input)?;
     let start_cave = "start".into();
     let small_caves_visitable_once = |next_cave: &Cave, path: &Path| -> bool {
         large_cave(next_cave) || !path.iter().any(|cave| *cave == next_cave)
@@ -22,12 +45,14 @@ pub fn part1(input: &str) -> usize {
     .filter(|path| path[path.len() - 1] == "end")
     .collect();
 
-    all_paths.len()
+    Ok(all_paths.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let connections = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let connections = parse(input)?;
     let start_cave = "start".into();
     let small_caves_visitable_twice = |next_cave: &Cave, path: &Path| -> bool {
         next_cave != "start"
@@ -46,7 +71,7 @@ pub fn part2(input: &str) -> usize {
     .filter(|path| path[path.len() - 1] == "end")
     .collect();
 
-    all_paths.len()
+    Ok(all_paths.len())
 }
 
 fn traverse<'a>(
@@ -97,15 +122,13 @@ fn small_cave_visited_twice(path: &Path) -> bool {
     false
 }
 
-fn parse(input: &str) -> HashMap<Cave, Vec<Cave>> {
+fn parse(input: &str) -> Result<HashMap<Cave, Vec<Cave>>, PuzzleError> {
     let mut caves: HashSet<Cave> = HashSet::new();
     let mut paths: Vec<(Cave, Cave)> = Vec::new();
     let mut connections: HashMap<Cave, Vec<Cave>> = HashMap::new();
 
-    for line in input.trim_end().lines() {
-        let (left, right) = line.split_once("-").unwrap();
-        let left_cave: Cave = left.into();
-        let right_cave: Cave = right.into();
+    for line in parsing::lines(input.trim_end()) {
+        let (left_cave, right_cave) = connection(&line).map_err(PuzzleError::on_day(DAY))?;
 
         caves.insert(left_cave.clone());
         caves.insert(right_cave.clone());
@@ -113,6 +136,10 @@ fn parse(input: &str) -> HashMap<Cave, Vec<Cave>> {
         paths.push((right_cave, left_cave));
     }
 
+    if !caves.contains("start") {
+        return Err(PuzzleError::new(DAY, "no `start` cave"));
+    }
+
     for cave in caves {
         let next_caves = paths
             .iter()
@@ -122,7 +149,28 @@ fn parse(input: &str) -> HashMap<Cave, Vec<Cave>> {
         connections.insert(cave, next_caves);
     }
 
-    connections
+    Ok(connections)
+}
+
+fn connection(line: &SourceLine) -> parsing::Result<(Cave, Cave)> {
+    let (left, right) = line.split_once(line.text, "-")?;
+
+    for name in [left, right] {
+        if name.is_empty() {
+            return Err(line.error(name, "expected a cave"));
+        }
+        if let Some(index) = name.find(|c: char| !c.is_ascii_alphabetic()) {
+            return Err(line.error(&name[index..], "cave names are letters"));
+        }
+    }
+
+    let (left_cave, right_cave): (Cave, Cave) = (left.into(), right.into());
+
+    if large_cave(&left_cave) && large_cave(&right_cave) {
+        return Err(line.error(line.text, "two large caves connect, so paths never end"));
+    }
+
+    Ok((left_cave, right_cave))
 }
 
 #[cfg(test)]
@@ -189,4 +237,23 @@ start-RW\n"
         assert_eq!(part2(larger_input()), 103);
         assert_eq!(part2(large_input()), 3509);
     }
+
+    #[test]
+    fn invalid_connections() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("start-A\nA-B\n"),
+            "day 12, line 2: column 1, two large caves connect, so paths never end"
+        );
+        assert_eq!(
+            error("start-A\nA-\n"),
+            "day 12, line 2: column 3, expected a cave"
+        );
+        assert_eq!(
+            error("start-A\nA-b!\n"),
+            "day 12, line 2: column 4, cave names are letters"
+        );
+        assert_eq!(error("a-end\n"), "day 12: no `start` cave");
+    }
 }
@@ -1,6 +1,11 @@
+use crate::ocr;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashSet;
 use std::fmt::Debug;
 
+const DAY: u8 = 13;
+
 #[derive(Clone, Eq, Hash, PartialEq)]
 struct Point {
     x: isize,
@@ -19,18 +24,39 @@ enum Fold {
     Vertically(isize),
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (points, folds) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that is neither a dot such as `6,10` nor a fold such as
+/// `fold along y=7`, or when there are no dots or no folds.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (points, folds) = parse(input)?;
     let mut set: HashSet<Point> = HashSet::new();
 
-    let fold = folds.iter().next().unwrap();
+    let fold = folds
+        .first()
+        .ok_or_else(|| PuzzleError::new(DAY, "no fold instructions"))?;
     for mut point in points {
         reflect(&mut point, fold);
         set.insert(point);
     }
 
-    set.len()
+    Ok(set.len())
 }
 
 fn reflect(point: &mut Point, fold: &Fold) {
@@ -56,9 +82,33 @@ fn reflect_vertically(point: &mut Point, at: isize) {
     }
 }
 
-#[must_use]
-pub fn part2(input: &str) -> String {
-    let (points, folds) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    let points = fold_all(input)?;
+
+    // Folding leaves every point at non-negative coordinates.
+    Ok(ocr::recognize_points(
+        points
+            .iter()
+            .map(|point| (point.x.unsigned_abs(), point.y.unsigned_abs())),
+    )
+    .unwrap_or_else(|| format(&points)))
+}
+
+/// The folded paper as a `##`/`..` picture; part 2 falls back to it when the
+/// letters aren't recognized.
+///
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn render(input: &str) -> Result<String, PuzzleError> {
+    Ok(format(&fold_all(input)?))
+}
+
+fn fold_all(input: &str) -> Result<HashSet<Point>, PuzzleError> {
+    let (points, folds) = parse(input)?;
     let mut set: HashSet<Point> = HashSet::new();
 
     for mut point in points {
@@ -68,9 +118,10 @@ pub fn part2(input: &str) -> String {
         set.insert(point);
     }
 
-    format(&set)
+    Ok(set)
 }
 
+// Never empty: `parse` insists on at least one dot.
 fn format(points: &HashSet<Point>) -> String {
     let max_x = points.iter().map(|point| point.x).max().unwrap();
     let max_y = points.iter().map(|point| point.y).max().unwrap();
@@ -92,28 +143,42 @@ fn format(points: &HashSet<Point>) -> String {
     lines.join("\n")
 }
 
-fn parse(input: &str) -> (Vec<Point>, Vec<Fold>) {
+fn parse(input: &str) -> Result<(Vec<Point>, Vec<Fold>), PuzzleError> {
     let mut points: Vec<Point> = Vec::new();
     let mut folds: Vec<Fold> = Vec::new();
 
-    for line in input.trim_end().lines() {
-        if let Some(_) = line.find(',') {
-            let (x_string, y_string) = line.split_once(',').unwrap();
-            points.push(Point {
-                x: x_string.parse().unwrap(),
-                y: y_string.parse().unwrap(),
-            });
-        } else if line.starts_with("fold") {
-            let (directive, value) = line.split_once('=').unwrap();
-            match directive {
-                "fold along x" => folds.push(Fold::Vertically(value.parse().unwrap())),
-                "fold along y" => folds.push(Fold::Horizontally(value.parse().unwrap())),
-                _ => panic!("unknown directive: {}", directive),
-            }
+    for line in parsing::lines(input.trim_end()) {
+        if line.text.contains(',') {
+            points.push(point(&line).map_err(PuzzleError::on_day(DAY))?);
+        } else if !line.text.is_empty() {
+            folds.push(fold(&line).map_err(PuzzleError::on_day(DAY))?);
         }
     }
 
-    (points, folds)
+    if points.is_empty() {
+        return Err(PuzzleError::new(DAY, "no dots on the paper"));
+    }
+
+    Ok((points, folds))
+}
+
+fn point(line: &SourceLine) -> parsing::Result<Point> {
+    let (x_string, y_string) = line.split_once(line.text, ",")?;
+
+    Ok(Point {
+        x: line.parse(x_string)?,
+        y: line.parse(y_string)?,
+    })
+}
+
+fn fold(line: &SourceLine) -> parsing::Result<Fold> {
+    let (directive, value) = line.split_once(line.text, "=")?;
+
+    match directive {
+        "fold along x" => Ok(Fold::Vertically(line.parse(value)?)),
+        "fold along y" => Ok(Fold::Horizontally(line.parse(value)?)),
+        _ => Err(line.error(directive, format!("unknown directive `{directive}`"))),
+    }
 }
 
 #[cfg(test)]
@@ -149,4 +214,29 @@ fold along x=5\n"
     fn part1_example() {
         assert_eq!(part1(input()), 17)
     }
+
+    #[test]
+    fn render_example() {
+        assert_eq!(
+            render(input()).unwrap(),
+            "\
+##########
+##......##
+##......##
+##......##
+##########"
+        )
+    }
+
+    #[test]
+    fn invalid_instructions() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("6,10\n\nfold along z=3\n"),
+            "day 13, line 3: column 1, unknown directive `fold along z`"
+        );
+        assert_eq!(error("6,10\n"), "day 13: no fold instructions");
+        assert_eq!(error("fold along y=7\n"), "day 13: no dots on the paper");
+    }
 }
@@ -1,21 +1,50 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 14;
+
 type Rules = HashMap<(char, char), char>;
 type Polymer = Vec<char>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (polymer, rules) = parse(input);
-    max_element_count_difference(&polymer, &rules, 10)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let (polymer, rules) = parse(input);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a template of at least two elements followed
+/// by rules such as `CH -> B`, or when a pair has no rule.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (polymer, rules) = parse(input)?;
+    max_element_count_difference(&polymer, &rules, 10)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (polymer, rules) = parse(input)?;
     max_element_count_difference(&polymer, &rules, 40)
 }
 
-fn max_element_count_difference(polymer: &Polymer, rules: &Rules, steps: usize) -> usize {
+fn max_element_count_difference(
+    polymer: &Polymer,
+    rules: &Rules,
+    steps: usize,
+) -> Result<usize, PuzzleError> {
     let mut pair_counts: HashMap<(char, char), usize> = HashMap::new();
     let mut new: char;
 
@@ -25,7 +54,9 @@ fn max_element_count_difference(polymer: &Polymer, rules: &Rules, steps: usize)
 
     for _step in 1..=steps {
         for ((a, b), count) in pair_counts.clone().into_iter() {
-            new = *rules.get(&(a, b)).unwrap();
+            new = *rules
+                .get(&(a, b))
+                .ok_or_else(|| PuzzleError::new(DAY, format!("no rule for `{a}{b}`")))?;
             *pair_counts.entry((a, new)).or_insert(0) += count;
             *pair_counts.entry((new, b)).or_insert(0) += count;
             *pair_counts.entry((a, b)).or_insert(0) -= count;
@@ -38,10 +69,11 @@ fn max_element_count_difference(polymer: &Polymer, rules: &Rules, steps: usize)
         *counts.entry(b).or_insert(0) += count;
     }
 
-    let largest_count = counts.values().max().unwrap();
-    let smallest_count = counts.values().min().unwrap();
+    // `parse` guarantees at least one pair, so there's at least one count.
+    let largest_count = counts.values().max().unwrap_or(&0);
+    let smallest_count = counts.values().min().unwrap_or(&0);
 
-    largest_count - smallest_count
+    Ok(largest_count - smallest_count)
 }
 
 #[allow(dead_code)]
@@ -61,21 +93,39 @@ fn print(polymer: &Polymer) {
     println!("{}", &polymer.iter().cloned().collect::<String>());
 }
 
-fn parse(input: &str) -> (Polymer, Rules) {
-    let (template, rule_lines) = input.trim_end().split_once("\n\n").unwrap();
-    let mut rules: HashMap<(char, char), char> = HashMap::new();
+fn parse(input: &str) -> Result<(Polymer, Rules), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [template, rule_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(DAY, "expected a template and rules"));
+    };
+    let template: Polymer = match &template[..] {
+        [line] if line.text.chars().count() >= 2 => line.text.chars().collect(),
+        _ => {
+            return Err(PuzzleError::new(
+                DAY,
+                "expected a template of at least two elements",
+            ));
+        }
+    };
+    let rules: Rules = rule_lines
+        .iter()
+        .map(rule)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((template, rules))
+}
 
-    for line in rule_lines.lines() {
-        let (pair, element) = line.split_once(" -> ").unwrap();
-        let mut pair_elements = pair.chars();
-        let a = pair_elements.next().unwrap();
-        let b = pair_elements.next().unwrap();
-        let c = element.chars().next().unwrap();
+fn rule(line: &SourceLine) -> parsing::Result<((char, char), char)> {
+    let (pair, element) = line.arrow()?;
+    let pair_elements: Vec<char> = pair.chars().collect();
+    let elements: Vec<char> = element.chars().collect();
 
-        rules.insert((a, b), c);
+    match (&pair_elements[..], &elements[..]) {
+        (&[a, b], &[c]) => Ok(((a, b), c)),
+        ([_, _], _) => Err(line.error(element, "expected one element")),
+        _ => Err(line.error(pair, "expected a pair of elements")),
     }
-
-    (template.chars().collect(), rules)
 }
 
 #[cfg(test)]
@@ -109,4 +159,15 @@ CN -> C\n"
         assert_eq!(part1(input()), 1588);
         assert_eq!(part2(input()), 2188189693529);
     }
+
+    #[test]
+    fn invalid_rules() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("NNCB\n\nCH -> B\nHHH -> N\n"),
+            "day 14, line 4: column 1, expected a pair of elements"
+        );
+        assert_eq!(error("NNC\n\nNN -> C\n"), "day 14: no rule for `NC`");
+    }
 }
@@ -1,96 +1,297 @@
-#[derive(Debug)]
-enum Payload {
+use crate::puzzle::PuzzleError;
+use std::fmt::{self, Write};
+
+const DAY: u8 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
     Literal(usize),
     Operator(usize, Vec<Packet>),
 }
 
-impl PartialEq for Payload {
-    fn eq(&self, other: &Self) -> bool {
-        use Payload::*;
-        match (self, other) {
-            (Literal(a), Literal(b)) => a == b,
-            (Operator(at, a), Operator(bt, b)) => {
-                at == bt && a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a == b)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    pub version: usize,
+    pub payload: Payload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketError {
+    InvalidHex { position: usize, digit: char },
+    Truncated { bit: usize },
+    LiteralTooLarge { bit: usize },
+    VersionTooLarge(usize),
+    InvalidTypeId(usize),
+    TooManySubpackets(usize),
+    SubpacketsOverrun { end: usize, bit: usize },
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHex { position, digit } => {
+                write!(f, "invalid hex digit {digit:?} at position {position}")
+            }
+            Self::Truncated { bit } => write!(f, "packet ends early at bit {bit}"),
+            Self::LiteralTooLarge { bit } => {
+                write!(f, "literal starting at bit {bit} does not fit in a usize")
+            }
+            Self::VersionTooLarge(version) => {
+                write!(f, "version {version} does not fit in 3 bits")
+            }
+            Self::InvalidTypeId(type_id) => write!(f, "{type_id} is not an operator type ID"),
+            Self::TooManySubpackets(count) => {
+                write!(f, "{count} subpackets do not fit in an 11-bit count")
+            }
+            Self::SubpacketsOverrun { end, bit } => {
+                write!(
+                    f,
+                    "subpackets run to bit {bit}, past their end at bit {end}"
+                )
             }
-            _ => false,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Packet {
-    version: usize,
-    payload: Payload,
+impl std::error::Error for PacketError {}
+
+// Reads big-endian bit fields of any width from a byte buffer.
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
 }
 
-fn parse(input: &str, mut offset: usize) -> (Packet, usize) {
-    //        println!("packet: {:b}", usize::from_str_radix(input, 16).unwrap());
-    let version = extract(input, offset, 3);
-    let type_id = extract(input, offset + 3, 3);
+// Appends big-endian bit fields, padding the last byte with zeros.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    length: usize,
+}
 
-    match type_id {
-        4 => {
-            let (literal, new_offset) = parse_literal(input, offset + 6);
-            offset = new_offset;
+impl<'a> BitReader<'a> {
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
 
-            (
-                Packet {
-                    version,
-                    payload: Payload::Literal(literal),
-                },
-                offset,
-            )
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // `None` if the buffer runs out first.
+    pub fn read(&mut self, bit_length: usize) -> Option<usize> {
+        let mut value = 0;
+
+        for _ in 0..bit_length {
+            let byte = *self.bytes.get(self.position / 8)?;
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+
+            value = (value << 1) | usize::from(bit);
+            self.position += 1;
         }
-        _ => {
-            let length_type_id = extract(input, offset + 6, 1);
-            let mut subpackets: Vec<Packet> = Vec::new();
 
-            if length_type_id == 0 {
-                let payload_length = extract(input, offset + 7, 15);
+        Some(value)
+    }
+}
+
+impl BitWriter {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn write(&mut self, value: usize, bit_length: usize) {
+        for shift in (0..bit_length).rev() {
+            if self.length.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> shift) & 1 == 1 {
+                self.bytes[self.length / 8] |= 1 << (7 - self.length % 8);
+            }
+            self.length += 1;
+        }
+    }
+
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        self.bytes.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02X}");
+            hex
+        })
+    }
+}
+
+impl Packet {
+    /// # Errors
+    ///
+    /// Fails on a non-hex digit, a packet cut short, or a literal too large
+    /// for a `usize`.
+    pub fn decode(hex: &str) -> Result<Self, PacketError> {
+        let bytes = hex_to_bytes(hex)?;
 
-                offset += 22;
-                let subpackets_end = offset + payload_length;
+        parse(&mut BitReader::new(&bytes))
+    }
 
-                loop {
-                    let (subpacket, new_offset) = parse(input, offset);
+    /// Operators always use the subpacket-count length type.
+    ///
+    /// # Errors
+    ///
+    /// Fails on any field too wide for its slot, rather than writing a
+    /// different packet: a version above 7, a type ID that isn't an operator,
+    /// or more than 2047 subpackets.
+    pub fn encode(&self) -> Result<String, PacketError> {
+        let mut writer = BitWriter::default();
+        self.write(&mut writer)?;
+
+        Ok(writer.to_hex())
+    }
+
+    fn write(&self, writer: &mut BitWriter) -> Result<(), PacketError> {
+        if self.version > 0b111 {
+            return Err(PacketError::VersionTooLarge(self.version));
+        }
+        writer.write(self.version, 3);
 
-                    offset = new_offset;
-                    subpackets.push(subpacket);
+        match &self.payload {
+            Payload::Literal(value) => {
+                writer.write(4, 3);
+
+                let groups = (usize::BITS - value.leading_zeros()).max(1).div_ceil(4);
+                for group in (0..groups).rev() {
+                    let more = usize::from(group > 0) << 4;
+                    writer.write(more | (value >> (group * 4)) & 0b1111, 5);
+                }
+            }
+            Payload::Operator(type_id, subpackets) => {
+                if *type_id > 0b111 || *type_id == 4 {
+                    return Err(PacketError::InvalidTypeId(*type_id));
+                }
+                if subpackets.len() > 0b111_1111_1111 {
+                    return Err(PacketError::TooManySubpackets(subpackets.len()));
+                }
+                writer.write(*type_id, 3);
+                writer.write(1, 1);
+                writer.write(subpackets.len(), 11);
 
-                    if offset >= subpackets_end {
-                        break;
-                    }
+                for subpacket in subpackets {
+                    subpacket.write(writer)?;
                 }
-            } else {
-                let subpacket_count = extract(input, offset + 7, 11);
-                offset += 18;
+            }
+        }
 
-                for _ in 1..=subpacket_count {
-                    let (subpacket, new_offset) = parse(input, offset);
+        Ok(())
+    }
+}
 
-                    offset = new_offset;
-                    subpackets.push(subpacket);
+// An S-expression with each packet's version after a `v`, e.g.
+// `(v1 < v6:10 v2:20)`.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.payload {
+            Payload::Literal(value) => write!(f, "v{}:{value}", self.version),
+            Payload::Operator(type_id, subpackets) => {
+                let name = match type_id {
+                    0 => "+".to_owned(),
+                    1 => "*".to_owned(),
+                    2 => "min".to_owned(),
+                    3 => "max".to_owned(),
+                    5 => ">".to_owned(),
+                    6 => "<".to_owned(),
+                    7 => "=".to_owned(),
+                    _ => format!("op{type_id}"),
+                };
+                write!(f, "(v{} {name}", self.version)?;
+
+                for subpacket in subpackets {
+                    write!(f, " {subpacket}")?;
                 }
+
+                write!(f, ")")
             }
+        }
+    }
+}
 
-            (
-                Packet {
-                    version,
-                    payload: Payload::Operator(type_id, subpackets),
-                },
-                offset,
-            )
+// An odd trailing digit fills the high half of the last byte.
+fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, PacketError> {
+    let nibbles = hex
+        .chars()
+        .enumerate()
+        .map(|(position, digit)| {
+            digit
+                .to_digit(16)
+                .ok_or(PacketError::InvalidHex { position, digit })
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+
+    Ok(nibbles
+        .chunks(2)
+        .map(|pair| {
+            let low = pair.get(1).copied().unwrap_or(0);
+            u8::try_from(pair[0] << 4 | low).unwrap()
+        })
+        .collect())
+}
+
+fn read(reader: &mut BitReader, bit_length: usize) -> Result<usize, PacketError> {
+    reader.read(bit_length).ok_or(PacketError::Truncated {
+        bit: reader.position(),
+    })
+}
+
+fn parse(reader: &mut BitReader) -> Result<Packet, PacketError> {
+    let version = read(reader, 3)?;
+    let type_id = read(reader, 3)?;
+
+    if type_id == 4 {
+        let payload = Payload::Literal(parse_literal(reader)?);
+        return Ok(Packet { version, payload });
+    }
+
+    let mut subpackets: Vec<Packet> = Vec::new();
+
+    if read(reader, 1)? == 0 {
+        let payload_length = read(reader, 15)?;
+        let subpackets_end = reader.position() + payload_length;
+
+        while reader.position() < subpackets_end {
+            subpackets.push(parse(reader)?);
+        }
+        if reader.position() != subpackets_end {
+            return Err(PacketError::SubpacketsOverrun {
+                end: subpackets_end,
+                bit: reader.position(),
+            });
+        }
+    } else {
+        let subpacket_count = read(reader, 11)?;
+
+        for _ in 1..=subpacket_count {
+            subpackets.push(parse(reader)?);
         }
     }
+
+    Ok(Packet {
+        version,
+        payload: Payload::Operator(type_id, subpackets),
+    })
 }
 
-fn parse_literal(input: &str, mut offset: usize) -> (usize, usize) {
+fn parse_literal(reader: &mut BitReader) -> Result<usize, PacketError> {
+    let start = reader.position();
     let mut value = 0usize;
-    let mut group: usize;
 
     loop {
-        group = extract(input, offset, 5);
-        offset += 5;
+        let group = read(reader, 5)?;
+        if value >> (usize::BITS - 4) != 0 {
+            return Err(PacketError::LiteralTooLarge { bit: start });
+        }
         value = (value << 4) ^ (group & 0b1111);
 
         if (group & 0b10000) == 0 {
@@ -98,29 +299,36 @@ fn parse_literal(input: &str, mut offset: usize) -> (usize, usize) {
         }
     }
 
-    (value, offset)
+    Ok(value)
 }
 
-fn extract(input: &str, start_bit: usize, bit_length: usize) -> usize {
-    let start_char: usize = start_bit / 4usize;
-    let char_length = (bit_length as f32 / 4f32).ceil() as usize;
-    let end_char: usize = (start_char + char_length + 1).min(input.len());
-
-    let chars = input.get(start_char..end_char).unwrap();
-    let raw_value = usize::from_str_radix(chars, 16).unwrap();
-
-    let source_end_bit = end_char * 4;
-    let extra_end_bits = source_end_bit - (start_bit + bit_length);
-    let bitmask = (usize::MAX << bit_length) ^ usize::MAX;
-
-    (raw_value >> extra_end_bits) & bitmask
+fn decode_input(input: &str) -> Result<Packet, PuzzleError> {
+    Packet::decode(input.trim_end()).map_err(|error| PuzzleError::new(DAY, error.to_string()))
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (packet, _offset) = parse(input.trim_end(), 0);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
 
-    version_sum(&packet)
+/// # Errors
+///
+/// Fails on a transmission [`Packet::decode`] rejects.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let packet = decode_input(input)?;
+
+    Ok(version_sum(&packet))
 }
 
 fn version_sum(packet: &Packet) -> usize {
@@ -136,74 +344,102 @@ fn version_sum(packet: &Packet) -> usize {
     }
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (packet, _offset) = parse(input.trim_end(), 0);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], on a minimum or maximum of no
+/// subpackets, on a comparison of other than two, or when the value
+/// overflows a `usize`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let packet = decode_input(input)?;
     evaluate(&packet)
 }
 
-fn evaluate(packet: &Packet) -> usize {
-    match &packet.payload {
-        Payload::Literal(value) => *value,
-        Payload::Operator(type_id, subpackets) => match type_id {
-            0 => subpackets.iter().map(|subpacket| evaluate(subpacket)).sum(),
-            1 => subpackets
-                .iter()
-                .map(|subpacket| evaluate(subpacket))
-                .product(),
-            2 => subpackets
-                .iter()
-                .map(|subpacket| evaluate(subpacket))
-                .min()
-                .unwrap(),
-            3 => subpackets
-                .iter()
-                .map(|subpacket| evaluate(subpacket))
-                .max()
-                .unwrap(),
-            5 => {
-                let mut iterator = subpackets.iter();
-                if evaluate(iterator.next().unwrap()) > evaluate(iterator.next().unwrap()) {
-                    1
-                } else {
-                    0
-                }
-            }
-            6 => {
-                let mut iterator = subpackets.iter();
-                if evaluate(iterator.next().unwrap()) < evaluate(iterator.next().unwrap()) {
-                    1
-                } else {
-                    0
-                }
-            }
-            7 => {
-                let mut iterator = subpackets.iter();
-                if evaluate(iterator.next().unwrap()) == evaluate(iterator.next().unwrap()) {
-                    1
-                } else {
-                    0
-                }
-            }
-            _ => panic!("unsupported type ID: {}", type_id),
-        },
+fn evaluate(packet: &Packet) -> Result<usize, PuzzleError> {
+    let (type_id, subpackets) = match &packet.payload {
+        Payload::Literal(value) => return Ok(*value),
+        Payload::Operator(type_id, subpackets) => (type_id, subpackets),
+    };
+    let values = subpackets
+        .iter()
+        .map(evaluate)
+        .collect::<Result<Vec<_>, _>>()?;
+    let overflow = || PuzzleError::new(DAY, "the value overflows a usize");
+    let empty = || PuzzleError::new(DAY, format!("type {type_id} has no subpackets"));
+
+    match (type_id, &values[..]) {
+        (0, _) => values
+            .iter()
+            .try_fold(0usize, |sum, &value| sum.checked_add(value))
+            .ok_or_else(overflow),
+        (1, _) => values
+            .iter()
+            .try_fold(1usize, |product, &value| product.checked_mul(value))
+            .ok_or_else(overflow),
+        (2, _) => values.iter().copied().min().ok_or_else(empty),
+        (3, _) => values.iter().copied().max().ok_or_else(empty),
+        (5, &[a, b]) => Ok(usize::from(a > b)),
+        (6, &[a, b]) => Ok(usize::from(a < b)),
+        (7, &[a, b]) => Ok(usize::from(a == b)),
+        _ => Err(PuzzleError::new(
+            DAY,
+            format!(
+                "type {type_id} compares two subpackets, not {}",
+                values.len()
+            ),
+        )),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Rng;
+
+    fn decode(hex: &str) -> (Packet, usize) {
+        let bytes = hex_to_bytes(hex).unwrap();
+        let mut reader = BitReader::new(&bytes);
+
+        (parse(&mut reader).unwrap(), reader.position())
+    }
+
+    fn random_packet(rng: &mut Rng, depth: usize) -> Packet {
+        let version = rng.below(8);
+
+        if depth == 0 || rng.below(3) == 0 {
+            let value = match rng.below(3) {
+                0 => rng.below(16),
+                1 => rng.below(1 << 20),
+                _ => rng.below(usize::MAX),
+            };
+            return Packet {
+                version,
+                payload: Payload::Literal(value),
+            };
+        }
+
+        let type_id = [0, 1, 2, 3, 5, 6, 7][rng.below(7)];
+        let count = if type_id >= 5 { 2 } else { 1 + rng.below(4) };
+        let subpackets = (0..count).map(|_| random_packet(rng, depth - 1)).collect();
+
+        Packet {
+            version,
+            payload: Payload::Operator(type_id, subpackets),
+        }
+    }
 
     #[test]
     fn part1_examples() {
-        let payload = "D2FE28";
-
-        assert_eq!(extract(payload, 0, 3), 0b110);
-        assert_eq!(extract(payload, 3, 3), 0b100);
-        assert_eq!(extract(payload, 6, 5), 0b10111);
-        assert_eq!(extract(payload, 11, 5), 0b11110);
+        let bytes = hex_to_bytes("D2FE28").unwrap();
+        let mut reader = BitReader::new(&bytes);
+
+        assert_eq!(reader.read(3), Some(0b110));
+        assert_eq!(reader.read(3), Some(0b100));
+        assert_eq!(reader.read(5), Some(0b10111));
+        assert_eq!(reader.read(5), Some(0b11110));
+        assert_eq!(reader.position(), 16);
+        assert_eq!(reader.read(9), None);
         assert_eq!(
-            parse(payload, 0),
+            decode("D2FE28"),
             (
                 Packet {
                     version: 6,
@@ -212,9 +448,8 @@ mod tests {
                 21
             )
         );
-
         assert_eq!(
-            parse("38006F45291200", 0),
+            decode("38006F45291200"),
             (
                 Packet {
                     version: 1,
@@ -237,7 +472,7 @@ mod tests {
         );
 
         assert_eq!(
-            parse("EE00D40C823060", 0),
+            decode("EE00D40C823060"),
             (
                 Packet {
                     version: 7,
@@ -280,4 +515,118 @@ mod tests {
         assert_eq!(part2("9C005AC2F8F0"), 0);
         assert_eq!(part2("9C0141080250320F1802104A08"), 1);
     }
+
+    #[test]
+    fn evaluation_errors() {
+        let error = |packet: Packet| {
+            try_part2(&packet.encode().unwrap())
+                .unwrap_err()
+                .to_string()
+        };
+        let literal = |value| Packet {
+            version: 0,
+            payload: Payload::Literal(value),
+        };
+        let operator = |type_id, subpackets| Packet {
+            version: 0,
+            payload: Payload::Operator(type_id, subpackets),
+        };
+
+        assert_eq!(
+            error(operator(2, vec![])),
+            "day 16: type 2 has no subpackets"
+        );
+        assert_eq!(
+            error(operator(5, vec![literal(1)])),
+            "day 16: type 5 compares two subpackets, not 1"
+        );
+        assert_eq!(
+            error(operator(0, vec![literal(usize::MAX), literal(1)])),
+            "day 16: the value overflows a usize"
+        );
+        assert_eq!(
+            try_part1("D2FE").unwrap_err().to_string(),
+            "day 16: packet ends early at bit 16"
+        );
+    }
+
+    #[test]
+    fn encode_examples() {
+        let packet = Packet::decode("D2FE28").unwrap();
+        assert_eq!(packet.encode().unwrap(), "D2FE28");
+
+        let packet = Packet::decode("38006F45291200").unwrap();
+        assert_eq!(packet.to_string(), "(v1 < v6:10 v2:20)");
+        assert_eq!(Packet::decode(&packet.encode().unwrap()), Ok(packet));
+
+        let packet = Packet::decode("EE00D40C823060").unwrap();
+        assert_eq!(packet.encode().unwrap(), "EE00D40C823060");
+        assert_eq!(packet.to_string(), "(v7 max v2:1 v4:2 v1:3)");
+    }
+
+    #[test]
+    fn encode_errors() {
+        let literal = |version| Packet {
+            version,
+            payload: Payload::Literal(1),
+        };
+
+        assert_eq!(literal(8).encode(), Err(PacketError::VersionTooLarge(8)));
+
+        let too_many = Packet {
+            version: 0,
+            payload: Payload::Operator(0, vec![literal(0); 2048]),
+        };
+        assert_eq!(too_many.encode(), Err(PacketError::TooManySubpackets(2048)));
+
+        let nested = Packet {
+            version: 0,
+            payload: Payload::Operator(4, vec![literal(9)]),
+        };
+        assert_eq!(nested.encode(), Err(PacketError::InvalidTypeId(4)));
+
+        let most = Packet {
+            version: 7,
+            payload: Payload::Operator(0, vec![literal(0); 2047]),
+        };
+        assert_eq!(Packet::decode(&most.encode().unwrap()), Ok(most));
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Packet::decode("D2FG28"),
+            Err(PacketError::InvalidHex {
+                position: 3,
+                digit: 'G'
+            })
+        );
+        assert_eq!(
+            Packet::decode("D2FE").unwrap_err().to_string(),
+            "packet ends early at bit 16"
+        );
+        assert_eq!(Packet::decode(""), Err(PacketError::Truncated { bit: 0 }));
+        assert_eq!(
+            Packet::decode("13FFFFFFFFFFFFFFFFFFFFE0"),
+            Err(PacketError::LiteralTooLarge { bit: 6 })
+        );
+        // The example operator with its 27-bit length cut to 26.
+        assert_eq!(
+            Packet::decode("38006B45291200"),
+            Err(PacketError::SubpacketsOverrun { end: 48, bit: 49 })
+        );
+    }
+
+    #[test]
+    fn round_trip_random_packets() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..200 {
+            let packet = random_packet(&mut rng, 4);
+            let hex = packet.encode().unwrap();
+
+            assert_eq!(Packet::decode(&hex), Ok(packet.clone()), "{hex}");
+            assert_eq!(part1(&hex), version_sum(&packet));
+        }
+    }
 }
@@ -1,6 +1,10 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
 
+const DAY: u8 = 17;
+
 struct Probe {
     x: isize,
     y: isize,
@@ -40,15 +44,28 @@ enum SimulationResult {
     Miss,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (x_range_string, y_range_string) = input
-        .trim_start_matches("target area: ")
-        .trim_end()
-        .split_once(", ")
-        .unwrap();
-    let x_range = parse_range(x_range_string);
-    let y_range = parse_range(y_range_string);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a line such as
+/// `target area: x=20..30, y=-10..-5`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (x_range, y_range) = parse(input)?;
     let mut max_y: usize = 0;
 
     for dx in 0..100 {
@@ -60,18 +77,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    max_y
+    Ok(max_y)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (x_range_string, y_range_string) = input
-        .trim_start_matches("target area: ")
-        .trim_end()
-        .split_once(", ")
-        .unwrap();
-    let x_range = parse_range(x_range_string);
-    let y_range = parse_range(y_range_string);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (x_range, y_range) = parse(input)?;
     let mut hit_count: usize = 0;
 
     for dx in 0..250 {
@@ -85,7 +98,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    hit_count
+    Ok(hit_count)
 }
 
 fn simulate(
@@ -107,13 +120,17 @@ fn simulate(
     }
 }
 
-fn parse_range(input: &str) -> RangeInclusive<isize> {
-    let chars_to_remove: &[_] = &['x', 'y', '='];
-    let (start_string, end_string) = input
-        .trim_start_matches(chars_to_remove)
-        .split_once("..")
-        .unwrap();
-    start_string.parse().unwrap()..=end_string.parse().unwrap()
+fn parse(input: &str) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>), PuzzleError> {
+    let lines = parsing::lines(input.trim_end());
+    let [line] = &lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected one target area"));
+    };
+    let bounds: Vec<isize> = line
+        .fields("target area: x={}..{}, y={}..{}")
+        .and_then(|fields| fields.iter().map(|field| line.parse(field)).collect())
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((bounds[0]..=bounds[1], bounds[2]..=bounds[3]))
 }
 
 #[cfg(test)]
@@ -133,4 +150,14 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 112)
     }
+
+    #[test]
+    fn invalid_target_area() {
+        assert_eq!(
+            try_part1("target area: x=20..30, y=-10..\n")
+                .unwrap_err()
+                .to_string(),
+            "day 17, line 1: column 31, invalid value ``"
+        );
+    }
 }
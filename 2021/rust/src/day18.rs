@@ -1,5 +1,45 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 18;
+
 type Number = Vec<(usize, usize)>;
 
+fn parse_numbers(input: &str) -> Result<Vec<Number>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| {
+            let rest = element(line, line.text, 0)?;
+
+            if !line.text.starts_with('[') {
+                Err(line.error(line.text, "expected a pair"))
+            } else if !rest.is_empty() {
+                Err(line.error(rest, format!("unexpected `{rest}`")))
+            } else {
+                Ok(parse(line.text))
+            }
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+// Checks that `text` starts with a regular number or a pair nested no more
+// than four deep, returning whatever follows it.
+fn element<'a>(line: &SourceLine<'a>, text: &'a str, depth: usize) -> parsing::Result<&'a str> {
+    match text.chars().next() {
+        Some(digit) if digit.is_ascii_digit() => Ok(&text[1..]),
+        Some('[') if depth == 4 => Err(line.error(text, "pairs nest at most four deep")),
+        Some('[') => {
+            let rest = element(line, &text[1..], depth + 1)?;
+            let rest = line.strip_prefix(rest, ",")?;
+            let rest = element(line, rest, depth + 1)?;
+
+            line.strip_prefix(rest, "]")
+        }
+        _ => Err(line.error(text, "expected a digit or `[`")),
+    }
+}
+
 fn parse(input: &str) -> Number {
     let mut depth: usize = 0;
     let mut number: Number = Vec::new();
@@ -81,17 +121,41 @@ fn magnitude(mut number: Number) -> usize {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let numbers: Vec<Number> = input.lines().map(|line| parse(line)).collect();
-    let final_number: Number = numbers.into_iter().reduce(|a, b| add(a, b)).unwrap();
-
-    magnitude(final_number)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let numbers: Vec<Number> = input.lines().map(|line| parse(line)).collect();
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a snailfish number of single digits nested at
+/// most four deep, or when there are no numbers.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let numbers: Vec<Number> = parse_numbers(input)?;
+    let final_number: Number = numbers
+        .into_iter()
+        .reduce(|a, b| add(a, b))
+        .ok_or_else(|| PuzzleError::new(DAY, "no snailfish numbers"))?;
+
+    Ok(magnitude(final_number))
+}
+
+/// # Errors
+///
+/// Fails on a line [`try_part1`] rejects.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let numbers: Vec<Number> = parse_numbers(input)?;
     let mut max_magnitude: usize = 0;
 
     for a in numbers.iter() {
@@ -102,7 +166,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    max_magnitude
+    Ok(max_magnitude)
 }
 
 #[cfg(test)]
@@ -189,4 +253,23 @@ mod tests {
             3993
         );
     }
+
+    #[test]
+    fn invalid_numbers() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("[1,2]\n[1;2]\n"),
+            "day 18, line 2: column 3, expected `,`"
+        );
+        assert_eq!(
+            error("[[[[[1,2],3],4],5],6]\n"),
+            "day 18, line 1: column 5, pairs nest at most four deep"
+        );
+        assert_eq!(
+            error("[1,2]]\n"),
+            "day 18, line 1: column 6, unexpected `]`"
+        );
+        assert_eq!(error("7\n"), "day 18, line 1: column 1, expected a pair");
+    }
 }
@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashSet;
 
+const DAY: u8 = 20;
+
 type Point = (isize, isize);
 type Algorithm = Vec<char>;
 
@@ -11,26 +15,47 @@ struct Image {
     max: isize,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (algorithm, mut image) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a 512-pixel algorithm line followed by a
+/// square image, all of `#` and `.`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (algorithm, mut image) = parse(input)?;
 
     for step in 0..2 {
         enhance(&mut image, &algorithm, step)
     }
 
-    image.light_pixels.len()
+    Ok(image.light_pixels.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (algorithm, mut image) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (algorithm, mut image) = parse(input)?;
 
     for step in 0..50 {
         enhance(&mut image, &algorithm, step)
     }
 
-    image.light_pixels.len()
+    Ok(image.light_pixels.len())
 }
 
 fn enhance(image: &mut Image, algorithm: &Algorithm, step: usize) {
@@ -97,28 +122,48 @@ fn get_digit(pixels: &HashSet<Point>, x: isize, y: isize) -> char {
     }
 }
 
-fn parse(input: &str) -> (Algorithm, Image) {
-    let (algorithm_string, image_string) = input.split_once("\n\n").unwrap();
-    let algorithm = algorithm_string.trim_end().chars().collect();
+fn parse(input: &str) -> Result<(Algorithm, Image), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [algorithm_lines, image_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(DAY, "expected an algorithm and an image"));
+    };
+    let [algorithm_line] = &algorithm_lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected the algorithm on one line"));
+    };
     let mut light_pixels: HashSet<Point> = HashSet::new();
 
-    for (y, row) in image_string.lines().enumerate() {
-        for (x, ch) in row.chars().enumerate() {
+    let algorithm = pixels(algorithm_line, 512).map_err(PuzzleError::on_day(DAY))?;
+    for (y, row) in image_lines.iter().enumerate() {
+        let row = pixels(row, image_lines.len()).map_err(PuzzleError::on_day(DAY))?;
+
+        for (x, ch) in row.into_iter().enumerate() {
             if ch == '#' {
                 light_pixels.insert((x as isize, y as isize));
             }
         }
     }
 
-    (
+    Ok((
         algorithm,
         Image {
             light_pixels,
             dark_pixels: HashSet::new(),
             min: -2,
-            max: (image_string.lines().count() + 1) as isize,
+            max: (image_lines.len() + 1) as isize,
         },
-    )
+    ))
+}
+
+fn pixels(line: &SourceLine, count: usize) -> parsing::Result<Vec<char>> {
+    let text = line.text.trim_end();
+
+    if let Some(index) = text.find(|ch| ch != '#' && ch != '.') {
+        Err(line.error(&text[index..], "expected `#` or `.`"))
+    } else if text.len() == count {
+        Ok(text.chars().collect())
+    } else {
+        Err(line.error(text, format!("expected {count} pixels")))
+    }
 }
 
 #[cfg(test)]
@@ -141,4 +186,23 @@ mod tests {
         assert_eq!(part1(input()), 24);
         assert_eq!(part2(input()), 3352);
     }
+
+    #[test]
+    fn invalid_input() {
+        let error = |input: &str| try_part1(input).unwrap_err().to_string();
+        let algorithm = input().lines().next().unwrap();
+
+        assert_eq!(
+            error(&format!("{algorithm}\n\n#.\n.#.\n")),
+            "day 20, line 4: column 1, expected 2 pixels"
+        );
+        assert_eq!(
+            error(&format!("{algorithm}\n\n#.\n.o\n")),
+            "day 20, line 4: column 2, expected `#` or `.`"
+        );
+        assert_eq!(
+            error("#.#\n\n#.\n.#\n"),
+            "day 20, line 1: column 1, expected 512 pixels"
+        );
+    }
 }
@@ -18,3 +18,9 @@ pub mod day16;
 pub mod day17;
 pub mod day18;
 pub mod day20;
+pub mod ocr;
+pub mod parsing;
+pub mod puzzle;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,234 @@
+// A deliberate mirror of 2022/rust/src/ocr.rs: each year is its own crate
+// with no shared library, so change both copies together.
+
+use std::collections::HashSet;
+
+// The block alphabets puzzles draw their answers in. Glyphs sit at a fixed
+// pitch, so a letter starts every `pitch` columns.
+struct Font {
+    height: usize,
+    pitch: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+#[rustfmt::skip]
+const LARGE: Font = Font {
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        (
+            'A',
+            &[
+                "..##..", ".#..#.", "#....#", "#....#", "#....#", "######",
+                "#....#", "#....#", "#....#", "#....#",
+            ],
+        ),
+        (
+            'B',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#....#",
+                "#....#", "#....#", "#....#", "#####.",
+            ],
+        ),
+        (
+            'C',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "#.....", "#....#", ".####.",
+            ],
+        ),
+        (
+            'E',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....",
+                "#.....", "#.....", "#.....", "######",
+            ],
+        ),
+        (
+            'F',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....",
+                "#.....", "#.....", "#.....", "#.....",
+            ],
+        ),
+        (
+            'G',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#..###",
+                "#....#", "#....#", "#...##", ".###.#",
+            ],
+        ),
+        (
+            'H',
+            &[
+                "#....#", "#....#", "#....#", "#....#", "######", "#....#",
+                "#....#", "#....#", "#....#", "#....#",
+            ],
+        ),
+        (
+            'J',
+            &[
+                "...###", "....#.", "....#.", "....#.", "....#.", "....#.",
+                "....#.", "#...#.", "#...#.", ".###..",
+            ],
+        ),
+        (
+            'K',
+            &[
+                "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....",
+                "#.#...", "#..#..", "#...#.", "#....#",
+            ],
+        ),
+        (
+            'L',
+            &[
+                "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "#.....", "#.....", "######",
+            ],
+        ),
+        (
+            'N',
+            &[
+                "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#",
+                "#..#.#", "#...##", "#...##", "#....#",
+            ],
+        ),
+        (
+            'P',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#.....",
+                "#.....", "#.....", "#.....", "#.....",
+            ],
+        ),
+        (
+            'R',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..",
+                "#...#.", "#...#.", "#....#", "#....#",
+            ],
+        ),
+        (
+            'X',
+            &[
+                "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..",
+                ".#..#.", ".#..#.", "#....#", "#....#",
+            ],
+        ),
+        (
+            'Z',
+            &[
+                "######", ".....#", ".....#", "....#.", "...#..", "..#...",
+                ".#....", "#.....", "#.....", "######",
+            ],
+        ),
+    ],
+};
+
+// Reads capital letters from a text grid where `#` marks a lit pixel.
+#[must_use]
+pub fn recognize(grid: &str) -> Option<String> {
+    recognize_points(grid.lines().enumerate().flat_map(|(y, line)| {
+        line.chars()
+            .enumerate()
+            .filter(|(_x, ch)| *ch == '#')
+            .map(move |(x, _ch)| (x, y))
+    }))
+}
+
+// Reads capital letters from lit (x, y) pixels. The first letter must start
+// at x = 0; the font is picked by the height of the lit rows.
+pub fn recognize_points<I>(points: I) -> Option<String>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let lit: HashSet<(usize, usize)> = points.into_iter().collect();
+    let top = lit.iter().map(|&(_x, y)| y).min()?;
+    let bottom = lit.iter().map(|&(_x, y)| y).max()?;
+    let right = lit.iter().map(|&(x, _y)| x).max()?;
+    let font = [&SMALL, &LARGE]
+        .into_iter()
+        .find(|font| font.height == bottom - top + 1)?;
+
+    (0..=right)
+        .step_by(font.pitch)
+        .map(|left| {
+            font.glyphs
+                .iter()
+                .find(|(_letter, rows)| {
+                    rows.iter().enumerate().all(|(dy, row)| {
+                        row.chars().enumerate().all(|(dx, ch)| {
+                            let pixel = (left + dx, top + dy);
+                            (ch == '#') == lit.contains(&pixel)
+                        })
+                    })
+                })
+                .map(|(letter, _rows)| *letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_letters() {
+        let grid = "\
+###..####.####.#..#.####.####.#..#..##..
+#..#....#.#....#.#..#.......#.#..#.#..#.
+#..#...#..###..##...###....#..####.#....
+###...#...#....#.#..#.....#...#..#.#.##.
+#.#..#....#....#.#..#....#....#..#.#..#.
+#..#.####.####.#..#.#....####.#..#..###.";
+
+        assert_eq!(recognize(grid), Some("RZEKFZHG".into()));
+    }
+
+    #[test]
+    fn large_letters() {
+        let grid = "\
+#....#.....###
+#....#......#.
+#....#......#.
+#....#......#.
+######......#.
+#....#......#.
+#....#......#.
+#....#..#...#.
+#....#..#...#.
+#....#...###..";
+
+        assert_eq!(recognize(grid), Some("HJ".into()));
+    }
+
+    #[test]
+    fn unknown_shapes() {
+        assert_eq!(recognize(""), None);
+        assert_eq!(recognize("#\n#\n#"), None);
+        assert_eq!(recognize("####\n#..#\n#..#\n#..#\n#..#\n####"), None);
+    }
+}
@@ -1,11 +1,33 @@
+use crate::puzzle::PuzzleError;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    input.lines().count()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    input.chars().count()
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on malformed input.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(input.lines().count())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(input.chars().count())
 }
 
 #[cfg(test)]
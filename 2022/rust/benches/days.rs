@@ -50,10 +50,24 @@ pub fn day14(c: &mut Criterion) {
 
 day!(day15, "15");
 
+pub fn day16(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Day 16");
+    let input = std::fs::read_to_string("../inputs/16").unwrap();
+
+    group.significance_level(0.1).sample_size(10);
+    group.bench_function("30 minutes, 1 agent", |b| {
+        b.iter(|| day16::optimize(black_box(&input), "AA", 1, 30))
+    });
+    group.bench_function("26 minutes, 2 agents", |b| {
+        b.iter(|| day16::optimize(black_box(&input), "AA", 2, 26))
+    });
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().significance_level(0.1).sample_size(25);
     targets = day01, day02, day03, day04, day05, day06, day07, day08, day09,
-    day10, day11, day12, day13, day14, day15
+    day10, day11, day12, day13, day14, day15, day16
 }
 criterion_main!(benches);
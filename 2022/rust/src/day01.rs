@@ -1,31 +1,51 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 1;
+
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    *group_sum(input).iter().max().unwrap()
+    try_part1(input).unwrap()
 }
 
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let mut elf_calories = group_sum(input);
-    elf_calories.sort_unstable();
-    elf_calories.reverse();
+    try_part2(input).unwrap()
+}
 
-    elf_calories.iter().take(3).sum()
+/// # Errors
+///
+/// Fails on a calorie count that isn't a number, or when there are no
+/// elves at all.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    group_sum(input)?
+        .into_iter()
+        .max()
+        .ok_or_else(|| PuzzleError::new(DAY, "no elves found"))
 }
 
-fn group_sum(input: &str) -> Vec<usize> {
-    let mut totals: Vec<usize> = Vec::new();
-    let mut current_total: usize = 0;
+/// # Errors
+///
+/// Fails on a calorie count that isn't a number.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut elf_calories = group_sum(input)?;
+    elf_calories.sort_unstable();
+    elf_calories.reverse();
 
-    for line in input.lines() {
-        if let Ok(value) = line.trim().parse::<usize>() {
-            current_total += value;
-        } else {
-            totals.push(current_total);
-            current_total = 0;
-        }
-    }
+    Ok(elf_calories.iter().take(3).sum())
+}
 
-    totals
+fn group_sum(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    parsing::blocks(input)
+        .iter()
+        .map(|lines| {
+            lines
+                .iter()
+                .map(|line| line.parse::<usize>(line.text))
+                .sum()
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
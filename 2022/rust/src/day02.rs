@@ -1,3 +1,8 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 2;
+
 #[derive(Clone, Copy)]
 enum Shape {
     Rock = 1,
@@ -6,59 +11,88 @@ enum Shape {
 }
 
 impl Shape {
-    fn from_code(code: &str) -> Self {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "A" => Some(Self::Rock),
+            "B" => Some(Self::Paper),
+            "C" => Some(Self::Scissors),
+            _ => None,
+        }
+    }
+
+    fn from_encrypted(code: Code) -> Self {
         match code {
-            "A" => Self::Rock,
-            "B" => Self::Paper,
-            "C" => Self::Scissors,
-            other => panic!("expected valid shape code, got {other}"),
+            Code::X => Self::Rock,
+            Code::Y => Self::Paper,
+            Code::Z => Self::Scissors,
         }
     }
+}
+
+#[derive(Clone, Copy)]
+enum Code {
+    X,
+    Y,
+    Z,
+}
 
-    fn from_encrypted(code: &str) -> Self {
+impl Code {
+    fn from_code(code: &str) -> Option<Self> {
         match code {
-            "X" => Self::Rock,
-            "Y" => Self::Paper,
-            "Z" => Self::Scissors,
-            other => panic!("expected valid encrypted shape code, got {other}"),
+            "X" => Some(Self::X),
+            "Y" => Some(Self::Y),
+            "Z" => Some(Self::Z),
+            _ => None,
         }
     }
 }
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let pairs = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a round that isn't an `A`-`C` shape and an `X`-`Z` code.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut score: usize = 0;
 
-    for (opponent, suggestion) in pairs {
-        let opponent_shape = Shape::from_code(opponent);
+    for (opponent_shape, suggestion) in pairs {
         let suggested_shape = Shape::from_encrypted(suggestion);
+
         score += shape_score(suggested_shape)
             + outcome_score(suggested_shape, opponent_shape);
     }
 
-    score
+    Ok(score)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let pairs = parse(input);
+/// # Errors
+///
+/// Fails on a round that isn't an `A`-`C` shape and an `X`-`Z` code.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut score: usize = 0;
 
-    for (opponent, desired_outcome) in pairs {
-        let opponent_shape = Shape::from_code(opponent);
+    for (opponent_shape, desired_outcome) in pairs {
         let your_shape = match desired_outcome {
-            "X" => losing_shape(opponent_shape),
-            "Y" => opponent_shape,
-            "Z" => winning_shape(opponent_shape),
-            other => panic!("expected valid desired outcome code, got {other}"),
+            Code::X => losing_shape(opponent_shape),
+            Code::Y => opponent_shape,
+            Code::Z => winning_shape(opponent_shape),
         };
 
         score +=
             shape_score(your_shape) + outcome_score(your_shape, opponent_shape);
     }
 
-    score
+    Ok(score)
 }
 
 fn winning_shape(opponent_shape: Shape) -> Shape {
@@ -91,12 +125,24 @@ fn outcome_score(yours: Shape, opponents: Shape) -> usize {
     }
 }
 
-fn parse(input: &str) -> Vec<(&str, &str)> {
-    input
-        .lines()
+fn parse(input: &str) -> parsing::Result<Vec<(Shape, Code)>> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
-            let mut pair = line.split_whitespace();
-            (pair.next().unwrap(), pair.next().unwrap())
+            let line = line.trim();
+            let (opponent, yours) = line.split_once(line.text, " ")?;
+            let opponent_shape =
+                Shape::from_code(opponent).ok_or_else(|| {
+                    line.error(
+                        opponent,
+                        format!("invalid shape code `{opponent}`"),
+                    )
+                })?;
+            let code = Code::from_code(yours).ok_or_else(|| {
+                line.error(yours, format!("invalid code `{yours}`"))
+            })?;
+
+            Ok((opponent_shape, code))
         })
         .collect()
 }
@@ -1,6 +1,24 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 3;
+
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let rucksacks: Vec<&str> = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a rucksack with an odd number of items or a non-letter item.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let rucksacks: Vec<&str> =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
     let split_rucksacks: Vec<(&str, &str)> = rucksacks
         .into_iter()
         .map(|line| line.split_at(line.len() / 2))
@@ -16,16 +34,27 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    common_chars.into_iter().map(priority).sum()
+    Ok(common_chars.into_iter().map(priority).sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let rucksacks: Vec<&str> = parse(input);
+/// # Errors
+///
+/// Fails like [`try_part1`], and when the rucksacks don't split into
+/// groups of three.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let rucksacks: Vec<&str> =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut common_chars: Vec<char> = Vec::new();
 
+    if !rucksacks.len().is_multiple_of(3) {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected groups of three rucksacks",
+        ));
+    }
+
     for group in rucksacks.chunks(3) {
-        let first_rucksack: &str = group.iter().next().unwrap();
+        let first_rucksack: &str = group[0];
         let other_rucksacks: Vec<&str> =
             group.iter().skip(1).copied().collect();
 
@@ -37,7 +66,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    common_chars.into_iter().map(priority).sum()
+    Ok(common_chars.into_iter().map(priority).sum())
 }
 
 fn priority(ch: char) -> usize {
@@ -50,8 +79,25 @@ fn priority(ch: char) -> usize {
     }
 }
 
-fn parse(input: &str) -> Vec<&str> {
-    input.lines().map(str::trim).collect()
+fn parse(input: &str) -> parsing::Result<Vec<&str>> {
+    parsing::lines(input)
+        .into_iter()
+        .map(|line| {
+            let line = line.trim();
+
+            match line.text.find(|ch: char| !ch.is_ascii_alphabetic()) {
+                Some(index) => {
+                    Err(line
+                        .error(&line.text[index..], "expected an item letter"))
+                }
+                None if !line.text.len().is_multiple_of(2) => {
+                    Err(line
+                        .error(line.text, "expected an even number of items"))
+                }
+                None => Ok(line.text),
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
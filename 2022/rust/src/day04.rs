@@ -1,10 +1,26 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::ops::RangeInclusive;
 
+const DAY: u8 = 4;
+
 type Range = RangeInclusive<usize>;
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let range_pairs = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't two `a-b` ranges.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let range_pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut count: usize = 0;
 
     for (left, right) in range_pairs {
@@ -13,12 +29,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let range_pairs = parse(input);
+/// # Errors
+///
+/// Fails on a line that isn't two `a-b` ranges.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let range_pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut count: usize = 0;
 
     for (left, right) in range_pairs {
@@ -27,7 +45,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
 fn overlapping(left: &Range, right: &Range) -> bool {
@@ -41,23 +59,24 @@ fn proper_subset(left: &Range, right: &Range) -> bool {
     left.contains(right.start()) && left.contains(right.end())
 }
 
-fn parse(input: &str) -> Vec<(Range, Range)> {
-    input
-        .lines()
+fn parse(input: &str) -> parsing::Result<Vec<(Range, Range)>> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
-            let (left, right) =
-                line.trim().split_once(',').expect("pair of ranges");
-            (parse_range(left), parse_range(right))
+            let line = line.trim();
+            let (left, right) = line.split_once(line.text, ",")?;
+
+            Ok((parse_range(&line, left)?, parse_range(&line, right)?))
         })
         .collect()
 }
 
-fn parse_range(input: &str) -> Range {
-    let (from_string, to_string) = input.split_once('-').expect("range");
-    let from: usize = from_string.parse().unwrap();
-    let to: usize = to_string.parse().unwrap();
+fn parse_range(line: &SourceLine, field: &str) -> parsing::Result<Range> {
+    let (from_string, to_string) = line.split_once(field, "-")?;
+    let from: usize = line.parse(from_string)?;
+    let to: usize = line.parse(to_string)?;
 
-    from..=to
+    Ok(from..=to)
 }
 
 #[cfg(test)]
@@ -83,4 +102,16 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 4)
     }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            try_part1("2-4,6-8\n2-3;4-5\n").unwrap_err().to_string(),
+            "day 04, line 2: column 1, expected `,`"
+        );
+        assert_eq!(
+            try_part2("2-4,6-x\n").unwrap_err().to_string(),
+            "day 04, line 1: column 7, invalid value `x`"
+        );
+    }
 }
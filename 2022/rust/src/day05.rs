@@ -1,6 +1,9 @@
-use regex::Regex;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::VecDeque;
 
+const DAY: u8 = 5;
+
 type Crate = char;
 type StackId = usize;
 
@@ -14,30 +17,57 @@ struct Stack {
 #[derive(Debug)]
 #[allow(dead_code)]
 struct Instruction {
+    line: usize,
     count: usize,
     from: StackId,
     to: StackId,
 }
 
+impl Instruction {
+    fn error(&self, message: &str) -> PuzzleError {
+        PuzzleError {
+            day: DAY,
+            line: Some(self.line),
+            message: message.into(),
+        }
+    }
+}
+
 #[must_use]
 pub fn part1(input: &str) -> String {
-    let (mut stacks, instructions) = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a malformed drawing or instruction, or a move that takes more
+/// crates than its stack holds.
+pub fn try_part1(input: &str) -> Result<String, PuzzleError> {
+    let (mut stacks, instructions) = parse(input)?;
 
     for instruction in instructions {
         for _ in 1..=instruction.count {
-            move_single(&mut stacks, instruction.from, instruction.to);
+            move_single(&mut stacks, instruction.from, instruction.to)
+                .ok_or_else(|| {
+                    instruction.error("not enough crates to move")
+                })?;
         }
     }
 
-    stacks
-        .iter()
-        .map(|stack| stack.contents.iter().next().unwrap())
-        .collect()
+    top_crates(&stacks)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> String {
-    let (mut stacks, instructions) = parse(input);
+/// # Errors
+///
+/// Fails on a malformed drawing or instruction, a move that takes more
+/// crates than its stack holds, or a stack left empty at the end.
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    let (mut stacks, instructions) = parse(input)?;
 
     for instruction in instructions {
         move_multiple(
@@ -45,29 +75,38 @@ pub fn part2(input: &str) -> String {
             instruction.from,
             instruction.to,
             instruction.count,
-        );
+        )
+        .ok_or_else(|| instruction.error("not enough crates to move"))?;
     }
 
+    top_crates(&stacks)
+}
+
+fn top_crates(stacks: &[Stack]) -> Result<String, PuzzleError> {
     stacks
         .iter()
-        .map(|stack| stack.contents.iter().next().unwrap())
-        .collect()
+        .map(|stack| stack.contents.front())
+        .collect::<Option<String>>()
+        .ok_or_else(|| PuzzleError::new(DAY, "a stack ended up empty"))
 }
 
-fn move_single(stacks: &mut [Stack], from_id: StackId, to_id: StackId) {
+fn move_single(
+    stacks: &mut [Stack],
+    from_id: StackId,
+    to_id: StackId,
+) -> Option<()> {
     let crate_to_move = stacks
         .iter_mut()
-        .find(|stack| stack.id == from_id)
-        .unwrap()
+        .find(|stack| stack.id == from_id)?
         .contents
-        .pop_front()
-        .unwrap();
+        .pop_front()?;
     stacks
         .iter_mut()
-        .find(|stack| stack.id == to_id)
-        .unwrap()
+        .find(|stack| stack.id == to_id)?
         .contents
         .push_front(crate_to_move);
+
+    Some(())
 }
 
 fn move_multiple(
@@ -75,40 +114,51 @@ fn move_multiple(
     from_id: StackId,
     to_id: StackId,
     count: usize,
-) {
+) -> Option<()> {
     let from_stack = &mut stacks
         .iter_mut()
-        .find(|stack| stack.id == from_id)
-        .unwrap()
+        .find(|stack| stack.id == from_id)?
         .contents;
 
+    if from_stack.len() < count {
+        return None;
+    }
+
     let mut crates_to_move: VecDeque<char> = VecDeque::new();
 
     for _ in 1..=count {
-        crates_to_move.push_front(from_stack.pop_front().unwrap());
+        crates_to_move.push_front(from_stack.pop_front()?);
     }
 
-    let to_stack = stacks.iter_mut().find(|stack| stack.id == to_id).unwrap();
+    let to_stack = stacks.iter_mut().find(|stack| stack.id == to_id)?;
 
     for crate_to_move in crates_to_move {
         to_stack.contents.push_front(crate_to_move);
     }
+
+    Some(())
 }
 
-fn parse(input: &str) -> (Vec<Stack>, Vec<Instruction>) {
-    let (stack_lines, instruction_lines) =
-        input.split_once("\n\n").expect("valid input");
+fn parse(input: &str) -> Result<(Vec<Stack>, Vec<Instruction>), PuzzleError> {
+    match &parsing::blocks(input)[..] {
+        [stack_lines, instruction_lines] => {
+            let stacks = parse_stacks(stack_lines);
+            let instructions = parse_instructions(instruction_lines, &stacks)
+                .map_err(PuzzleError::on_day(DAY))?;
 
-    (
-        parse_stacks(stack_lines),
-        parse_instructions(instruction_lines),
-    )
+            Ok((stacks, instructions))
+        }
+        _ => Err(PuzzleError::new(
+            DAY,
+            "expected stacks, a blank line, then instructions",
+        )),
+    }
 }
 
-fn parse_stacks(stack_lines: &str) -> Vec<Stack> {
+fn parse_stacks(stack_lines: &[SourceLine]) -> Vec<Stack> {
     let map: Vec<Vec<char>> = stack_lines
-        .lines()
-        .map(|line| line.chars().collect())
+        .iter()
+        .map(|line| line.text.chars().collect())
         .collect();
     let size: usize = map.len() - 1;
     let id_row: usize = map.len() - 1;
@@ -135,21 +185,31 @@ fn parse_stacks(stack_lines: &str) -> Vec<Stack> {
     stacks
 }
 
-fn parse_instructions(instruction_lines: &str) -> Vec<Instruction> {
-    let instruction_regex =
-        Regex::new(r"^move (\d+) from (\d+) to (\d+)$").expect("valid regex");
-
+fn parse_instructions(
+    instruction_lines: &[SourceLine],
+    stacks: &[Stack],
+) -> parsing::Result<Vec<Instruction>> {
     instruction_lines
-        .lines()
+        .iter()
         .map(|line| {
-            let captures = instruction_regex
-                .captures(line.trim())
-                .expect("instruction format");
-            Instruction {
-                count: captures[1].parse::<usize>().expect("numeric count"),
-                from: captures[2].parse::<usize>().expect("numeric from"),
-                to: captures[3].parse::<usize>().expect("numeric to"),
-            }
+            let line = line.trim();
+            let fields = line.fields("move {} from {} to {}")?;
+            let stack_id = |field| {
+                let id = line.parse(field)?;
+
+                if stacks.iter().any(|stack| stack.id == id) {
+                    Ok(id)
+                } else {
+                    Err(line.error(field, format!("no stack {id}")))
+                }
+            };
+
+            Ok(Instruction {
+                line: line.number,
+                count: line.parse(fields[0])?,
+                from: stack_id(fields[1])?,
+                to: stack_id(fields[2])?,
+            })
         })
         .collect()
 }
@@ -179,4 +239,21 @@ move 1 from 1 to 2\n"
     fn part2_example() {
         assert_eq!(part2(input()), "MCD")
     }
+
+    #[test]
+    fn malformed_input() {
+        let wrong_stack =
+            input().replace("move 3 from 1 to 3", "move 3 from 1 to 4");
+        assert_eq!(
+            try_part1(&wrong_stack).unwrap_err().to_string(),
+            "day 05, line 7: column 18, no stack 4"
+        );
+
+        let too_many =
+            input().replace("move 3 from 1 to 3", "move 4 from 1 to 3");
+        assert_eq!(
+            try_part2(&too_many).unwrap_err().to_string(),
+            "day 05, line 7: not enough crates to move"
+        );
+    }
 }
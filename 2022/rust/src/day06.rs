@@ -1,24 +1,44 @@
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 6;
+
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    all_different_position::<4>(input)
+    try_part1(input).unwrap()
 }
 
 #[must_use]
 pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when no four consecutive characters are all different.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    all_different_position::<4>(input)
+        .ok_or_else(|| PuzzleError::new(DAY, "no start-of-packet marker"))
+}
+
+/// # Errors
+///
+/// Fails when no fourteen consecutive characters are all different.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     all_different_position::<14>(input)
+        .ok_or_else(|| PuzzleError::new(DAY, "no start-of-message marker"))
 }
 
-fn all_different_position<const WINDOW: usize>(input: &str) -> usize {
+fn all_different_position<const WINDOW: usize>(input: &str) -> Option<usize> {
     let mut recent: [u8; WINDOW] = [0; WINDOW];
 
     for (position, ch) in input.chars().enumerate() {
         recent[position % WINDOW] = ch as u8;
         if position >= WINDOW && all_different::<WINDOW>(&recent) {
-            return position + 1;
+            return Some(position + 1);
         }
     }
 
-    0
+    None
 }
 
 fn all_different<const N: usize>(values: &[u8; N]) -> bool {
@@ -1,203 +1,329 @@
-use std::collections::HashMap;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::fmt::Write;
 
-#[derive(Debug, PartialEq)]
-struct File {
+const DAY: u8 = 7;
+const DISK_SIZE: usize = 70_000_000;
+const SPACE_NEEDED: usize = 30_000_000;
+
+pub type NodeId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    File,
+    Directory,
+}
+
+#[derive(Debug)]
+struct Node {
     name: String,
+    parent: Option<NodeId>,
     size: usize,
+    children: Vec<NodeId>,
+    kind: Kind,
 }
 
-#[derive(Debug, PartialEq)]
-struct Directory {
-    name: String,
-    parents: Vec<String>,
-    entries: Vec<Entry>,
+// What `find` reports for each node; `size` is the total size of everything
+// below a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub id: NodeId,
+    pub name: &'a str,
+    pub kind: Kind,
+    pub size: usize,
+    pub depth: usize,
+}
+
+// Nodes live in an arena indexed by `NodeId`, with the root directory at 0.
+#[derive(Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
 }
 
-impl Directory {
-    fn path(&self) -> String {
-        if self.parents.is_empty() {
-            if self.name == "/" {
-                self.name.clone()
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".into(),
+                parent: None,
+                size: 0,
+                children: vec![],
+                kind: Kind::Directory,
+            }],
+        }
+    }
+}
+
+impl FileSystem {
+    /// Replays a `cd`/`ls` transcript, rejecting anything a real shell could
+    /// not have printed.
+    ///
+    /// # Errors
+    ///
+    /// Fails on an unknown command, listing output outside an `ls`, a `cd`
+    /// above the root or into a directory that hasn't been listed, or an
+    /// entry that contradicts an earlier listing.
+    pub fn from_transcript(input: &str) -> parsing::Result<Self> {
+        let mut filesystem = Self::default();
+        let mut current = filesystem.root();
+        let mut listing = false;
+
+        for line in parsing::lines(input) {
+            let line = line.trim();
+
+            if let Some(command) = line.text.strip_prefix("$ ") {
+                let (name, argument) =
+                    command.split_once(' ').unwrap_or((command, ""));
+                listing = false;
+
+                match name {
+                    "cd" => {
+                        current = filesystem.cd(&line, current, argument)?;
+                    }
+                    "ls" => listing = true,
+                    _ => {
+                        return Err(line.error(
+                            command,
+                            format!("unknown command `{name}`"),
+                        ))
+                    }
+                }
+            } else if listing {
+                filesystem.record(&line, current)?;
             } else {
-                format!("/{}", self.name)
+                return Err(
+                    line.error(line.text, "listing output without `ls`")
+                );
             }
-        } else {
-            format!("/{}/{}", self.parents.join("/"), self.name)
         }
+
+        Ok(filesystem)
     }
 
-    fn parent_path(&self) -> String {
-        if self.parents.is_empty() {
-            "/".into()
+    fn cd(
+        &self,
+        line: &SourceLine,
+        current: NodeId,
+        argument: &str,
+    ) -> parsing::Result<NodeId> {
+        match argument {
+            "/" => Ok(self.root()),
+            ".." => self.nodes[current].parent.ok_or_else(|| {
+                line.error(argument, "cd above the root directory")
+            }),
+            name => self
+                .child(current, name)
+                .filter(|&child| self.nodes[child].kind == Kind::Directory)
+                .ok_or_else(|| {
+                    line.error(
+                        argument,
+                        format!("cd to nonexistent directory `{name}`"),
+                    )
+                }),
+        }
+    }
+
+    // Listing the same directory twice is fine as long as it agrees with
+    // what was seen before.
+    fn record(
+        &mut self,
+        line: &SourceLine,
+        directory: NodeId,
+    ) -> parsing::Result<()> {
+        let (dir_or_size, name) = line.split_once(line.text, " ")?;
+        let (kind, size) = if dir_or_size == "dir" {
+            (Kind::Directory, 0)
         } else {
-            format!("/{}", self.parents.join("/"))
+            (Kind::File, line.parse(dir_or_size)?)
+        };
+
+        if let Some(existing) = self.child(directory, name) {
+            let node = &self.nodes[existing];
+
+            return match (node.kind, kind) {
+                (Kind::File, Kind::File) if node.size != size => {
+                    Err(line.error(name, format!("`{name}` changed size")))
+                }
+                (Kind::File, Kind::Directory) => Err(line.error(
+                    name,
+                    format!("`{name}` was already listed as a file"),
+                )),
+                (Kind::Directory, Kind::File) => Err(line.error(
+                    name,
+                    format!("`{name}` was already listed as a directory"),
+                )),
+                _ => Ok(()),
+            };
         }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.into(),
+            parent: Some(directory),
+            size,
+            children: vec![],
+            kind,
+        });
+        self.nodes[directory].children.push(id);
+
+        Ok(())
     }
-}
 
-#[derive(Debug, PartialEq)]
-enum Entry {
-    File(File),
-    Directory(Directory),
-}
+    #[must_use]
+    pub fn root(&self) -> NodeId {
+        0
+    }
 
-#[must_use]
-pub fn part1(input: &str) -> usize {
-    let root = parse(input);
-    let mut directory_totals: HashMap<String, usize> = HashMap::new();
-    let mut total_at_most_100k: usize = 0;
+    #[must_use]
+    pub fn child(&self, directory: NodeId, name: &str) -> Option<NodeId> {
+        self.nodes[directory]
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].name == name)
+    }
+
+    // Resolves an absolute path such as `/a/e`.
+    #[must_use]
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(self.root(), |node, name| self.child(node, name))
+    }
 
-    compute_totals(&root, &mut directory_totals);
+    #[must_use]
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut node = id;
 
-    for (_name, total) in directory_totals {
-        if total <= 100_000 {
-            total_at_most_100k += total;
+        while let Some(parent) = self.nodes[node].parent {
+            names.push(self.nodes[node].name.as_str());
+            node = parent;
         }
-    }
+        names.reverse();
 
-    total_at_most_100k
-}
+        format!("/{}", names.join("/"))
+    }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let root = parse(input);
-    let mut directory_totals: HashMap<String, usize> = HashMap::new();
+    #[must_use]
+    pub fn du(&self, id: NodeId) -> usize {
+        let node = &self.nodes[id];
 
-    compute_totals(&root, &mut directory_totals);
+        node.size
+            + node
+                .children
+                .iter()
+                .map(|&child| self.du(child))
+                .sum::<usize>()
+    }
 
-    let mut sorted: Vec<(String, usize)> =
-        directory_totals.into_iter().collect();
-    sorted.sort_unstable_by(|(_name1, value1), (_name2, value2)| {
-        value1.partial_cmp(value2).unwrap()
-    });
+    // Every node matching `predicate`, in depth-first listing order.
+    pub fn find<F>(&self, predicate: F) -> Vec<Entry<'_>>
+    where
+        F: Fn(&Entry) -> bool,
+    {
+        let mut sizes = vec![0; self.nodes.len()];
+        self.sizes(self.root(), &mut sizes);
 
-    let (_name, size) = sorted
-        .iter()
-        .find(|(_name, size)| *size > 8_381_165)
-        .unwrap();
+        let mut matches = vec![];
+        let mut stack = vec![(self.root(), 0)];
 
-    *size
-}
+        while let Some((id, depth)) = stack.pop() {
+            let node = &self.nodes[id];
+            let entry = Entry {
+                id,
+                name: &node.name,
+                kind: node.kind,
+                size: sizes[id],
+                depth,
+            };
 
-fn compute_totals(directory: &Directory, totals: &mut HashMap<String, usize>) {
-    for entry in &directory.entries {
-        match entry {
-            Entry::File(file) => {
-                totals
-                    .entry(directory.path())
-                    .and_modify(|total| *total += file.size)
-                    .or_insert(file.size);
-            }
-            Entry::Directory(child_directory) => {
-                compute_totals(child_directory, totals);
-                let child_directory_size: usize =
-                    *totals.get(&child_directory.path()).unwrap();
-
-                totals
-                    .entry(directory.path())
-                    .and_modify(|total| *total += child_directory_size)
-                    .or_insert(child_directory_size);
+            if predicate(&entry) {
+                matches.push(entry);
             }
+            stack.extend(
+                node.children.iter().rev().map(|&child| (child, depth + 1)),
+            );
         }
+
+        matches
     }
-}
 
-fn parse(input: &str) -> Directory {
-    let mut root = Directory {
-        name: "/".into(),
-        parents: vec![],
-        entries: Vec::new(),
-    };
-
-    let mut current_directory = &mut root;
-
-    for line in input.lines() {
-        if line.starts_with("$ ") {
-            let command: Vec<&str> = line
-                .trim()
-                .strip_prefix("$ ")
-                .unwrap()
-                .split_whitespace()
-                .collect();
-
-            if command[0] == "cd" {
-                match command.get(1) {
-                    Some(&"/") => {
-                        current_directory = &mut root;
-                    }
-                    Some(&"..") => {
-                        let parent_path = current_directory.parent_path();
-                        let new_directory = if parent_path == "/" {
-                            &mut root
-                        } else {
-                            find_directory(&mut root, &parent_path)
-                                .expect("cd to nonexistent directory")
-                        };
-
-                        current_directory = new_directory;
-                    }
-                    Some(&name) => {
-                        let new_directory = current_directory
-                            .entries
-                            .iter_mut()
-                            .find_map(|entry| match entry {
-                                Entry::Directory(directory) => {
-                                    if directory.name == name {
-                                        Some(directory)
-                                    } else {
-                                        None
-                                    }
-                                }
-                                Entry::File(_) => None,
-                            })
-                            .expect("cd to nonexistent directory");
-                        current_directory = new_directory;
+    fn sizes(&self, id: NodeId, sizes: &mut [usize]) -> usize {
+        let node = &self.nodes[id];
+        sizes[id] = node.size
+            + node
+                .children
+                .iter()
+                .map(|&child| self.sizes(child, sizes))
+                .sum::<usize>();
+
+        sizes[id]
+    }
+
+    // Renders like the puzzle description: `- a (dir)` and
+    // `- b.txt (file, size=14848514)`, indented two spaces per level.
+    #[must_use]
+    pub fn tree(&self) -> String {
+        self.find(|_| true)
+            .iter()
+            .fold(String::new(), |mut output, entry| {
+                let indent = "  ".repeat(entry.depth);
+                let _ = match entry.kind {
+                    Kind::Directory => {
+                        writeln!(output, "{indent}- {} (dir)", entry.name)
                     }
-                    _ => {}
+                    Kind::File => writeln!(
+                        output,
+                        "{indent}- {} (file, size={})",
+                        entry.name, entry.size
+                    ),
                 };
-            }
-        } else {
-            let (dir_or_size, name) = line.split_once(' ').unwrap();
-            let entry = if dir_or_size == "dir" {
-                let mut current_parents = current_directory.parents.clone();
+                output
+            })
+    }
+}
 
-                if current_directory.name != "/" {
-                    current_parents.push(current_directory.name.clone());
-                }
+#[must_use]
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
 
-                Entry::Directory(Directory {
-                    name: name.into(),
-                    parents: current_parents,
-                    entries: Vec::new(),
-                })
-            } else {
-                Entry::File(File {
-                    name: name.into(),
-                    size: dir_or_size.parse::<usize>().unwrap(),
-                })
-            };
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
 
-            current_directory.entries.push(entry);
-        }
-    }
+/// # Errors
+///
+/// Fails on a transcript [`FileSystem::from_transcript`] rejects.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let filesystem =
+        FileSystem::from_transcript(input).map_err(PuzzleError::on_day(DAY))?;
 
-    root
+    Ok(filesystem
+        .find(|entry| entry.kind == Kind::Directory && entry.size <= 100_000)
+        .iter()
+        .map(|entry| entry.size)
+        .sum())
 }
 
-fn find_directory<'a>(
-    directory: &'a mut Directory,
-    path: &String,
-) -> Option<&'a mut Directory> {
-    directory.entries.iter_mut().find_map(|entry| match entry {
-        Entry::Directory(child_directory) => {
-            if child_directory.path() == *path {
-                Some(child_directory)
-            } else {
-                find_directory(child_directory, path)
-            }
-        }
-        Entry::File(_) => None,
-    })
+/// # Errors
+///
+/// Fails on a transcript [`FileSystem::from_transcript`] rejects, or when
+/// no single directory frees enough space.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let filesystem =
+        FileSystem::from_transcript(input).map_err(PuzzleError::on_day(DAY))?;
+    let free = DISK_SIZE.saturating_sub(filesystem.du(filesystem.root()));
+    let to_delete = SPACE_NEEDED.saturating_sub(free);
+
+    filesystem
+        .find(|entry| entry.kind == Kind::Directory && entry.size >= to_delete)
+        .iter()
+        .map(|entry| entry.size)
+        .min()
+        .ok_or_else(|| PuzzleError::new(DAY, "no directory is large enough"))
 }
 
 #[cfg(test)]
@@ -232,71 +358,83 @@ $ ls
     }
 
     #[test]
-    fn test_parse() {
+    fn tree_rendering() {
+        let filesystem = FileSystem::from_transcript(input()).unwrap();
+
         assert_eq!(
-            parse(input()),
-            Directory {
-                name: "/".into(),
-                parents: vec![],
-                entries: vec![
-                    Entry::Directory(Directory {
-                        name: "a".into(),
-                        parents: vec![],
-                        entries: vec![
-                            Entry::Directory(Directory {
-                                name: "e".into(),
-                                parents: vec!["a".into()],
-                                entries: vec![Entry::File(File {
-                                    name: "i".into(),
-                                    size: 584
-                                }),]
-                            }),
-                            Entry::File(File {
-                                name: "f".into(),
-                                size: 29116
-                            }),
-                            Entry::File(File {
-                                name: "g".into(),
-                                size: 2557
-                            }),
-                            Entry::File(File {
-                                name: "h.lst".into(),
-                                size: 62596
-                            }),
-                        ]
-                    }),
-                    Entry::File(File {
-                        name: "b.txt".into(),
-                        size: 14_848_514
-                    }),
-                    Entry::File(File {
-                        name: "c.dat".into(),
-                        size: 8_504_156
-                    }),
-                    Entry::Directory(Directory {
-                        name: "d".into(),
-                        parents: vec![],
-                        entries: vec![
-                            Entry::File(File {
-                                name: "j".into(),
-                                size: 4_060_174
-                            }),
-                            Entry::File(File {
-                                name: "d.log".into(),
-                                size: 8_033_020
-                            }),
-                            Entry::File(File {
-                                name: "d.ext".into(),
-                                size: 5_626_152
-                            }),
-                            Entry::File(File {
-                                name: "k".into(),
-                                size: 7_214_296
-                            }),
-                        ]
-                    }),
-                ]
-            }
+            filesystem.tree(),
+            "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+"
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let filesystem = FileSystem::from_transcript(input()).unwrap();
+        let directories: Vec<(String, usize)> = filesystem
+            .find(|entry| entry.kind == Kind::Directory)
+            .iter()
+            .map(|entry| (filesystem.path(entry.id), entry.size))
+            .collect();
+
+        assert_eq!(
+            directories,
+            vec![
+                ("/".into(), 48_381_165),
+                ("/a".into(), 94853),
+                ("/a/e".into(), 584),
+                ("/d".into(), 24_933_642),
+            ]
+        );
+        assert_eq!(filesystem.du(filesystem.root()), 48_381_165);
+        assert_eq!(
+            filesystem.lookup("/a/e/i").map(|id| filesystem.du(id)),
+            Some(584)
+        );
+        assert_eq!(filesystem.lookup("/a/x"), None);
+    }
+
+    #[test]
+    fn invalid_transcripts() {
+        let error = |input| FileSystem::from_transcript(input).unwrap_err();
+
+        assert_eq!(
+            error("$ cd /\n$ cd ..\n").to_string(),
+            "line 2, column 6: cd above the root directory"
+        );
+        assert_eq!(
+            error("$ cd /\n$ ls\ndir a\n$ cd b\n").to_string(),
+            "line 4, column 6: cd to nonexistent directory `b`"
+        );
+        assert_eq!(
+            error("$ cd /\n14 x\n").to_string(),
+            "line 2, column 1: listing output without `ls`"
+        );
+        assert_eq!(
+            error("$ ls\n14 x\n$ ls\ndir x\n").to_string(),
+            "line 4, column 5: `x` was already listed as a file"
+        );
+        assert_eq!(
+            error("$ pwd\n").to_string(),
+            "line 1, column 3: unknown command `pwd`"
+        );
+        assert_eq!(
+            try_part1("$ ls\nabc x\n").unwrap_err().to_string(),
+            "day 07, line 2: column 1, invalid value `abc`"
         );
     }
 
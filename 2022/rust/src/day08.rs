@@ -1,5 +1,9 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::borrow::Borrow;
 
+const DAY: u8 = 8;
+
 #[derive(Debug)]
 struct HeightMap {
     row_major: Vec<Vec<u8>>,
@@ -85,7 +89,19 @@ impl HeightMap {
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let height_map = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a grid that isn't square or holds something other than digits.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let height_map = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut visible_count = 0usize;
 
     for (row, row_heights) in height_map.row_major.iter().enumerate() {
@@ -96,12 +112,15 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    visible_count
+    Ok(visible_count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let height_map = parse(input);
+/// # Errors
+///
+/// Fails on a grid that isn't square or holds something other than
+/// digits, or on an empty grid.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let height_map = parse(input).map_err(PuzzleError::on_day(DAY))?;
 
     height_map
         .row_major
@@ -113,17 +132,32 @@ pub fn part2(input: &str) -> usize {
                 .enumerate()
                 .map(|(col, _height)| height_map.scenic_score(row, col))
                 .max()
-                .unwrap()
+                .unwrap_or(0)
         })
         .max()
-        .unwrap()
+        .ok_or_else(|| PuzzleError::new(DAY, "no trees found"))
 }
 
-fn parse(input: &str) -> HeightMap {
-    let row_major: Vec<Vec<u8>> = input
-        .lines()
-        .map(|line| line.chars().map(|ch| ch as u8 - 48).collect())
-        .collect();
+fn parse(input: &str) -> parsing::Result<HeightMap> {
+    let lines = parsing::lines(input);
+    let row_major: Vec<Vec<u8>> = lines
+        .iter()
+        .map(|line| {
+            if line.text.len() != lines.len() {
+                return Err(line.error(line.text, "expected a square grid"));
+            }
+
+            line.text
+                .char_indices()
+                .map(|(index, ch)| match ch {
+                    '0'..='9' => Ok(ch as u8 - b'0'),
+                    _ => {
+                        Err(line.error(&line.text[index..], "expected a digit"))
+                    }
+                })
+                .collect()
+        })
+        .collect::<parsing::Result<_>>()?;
 
     let mut col_major: Vec<Vec<u8>> =
         row_major.iter().map(|_| vec![]).collect();
@@ -134,10 +168,10 @@ fn parse(input: &str) -> HeightMap {
         }
     }
 
-    HeightMap {
+    Ok(HeightMap {
         row_major,
         col_major,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -1,5 +1,9 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashSet;
 
+const DAY: u8 = 9;
+
 #[derive(Clone, Copy)]
 enum Direction {
     Up,
@@ -54,7 +58,20 @@ impl Position {
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let motions = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a motion that isn't a direction (`U`, `D`, `L`, `R`) and a
+/// step count.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let motions = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut head = Position::default();
     let mut tail = Position::default();
     let mut visited: HashSet<Position> = HashSet::new();
@@ -71,12 +88,15 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    visited.len()
+    Ok(visited.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let motions = parse(input);
+/// # Errors
+///
+/// Fails on a motion that isn't a direction (`U`, `D`, `L`, `R`) and a
+/// step count.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let motions = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut head = Position::default();
     let mut knots = Vec::new();
     for _ in 0..9 {
@@ -102,28 +122,34 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    visited.len()
+    Ok(visited.len())
 }
 
 fn non_contiguous(a: &Position, b: &Position) -> bool {
     (a.x - b.x).abs() > 1 || (a.y - b.y).abs() > 1
 }
 
-fn parse(input: &str) -> Vec<Motion> {
-    input
-        .lines()
+fn parse(input: &str) -> parsing::Result<Vec<Motion>> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
+            let line = line.trim();
             let (direction_string, amount_string) =
-                line.trim().split_once(' ').unwrap();
+                line.split_once(line.text, " ")?;
             let direction = match direction_string {
                 "U" => Direction::Up,
                 "D" => Direction::Down,
                 "L" => Direction::Left,
                 "R" => Direction::Right,
-                _ => panic!("invalid direction"),
+                _ => {
+                    return Err(
+                        line.error(direction_string, "invalid direction")
+                    )
+                }
             };
-            let amount = amount_string.parse::<usize>().unwrap();
-            Motion { direction, amount }
+            let amount = line.parse(amount_string)?;
+
+            Ok(Motion { direction, amount })
         })
         .collect()
 }
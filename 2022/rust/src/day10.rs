@@ -1,3 +1,9 @@
+use crate::ocr;
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 10;
+
 #[derive(Debug)]
 enum Instruction {
     Noop,
@@ -22,7 +28,19 @@ impl Instruction {
 
 #[must_use]
 pub fn part1(input: &str) -> isize {
-    let instructions = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on an instruction other than `noop` or `addx <value>`.
+pub fn try_part1(input: &str) -> Result<isize, PuzzleError> {
+    let instructions = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut signal_strengths: Vec<isize> = Vec::new();
     let mut cycle: usize = 1;
     let mut x: isize = 1;
@@ -38,12 +56,29 @@ pub fn part1(input: &str) -> isize {
         x = instruction.execute(x);
     }
 
-    signal_strengths.iter().sum()
+    Ok(signal_strengths.iter().sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> Vec<String> {
-    let instructions = parse(input);
+/// The letters on the screen, or the screen itself when they aren't
+/// recognized.
+///
+/// # Errors
+///
+/// Fails when [`screen`] does.
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    let screen = screen(input)?.join("\n");
+
+    Ok(ocr::recognize(&screen).unwrap_or(screen))
+}
+
+/// The raw CRT rows, with `#` for lit pixels; part 2 falls back to them.
+///
+/// # Errors
+///
+/// Fails on an instruction other than `noop` or `addx <value>`, or on a
+/// program that runs past the last pixel of the screen.
+pub fn screen(input: &str) -> Result<Vec<String>, PuzzleError> {
+    let instructions = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut cycle: usize = 1;
     let mut x: isize = 1;
     let mut crt: Vec<Vec<char>> =
@@ -52,6 +87,12 @@ pub fn part2(input: &str) -> Vec<String> {
     for instruction in instructions {
         for _ in 0..instruction.cycle_count() {
             let row = (cycle - 1) / 40;
+            if row >= crt.len() {
+                return Err(PuzzleError::new(
+                    DAY,
+                    "program runs past the screen",
+                ));
+            }
             let sprite = isize::try_from((cycle - 1) % 40).unwrap();
             crt[row].push(if (sprite - x).abs() <= 1 { '#' } else { '.' });
 
@@ -61,9 +102,10 @@ pub fn part2(input: &str) -> Vec<String> {
         x = instruction.execute(x);
     }
 
-    crt.into_iter()
+    Ok(crt
+        .into_iter()
         .map(|row| row.into_iter().collect::<String>())
-        .collect()
+        .collect())
 }
 
 fn record_signal(cycle: usize) -> bool {
@@ -74,15 +116,19 @@ fn record_signal(cycle: usize) -> bool {
     }
 }
 
-fn parse(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|line| match line.split(' ').collect::<Vec<&str>>()[..] {
-            ["noop"] => Instruction::Noop,
-            ["addx", value_string] => {
-                Instruction::Addx(value_string.parse::<isize>().unwrap())
+fn parse(input: &str) -> parsing::Result<Vec<Instruction>> {
+    parsing::lines(input)
+        .into_iter()
+        .map(|line| {
+            let line = line.trim();
+
+            match line.text.split(' ').collect::<Vec<&str>>()[..] {
+                ["noop"] => Ok(Instruction::Noop),
+                ["addx", value_string] => {
+                    Ok(Instruction::Addx(line.parse(value_string)?))
+                }
+                _ => Err(line.error(line.text, "invalid instruction")),
             }
-            _ => panic!("invalid instruction"),
         })
         .collect()
 }
@@ -258,6 +304,7 @@ noop\n"
                 "######......######......######......####",
                 "#######.......#######.......#######.....",
             ]
+            .join("\n")
         )
     }
 }
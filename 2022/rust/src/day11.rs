@@ -1,37 +1,271 @@
-use std::collections::{HashMap, VecDeque};
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Rem;
+use std::str::FromStr;
 
-#[derive(Debug)]
-struct Item {
-    worry_level: usize,
+const DAY: u8 = 11;
+
+// Anything worry levels can be computed in. `i128` is plenty once worry is
+// kept in check and reports overflow when it isn't; `BigInt` tracks worry
+// exactly.
+pub trait Worry: Clone + PartialEq + From<u64> + Rem<Output = Self> {
+    /// # Errors
+    ///
+    /// Fails when the result doesn't fit, or on division by zero.
+    fn apply(
+        op: BinaryOp,
+        left: Self,
+        right: Self,
+    ) -> Result<Self, ArithmeticError>;
 }
 
-#[derive(Debug)]
-enum Operation {
-    Add(usize),
-    Multiply(usize),
-    Square,
+impl Worry for i128 {
+    fn apply(
+        op: BinaryOp,
+        left: Self,
+        right: Self,
+    ) -> Result<Self, ArithmeticError> {
+        if op == BinaryOp::Divide && right == 0 {
+            return Err(ArithmeticError::DivideByZero);
+        }
+
+        match op {
+            BinaryOp::Add => left.checked_add(right),
+            BinaryOp::Subtract => left.checked_sub(right),
+            BinaryOp::Multiply => left.checked_mul(right),
+            BinaryOp::Divide => left.checked_div(right),
+        }
+        .ok_or(ArithmeticError::Overflow)
+    }
+}
+
+impl Worry for BigInt {
+    fn apply(
+        op: BinaryOp,
+        left: Self,
+        right: Self,
+    ) -> Result<Self, ArithmeticError> {
+        Ok(match op {
+            BinaryOp::Add => left + right,
+            BinaryOp::Subtract => left - right,
+            BinaryOp::Multiply => left * right,
+            BinaryOp::Divide if right == BigInt::from(0) => {
+                return Err(ArithmeticError::DivideByZero)
+            }
+            BinaryOp::Divide => left / right,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithmeticError {
+    DivideByZero,
+    Overflow,
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivideByZero => write!(f, "division by zero"),
+            Self::Overflow => write!(f, "worry overflowed"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl BinaryOp {
+    fn symbol(self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Subtract => '-',
+            Self::Multiply => '*',
+            Self::Divide => '/',
+        }
+    }
+}
+
+// The right hand side of `new = ...`, e.g. `old * (old + 2) / 3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Old,
+    Constant(u64),
+    Binary(BinaryOp, Box<Operation>, Box<Operation>),
 }
 
 impl Operation {
-    fn execute(&self, worry_level: usize) -> usize {
+    /// # Errors
+    ///
+    /// Fails when [`Worry::apply`] does for any step of the expression.
+    pub fn evaluate<W: Worry>(&self, old: &W) -> Result<W, ArithmeticError> {
+        match self {
+            Self::Old => Ok(old.clone()),
+            Self::Constant(value) => Ok(W::from(*value)),
+            Self::Binary(op, left, right) => {
+                W::apply(*op, left.evaluate(old)?, right.evaluate(old)?)
+            }
+        }
+    }
+
+    fn divides(&self) -> bool {
+        match self {
+            Self::Old | Self::Constant(_) => false,
+            Self::Binary(op, left, right) => {
+                *op == BinaryOp::Divide || left.divides() || right.divides()
+            }
+        }
+    }
+
+    fn parse(line: &SourceLine, text: &str) -> parsing::Result<Self> {
+        let mut parser = OperationParser {
+            line,
+            text,
+            position: 0,
+        };
+        let operation = parser.expression()?;
+        parser.skip_whitespace();
+
+        if parser.position < text.len() {
+            return Err(
+                line.error(&text[parser.position..], "unexpected input")
+            );
+        }
+
+        Ok(operation)
+    }
+}
+
+impl FromStr for Operation {
+    type Err = parsing::ParseError;
+
+    fn from_str(text: &str) -> parsing::Result<Self> {
+        match parsing::lines(text)[..] {
+            [line] => Self::parse(&line, line.text),
+            _ => Err(parsing::ParseError {
+                line: 1,
+                column: 1,
+                message: "expected a single line".into(),
+            }),
+        }
+    }
+}
+
+// Fully parenthesized, so `Display` never needs to know about precedence.
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Operation::Add(amount) => worry_level + amount,
-            Operation::Multiply(amount) => worry_level * amount,
-            Operation::Square => worry_level * worry_level,
+            Self::Old => write!(f, "old"),
+            Self::Constant(value) => write!(f, "{value}"),
+            Self::Binary(op, left, right) => {
+                write!(f, "({left} {} {right})", op.symbol())
+            }
+        }
+    }
+}
+
+// Recursive descent over `+ -` then `* /`, both left associative.
+struct OperationParser<'a, 'b> {
+    line: &'b SourceLine<'a>,
+    text: &'b str,
+    position: usize,
+}
+
+impl OperationParser<'_, '_> {
+    fn expression(&mut self) -> parsing::Result<Operation> {
+        let mut left = self.term()?;
+
+        while let Some(op) = self.operator(&[BinaryOp::Add, BinaryOp::Subtract])
+        {
+            left =
+                Operation::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+
+        Ok(left)
+    }
+
+    fn term(&mut self) -> parsing::Result<Operation> {
+        let mut left = self.factor()?;
+
+        while let Some(op) =
+            self.operator(&[BinaryOp::Multiply, BinaryOp::Divide])
+        {
+            left =
+                Operation::Binary(op, Box::new(left), Box::new(self.factor()?));
+        }
+
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> parsing::Result<Operation> {
+        self.skip_whitespace();
+        let rest = &self.text[self.position..];
+
+        if rest.starts_with('(') {
+            self.position += 1;
+            let inner = self.expression()?;
+            self.skip_whitespace();
+            if !self.text[self.position..].starts_with(')') {
+                return Err(self.error("expected `)`"));
+            }
+            self.position += 1;
+            return Ok(inner);
         }
+
+        if rest.starts_with("old") {
+            self.position += 3;
+            return Ok(Operation::Old);
+        }
+
+        let digits = rest.len()
+            - rest
+                .trim_start_matches(|ch: char| ch.is_ascii_digit())
+                .len();
+        if digits == 0 {
+            return Err(self.error("expected `old`, a number or `(`"));
+        }
+        self.position += digits;
+
+        self.line.parse(&rest[..digits]).map(Operation::Constant)
+    }
+
+    fn operator(&mut self, ops: &[BinaryOp]) -> Option<BinaryOp> {
+        self.skip_whitespace();
+        let next = self.text[self.position..].chars().next()?;
+        let op = ops.iter().copied().find(|op| op.symbol() == next)?;
+        self.position += 1;
+
+        Some(op)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, message: &str) -> parsing::ParseError {
+        self.line.error(&self.text[self.position..], message)
     }
 }
 
 #[derive(Debug)]
 struct Test {
-    divisor: usize,
+    divisor: u64,
     true_monkey_id: MonkeyID,
     false_monkey_id: MonkeyID,
 }
 
 impl Test {
-    fn run(&self, value: usize) -> MonkeyID {
-        if value % self.divisor == 0 {
+    fn run<W: Worry>(&self, value: &W) -> MonkeyID {
+        if value.clone() % W::from(self.divisor) == W::from(0) {
             self.true_monkey_id
         } else {
             self.false_monkey_id
@@ -39,192 +273,255 @@ impl Test {
     }
 }
 
-type MonkeyID = u8;
+pub type MonkeyID = u8;
 
 #[derive(Debug)]
 struct Monkey {
-    #[allow(dead_code)]
     id: MonkeyID,
-    inventory: VecDeque<Item>,
+    items: Vec<u64>,
     operation: Operation,
     test: Test,
-    inspection_count: usize,
 }
 
-impl Monkey {
-    fn play(&mut self, transfer_queue: &mut HashMap<MonkeyID, Vec<Item>>) {
-        for mut item in self.inventory.drain(..) {
-            item.worry_level = self.operation.execute(item.worry_level) / 3;
-            self.inspection_count += 1;
+// How worry is kept from growing without bound after each inspection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relief {
+    // Divide (rounding down) by a constant, as in part 1.
+    Divide(u64),
+    // Work modulo the LCM of every monkey's divisor, which leaves all the
+    // divisibility tests unchanged. Operations must not divide.
+    ModuloLcm,
+    // No relief at all; worry is tracked exactly with big integers.
+    Exact,
+}
 
-            let to_id = self.test.run(item.worry_level);
-            transfer_queue.get_mut(&to_id).unwrap().push(item);
+// How many items each monkey inspected in each round, with monkeys in
+// ascending ID order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct History {
+    pub ids: Vec<MonkeyID>,
+    pub rounds: Vec<Vec<usize>>,
+}
+
+impl History {
+    #[must_use]
+    pub fn totals(&self) -> Vec<usize> {
+        let mut totals = vec![0; self.ids.len()];
+        for round in &self.rounds {
+            for (total, count) in totals.iter_mut().zip(round) {
+                *total += count;
+            }
         }
+        totals
     }
 
-    fn play_rough(
-        &mut self,
-        transfer_queue: &mut HashMap<MonkeyID, Vec<Item>>,
-    ) {
-        for mut item in self.inventory.drain(..) {
-            item.worry_level = self.operation.execute(item.worry_level);
-            self.inspection_count += 1;
-
-            let to_id = self.test.run(item.worry_level);
-            transfer_queue.get_mut(&to_id).unwrap().push(item);
-        }
+    // The product of the two highest inspection totals.
+    #[must_use]
+    pub fn monkey_business(&self) -> usize {
+        let mut totals = self.totals();
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        totals.iter().take(2).product()
     }
 }
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let mut monkeys = parse(input);
-    let mut transfer_queue: HashMap<MonkeyID, Vec<Item>> = HashMap::new();
-    let mut ids: Vec<MonkeyID> = monkeys.keys().copied().collect();
-    ids.sort_unstable();
-
-    for _round in 0..20 {
-        for id in &ids {
-            for &id in monkeys.keys() {
-                transfer_queue.insert(id, Vec::new());
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when [`simulate`] does.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(simulate(input, Relief::Divide(3), 20)?.monkey_business())
+}
+
+/// # Errors
+///
+/// Fails when [`simulate`] does.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(simulate(input, Relief::ModuloLcm, 10_000)?.monkey_business())
+}
+
+/// # Errors
+///
+/// Fails on a malformed or duplicate monkey, a throw to a missing monkey,
+/// dividing worry by zero, modular relief for a monkey whose operation
+/// divides, or when the divisors' LCM or a worry level overflows.
+pub fn simulate(
+    input: &str,
+    relief: Relief,
+    rounds: usize,
+) -> Result<History, PuzzleError> {
+    let monkeys = parse(input)?;
+
+    match relief {
+        Relief::Divide(0) => {
+            Err(PuzzleError::new(DAY, "cannot divide worry by zero"))
+        }
+        Relief::Divide(divisor) => {
+            let divisor = i128::from(divisor);
+            play::<i128>(&monkeys, rounds, |worry| worry / divisor)
+        }
+        Relief::ModuloLcm => {
+            if let Some(monkey) =
+                monkeys.iter().find(|monkey| monkey.operation.divides())
+            {
+                return Err(PuzzleError::new(
+                    DAY,
+                    format!(
+                        "monkey {} divides, which modular relief cannot track",
+                        monkey.id
+                    ),
+                ));
             }
+            let modulus = monkeys
+                .iter()
+                .map(|monkey| monkey.test.divisor)
+                .try_fold(1, lcm)
+                .map(i128::from)
+                .ok_or_else(|| {
+                    PuzzleError::new(DAY, "the divisors' LCM overflows a u64")
+                })?;
+            play::<i128>(&monkeys, rounds, |worry| worry.rem_euclid(modulus))
+        }
+        Relief::Exact => play::<BigInt>(&monkeys, rounds, |worry| worry),
+    }
+}
+
+fn play<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relieve: impl Fn(W) -> W,
+) -> Result<History, PuzzleError> {
+    let index: HashMap<MonkeyID, usize> = monkeys
+        .iter()
+        .enumerate()
+        .map(|(index, monkey)| (monkey.id, index))
+        .collect();
+    let mut inventories: Vec<Vec<W>> = monkeys
+        .iter()
+        .map(|monkey| monkey.items.iter().map(|&item| W::from(item)).collect())
+        .collect();
+    let mut history = History {
+        ids: monkeys.iter().map(|monkey| monkey.id).collect(),
+        rounds: Vec::with_capacity(rounds),
+    };
 
-            monkeys.get_mut(id).unwrap().play(&mut transfer_queue);
+    for _round in 0..rounds {
+        let mut counts = vec![0; monkeys.len()];
 
-            for (to_id, items) in transfer_queue.drain() {
-                let recipient = monkeys.get_mut(&to_id).unwrap();
-                for item in items {
-                    recipient.inventory.push_back(item);
-                }
+        for (from, monkey) in monkeys.iter().enumerate() {
+            for worry in std::mem::take(&mut inventories[from]) {
+                let worry =
+                    monkey.operation.evaluate(&worry).map_err(|error| {
+                        PuzzleError::new(
+                            DAY,
+                            format!("monkey {}: {error}", monkey.id),
+                        )
+                    })?;
+                let worry = relieve(worry);
+                counts[from] += 1;
+
+                inventories[index[&monkey.test.run(&worry)]].push(worry);
             }
         }
+
+        history.rounds.push(counts);
     }
 
-    product_of_highest_two_inspection_counts(&monkeys)
+    Ok(history)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut monkeys = parse(input);
-    let modulo: usize = monkeys
+fn lcm(a: u64, b: u64) -> Option<u64> {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    (a / x).checked_mul(b)
+}
+
+fn parse(input: &str) -> Result<Vec<Monkey>, PuzzleError> {
+    let mut monkeys: Vec<Monkey> = parsing::blocks(input)
         .iter()
-        .map(|(_id, monkey)| monkey.test.divisor)
-        .product();
-    let mut transfer_queue: HashMap<MonkeyID, Vec<Item>> = HashMap::new();
-    let mut ids: Vec<MonkeyID> = monkeys.keys().copied().collect();
-    ids.sort_unstable();
-
-    for _round in 0..10_000 {
-        for id in &ids {
-            for &id in monkeys.keys() {
-                transfer_queue.insert(id, Vec::new());
-            }
+        .map(|lines| parse_monkey(lines))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    monkeys.sort_unstable_by_key(|monkey| monkey.id);
 
-            monkeys.get_mut(id).unwrap().play_rough(&mut transfer_queue);
+    for pair in monkeys.windows(2) {
+        if pair[0].id == pair[1].id {
+            return Err(PuzzleError::new(
+                DAY,
+                format!("monkey {} is defined twice", pair[0].id),
+            ));
+        }
+    }
 
-            for (to_id, items) in transfer_queue.drain() {
-                let recipient = monkeys.get_mut(&to_id).unwrap();
-                for mut item in items {
-                    item.worry_level %= modulo;
-                    recipient.inventory.push_back(item);
-                }
+    for monkey in &monkeys {
+        for to_id in [monkey.test.true_monkey_id, monkey.test.false_monkey_id] {
+            if monkeys.binary_search_by_key(&to_id, |m| m.id).is_err() {
+                return Err(PuzzleError::new(
+                    DAY,
+                    format!(
+                        "monkey {} throws to missing monkey {to_id}",
+                        monkey.id
+                    ),
+                ));
             }
         }
     }
 
-    product_of_highest_two_inspection_counts(&monkeys)
+    Ok(monkeys)
 }
 
-fn product_of_highest_two_inspection_counts(
-    monkeys: &HashMap<MonkeyID, Monkey>,
-) -> usize {
-    let mut inspection_counts: Vec<usize> = monkeys
-        .iter()
-        .map(|(_id, monkey)| monkey.inspection_count)
-        .collect();
-    inspection_counts.sort_unstable();
-    inspection_counts.reverse();
-    inspection_counts.iter().take(2).product()
+fn parse_monkey(lines: &[SourceLine]) -> parsing::Result<Monkey> {
+    let [header, items, operation, test, if_true, if_false] = lines else {
+        let line = lines[0];
+        return Err(line.error(line.text, "expected six lines per monkey"));
+    };
+    let id = parse_field(header, "Monkey {}:")?;
+    let items = items.trim();
+    let (_key, worry_levels) = items.key_value()?;
+    let items = worry_levels
+        .split(',')
+        .map(|worry_string| items.parse(worry_string))
+        .collect::<parsing::Result<_>>()?;
+    let operation = operation.trim();
+    let operation = Operation::parse(
+        &operation,
+        operation.fields("Operation: new = {}")?[0],
+    )?;
+    let divisor = parse_field(test, "Test: divisible by {}")?;
+    if divisor == 0 {
+        return Err(test.error(test.text, "divisor must be positive"));
+    }
+    let test = Test {
+        divisor,
+        true_monkey_id: parse_field(if_true, "If true: throw to monkey {}")?,
+        false_monkey_id: parse_field(if_false, "If false: throw to monkey {}")?,
+    };
+
+    Ok(Monkey {
+        id,
+        items,
+        operation,
+        test,
+    })
 }
 
-fn parse(input: &str) -> HashMap<MonkeyID, Monkey> {
-    input
-        .split("\n\n")
-        .map(|monkey_lines| {
-            let mut iter = monkey_lines.lines();
-            let id = iter
-                .next()
-                .unwrap()
-                .strip_prefix("Monkey ")
-                .unwrap()
-                .strip_suffix(':')
-                .unwrap()
-                .parse::<MonkeyID>()
-                .unwrap();
-            let inventory = iter
-                .next()
-                .unwrap()
-                .strip_prefix("  Starting items: ")
-                .unwrap()
-                .split(", ")
-                .map(|worry_string| Item {
-                    worry_level: worry_string.parse::<usize>().unwrap(),
-                })
-                .collect();
-            let operation = match iter
-                .next()
-                .unwrap()
-                .strip_prefix("  Operation: new = old ")
-                .unwrap()
-                .split_once(' ')
-                .unwrap()
-            {
-                ("*", "old") => Operation::Square,
-                ("*", value_string) => {
-                    Operation::Multiply(value_string.parse::<usize>().unwrap())
-                }
-                ("+", value_string) => {
-                    Operation::Add(value_string.parse::<usize>().unwrap())
-                }
-                _ => panic!("invalid operation"),
-            };
-            let divisor = iter
-                .next()
-                .unwrap()
-                .strip_prefix("  Test: divisible by ")
-                .unwrap()
-                .parse::<usize>()
-                .unwrap();
-            let test = Test {
-                divisor,
-                true_monkey_id: iter
-                    .next()
-                    .unwrap()
-                    .strip_prefix("    If true: throw to monkey ")
-                    .unwrap()
-                    .parse::<MonkeyID>()
-                    .unwrap(),
-                false_monkey_id: iter
-                    .next()
-                    .unwrap()
-                    .strip_prefix("    If false: throw to monkey ")
-                    .unwrap()
-                    .parse::<MonkeyID>()
-                    .unwrap(),
-            };
-
-            (
-                id,
-                Monkey {
-                    id,
-                    inventory,
-                    operation,
-                    test,
-                    inspection_count: 0,
-                },
-            )
-        })
-        .collect()
+fn parse_field<T: FromStr>(
+    line: &SourceLine,
+    pattern: &str,
+) -> parsing::Result<T> {
+    let line = line.trim();
+
+    line.parse(line.fields(pattern)?[0])
 }
 
 #[cfg(test)]
@@ -271,4 +568,123 @@ Monkey 3:
     fn part2_example() {
         assert_eq!(part2(input()), 2713310158)
     }
+
+    #[test]
+    fn inspection_history() {
+        let history = simulate(input(), Relief::ModuloLcm, 20).unwrap();
+
+        assert_eq!(history.ids, vec![0, 1, 2, 3]);
+        assert_eq!(history.rounds[0], vec![2, 4, 3, 6]);
+        assert_eq!(history.totals(), vec![99, 97, 8, 103]);
+    }
+
+    #[test]
+    fn exact_worry_matches_modular_worry() {
+        let exact = simulate(input(), Relief::Exact, 12).unwrap();
+        let modular = simulate(input(), Relief::ModuloLcm, 12).unwrap();
+
+        assert_eq!(exact, modular);
+    }
+
+    #[test]
+    fn arbitrary_operations() {
+        let operation: Operation =
+            "(old - 1) * 3 + old / 2 - 4".parse().unwrap();
+
+        assert_eq!(
+            operation.to_string(),
+            "((((old - 1) * 3) + (old / 2)) - 4)"
+        );
+        assert_eq!(operation.evaluate(&10_i128), Ok(28));
+        assert_eq!(operation.evaluate(&BigInt::from(10)), Ok(28.into()));
+
+        let operation: Operation = "old / (old - old)".parse().unwrap();
+        assert_eq!(
+            operation.evaluate(&5_i128),
+            Err(ArithmeticError::DivideByZero)
+        );
+        assert_eq!(
+            operation.evaluate(&BigInt::from(5)),
+            Err(ArithmeticError::DivideByZero)
+        );
+
+        let operation: Operation = "old * old".parse().unwrap();
+        assert_eq!(
+            operation.evaluate(&i128::MAX),
+            Err(ArithmeticError::Overflow)
+        );
+
+        assert_eq!(
+            "old * (2 +".parse::<Operation>().unwrap_err().to_string(),
+            "line 1, column 11: expected `old`, a number or `(`"
+        );
+        assert_eq!(
+            "old old".parse::<Operation>().unwrap_err().to_string(),
+            "line 1, column 5: unexpected input"
+        );
+    }
+
+    #[test]
+    fn modular_relief_rejects_division() {
+        let input = input().replacen("old * 19", "old / 2 * 19", 1);
+
+        assert_eq!(
+            simulate(&input, Relief::ModuloLcm, 1)
+                .unwrap_err()
+                .to_string(),
+            "day 11: monkey 0 divides, which modular relief cannot track"
+        );
+        assert_eq!(
+            simulate(&input, Relief::Divide(3), 20)
+                .unwrap()
+                .rounds
+                .len(),
+            20
+        );
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(
+            simulate(input(), Relief::Divide(1), 100)
+                .unwrap_err()
+                .to_string(),
+            "day 11: monkey 0: worry overflowed"
+        );
+
+        // The LCM fits in a u64 but squaring worry below it overflows i128.
+        let squaring = "\
+Monkey 0:
+  Starting items: 3
+  Operation: new = old * old
+  Test: divisible by 4294967291
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old
+  Test: divisible by 3221225473
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+";
+        assert_eq!(
+            simulate(squaring, Relief::ModuloLcm, 10_000)
+                .unwrap_err()
+                .to_string(),
+            "day 11: monkey 0: worry overflowed"
+        );
+
+        let wider = input().replacen("by 23", "by 4294967291", 1).replacen(
+            "by 19",
+            "by 4294967279",
+            1,
+        );
+        assert_eq!(
+            simulate(&wider, Relief::ModuloLcm, 1)
+                .unwrap_err()
+                .to_string(),
+            "day 11: the divisors' LCM overflows a u64"
+        );
+    }
 }
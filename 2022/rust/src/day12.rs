@@ -1,9 +1,13 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashMap},
     fmt::Debug,
 };
 
+const DAY: u8 = 12;
+
 #[derive(Eq, PartialEq, PartialOrd, Ord)]
 struct Height(usize);
 
@@ -32,17 +36,32 @@ type HeightMap = HashMap<Position, Height>;
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (height_map, start_position, target_position) = parse(input);
-
-    shortest_path(&height_map, start_position, target_position)
-        .unwrap()
-        .len()
-        - 1
+    try_part1(input).unwrap()
 }
 
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let (height_map, _start_position, target_position) = parse(input);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on an unknown height, a missing `S` or `E`, or when the summit
+/// can't be reached.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (height_map, start_position, target_position) = parse(input)?;
+
+    shortest_path(&height_map, start_position, target_position)
+        .map(|path| path.len() - 1)
+        .ok_or_else(|| PuzzleError::new(DAY, "no path to the summit"))
+}
+
+/// # Errors
+///
+/// Fails on an unknown height, a missing `E`, or when no square at
+/// elevation `a` reaches the summit.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (height_map, _start_position, target_position) = parse(input)?;
 
     height_map
         .iter()
@@ -60,7 +79,7 @@ pub fn part2(input: &str) -> usize {
         })
         .map(|path| path.len() - 1)
         .min()
-        .unwrap()
+        .ok_or_else(|| PuzzleError::new(DAY, "no path to the summit"))
 }
 
 fn shortest_path(
@@ -131,32 +150,41 @@ fn possible_next_positions(
         .collect()
 }
 
-fn parse(input: &str) -> (HeightMap, Position, Position) {
-    let mut start_position: Position = Position(0, 0);
-    let mut target_position: Position = Position(0, 0);
-
-    let height_map: HeightMap = input
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(col, ch)| match ch {
-                    'S' => {
-                        start_position = Position(row, col);
-                        (Position(row, col), Height(0))
-                    }
-                    'E' => {
-                        target_position = Position(row, col);
-                        (Position(row, col), Height(25))
-                    }
-                    _ => (Position(row, col), Height(ch as usize - 97)),
-                })
-                .collect::<Vec<(Position, Height)>>()
-        })
-        .collect();
+fn parse(input: &str) -> Result<(HeightMap, Position, Position), PuzzleError> {
+    let mut start_position: Option<Position> = None;
+    let mut target_position: Option<Position> = None;
+    let mut height_map: HeightMap = HashMap::new();
+
+    for (row, line) in parsing::lines(input).into_iter().enumerate() {
+        for (col, ch) in line.text.char_indices() {
+            let height = match ch {
+                'S' => {
+                    start_position = Some(Position(row, col));
+                    0
+                }
+                'E' => {
+                    target_position = Some(Position(row, col));
+                    25
+                }
+                'a'..='z' => ch as usize - 97,
+                _ => {
+                    let error =
+                        line.error(&line.text[col..], "expected a height");
+                    return Err(PuzzleError::on_day(DAY)(error));
+                }
+            };
 
-    (height_map, start_position, target_position)
+            height_map.insert(Position(row, col), Height(height));
+        }
+    }
+
+    match (start_position, target_position) {
+        (Some(start), Some(target)) => Ok((height_map, start, target)),
+        _ => Err(PuzzleError::new(
+            DAY,
+            "expected both a start (S) and a summit (E)",
+        )),
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::{collections::HashSet, fmt::Debug, ops::RangeInclusive};
 
+const DAY: u8 = 14;
+
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 struct Point {
     x: usize,
@@ -7,12 +11,13 @@ struct Point {
 }
 
 impl Point {
-    fn parse(input: &str) -> Self {
-        let (x_string, y_string) = input.split_once(',').unwrap();
-        Point {
-            x: x_string.parse().unwrap(),
-            y: y_string.parse().unwrap(),
-        }
+    fn parse(line: &SourceLine, field: &str) -> parsing::Result<Self> {
+        let (x_string, y_string) = line.split_once(field, ",")?;
+
+        Ok(Point {
+            x: line.parse(x_string)?,
+            y: line.parse(y_string)?,
+        })
     }
 
     fn down(&self) -> Self {
@@ -98,7 +103,19 @@ impl Map {
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let map = parse(input);
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a malformed or diagonal rock path, or when there are none.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let map = parse(input)?;
     let source = Point { x: 500, y: 0 };
     let mut sand: HashSet<Point> = HashSet::new();
     let mut previous_point = source;
@@ -110,12 +127,14 @@ pub fn part1(input: &str) -> usize {
         sand.insert(rest_point);
     }
 
-    sand.len()
+    Ok(sand.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut map = parse(input);
+/// # Errors
+///
+/// Fails on a malformed or diagonal rock path, or when there are none.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut map = parse(input)?;
     map.lines.push(Line::Horizontal(0..=10000, map.bottom + 2));
     map.bottom += 3;
 
@@ -133,7 +152,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    sand.len()
+    Ok(sand.len())
 }
 
 fn settle(
@@ -155,19 +174,27 @@ fn settle(
     Some((point, previous_point))
 }
 
-fn parse(input: &str) -> Map {
-    let lines: Vec<Line> =
-        input
-            .lines()
-            .flat_map(|line| {
-                Line::from_points(
-                    &line
-                        .split(" -> ")
-                        .map(Point::parse)
-                        .collect::<Vec<Point>>()[..],
-                )
-            })
-            .collect();
+fn parse(input: &str) -> Result<Map, PuzzleError> {
+    let mut lines: Vec<Line> = vec![];
+
+    for line in parsing::lines(input) {
+        let fields: Vec<&str> = line.text.split("->").collect();
+        let points = fields
+            .iter()
+            .map(|field| Point::parse(&line, field))
+            .collect::<parsing::Result<Vec<Point>>>()
+            .map_err(PuzzleError::on_day(DAY))?;
+
+        for (index, pair) in points.windows(2).enumerate() {
+            if pair[0].x != pair[1].x && pair[0].y != pair[1].y {
+                let error = line.error(fields[index + 1], "diagonal rock path");
+                return Err(PuzzleError::on_day(DAY)(error));
+            }
+        }
+
+        lines.extend(Line::from_points(&points));
+    }
+
     let bottom = *lines
         .iter()
         .map(|line| match line {
@@ -175,9 +202,9 @@ fn parse(input: &str) -> Map {
             Line::Horizontal(_x_range, y) => y,
         })
         .max()
-        .unwrap();
+        .ok_or_else(|| PuzzleError::new(DAY, "no rock paths"))?;
 
-    Map { lines, bottom }
+    Ok(Map { lines, bottom })
 }
 
 #[cfg(test)]
@@ -199,4 +226,18 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 93)
     }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            try_part1("498,4 -> 498,6\n503,4 -> 502,9\n")
+                .unwrap_err()
+                .to_string(),
+            "day 14, line 2: column 9, diagonal rock path"
+        );
+        assert_eq!(
+            try_part2("").unwrap_err().to_string(),
+            "day 14: no rock paths"
+        );
+    }
 }
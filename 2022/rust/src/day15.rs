@@ -1,6 +1,9 @@
-use regex::Regex;
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::{collections::HashSet, ops::RangeInclusive};
 
+const DAY: u8 = 15;
+
 struct Point {
     x: isize,
     y: isize,
@@ -40,11 +43,31 @@ impl Sensor {
 
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a sensor report.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     part1_(input, 2_000_000)
 }
 
-fn part1_(input: &str, target_row: isize) -> usize {
-    let sensors = parse(input);
+/// # Errors
+///
+/// Fails on a line that isn't a sensor report, or when the search area
+/// has no gap for the beacon.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    part2_(input, 4_000_000)
+}
+
+fn part1_(input: &str, target_row: isize) -> Result<usize, PuzzleError> {
+    let sensors = parse(input)?;
     let mut xs: HashSet<isize> = HashSet::new();
 
     for sensor in sensors {
@@ -55,17 +78,12 @@ fn part1_(input: &str, target_row: isize) -> usize {
         }
     }
 
-    xs.len() - 1
-}
-
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    part2_(input, 4_000_000)
+    Ok(xs.len().saturating_sub(1))
 }
 
 #[allow(clippy::cast_sign_loss)]
-fn part2_(input: &str, max_coordinate: isize) -> usize {
-    let sensors = parse(input);
+fn part2_(input: &str, max_coordinate: isize) -> Result<usize, PuzzleError> {
+    let sensors = parse(input)?;
 
     for y in 0..=max_coordinate {
         let mut spans: Vec<RangeInclusive<isize>> = sensors
@@ -74,43 +92,45 @@ fn part2_(input: &str, max_coordinate: isize) -> usize {
             .collect();
         spans.sort_unstable_by_key(|range| (*range.start(), *range.end()));
 
-        let mut x = spans[0].end() + 1;
+        let Some(first) = spans.first() else {
+            continue;
+        };
+        let mut x = first.end() + 1;
 
         for range in spans.iter().skip(1) {
             if x < *range.start() {
-                return (x * 4_000_000 + y) as usize;
+                return Ok((x * 4_000_000 + y) as usize);
             }
 
             x = x.max(range.end() + 1);
         }
     }
 
-    0
+    Err(PuzzleError::new(DAY, "no gap for the distress beacon"))
 }
 
-fn parse(input: &str) -> Vec<Sensor> {
-    let regex = Regex::new(
-        r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)",
-    )
-    .unwrap();
-
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Sensor>, PuzzleError> {
+    parsing::lines(input)
+        .into_iter()
         .map(|line| {
-            let captures = regex.captures(line.trim()).unwrap();
+            let line = line.trim();
+            let fields = line.fields(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            )?;
             let location = Point {
-                x: captures[1].parse::<isize>().unwrap(),
-                y: captures[2].parse::<isize>().unwrap(),
+                x: line.parse(fields[0])?,
+                y: line.parse(fields[1])?,
             };
             let nearest_beacon = Point {
-                x: captures[3].parse::<isize>().unwrap(),
-                y: captures[4].parse::<isize>().unwrap(),
+                x: line.parse(fields[2])?,
+                y: line.parse(fields[3])?,
             };
             let size = location.distance(&nearest_beacon);
 
-            Sensor { location, size }
+            Ok(Sensor { location, size })
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -137,11 +157,27 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3\n"
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1_(input(), 10), 26)
+        assert_eq!(part1_(input(), 10).unwrap(), 26)
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2_(input(), 20), 56000011)
+        assert_eq!(part2_(input(), 20).unwrap(), 56000011)
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            part1_("Sensor at x=2, y=18: closest beacon at x=-2, y=15\n", 10)
+                .unwrap_err()
+                .to_string(),
+            "day 15, line 1: column 18, expected `: closest beacon is at x=`"
+        );
+        assert_eq!(
+            part2_(&input().replace("x=20, y=1:", "x=11, y=14:"), 0)
+                .unwrap_err()
+                .to_string(),
+            "day 15: no gap for the distress beacon"
+        );
     }
 }
@@ -1,5 +1,8 @@
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use std::collections::{HashMap, VecDeque};
+
+const DAY: u8 = 16;
 
 type ValveName = String;
 
@@ -9,145 +12,280 @@ struct Valve {
     adjacencies: Vec<ValveName>,
 }
 
+// Only the valves worth opening, with travel times between them. The start
+// valve is appended last so it has a row in `distances` too.
+struct Network {
+    names: Vec<ValveName>,
+    flow_rates: Vec<usize>,
+    distances: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opening {
+    pub agent: usize,
+    pub valve: ValveName,
+    pub minute: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plan {
+    pub pressure: usize,
+    pub openings: Vec<Opening>,
+}
+
+// The best a single agent can do opening exactly the valves in a mask: the
+// pressure released and the (valve, minute) openings that achieve it.
+#[derive(Clone, Default)]
+struct Route {
+    pressure: usize,
+    openings: Vec<(usize, usize)>,
+}
+
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let valves = parse(input);
-    let distances: HashMap<ValveName, HashMap<ValveName, usize>> = valves
-        .keys()
-        .map(|name| (name.clone(), find_distances(name.clone(), &valves)))
-        .collect();
-
-    dbg!(&distances);
-
-    let (path, pressure) = find_maximal_pressure(
-        &valves,
-        &distances,
-        &"AA".into(),
-        vec!["AA".into()],
-        valves.keys().filter(|name| name != &"AA").collect(),
-        30,
-        0,
-    );
-
-    dbg!(&path);
-
-    pressure
+    try_part1(input).unwrap()
 }
 
-fn find_maximal_pressure(
-    valves: &HashMap<ValveName, Valve>,
-    distances: &HashMap<ValveName, HashMap<ValveName, usize>>,
-    current_name: &String,
-    path: Vec<ValveName>,
-    closed_valves: HashSet<&ValveName>,
-    time_left: usize,
-    pressure_released: usize,
-) -> (Vec<ValveName>, usize) {
-    //    println!(
-    //        "at {}, time_left={}, pressure_released={}",
-    //        &current_name, &time_left, &pressure_released
-    //    );
-    if time_left == 0 {
-        return (path, pressure_released);
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when [`optimize`] does.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(optimize(input, "AA", 1, 30)?.pressure)
+}
+
+/// # Errors
+///
+/// Fails when [`optimize`] does.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(optimize(input, "AA", 2, 26)?.pressure)
+}
+
+/// Splits the useful valves between `agents` who all leave `start` at minute
+/// zero, maximizing the pressure released by the end of `minutes`.
+///
+/// # Errors
+///
+/// Fails on a malformed valve line, a tunnel to an unknown valve, or a
+/// `start` that isn't a valve.
+pub fn optimize(
+    input: &str,
+    start: &str,
+    agents: usize,
+    minutes: usize,
+) -> Result<Plan, PuzzleError> {
+    let valves = parse(input)?;
+    if !valves.contains_key(start) {
+        return Err(PuzzleError::new(
+            DAY,
+            format!("no starting valve `{start}`"),
+        ));
     }
 
-    let options: Vec<(Vec<ValveName>, usize)> = closed_valves
-        .iter()
-        .filter_map(|next_name| {
-            let next = &valves[*next_name];
-            let time_required = distances[current_name][*next_name] + 1;
-
-            if time_required < time_left && next.flow_rate > 0 {
-                let mut new_closed_valves = closed_valves.clone();
-                new_closed_valves.remove(next_name);
-                let mut new_path = path.clone();
-                new_path.push(next_name.to_string());
-
-                Some(find_maximal_pressure(
-                    valves,
-                    distances,
-                    next_name,
-                    new_path,
-                    new_closed_valves,
-                    time_left - time_required,
-                    pressure_released
-                        + (time_left - time_required) * next.flow_rate,
-                ))
-            } else {
-                None
+    let network = Network::new(&valves, start);
+    let routes = network.routes(minutes);
+    let full = routes.len() - 1;
+
+    // best[mask] is the most pressure the agents planned so far can release
+    // using only valves in `mask`, and split[k][mask] the valves agent k
+    // takes to get there.
+    let mut best = vec![0; routes.len()];
+    let mut splits: Vec<Vec<usize>> = vec![];
+
+    for _ in 0..agents {
+        let mut next = vec![0; routes.len()];
+        let mut split = vec![0; routes.len()];
+
+        for mask in 0..routes.len() {
+            let mut subset = mask;
+
+            loop {
+                let pressure = routes[subset].pressure + best[mask ^ subset];
+                if pressure > next[mask] {
+                    next[mask] = pressure;
+                    split[mask] = subset;
+                }
+                if subset == 0 {
+                    break;
+                }
+                subset = (subset - 1) & mask;
             }
-        })
-        .collect();
+        }
+
+        best = next;
+        splits.push(split);
+    }
+
+    let mut openings = vec![];
+    let mut remaining = full;
 
-    options
-        .into_iter()
-        .max_by_key(|(_path, pressure)| pressure.clone())
-        .unwrap_or((path, pressure_released))
+    for (agent, split) in splits.iter().enumerate().rev() {
+        let subset = split[remaining];
+
+        openings.extend(routes[subset].openings.iter().map(
+            |&(valve, minute)| Opening {
+                agent,
+                valve: network.names[valve].clone(),
+                minute,
+            },
+        ));
+        remaining ^= subset;
+    }
+    openings.sort_unstable_by_key(|opening| (opening.minute, opening.agent));
+
+    Ok(Plan {
+        pressure: best[full],
+        openings,
+    })
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    input.lines().count()
+impl Network {
+    fn new(valves: &HashMap<ValveName, Valve>, start: &str) -> Self {
+        let mut names: Vec<ValveName> = valves
+            .iter()
+            .filter(|(_name, valve)| valve.flow_rate > 0)
+            .map(|(name, _valve)| name.clone())
+            .collect();
+        names.sort_unstable();
+        names.push(start.to_owned());
+
+        let flow_rates =
+            names.iter().map(|name| valves[name].flow_rate).collect();
+        let distances = names
+            .iter()
+            .map(|from| {
+                let reachable = find_distances(from, valves);
+                names
+                    .iter()
+                    .map(|to| reachable.get(to).copied().unwrap_or(usize::MAX))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            names,
+            flow_rates,
+            distances,
+        }
+    }
+
+    // One route per subset of the useful valves, found with a depth-first
+    // walk that records the best pressure for each set of opened valves.
+    fn routes(&self, minutes: usize) -> Vec<Route> {
+        let useful = self.names.len() - 1;
+        let mut routes = vec![Route::default(); 1 << useful];
+        let mut openings = vec![];
+
+        self.walk(useful, minutes, minutes, 0, 0, &mut openings, &mut routes);
+
+        routes
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn walk(
+        &self,
+        position: usize,
+        minutes: usize,
+        time_left: usize,
+        opened: usize,
+        pressure: usize,
+        openings: &mut Vec<(usize, usize)>,
+        routes: &mut [Route],
+    ) {
+        if pressure > routes[opened].pressure {
+            routes[opened] = Route {
+                pressure,
+                openings: openings.clone(),
+            };
+        }
+
+        for next in 0..routes.len().trailing_zeros() as usize {
+            let time_required =
+                self.distances[position][next].saturating_add(1);
+
+            if opened & (1 << next) != 0 || time_required >= time_left {
+                continue;
+            }
+
+            let time_left = time_left - time_required;
+            openings.push((next, minutes - time_left));
+            self.walk(
+                next,
+                minutes,
+                time_left,
+                opened | (1 << next),
+                pressure + time_left * self.flow_rates[next],
+                openings,
+                routes,
+            );
+            openings.pop();
+        }
+    }
 }
 
 fn find_distances(
-    start: ValveName,
+    start: &str,
     valves: &HashMap<ValveName, Valve>,
 ) -> HashMap<ValveName, usize> {
-    let mut distances = HashMap::new();
-    let mut distance = 0usize;
-    let mut frontier: Vec<&ValveName> = vec![&start];
-
-    while !&frontier.is_empty() {
-        let mut new_frontier = vec![];
-        for &name in &frontier {
-            distances.insert(name.clone(), distance);
-
-            for next in &valves[name].adjacencies {
-                if !distances.contains_key(next) {
-                    new_frontier.push(next);
-                }
-            }
-        }
+    let mut distances = HashMap::from([(start.to_owned(), 0)]);
+    let mut queue = VecDeque::from([start]);
 
-        frontier.clear();
-        for name in new_frontier {
-            frontier.push(name);
-        }
+    while let Some(name) = queue.pop_front() {
+        let distance = distances[name];
 
-        distance += 1;
+        for next in &valves[name].adjacencies {
+            if !distances.contains_key(next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
     }
 
     distances
 }
 
-fn parse(input: &str) -> HashMap<ValveName, Valve> {
-    let valve_regex = Regex::new(r"Valve (\w+) has flow rate=(\d+)").unwrap();
-    let tunnel_regex = Regex::new(r"tunnels? leads? to valves? (.*)").unwrap();
-
-    input
-        .lines()
-        .map(|line| {
-            let (valve_string, tunnels_string) = line.split_once("; ").unwrap();
-            let captures = valve_regex.captures(valve_string).unwrap();
-            let name = captures[1].to_owned();
-            let flow_rate = captures[2].parse::<usize>().unwrap();
-            let adjacencies: Vec<String> =
-                tunnel_regex.captures(tunnels_string).unwrap()[1]
-                    .split(", ")
-                    .map(|s| s.to_owned())
-                    .collect();
-
-            (
-                name.clone(),
-                Valve {
-                    flow_rate,
-                    adjacencies,
-                },
-            )
-        })
-        .collect::<HashMap<ValveName, Valve>>()
-        .into()
+fn parse(input: &str) -> Result<HashMap<ValveName, Valve>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let mut valves = HashMap::new();
+    let mut tunnels = vec![];
+
+    for line in &lines {
+        let line = line.trim();
+        let fields = line
+            .fields("Valve {} has flow rate={}; tunnels lead to valves {}")
+            .or_else(|_| {
+                line.fields(
+                    "Valve {} has flow rate={}; tunnel leads to valve {}",
+                )
+            })
+            .map_err(PuzzleError::on_day(DAY))?;
+        let flow_rate =
+            line.parse(fields[1]).map_err(PuzzleError::on_day(DAY))?;
+        let adjacencies: Vec<String> =
+            fields[2].split(", ").map(ToOwned::to_owned).collect();
+
+        tunnels.extend(fields[2].split(", ").map(|name| (line, name)));
+        valves.insert(
+            fields[0].to_owned(),
+            Valve {
+                flow_rate,
+                adjacencies,
+            },
+        );
+    }
+
+    for (line, name) in tunnels {
+        if !valves.contains_key(name) {
+            let error = line.error(name, format!("unknown valve `{name}`"));
+            return Err(PuzzleError::on_day(DAY)(error));
+        }
+    }
+
+    Ok(valves)
 }
 
 #[cfg(test)]
@@ -175,6 +313,59 @@ Valve JJ has flow rate=21; tunnel leads to valve II\n"
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(input()), 10)
+        assert_eq!(part2(input()), 1707);
+    }
+
+    #[test]
+    fn opening_schedule() {
+        let plan = optimize(input(), "AA", 2, 26).unwrap();
+        let mut openings: Vec<(usize, &str, usize)> = plan
+            .openings
+            .iter()
+            .map(|opening| {
+                (opening.agent, opening.valve.as_str(), opening.minute)
+            })
+            .collect();
+        openings.sort_unstable();
+
+        assert_eq!(plan.pressure, 1707);
+        assert_eq!(
+            openings,
+            vec![
+                (0, "DD", 2),
+                (0, "EE", 11),
+                (0, "HH", 7),
+                (1, "BB", 7),
+                (1, "CC", 9),
+                (1, "JJ", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn agents_and_time_limits() {
+        let pressure = |start, agents, minutes| {
+            optimize(input(), start, agents, minutes).unwrap().pressure
+        };
+
+        assert_eq!(pressure("AA", 0, 30), 0);
+        assert_eq!(pressure("AA", 1, 2), 0);
+        assert_eq!(pressure("AA", 1, 3), 20);
+        assert_eq!(pressure("AA", 3, 26), 1794);
+        assert!(pressure("HH", 1, 30) > 0);
+        assert_eq!(
+            optimize(input(), "ZZ", 1, 30).unwrap_err().to_string(),
+            "day 16: no starting valve `ZZ`"
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            try_part1(&input().replace("valve II", "valve KK"))
+                .unwrap_err()
+                .to_string(),
+            "day 16, line 10: column 50, unknown valve `KK`"
+        );
     }
 }
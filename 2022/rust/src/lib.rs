@@ -17,3 +17,10 @@ pub mod day13;
 pub mod day14;
 pub mod day15;
 pub mod day16;
+pub mod parsing;
+pub mod nested_list;
+pub mod ocr;
+pub mod puzzle;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,321 @@
+use crate::parsing::{self, ParseError, SourceLine};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{cmp::Ordering, fmt, iter::Peekable, str::CharIndices, str::FromStr};
+
+type Chars<'a> = Peekable<CharIndices<'a>>;
+
+// Arbitrarily nested lists of items, written like `[1,[2,[]],3]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NestedList<T> {
+    Item(T),
+    List(Vec<NestedList<T>>),
+}
+
+impl<T: Ord> NestedList<T> {
+    // The distress signal ordering: an item compared against a list is
+    // treated as a one-item list, so `[1]` and `1` come out `Equal`.
+    pub fn signal_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Item(left), Self::Item(right)) => left.cmp(right),
+            (Self::List(left), Self::List(right)) => {
+                Self::list_cmp(left, right)
+            }
+            (Self::Item(_), Self::List(right)) => {
+                Self::list_cmp(std::slice::from_ref(self), right)
+            }
+            (Self::List(left), Self::Item(_)) => {
+                Self::list_cmp(left, std::slice::from_ref(other))
+            }
+        }
+    }
+
+    fn list_cmp(left: &[Self], right: &[Self]) -> Ordering {
+        left.iter()
+            .zip(right)
+            .map(|(left, right)| left.signal_cmp(right))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| left.len().cmp(&right.len()))
+    }
+
+    // Breaks `signal_cmp` ties so the order agrees with `==`: items sort
+    // before lists, then lists compare element by element.
+    fn structural_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Item(left), Self::Item(right)) => left.cmp(right),
+            (Self::Item(_), Self::List(_)) => Ordering::Less,
+            (Self::List(_), Self::Item(_)) => Ordering::Greater,
+            (Self::List(left), Self::List(right)) => left
+                .iter()
+                .zip(right)
+                .map(|(left, right)| left.structural_cmp(right))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| left.len().cmp(&right.len())),
+        }
+    }
+}
+
+impl<T: Ord> Ord for NestedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.signal_cmp(other)
+            .then_with(|| self.structural_cmp(other))
+    }
+}
+
+impl<T: Ord> PartialOrd for NestedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: FromStr> NestedList<T> {
+    /// Parses a whole line holding one bracketed list.
+    ///
+    /// # Errors
+    ///
+    /// Fails on unbalanced brackets, an element `T` can't parse, or text left
+    /// over after the closing bracket.
+    pub fn parse_line(line: &SourceLine) -> parsing::Result<Self> {
+        let line = line.trim();
+        let mut chars = line.text.char_indices().peekable();
+        let list = Self::parse_list(&line, &mut chars)?;
+
+        match chars.next() {
+            Some((index, _)) => {
+                Err(line
+                    .error(&line.text[index..], "unexpected trailing input"))
+            }
+            None => Ok(list),
+        }
+    }
+
+    fn parse_list(
+        line: &SourceLine,
+        chars: &mut Chars,
+    ) -> parsing::Result<Self> {
+        let mut list = vec![];
+        consume(line, chars, '[')?;
+        skip_whitespace(chars);
+
+        if let Some((_, ']')) = chars.peek() {
+            chars.next();
+            return Ok(Self::List(list));
+        }
+
+        loop {
+            list.push(match chars.peek() {
+                Some((_, '[')) => Self::parse_list(line, chars)?,
+                Some(_) => Self::parse_item(line, chars)?,
+                None => {
+                    return Err(end_of_line(line, "unexpected end of input"))
+                }
+            });
+            skip_whitespace(chars);
+
+            match chars.next() {
+                Some((_, ',')) => skip_whitespace(chars),
+                Some((_, ']')) => return Ok(Self::List(list)),
+                Some((index, ch)) => {
+                    return Err(line.error(
+                        &line.text[index..],
+                        format!("expected `,` or `]`, got `{ch}`"),
+                    ))
+                }
+                None => {
+                    return Err(end_of_line(line, "unexpected end of input"))
+                }
+            }
+        }
+    }
+
+    fn parse_item(
+        line: &SourceLine,
+        chars: &mut Chars,
+    ) -> parsing::Result<Self> {
+        let start = chars.peek().map_or(line.text.len(), |&(index, _)| index);
+        let mut end = start;
+
+        while let Some(&(index, ch)) = chars.peek() {
+            if ch == ',' || ch == ']' || ch == '[' {
+                break;
+            }
+            end = index + ch.len_utf8();
+            chars.next();
+        }
+
+        let field = &line.text[start..end];
+        if field.trim().is_empty() {
+            return Err(line.error(field, "expected an item"));
+        }
+
+        line.parse(field).map(Self::Item)
+    }
+}
+
+fn skip_whitespace(chars: &mut Chars) {
+    while chars.next_if(|(_, ch)| ch.is_whitespace()).is_some() {}
+}
+
+fn consume(
+    line: &SourceLine,
+    chars: &mut Chars,
+    expected: char,
+) -> parsing::Result<()> {
+    match chars.next() {
+        Some((_, ch)) if ch == expected => Ok(()),
+        Some((index, ch)) => Err(line.error(
+            &line.text[index..],
+            format!("expected `{expected}`, got `{ch}`"),
+        )),
+        None => Err(end_of_line(line, &format!("expected `{expected}`"))),
+    }
+}
+
+fn end_of_line(line: &SourceLine, message: &str) -> ParseError {
+    line.error(&line.text[line.text.len()..], message)
+}
+
+impl<T: FromStr> FromStr for NestedList<T> {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> parsing::Result<Self> {
+        match parsing::lines(text)[..] {
+            [line] => Self::parse_line(&line),
+            _ => Err(ParseError {
+                line: 1,
+                column: 1,
+                message: "expected a single line".into(),
+            }),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for NestedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Item(item) => write!(f, "{item}"),
+            Self::List(list) => {
+                write!(f, "[")?;
+                for (index, element) in list.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl<T: Into<Value>> From<NestedList<T>> for Value {
+    fn from(list: NestedList<T>) -> Self {
+        match list {
+            NestedList::Item(item) => item.into(),
+            NestedList::List(list) => {
+                Value::Array(list.into_iter().map(Value::from).collect())
+            }
+        }
+    }
+}
+
+// JSON arrays become lists; anything else must deserialize as an item.
+impl<T: DeserializeOwned> TryFrom<Value> for NestedList<T> {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Array(values) => values
+                .into_iter()
+                .map(Self::try_from)
+                .collect::<Result<_, _>>()
+                .map(Self::List),
+            value => serde_json::from_value(value).map(Self::Item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    fn list(text: &str) -> NestedList<u32> {
+        text.parse().unwrap()
+    }
+
+    fn random_list(rng: &mut Rng, depth: usize) -> NestedList<u32> {
+        if depth == 0 || rng.below(3) == 0 {
+            return NestedList::Item(u32::try_from(rng.below(1000)).unwrap());
+        }
+
+        let length = rng.below(5);
+        NestedList::List(
+            (0..length).map(|_| random_list(rng, depth - 1)).collect(),
+        )
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let parsed = list("[ 1, [2,[]] ,30 ]");
+
+        assert_eq!(
+            parsed,
+            NestedList::List(vec![
+                NestedList::Item(1),
+                NestedList::List(vec![
+                    NestedList::Item(2),
+                    NestedList::List(vec![])
+                ]),
+                NestedList::Item(30),
+            ])
+        );
+        assert_eq!(parsed.to_string(), "[1,[2,[]],30]");
+        assert_eq!(
+            "[1,,2]".parse::<NestedList<u32>>().unwrap_err().to_string(),
+            "line 1, column 4: expected an item"
+        );
+        assert_eq!(
+            "[1 2]".parse::<NestedList<u32>>().unwrap_err().to_string(),
+            "line 1, column 2: invalid value `1 2`"
+        );
+        assert_eq!(
+            "[1]]".parse::<NestedList<u32>>().unwrap_err().to_string(),
+            "line 1, column 4: unexpected trailing input"
+        );
+    }
+
+    #[test]
+    fn signal_ordering() {
+        assert!(list("[1,1,3,1,1]") < list("[1,1,5,1,1]"));
+        assert!(list("[[1],[2,3,4]]") < list("[[1],4]"));
+        assert!(list("[9]") > list("[[8,7,6]]"));
+        assert!(list("[[]]") < list("[[[]]]"));
+        assert_eq!(list("[[2]]").signal_cmp(&list("[2]")), Ordering::Equal);
+        assert_eq!(list("[[2]]").cmp(&list("[2]")), Ordering::Greater);
+    }
+
+    #[test]
+    fn round_trip_random_lists() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let lists: Vec<NestedList<u32>> = (0..300)
+            .map(|_| NestedList::List(vec![random_list(&mut rng, 5)]))
+            .collect();
+
+        for list in &lists {
+            let text = list.to_string();
+            let json = Value::from(list.clone());
+
+            assert_eq!(&text.parse::<NestedList<u32>>().unwrap(), list);
+            assert_eq!(json.to_string(), text);
+            assert_eq!(&NestedList::try_from(json).unwrap(), list);
+        }
+
+        for pair in lists.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+
+            assert_eq!(a.cmp(b), b.cmp(a).reverse());
+            assert_eq!(a.cmp(b).is_eq(), a == b);
+        }
+    }
+}
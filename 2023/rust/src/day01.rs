@@ -1,23 +1,57 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 1;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let numbers: Vec<usize> = numbers(line);
-            numbers[numbers.len() - 1] + 10 * numbers[0]
-        })
-        .sum()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let numbers: Vec<usize> = numbers_enhanced(line);
-            numbers[numbers.len() - 1] + 10 * numbers[0]
-        })
-        .sum()
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line without a digit.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    calibration_sum(input, numbers)
+}
+
+/// # Errors
+///
+/// Fails on a line without a digit, spelled out or not.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    calibration_sum(input, numbers_enhanced)
+}
+
+fn calibration_sum(
+    input: &str,
+    numbers: fn(&str) -> Vec<usize>,
+) -> Result<usize, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| calibration_value(line, &numbers(line.text)))
+        .sum::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn calibration_value(
+    line: &SourceLine<'_>,
+    numbers: &[usize],
+) -> parsing::Result<usize> {
+    match *numbers {
+        [first, .., last] | [first @ last] => Ok(last + 10 * first),
+        [] => Err(line.error(line.text, "expected a digit")),
+    }
 }
 
 fn numbers(line: &str) -> Vec<usize> {
@@ -104,4 +138,13 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input2()), 281)
     }
+
+    #[test]
+    fn lines_without_digits() {
+        assert_eq!(
+            try_part1("1abc2\nabc\n").unwrap_err().to_string(),
+            "day 01, line 2: column 1, expected a digit"
+        );
+        assert_eq!(try_part2("1abc2\nabone\n").unwrap(), 23);
+    }
 }
@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 2;
+
 #[derive(Debug)]
 struct Game {
     id: usize,
@@ -19,19 +23,38 @@ impl CubeCount {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't `Game <id>:` followed by `;`-separated
+/// rounds of red, green and blue cube counts.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let bag = CubeCount {
         reds: 12,
         greens: 13,
         blues: 14,
     };
 
-    parse(input)
+    Ok(parse(input)?
         .iter()
         .filter(|game| possible(game, &bag))
         .map(|game| game.id)
-        .sum()
+        .sum())
 }
 
 fn possible(game: &Game, bag: &CubeCount) -> bool {
@@ -42,9 +65,11 @@ fn possible(game: &Game, bag: &CubeCount) -> bool {
     })
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    parse(input)
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(parse(input)?
         .iter()
         .map(|game| {
             let mut minimums = CubeCount {
@@ -61,42 +86,51 @@ pub fn part2(input: &str) -> usize {
 
             minimums.power()
         })
-        .sum()
+        .sum())
 }
 
-fn parse(input: &str) -> Vec<Game> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Game>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
-            let (name, results_string) = line.split_once(':').unwrap();
-            let (_game, id_string) = name.split_once(' ').unwrap();
-            let id = id_string.parse().unwrap();
+            let (name, results_string) = line.split_once(line.text, ":")?;
+            let id = line.parse(line.strip_prefix(name, "Game ")?)?;
             let results: Vec<CubeCount> = results_string
                 .split(';')
-                .map(|string| {
-                    let color_counts: HashMap<&str, usize> = string
-                        .split(',')
-                        .map(|color_count_string| {
-                            let (count_string, color_string) =
-                                color_count_string
-                                    .trim()
-                                    .split_once(' ')
-                                    .unwrap();
-                            (color_string.trim(), count_string.parse().unwrap())
-                        })
-                        .collect();
-
-                    CubeCount {
-                        reds: *color_counts.get(&"red").unwrap_or(&0),
-                        greens: *color_counts.get(&"green").unwrap_or(&0),
-                        blues: *color_counts.get(&"blue").unwrap_or(&0),
-                    }
-                })
-                .collect();
-
-            Game { id, results }
+                .map(|string| cube_count(line, string))
+                .collect::<parsing::Result<_>>()?;
+
+            Ok(Game { id, results })
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn cube_count(
+    line: &SourceLine<'_>,
+    string: &str,
+) -> parsing::Result<CubeCount> {
+    let color_counts: HashMap<&str, usize> = string
+        .split(',')
+        .map(|color_count_string| {
+            let (count_string, color_string) =
+                line.split_once(color_count_string.trim(), " ")?;
+
+            match color_string.trim() {
+                color @ ("red" | "green" | "blue") => {
+                    Ok((color, line.parse(count_string)?))
+                }
+                _ => Err(line
+                    .error(color_string, "expected `red`, `green` or `blue`")),
+            }
         })
-        .collect()
+        .collect::<parsing::Result<_>>()?;
+
+    Ok(CubeCount {
+        reds: *color_counts.get(&"red").unwrap_or(&0),
+        greens: *color_counts.get(&"green").unwrap_or(&0),
+        blues: *color_counts.get(&"blue").unwrap_or(&0),
+    })
 }
 
 #[cfg(test)]
@@ -121,4 +155,22 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
     fn part2_example() {
         assert_eq!(part2(input()), 2286)
     }
+
+    #[test]
+    fn invalid_games() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("Game 1: 3 blue\nGame 2: 1 purple; 2 red"),
+            "day 02, line 2: column 11, expected `red`, `green` or `blue`"
+        );
+        assert_eq!(
+            error("Game x: 3 blue"),
+            "day 02, line 1: column 6, invalid value `x`"
+        );
+        assert_eq!(
+            error("Game 1: 3blue"),
+            "day 02, line 1: column 9, expected ` `"
+        );
+    }
 }
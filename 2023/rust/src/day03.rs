@@ -1,5 +1,9 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::ops::RangeInclusive;
 
+const DAY: u8 = 3;
+
 #[derive(Debug)]
 struct Number {
     value: usize,
@@ -72,9 +76,27 @@ struct Schematic {
     symbols: Vec<Symbol>,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let schematic = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the schematic's lines all have the same width.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let schematic = parse(input)?;
     let mut total: usize = 0;
 
     for number in schematic.numbers {
@@ -83,12 +105,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    total
+    Ok(total)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let schematic = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let schematic = parse(input)?;
     let mut total: usize = 0;
 
     for symbol in schematic.symbols {
@@ -101,17 +125,25 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    total
+    Ok(total)
 }
 
-fn parse(input: &str) -> Schematic {
+fn parse(input: &str) -> Result<Schematic, PuzzleError> {
     let mut numbers: Vec<Number> = Vec::new();
     let mut symbols: Vec<Symbol> = Vec::new();
+    let lines = parsing::lines(input);
+    let width = lines.first().map_or(0, |line| line.text.chars().count());
 
-    for (y, line) in input.lines().enumerate() {
+    for (y, line) in lines.iter().enumerate() {
         let mut maybe_number: Option<Number> = None;
 
-        for (x, ch) in line.chars().enumerate() {
+        if line.text.chars().count() != width {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {width} characters")),
+            ));
+        }
+
+        for (x, ch) in line.text.chars().enumerate() {
             match ch {
                 '0'..='9' => {
                     match maybe_number {
@@ -136,7 +168,7 @@ fn parse(input: &str) -> Schematic {
         maybe_number.map(|n| numbers.push(n));
     }
 
-    Schematic { numbers, symbols }
+    Ok(Schematic { numbers, symbols })
 }
 
 #[cfg(test)]
@@ -166,4 +198,12 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 467835)
     }
+
+    #[test]
+    fn ragged_schematic() {
+        assert_eq!(
+            try_part1("467..\n...*\n").unwrap_err().to_string(),
+            "day 03, line 2: column 1, expected 5 characters"
+        );
+    }
 }
@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashSet;
 
+const DAY: u8 = 4;
+
 #[derive(Debug)]
 struct Card {
     #[allow(dead_code)]
@@ -25,22 +29,52 @@ impl Card {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let cards = parse(input);
-
-    cards.iter().map(|card| card.points()).sum()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let cards = parse(input);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't `Card <number>:` followed by winning and
+/// revealed numbers separated by `|`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let cards = parse(input)?;
+
+    Ok(cards.iter().map(|card| card.points()).sum())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when a card wins copies of
+/// cards past the end of the table.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let cards = parse(input)?;
     let mut card_counts = vec![1; cards.len()];
 
     for (index, card) in cards.iter().enumerate() {
         let card_count = card_counts[index];
         let match_count = card.match_count();
 
+        if index + match_count >= cards.len() {
+            return Err(PuzzleError::new(
+                DAY,
+                "the card wins copies of cards past the end of the table",
+            )
+            .at_line(index + 1));
+        }
+
         if match_count > 0 {
             for copied_card_index in (index + 1)..=(index + match_count) {
                 card_counts[copied_card_index] += card_count;
@@ -48,37 +82,33 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    card_counts.iter().sum()
+    Ok(card_counts.iter().sum())
 }
 
-fn parse(input: &str) -> Vec<Card> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Card>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
-            let (id_string, numbers) = line.split_once(':').unwrap();
-            let number_string =
-                id_string.split_whitespace().skip(1).next().unwrap();
+            let (id_string, numbers) = line.split_once(line.text, ":")?;
+            let number_string = line.strip_prefix(id_string, "Card")?;
             let (winning_numbers_string, revealed_numbers_string) =
-                numbers.split_once('|').unwrap();
-
-            let winning_numbers: HashSet<usize> = winning_numbers_string
-                .trim()
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-            let revealed_numbers: HashSet<usize> = revealed_numbers_string
-                .trim()
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-
-            Card {
-                number: number_string.parse().unwrap(),
-                winning_numbers,
-                revealed_numbers,
-            }
+                line.split_once(numbers, "|")?;
+
+            Ok(Card {
+                number: line.parse(number_string)?,
+                winning_numbers: numbers_in(line, winning_numbers_string)?,
+                revealed_numbers: numbers_in(line, revealed_numbers_string)?,
+            })
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
+fn numbers_in(
+    line: &SourceLine<'_>,
+    field: &str,
+) -> parsing::Result<HashSet<usize>> {
+    field.split_whitespace().map(|s| line.parse(s)).collect()
 }
 
 #[cfg(test)]
@@ -104,4 +134,19 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
     fn part2_example() {
         assert_eq!(part2(input()), 30)
     }
+
+    #[test]
+    fn invalid_cards() {
+        assert_eq!(
+            try_part1("Card 1: 41 48 | 83 4x").unwrap_err().to_string(),
+            "day 04, line 1: column 20, invalid value `4x`"
+        );
+        assert_eq!(
+            try_part2("Card 1: 1 2 | 1 2\nCard 2: 3 | 4")
+                .unwrap_err()
+                .to_string(),
+            "day 04, line 1: the card wins copies of cards past the end of \
+             the table"
+        );
+    }
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashSet, ops::Range};
+use crate::interval::{IntervalSet, PiecewiseMap};
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 5;
 
 #[derive(Debug)]
 struct Almanac {
@@ -7,7 +11,7 @@ struct Almanac {
 }
 
 impl Almanac {
-    fn seed_ranges(&self) -> Vec<Range<usize>> {
+    fn seed_ranges(&self) -> IntervalSet {
         self.seeds
             .chunks(2)
             .map(|chunk| chunk[0]..(chunk[0] + chunk[1]))
@@ -21,56 +25,41 @@ struct Map {
     from: String,
     #[allow(dead_code)]
     to: String,
-    ranges: Vec<(Range<usize>, Range<usize>)>,
+    mapping: PiecewiseMap,
 }
 
 impl Map {
     fn convert(&self, value: usize) -> usize {
-        for (source_range, destination_range) in self.ranges.iter() {
-            if source_range.contains(&value) {
-                return destination_range.start + (value - source_range.start);
-            }
-        }
-
-        value
+        self.mapping.apply(value)
     }
 
-    fn invert(&self, value: usize) -> usize {
-        for (source_range, destination_range) in self.ranges.iter() {
-            if destination_range.contains(&value) {
-                return source_range.start + (value - destination_range.start);
-            }
-        }
-        value
-    }
-
-    fn cutpoints(&self) -> Vec<usize> {
-        let mut values: Vec<usize> = self
-            .ranges
-            .iter()
-            .flat_map(|(source, _)| [source.start, source.end])
-            .collect::<HashSet<usize>>()
-            .into_iter()
-            .collect::<Vec<usize>>()
-            .into_iter()
-            .flat_map(|point| {
-                if point > 0 {
-                    [point, point - 1]
-                } else {
-                    [point, point]
-                }
-            })
-            .collect::<HashSet<usize>>()
-            .into_iter()
-            .collect();
-        values.sort();
-        values
+    fn convert_ranges(&self, values: &IntervalSet) -> IntervalSet {
+        self.mapping.map(values)
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let almanac = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the almanac is a `seeds:` line followed by maps of
+/// three-number ranges, or when there are no seeds.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let almanac = parse(input)?;
 
     almanac
         .seeds
@@ -82,99 +71,84 @@ pub fn part1(input: &str) -> usize {
                 .fold(seed, |value, map| map.convert(value))
         })
         .min()
-        .unwrap()
+        .ok_or_else(|| PuzzleError::new(DAY, "no seeds"))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let almanac = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the seeds don't pair
+/// up into ranges.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let almanac = parse(input)?;
+
+    if almanac.seeds.len() % 2 != 0 {
+        return Err(PuzzleError::new(
+            DAY,
+            "the seeds don't pair up into ranges",
+        ));
+    }
 
-    let mut possible_values: Vec<usize> = almanac
+    almanac
         .maps
         .iter()
-        .rev()
-        .fold(vec![], |values, map| {
-            let mut inverted_values = values
-                .iter()
-                .map(|v| map.invert(*v))
-                .collect::<Vec<usize>>();
+        .fold(almanac.seed_ranges(), |values, map| {
+            map.convert_ranges(&values)
+        })
+        .min()
+        .ok_or_else(|| PuzzleError::new(DAY, "no seeds"))
+}
 
-            inverted_values.extend(map.cutpoints());
-            inverted_values
+fn parse(input: &str) -> Result<Almanac, PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [seeds_block, map_blocks @ ..] = &blocks[..] else {
+        return Err(PuzzleError::new(DAY, "expected seeds followed by maps"));
+    };
+    let [seeds_line] = &seeds_block[..] else {
+        return Err(PuzzleError::new(DAY, "expected the seeds on one line"));
+    };
+
+    let seeds = seeds_line
+        .strip_prefix(seeds_line.text, "seeds:")
+        .and_then(|seeds_string| {
+            seeds_string
+                .split_whitespace()
+                .map(|seed_string| seeds_line.parse(seed_string))
+                .collect()
         })
-        .into_iter()
-        .collect::<HashSet<usize>>()
-        .into_iter()
-        .filter(|value| {
-            almanac
-                .seed_ranges()
-                .iter()
-                .any(|range| range.contains(value))
+        .map_err(PuzzleError::on_day(DAY))?;
+    let maps = map_blocks
+        .iter()
+        .map(|lines| {
+            let (header, ranges) = lines.split_first().unwrap();
+            let fields = header.fields("{}-to-{} map:")?;
+            let mapping =
+                ranges.iter().map(range).collect::<parsing::Result<_>>()?;
+
+            Ok(Map {
+                from: fields[0].to_owned(),
+                to: fields[1].to_owned(),
+                mapping,
+            })
         })
-        .collect();
-    possible_values.sort();
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
 
-    possible_values
-        .into_iter()
-        .map(|seed| {
-            almanac
-                .maps
-                .iter()
-                .fold(seed, |value, map| map.convert(value))
-        })
-        .min()
-        .unwrap()
+    Ok(Almanac { seeds, maps })
 }
 
-fn parse(input: &str) -> Almanac {
-    let mut group_iter = input.split("\n\n");
-
-    let seeds = group_iter
-        .next()
-        .unwrap()
+fn range(
+    line: &SourceLine<'_>,
+) -> parsing::Result<(std::ops::Range<usize>, usize)> {
+    let numbers: Vec<usize> = line
+        .text
         .split_whitespace()
-        .skip(1)
-        .map(|seed_string| seed_string.parse().unwrap())
-        .collect();
-    let maps = group_iter
-        .map(|group| {
-            let mut line_iter = group.lines();
-            let mut word_iter = line_iter
-                .next()
-                .unwrap()
-                .split_once(' ')
-                .unwrap()
-                .0
-                .split('-')
-                .take(3);
-            let from = word_iter.next().unwrap();
-            let to = word_iter.skip(1).next().unwrap();
-            let ranges = line_iter
-                .map(|line| {
-                    let mut number_iter = line.split_whitespace();
-                    let destination_start =
-                        number_iter.next().unwrap().parse().unwrap();
-                    let source_start =
-                        number_iter.next().unwrap().parse().unwrap();
-                    let length: usize =
-                        number_iter.next().unwrap().parse().unwrap();
-
-                    (
-                        source_start..(source_start + length),
-                        destination_start..(destination_start + length),
-                    )
-                })
-                .collect();
-
-            Map {
-                from: from.to_owned(),
-                to: to.to_owned(),
-                ranges,
-            }
-        })
-        .collect();
+        .map(|field| line.parse(field))
+        .collect::<parsing::Result<_>>()?;
+    let [destination_start, source_start, length] = numbers[..] else {
+        return Err(line.error(line.text, "expected three numbers"));
+    };
 
-    Almanac { seeds, maps }
+    Ok((source_start..(source_start + length), destination_start))
 }
 
 #[cfg(test)]
@@ -227,4 +201,26 @@ humidity-to-location map:
     fn part2_example() {
         assert_eq!(part2(input()), 46)
     }
+
+    #[test]
+    fn invalid_almanacs() {
+        let error = |input| try_part2(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("seeds: 79 14\n\nseed-to-soil map:\n50 98\n"),
+            "day 05, line 4: column 1, expected three numbers"
+        );
+        assert_eq!(
+            error("seeds: 79 14\n\nseed to soil map:\n50 98 2\n"),
+            "day 05, line 3: column 1, expected `-to-`"
+        );
+        assert_eq!(
+            error("seeds: 79\n"),
+            "day 05: the seeds don't pair up into ranges"
+        );
+        assert_eq!(
+            try_part1("seeds:\n").unwrap_err().to_string(),
+            "day 05: no seeds"
+        );
+    }
 }
@@ -1,75 +1,121 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 6;
+
 #[derive(Debug)]
 struct Race {
     time: usize,
     record: usize,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let races = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a `Time:` line and a `Distance:` line with
+/// the same number of values, or when a race is too long to time.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let races = parse(input)?;
     let mut result = 1;
 
     for race in races {
-        result *= win_count(race.time, race.record);
+        result *= win_count(race.time, race.record)?;
     }
 
-    result
+    Ok(result)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let races = parse(input);
-    let time: usize = combine(races.iter().map(|r| r.time).collect());
-    let record: usize = combine(races.iter().map(|r| r.record).collect());
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the combined race is
+/// too long to time.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let races = parse(input)?;
+    let time: usize = combine(races.iter().map(|r| r.time).collect())?;
+    let record: usize = combine(races.iter().map(|r| r.record).collect())?;
 
     win_count(time, record)
 }
 
-fn win_count(time: usize, record: usize) -> usize {
+fn win_count(time: usize, record: usize) -> Result<usize, PuzzleError> {
     // distance = time*speed - speed^2
     // 0 = speed^2 - time*speed + distance
     // speed = (-b +/- sqrt(b^2 - 4ac)) / 2a
     // speed = (time +/- sqrt(time^2 - 4*distance)) / 2
 
-    let discriminant = ((time * time - 4 * record) as f64).sqrt();
+    let too_long = || PuzzleError::new(DAY, "a race is too long to time");
+    let square = time.checked_mul(time).ok_or_else(too_long)?;
+    let quadruple_record = record.checked_mul(4).ok_or_else(too_long)?;
+
+    // No speed beats the record when the parabola never rises above it.
+    let Some(difference) = square.checked_sub(quadruple_record) else {
+        return Ok(0);
+    };
+
+    let discriminant = (difference as f64).sqrt();
     let low = ((time as f64 - discriminant) / 2f64).floor() as usize;
     let high = ((time as f64 + discriminant) / 2f64).ceil() as usize;
 
-    high - low - 1
+    Ok((high - low).saturating_sub(1))
 }
 
-fn combine(values: Vec<usize>) -> usize {
+fn combine(values: Vec<usize>) -> Result<usize, PuzzleError> {
     values
         .iter()
         .map(|v| v.to_string())
         .collect::<Vec<String>>()
         .join("")
         .parse()
-        .unwrap()
+        .map_err(|_| PuzzleError::new(DAY, "a race is too long to time"))
 }
 
-fn parse(input: &str) -> Vec<Race> {
-    let mut line_iter = input.lines();
-
-    let times: Vec<usize> = line_iter
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse().unwrap())
-        .collect();
-    let records: Vec<usize> = line_iter
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse().unwrap())
-        .collect();
+fn parse(input: &str) -> Result<Vec<Race>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let [times_line, records_line] = &lines[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected a time line and a distance line",
+        ));
+    };
+
+    let times =
+        values(times_line, "Time:").map_err(PuzzleError::on_day(DAY))?;
+    let records =
+        values(records_line, "Distance:").map_err(PuzzleError::on_day(DAY))?;
+
+    if times.len() != records.len() {
+        return Err(PuzzleError::on_day(DAY)(records_line.error(
+            records_line.text,
+            format!("expected {} distances", times.len()),
+        )));
+    }
 
-    times
+    Ok(times
         .into_iter()
-        .zip(records.into_iter())
+        .zip(records)
         .map(|(time, record)| Race { time, record })
+        .collect())
+}
+
+fn values(line: &SourceLine<'_>, label: &str) -> parsing::Result<Vec<usize>> {
+    line.strip_prefix(line.text, label)?
+        .split_whitespace()
+        .map(|s| line.parse(s))
         .collect()
 }
 
@@ -92,4 +138,30 @@ Distance:  9  40  200"
     fn part2_example() {
         assert_eq!(part2(input()), 71503)
     }
+
+    #[test]
+    fn races_without_wins() {
+        assert_eq!(part1("Time: 4 5\nDistance: 4 2\n"), 0);
+        assert_eq!(part2("Time: 2\nDistance: 2\n"), 0);
+    }
+
+    #[test]
+    fn invalid_races() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("Time: 7 15\nDistance: 9\n"),
+            "day 06, line 2: column 1, expected 2 distances"
+        );
+        assert_eq!(
+            error("Time: 7\nRecord: 9\n"),
+            "day 06, line 2: column 1, expected `Distance:`"
+        );
+        assert_eq!(
+            try_part2("Time: 99999 99999 99999 99999\nDistance: 1 1 1 1\n")
+                .unwrap_err()
+                .to_string(),
+            "day 06: a race is too long to time"
+        );
+    }
 }
@@ -1,189 +1,331 @@
-use std::collections::HashMap;
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use std::cmp::Ordering;
+use std::fmt;
 
-#[derive(Debug)]
-struct Hand {
-    cards: Vec<char>,
-}
+const DAY: u8 = 7;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum HandKind {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+// How hands are ranked: the card order from weakest to strongest, which
+// cards are wild, and how many cards make a hand.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    order: Vec<char>,
+    wildcards: Vec<char>,
+    hand_size: usize,
 }
 
-impl Hand {
-    fn kind(&self) -> HandKind {
-        let mut grouped_count: HashMap<char, usize> = HashMap::new();
-
-        for card in &self.cards {
-            grouped_count
-                .entry(*card)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
+impl Rules {
+    #[must_use]
+    pub fn new(order: &str, wildcards: &str, hand_size: usize) -> Self {
+        Self {
+            order: order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+            hand_size,
         }
+    }
 
-        let mut sorted_counts: Vec<usize> =
-            grouped_count.into_values().collect();
-        sorted_counts.sort();
-        sorted_counts.reverse();
-
-        match &sorted_counts[..] {
-            &[5] => HandKind::FiveOfAKind,
-            &[4, 1] => HandKind::FourOfAKind,
-            &[3, 2] => HandKind::FullHouse,
-            &[3, 1, 1] => HandKind::ThreeOfAKind,
-            &[2, 2, 1] => HandKind::TwoPair,
-            &[2, 1, 1, 1] => HandKind::OnePair,
-            &[1, 1, 1, 1, 1] => HandKind::HighCard,
-            counts => panic!("invalid state: {:?}", counts),
-        }
+    #[must_use]
+    pub fn standard() -> Self {
+        Self::new("23456789TJQKA", "", 5)
     }
 
-    fn kind_part2(&self) -> HandKind {
-        let mut grouped_count: HashMap<char, usize> = HashMap::new();
+    // Jacks become jokers: wild when grouping, weakest when breaking ties.
+    #[must_use]
+    pub fn jokers() -> Self {
+        Self::new("J23456789TQKA", "J", 5)
+    }
 
-        for card in &self.cards {
-            grouped_count
-                .entry(*card)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
+    /// # Errors
+    ///
+    /// Fails on the wrong number of cards or a card not in the order.
+    pub fn hand(&self, cards: &str) -> Result<Hand, HandError> {
+        let cards: Vec<char> = cards.chars().collect();
+        if cards.len() != self.hand_size {
+            return Err(HandError::WrongSize {
+                expected: self.hand_size,
+                found: cards.len(),
+            });
         }
 
-        if let Some(joker_count) = grouped_count.remove(&'J') {
-            let mut cards_by_count = grouped_count
-                .iter()
-                .map(|(&card, &count)| (card, count))
-                .collect::<Vec<(char, usize)>>();
-            cards_by_count.sort_by_key(|(_card, count)| *count);
-            cards_by_count.reverse();
-
-            if let Some((highest_count_card, _)) = cards_by_count.get(0) {
-                grouped_count
-                    .entry(*highest_count_card)
-                    .and_modify(|count| *count += joker_count);
+        let strengths = cards
+            .iter()
+            .map(|card| {
+                self.order
+                    .iter()
+                    .position(|other| other == card)
+                    .ok_or(HandError::UnknownCard(*card))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Hand {
+            kind: self.kind(&cards),
+            cards,
+            strengths,
+        })
+    }
+
+    // Wildcards always do best joining the largest group; a hand of only
+    // wildcards is one big group.
+    fn kind(&self, cards: &[char]) -> Kind {
+        let mut groups: Vec<(char, usize)> = vec![];
+        let mut wild = 0;
+
+        for &card in cards {
+            if self.wildcards.contains(&card) {
+                wild += 1;
+            } else if let Some((_, count)) =
+                groups.iter_mut().find(|(other, _)| *other == card)
+            {
+                *count += 1;
             } else {
-                grouped_count.insert('J', joker_count);
+                groups.push((card, 1));
+            }
+        }
+
+        let mut counts: Vec<usize> =
+            groups.into_iter().map(|(_, count)| count).collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        match counts.first_mut() {
+            Some(largest) => *largest += wild,
+            None if wild > 0 => counts.push(wild),
+            None => {}
+        }
+
+        Kind(counts)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HandError {
+    WrongSize { expected: usize, found: usize },
+    UnknownCard(char),
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongSize { expected, found } => {
+                write!(f, "expected {expected} cards, found {found}")
             }
+            Self::UnknownCard(card) => write!(f, "unknown card {card:?}"),
         }
+    }
+}
+
+impl std::error::Error for HandError {}
+
+// The sizes of a hand's groups of matching cards, largest first. Comparing
+// these lexicographically gives the usual poker order for any hand size,
+// e.g. a full house `[3, 2]` beats three of a kind `[3, 1, 1]`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Kind(pub Vec<usize>);
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0[..] {
+            [5] => write!(f, "five of a kind"),
+            [4, 1] => write!(f, "four of a kind"),
+            [3, 2] => write!(f, "full house"),
+            [3, 1, 1] => write!(f, "three of a kind"),
+            [2, 2, 1] => write!(f, "two pair"),
+            [2, 1, 1, 1] => write!(f, "one pair"),
+            [1, 1, 1, 1, 1] => write!(f, "high card"),
+            _ => {
+                let counts: Vec<String> =
+                    self.0.iter().map(ToString::to_string).collect();
+                write!(f, "groups of {}", counts.join("+"))
+            }
+        }
+    }
+}
+
+// A hand ranked under some `Rules`. Only compare hands built from the same
+// rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hand {
+    cards: Vec<char>,
+    kind: Kind,
+    strengths: Vec<usize>,
+}
+
+impl Hand {
+    #[must_use]
+    pub fn cards(&self) -> &[char] {
+        &self.cards
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    // Why this hand ranks where it does against another one.
+    #[must_use]
+    pub fn explain(&self, other: &Self) -> Explanation {
+        if self.kind != other.kind {
+            return Explanation::Kind {
+                ordering: self.kind.cmp(&other.kind),
+                ours: self.kind.clone(),
+                theirs: other.kind.clone(),
+            };
+        }
+
+        self.strengths
+            .iter()
+            .zip(&other.strengths)
+            .position(|(ours, theirs)| ours != theirs)
+            .map_or(Explanation::Tie, |position| Explanation::Card {
+                ordering: self.strengths[position]
+                    .cmp(&other.strengths[position]),
+                position,
+                ours: self.cards[position],
+                theirs: other.cards[position],
+            })
+    }
+}
 
-        let mut sorted_counts: Vec<usize> =
-            grouped_count.into_values().collect();
-        sorted_counts.sort();
-        sorted_counts.reverse();
-
-        // FIXME: need to handle multiple slice sizes
-        match &sorted_counts[..] {
-            &[5] => HandKind::FiveOfAKind,
-            &[4, 1] => HandKind::FourOfAKind,
-            &[3, 2] => HandKind::FullHouse,
-            &[3, 1, 1] => HandKind::ThreeOfAKind,
-            &[2, 2, 1] => HandKind::TwoPair,
-            &[2, 1, 1, 1] => HandKind::OnePair,
-            &[1, 1, 1, 1, 1] => HandKind::HighCard,
-            counts => panic!("invalid state: {:?}", counts),
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind
+            .cmp(&other.kind)
+            .then_with(|| self.strengths.cmp(&other.strengths))
+    }
+}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cards.iter().collect::<String>())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Explanation {
+    // The hands have different kinds, which settles it.
+    Kind {
+        ordering: Ordering,
+        ours: Kind,
+        theirs: Kind,
+    },
+    // Same kind, so the first differing card (0-based) decides.
+    Card {
+        ordering: Ordering,
+        position: usize,
+        ours: char,
+        theirs: char,
+    },
+    Tie,
+}
+
+impl Explanation {
+    #[must_use]
+    pub fn ordering(&self) -> Ordering {
+        match self {
+            Self::Kind { ordering, .. } | Self::Card { ordering, .. } => {
+                *ordering
+            }
+            Self::Tie => Ordering::Equal,
         }
     }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = |ordering: &Ordering| match ordering {
+            Ordering::Less => "loses to",
+            Ordering::Equal => "ties",
+            Ordering::Greater => "beats",
+        };
 
-    fn strength(&self) -> Vec<usize> {
-        let card_strengths = self.cards.iter().map(|ch| match ch {
-            'A' => 14,
-            'K' => 13,
-            'Q' => 12,
-            'J' => 11,
-            'T' => 10,
-            '9' => 9,
-            '8' => 8,
-            '7' => 7,
-            '6' => 6,
-            '5' => 5,
-            '4' => 4,
-            '3' => 3,
-            '2' => 2,
-            invalid => panic!("saw invalid card: {:?}", invalid),
-        });
-
-        let mut total_strength = vec![self.kind() as usize];
-        total_strength.extend(card_strengths);
-
-        total_strength
-    }
-
-    fn strength_part2(&self) -> Vec<usize> {
-        let card_strengths = self.cards.iter().map(|ch| match ch {
-            'A' => 14,
-            'K' => 13,
-            'Q' => 12,
-            'T' => 10,
-            '9' => 9,
-            '8' => 8,
-            '7' => 7,
-            '6' => 6,
-            '5' => 5,
-            '4' => 4,
-            '3' => 3,
-            '2' => 2,
-            'J' => 1,
-            invalid => panic!("saw invalid card: {:?}", invalid),
-        });
-
-        let mut total_strength = vec![self.kind_part2() as usize];
-        total_strength.extend(card_strengths);
-
-        total_strength
+        match self {
+            Self::Kind {
+                ordering,
+                ours,
+                theirs,
+            } => write!(f, "{ours} {} {theirs}", verb(ordering)),
+            Self::Card {
+                ordering,
+                position,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "same kind; card {} {ours} {} {theirs}",
+                position + 1,
+                verb(ordering)
+            ),
+            Self::Tie => write!(f, "identical ranking"),
+        }
     }
 }
 
 type Bid = usize;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let mut hands_and_bids = parse(input);
-
-    hands_and_bids.sort_by(|(hand_a, _), (hand_b, _)| {
-        hand_a.strength().partial_cmp(&hand_b.strength()).unwrap()
-    });
-
-    hands_and_bids
-        .iter()
-        .enumerate()
-        .fold(0, |acc, (rank, (_hand, bid))| acc + (rank + 1) * bid)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let mut hands_and_bids = parse(input);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a hand of five cards followed by a bid.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    total_winnings(input, &Rules::standard())
+}
 
-    hands_and_bids.sort_by(|(hand_a, _), (hand_b, _)| {
-        hand_a
-            .strength_part2()
-            .partial_cmp(&hand_b.strength_part2())
-            .unwrap()
-    });
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    total_winnings(input, &Rules::jokers())
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a hand under `rules` followed by a bid.
+pub fn total_winnings(
+    input: &str,
+    rules: &Rules,
+) -> Result<usize, PuzzleError> {
+    let mut hands_and_bids = parse(input, rules)?;
+    hands_and_bids.sort_by(|(hand_a, _), (hand_b, _)| hand_a.cmp(hand_b));
 
-    hands_and_bids
+    Ok(hands_and_bids
         .iter()
         .enumerate()
-        .fold(0, |acc, (rank, (_hand, bid))| acc + (rank + 1) * bid)
+        .fold(0, |acc, (rank, (_hand, bid))| acc + (rank + 1) * bid))
 }
 
-fn parse(input: &str) -> Vec<(Hand, Bid)> {
-    input
-        .lines()
+fn parse(input: &str, rules: &Rules) -> Result<Vec<(Hand, Bid)>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
-            let (cards_string, bid_string) = line.split_once(' ').unwrap();
-            (
-                Hand {
-                    cards: cards_string.chars().collect(),
-                },
-                bid_string.parse().unwrap(),
-            )
+            let (cards_string, bid_string) = line.split_once(line.text, " ")?;
+            let hand = rules
+                .hand(cards_string)
+                .map_err(|error| line.error(cards_string, error.to_string()))?;
+
+            Ok((hand, line.parse(bid_string)?))
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -208,4 +350,76 @@ QQQJA 483"
     fn part2_example() {
         assert_eq!(part2(input()), 5905)
     }
+
+    #[test]
+    fn invalid_hands() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("32T3K 765\nT55J 684"),
+            "day 07, line 2: column 1, expected 5 cards, found 4"
+        );
+        assert_eq!(
+            error("32T3K 765\nT55J5 68x"),
+            "day 07, line 2: column 7, invalid value `68x`"
+        );
+    }
+
+    #[test]
+    fn kinds_and_explanations() {
+        let rules = Rules::jokers();
+        let hand = |cards| rules.hand(cards).unwrap();
+
+        assert_eq!(hand("JJJJJ").kind().to_string(), "five of a kind");
+        assert_eq!(hand("KTJJT").kind().to_string(), "four of a kind");
+        assert_eq!(
+            hand("KTJJT").explain(&hand("QQQJA")).to_string(),
+            "same kind; card 1 K beats Q"
+        );
+        assert_eq!(
+            hand("JKKK2").explain(&hand("QQQQ2")).to_string(),
+            "same kind; card 1 J loses to Q"
+        );
+        assert_eq!(
+            hand("32T3K").explain(&hand("KK677")).to_string(),
+            "one pair loses to two pair"
+        );
+        assert_eq!(hand("T55J5").explain(&hand("T55J5")), Explanation::Tie);
+        assert_eq!(
+            rules.hand("AAAA"),
+            Err(HandError::WrongSize {
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(rules.hand("AAAA1"), Err(HandError::UnknownCard('1')));
+    }
+
+    #[test]
+    fn other_hand_sizes_and_wildcards() {
+        let rules = Rules::new("123456", "16", 6);
+        let hand = |cards| rules.hand(cards).unwrap();
+
+        assert_eq!(hand("223345").kind(), &Kind(vec![2, 2, 1, 1]));
+        assert_eq!(hand("222335").kind().to_string(), "groups of 3+2+1");
+        assert_eq!(hand("122336").kind(), &Kind(vec![4, 2]));
+        assert!(hand("122336") > hand("222335"));
+        assert!(hand("222333") < hand("222235"));
+        assert!(hand("222233") > hand("222333"));
+    }
+
+    #[test]
+    fn ordering_matches_explanation() {
+        let rules = Rules::jokers();
+        let hands: Vec<Hand> = input()
+            .lines()
+            .map(|line| rules.hand(&line[..5]).unwrap())
+            .collect();
+
+        for a in &hands {
+            for b in &hands {
+                assert_eq!(a.cmp(b), a.explain(b).ordering());
+            }
+        }
+    }
 }
@@ -1,7 +1,11 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use num::integer;
 use rayon::prelude::*;
 use std::collections::HashMap;
 
+const DAY: u8 = 8;
+
 #[derive(Debug)]
 enum Instruction {
     Left,
@@ -9,27 +13,54 @@ enum Instruction {
 }
 
 type Node = String;
+type NodeMap = HashMap<Node, (Node, Node)>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (instructions, node_map) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a line of `L` and `R` instructions followed by
+/// `XXX = (YYY, ZZZ)` nodes that only lead to other nodes, or when there's
+/// no `AAA` node or its path never reaches a node ending in `Z`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (instructions, node_map) = parse(input)?;
     let starting_node = &"AAA".to_owned();
 
+    if !node_map.contains_key(starting_node) {
+        return Err(PuzzleError::new(DAY, "no `AAA` node"));
+    }
+
     path_length(starting_node, &instructions, &node_map)
 }
 
+// Stepping from the same node at the same instruction repeats the path, so
+// after visiting every such pair it can only go round in circles.
 fn path_length(
     starting_node: &String,
     instructions: &Vec<Instruction>,
-    node_map: &HashMap<Node, (Node, Node)>,
-) -> usize {
+    node_map: &NodeMap,
+) -> Result<usize, PuzzleError> {
     let mut node: &String = starting_node;
     let mut steps = 0usize;
 
     for instruction in instructions.iter().cycle() {
         steps += 1;
 
-        let (left_node, right_node) = node_map.get(node).unwrap();
+        let (left_node, right_node) = &node_map[node];
         match instruction {
             Instruction::Left => {
                 node = left_node;
@@ -42,55 +73,105 @@ fn path_length(
         if node.ends_with("Z") {
             break;
         }
+
+        if steps > instructions.len() * node_map.len() {
+            return Err(PuzzleError::new(
+                DAY,
+                format!(
+                    "the path from `{starting_node}` never reaches a node \
+                     ending in `Z`"
+                ),
+            ));
+        }
     }
 
-    steps
+    Ok(steps)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (instructions, node_map) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`] apart from a missing `AAA`
+/// node, or when no node ends in `A`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (instructions, node_map) = parse(input)?;
     let steps: Vec<usize> = nodes_ending_with_a(&node_map)
         .par_iter()
         .map(|starting_node| {
             path_length(starting_node, &instructions, &node_map)
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
     steps
         .into_iter()
         .reduce(|acc, x| integer::lcm(acc, x))
-        .unwrap()
+        .ok_or_else(|| PuzzleError::new(DAY, "no nodes end in `A`"))
 }
 
-fn nodes_ending_with_a(node_map: &HashMap<Node, (Node, Node)>) -> Vec<&Node> {
+fn nodes_ending_with_a(node_map: &NodeMap) -> Vec<&Node> {
     node_map.keys().filter(|node| node.ends_with("A")).collect()
 }
 
-fn parse(input: &str) -> (Vec<Instruction>, HashMap<Node, (Node, Node)>) {
-    let (instructions_string, nodes_string) = input.split_once("\n\n").unwrap();
-
-    let instructions = instructions_string
-        .chars()
-        .map(|ch| match ch {
-            'R' => Instruction::Right,
-            'L' => Instruction::Left,
-            invalid => panic!("invalid instruction: {:?}", invalid),
+fn parse(input: &str) -> Result<(Vec<Instruction>, NodeMap), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [instructions_block, node_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected instructions followed by nodes",
+        ));
+    };
+    let [instructions_line] = &instructions_block[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected the instructions on one line",
+        ));
+    };
+
+    let instructions =
+        instructions(instructions_line).map_err(PuzzleError::on_day(DAY))?;
+
+    let nodes: Vec<(&SourceLine<'_>, Vec<&str>)> = node_lines
+        .iter()
+        .map(|line| Ok((line, line.fields("{} = ({}, {})")?)))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    let node_map: NodeMap = nodes
+        .iter()
+        .map(|(_, fields)| {
+            (
+                fields[0].to_owned(),
+                (fields[1].to_owned(), fields[2].to_owned()),
+            )
         })
         .collect();
 
-    let mut node_map: HashMap<Node, (Node, Node)> = HashMap::new();
-    for line in nodes_string.lines() {
-        let (node, left_and_right) = line.split_once(" = ").unwrap();
-        let parens: &[_] = &['(', ')'];
-        let (left, right) = left_and_right
-            .trim_matches(parens)
-            .split_once(", ")
-            .unwrap();
-        node_map.insert(node.to_owned(), (left.to_owned(), right.to_owned()));
+    for (line, fields) in &nodes {
+        if let Some(unknown) = fields[1..]
+            .iter()
+            .find(|node| !node_map.contains_key(**node))
+        {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(unknown, format!("unknown node `{unknown}`")),
+            ));
+        }
     }
 
-    (instructions, node_map)
+    Ok((instructions, node_map))
+}
+
+fn instructions(line: &SourceLine<'_>) -> parsing::Result<Vec<Instruction>> {
+    let text = line.text.trim_end();
+
+    if text.is_empty() {
+        return Err(line.error(text, "expected instructions"));
+    }
+
+    text.char_indices()
+        .map(|(index, ch)| match ch {
+            'R' => Ok(Instruction::Right),
+            'L' => Ok(Instruction::Left),
+            _ => Err(line.error(&text[index..], "expected `L` or `R`")),
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -143,4 +224,28 @@ XXX = (XXX, XXX)"
     fn part2_example() {
         assert_eq!(part2(input3()), 6);
     }
+
+    #[test]
+    fn invalid_maps() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)"),
+            "day 08, line 3: column 8, unknown node `BBB`"
+        );
+        assert_eq!(
+            error("LX\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)"),
+            "day 08, line 1: column 2, expected `L` or `R`"
+        );
+        assert_eq!(
+            error("L\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)"),
+            "day 08: the path from `AAA` never reaches a node ending in `Z`"
+        );
+        assert_eq!(
+            try_part2("L\n\nBBB = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)")
+                .unwrap_err()
+                .to_string(),
+            "day 08: no nodes end in `A`"
+        );
+    }
 }
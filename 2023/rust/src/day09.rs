@@ -1,20 +1,45 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 9;
+
 type Sequence = Vec<isize>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> isize {
-    let sequences = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> isize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't at least two numbers.
+pub fn try_part1(input: &str) -> Result<isize, PuzzleError> {
+    let sequences = parse(input)?;
 
     let extrapolates: Vec<isize> = sequences
         .iter()
         .map(|sequence| extrapolate(sequence))
         .collect();
 
-    extrapolates.iter().sum()
+    Ok(extrapolates.iter().sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> isize {
-    let sequences: Vec<Sequence> = parse(input)
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<isize, PuzzleError> {
+    let sequences: Vec<Sequence> = parse(input)?
         .into_iter()
         .map(|sequence| sequence.into_iter().rev().collect())
         .collect();
@@ -24,7 +49,7 @@ pub fn part2(input: &str) -> isize {
         .map(|sequence| extrapolate(sequence))
         .collect();
 
-    extrapolates.iter().sum()
+    Ok(extrapolates.iter().sum())
 }
 
 fn derivative(sequence: &Sequence) -> Sequence {
@@ -60,15 +85,26 @@ fn extrapolate(initial_sequence: &Sequence) -> isize {
     tails.iter().sum()
 }
 
-fn parse(input: &str) -> Vec<Sequence> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Sequence>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
-            line.split_whitespace()
-                .map(|n| n.parse().unwrap())
-                .collect()
+            let sequence: Sequence = line
+                .text
+                .split_whitespace()
+                .map(|n| line.parse(n))
+                .collect::<parsing::Result<_>>()?;
+
+            if sequence.len() < 2 {
+                return Err(
+                    line.error(line.text, "expected at least two numbers")
+                );
+            }
+
+            Ok(sequence)
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -91,4 +127,16 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 2)
     }
+
+    #[test]
+    fn short_sequences() {
+        assert_eq!(
+            try_part1("0 3 6\n7\n").unwrap_err().to_string(),
+            "day 09, line 2: column 1, expected at least two numbers"
+        );
+        assert_eq!(
+            try_part1("0 3 x\n").unwrap_err().to_string(),
+            "day 09, line 1: column 5, invalid value `x`"
+        );
+    }
 }
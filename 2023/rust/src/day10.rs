@@ -1,11 +1,38 @@
-use std::collections::HashMap;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 
-type Map = HashMap<Location, Tile>;
+const DAY: u8 = 10;
 
 type Location = (usize, usize);
 
-#[derive(Debug, PartialEq)]
-enum Tile {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
     Vertical,
     Horizontal,
     NorthEast,
@@ -16,134 +43,332 @@ enum Tile {
     Start,
 }
 
-fn part1_with_start_tile(input: &str, start_tile: Tile) -> usize {
-    let (start_location, _bounds, mut map) = parse(input);
-    map.insert(start_location, start_tile);
+impl Tile {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '|' => Some(Tile::Vertical),
+            '-' => Some(Tile::Horizontal),
+            'L' => Some(Tile::NorthEast),
+            'J' => Some(Tile::NorthWest),
+            '7' => Some(Tile::SouthWest),
+            'F' => Some(Tile::SouthEast),
+            '.' => Some(Tile::Ground),
+            'S' => Some(Tile::Start),
+            _ => None,
+        }
+    }
+
+    fn joining(a: Direction, b: Direction) -> Self {
+        use Direction::{East, North, South, West};
+
+        match (a, b) {
+            (North, South) | (South, North) => Tile::Vertical,
+            (East, West) | (West, East) => Tile::Horizontal,
+            (North, East) | (East, North) => Tile::NorthEast,
+            (North, West) | (West, North) => Tile::NorthWest,
+            (South, West) | (West, South) => Tile::SouthWest,
+            (South, East) | (East, South) => Tile::SouthEast,
+            _ => panic!("no tile joins {a:?} to itself"),
+        }
+    }
+
+    #[must_use]
+    pub fn connections(self) -> &'static [Direction] {
+        use Direction::{East, North, South, West};
+
+        match self {
+            Tile::Vertical => &[North, South],
+            Tile::Horizontal => &[East, West],
+            Tile::NorthEast => &[North, East],
+            Tile::NorthWest => &[North, West],
+            Tile::SouthWest => &[South, West],
+            Tile::SouthEast => &[South, East],
+            Tile::Ground | Tile::Start => &[],
+        }
+    }
+
+    fn connects(self, direction: Direction) -> bool {
+        self.connections().contains(&direction)
+    }
 
-    find_loop(start_location, &map).len() / 2
+    fn box_drawing(self) -> char {
+        match self {
+            Tile::Vertical => '│',
+            Tile::Horizontal => '─',
+            Tile::NorthEast => '└',
+            Tile::NorthWest => '┘',
+            Tile::SouthWest => '┐',
+            Tile::SouthEast => '┌',
+            Tile::Ground | Tile::Start => '.',
+        }
+    }
 }
 
-#[must_use]
-pub fn part1(input: &str) -> usize {
-    part1_with_start_tile(input, Tile::SouthWest)
+pub struct Maze {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+    start: Location,
+    start_tile: Tile,
 }
 
-fn part2_with_start_tile(input: &str, start_tile: Tile) -> usize {
-    let (start_location, (max_x, max_y), mut map) = parse(input);
-    map.insert(start_location, start_tile);
+impl Maze {
+    /// # Errors
+    ///
+    /// Fails on an empty or ragged maze, an unknown tile, or anything but a
+    /// single start tile on a loop.
+    pub fn parse(input: &str) -> Result<Self, PuzzleError> {
+        let lines = parsing::lines(input);
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.text.chars().count());
+
+        if width == 0 {
+            return Err(PuzzleError::new(DAY, "expected a maze"));
+        }
 
-    let loop_path = find_loop(start_location, &map);
-    let mut inside = false;
-    let mut in_count = 0;
+        let tiles =
+            Self::tiles(&lines, width).map_err(PuzzleError::on_day(DAY))?;
+        let mut starts = tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile == Tile::Start)
+            .map(|(index, _)| (index % width, index / width));
+        let start = match (starts.next(), starts.next()) {
+            (Some(start), None) => start,
+            (None, _) => return Err(PuzzleError::new(DAY, "no start tile")),
+            (Some(_), Some(_)) => {
+                return Err(PuzzleError::new(DAY, "more than one start tile"))
+            }
+        };
+
+        let mut maze = Self {
+            width,
+            height,
+            tiles,
+            start,
+            start_tile: Tile::Ground,
+        };
+        maze.start_tile = maze.infer_start_tile().ok_or_else(|| {
+            PuzzleError::new(DAY, "the start tile isn't on a loop")
+        })?;
+        let (x, y) = start;
+        maze.tiles[y * width + x] = maze.start_tile;
+
+        Ok(maze)
+    }
 
-    for y in 0..max_y {
-        for x in 0..max_x {
-            use Tile::*;
-            let in_loop_path = loop_path.contains(&(x, y));
+    fn tiles(
+        lines: &[SourceLine<'_>],
+        width: usize,
+    ) -> parsing::Result<Vec<Tile>> {
+        let mut tiles = Vec::with_capacity(width * lines.len());
+
+        for line in lines {
+            if line.text.chars().count() != width {
+                return Err(
+                    line.error(line.text, format!("expected {width} tiles"))
+                );
+            }
 
-            match map.get(&(x, y)).unwrap() {
-                Ground => {
-                    if inside {
-                        in_count += 1;
-                    }
-                }
-                Vertical | SouthEast | SouthWest => {
-                    if in_loop_path {
-                        inside = !inside;
-                    } else {
-                        if inside {
-                            in_count += 1;
-                        }
-                    }
-                }
-                _ => {
-                    if inside && !in_loop_path {
-                        in_count += 1;
+            for (index, ch) in line.text.char_indices() {
+                tiles.push(Tile::from_char(ch).ok_or_else(|| {
+                    line.error(
+                        &line.text[index..],
+                        "expected one of `|-LJ7F.S`",
+                    )
+                })?);
+            }
+        }
+
+        Ok(tiles)
+    }
+
+    // The start tile joins two neighbours that connect back to it. With
+    // more than two candidates, pick the pair that actually closes a loop.
+    fn infer_start_tile(&self) -> Option<Tile> {
+        let candidates: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|&direction| {
+                self.neighbour(self.start, direction).is_some_and(|next| {
+                    self.tile(next).connects(direction.opposite())
+                })
+            })
+            .collect();
+
+        candidates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| {
+                candidates[i + 1..]
+                    .iter()
+                    .map(move |&b| Tile::joining(a, b))
+            })
+            .find(|&tile| self.trace(tile).is_some())
+    }
+
+    #[must_use]
+    pub fn start_tile(&self) -> Tile {
+        self.start_tile
+    }
+
+    fn tile(&self, (x, y): Location) -> Tile {
+        self.tiles[y * self.width + x]
+    }
+
+    fn neighbour(
+        &self,
+        (x, y): Location,
+        direction: Direction,
+    ) -> Option<Location> {
+        match direction {
+            Direction::North => y.checked_sub(1).map(|y| (x, y)),
+            Direction::South => (y + 1 < self.height).then_some((x, y + 1)),
+            Direction::East => (x + 1 < self.width).then_some((x + 1, y)),
+            Direction::West => x.checked_sub(1).map(|x| (x, y)),
+        }
+    }
+
+    // Follows the pipes from the start, treating the start as
+    // `start_tile`, until it comes back around.
+    fn trace(&self, start_tile: Tile) -> Option<Vec<Location>> {
+        let mut path = vec![self.start];
+        let mut heading = start_tile.connections()[0];
+
+        loop {
+            let location = self.neighbour(*path.last().unwrap(), heading)?;
+            let came_from = heading.opposite();
+
+            if location == self.start {
+                return start_tile.connects(came_from).then_some(path);
+            }
+
+            let tile = self.tile(location);
+            if !tile.connects(came_from) {
+                return None;
+            }
+
+            heading = *tile
+                .connections()
+                .iter()
+                .find(|&&direction| direction != came_from)
+                .unwrap();
+            path.push(location);
+        }
+    }
+
+    /// Every tile on the loop, in order, starting from `S`.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: [`Maze::parse`] only accepts a start tile on a
+    /// loop.
+    #[must_use]
+    pub fn main_loop(&self) -> Vec<Location> {
+        self.trace(self.start_tile).unwrap()
+    }
+
+    #[must_use]
+    pub fn farthest_distance(&self) -> usize {
+        self.main_loop().len() / 2
+    }
+
+    // The shoelace formula gives the area of the polygon through the tile
+    // centres, and Pick's theorem (A = i + b/2 - 1) turns that into the
+    // number of tiles strictly inside.
+    #[must_use]
+    pub fn enclosed_area(&self) -> usize {
+        let path = self.main_loop();
+        let twice_area = path
+            .iter()
+            .zip(path.iter().cycle().skip(1))
+            .map(|(&(x1, y1), &(x2, y2))| {
+                x1.cast_signed() * y2.cast_signed()
+                    - x2.cast_signed() * y1.cast_signed()
+            })
+            .sum::<isize>()
+            .unsigned_abs();
+
+        (twice_area + 2 - path.len()) / 2
+    }
+
+    // Crossing the loop flips inside and outside; a tile on the loop counts
+    // as a crossing when it connects north.
+    fn inside(&self, on_loop: &[bool]) -> Vec<bool> {
+        let mut inside = vec![false; self.tiles.len()];
+        for y in 0..self.height {
+            let mut crossings = 0;
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                if on_loop[index] {
+                    if self.tiles[index].connects(Direction::North) {
+                        crossings += 1;
                     }
+                } else {
+                    inside[index] = crossings % 2 == 1;
                 }
             }
         }
+
+        inside
     }
 
-    in_count
+    // The loop in box-drawing characters, with every other tile marked
+    // `I` (inside) or `O` (outside).
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut on_loop = vec![false; self.tiles.len()];
+        for (x, y) in self.main_loop() {
+            on_loop[y * self.width + x] = true;
+        }
+        let inside = self.inside(&on_loop);
+        let mut output = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                output.push(if on_loop[index] {
+                    self.tiles[index].box_drawing()
+                } else if inside[index] {
+                    'I'
+                } else {
+                    'O'
+                });
+            }
+            output.push('\n');
+        }
+
+        output
+    }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
-pub fn part2(input: &str) -> usize {
-    part2_with_start_tile(input, Tile::SouthWest)
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
 }
 
-fn find_loop(location: Location, map: &Map) -> Vec<Location> {
-    find_path(location, &location, &map, vec![]).unwrap()
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
 }
 
-fn find_path(
-    location: Location,
-    target_location: &Location,
-    map: &Map,
-    mut path_so_far: Vec<Location>,
-) -> Option<Vec<Location>> {
-    use Tile::*;
-
-    path_so_far.push(location);
-
-    let (x, y) = location;
-    let next_locations: Vec<Location> = match map.get(&location).unwrap() {
-        Vertical => vec![(x, y - 1), (x, y + 1)],
-        Horizontal => vec![(x - 1, y), (x + 1, y)],
-        NorthEast => vec![(x, y - 1), (x + 1, y)],
-        NorthWest => vec![(x, y - 1), (x - 1, y)],
-        SouthEast => vec![(x, y + 1), (x + 1, y)],
-        SouthWest => vec![(x, y + 1), (x - 1, y)],
-        _ => vec![],
-    }
-    .iter()
-    .cloned()
-    .collect();
-
-    if next_locations.contains(target_location) && path_so_far.len() > 2 {
-        return Some(path_so_far);
-    }
-
-    next_locations
-        .iter()
-        .filter(|location| !path_so_far.contains(location))
-        .find_map(|next_location| {
-            find_path(*next_location, target_location, map, path_so_far.clone())
-        })
+/// # Errors
+///
+/// Fails on any maze [`Maze::parse`] rejects.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(Maze::parse(input)?.farthest_distance())
 }
 
-fn parse(input: &str) -> (Location, (usize, usize), Map) {
-    let mut map: HashMap<Location, Tile> = HashMap::new();
-    let mut start_location: Location = (0, 0);
-    let max_y = input.lines().count();
-    let max_x = input.lines().next().unwrap().chars().count();
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            use Tile::*;
-
-            let tile = match ch {
-                '|' => Vertical,
-                '-' => Horizontal,
-                'L' => NorthEast,
-                'J' => NorthWest,
-                '7' => SouthWest,
-                'F' => SouthEast,
-                '.' => Ground,
-                'S' => Start,
-                invalid => panic!("invalid tile type found: {:?}", invalid),
-            };
-
-            if tile == Start {
-                start_location = (x, y);
-            }
-
-            map.insert((x, y), tile);
-        }
-    }
-
-    (start_location, (max_x, max_y), map)
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(Maze::parse(input)?.enclosed_area())
 }
 
 #[cfg(test)]
@@ -211,26 +436,95 @@ L7JLJL-JLJLJL--JLJ.L"
 
     #[test]
     fn part1_example1() {
-        assert_eq!(part1_with_start_tile(input(), Tile::SouthEast), 4)
+        assert_eq!(part1(input()), 4)
     }
 
     #[test]
     fn part1_example2() {
-        assert_eq!(part1_with_start_tile(input2(), Tile::SouthEast), 8)
+        assert_eq!(part1(input2()), 8)
     }
 
     #[test]
     fn part2_example1() {
-        assert_eq!(part2_with_start_tile(input3(), Tile::SouthEast), 4)
+        assert_eq!(part2(input3()), 4)
     }
 
     #[test]
     fn part2_example2() {
-        assert_eq!(part2_with_start_tile(input4(), Tile::SouthEast), 8);
+        assert_eq!(part2(input4()), 8);
     }
 
     #[test]
     fn part2_example3() {
-        assert_eq!(part2_with_start_tile(input5(), Tile::SouthWest), 10);
+        assert_eq!(part2(input5()), 10);
+    }
+
+    #[test]
+    fn part2_nothing_enclosed() {
+        assert_eq!(part2("S7\nLJ"), 0);
+        assert_eq!(part2("S-7\nL-J"), 0);
+    }
+
+    #[test]
+    fn start_tile_inference() {
+        assert_eq!(Maze::parse(input()).unwrap().start_tile(), Tile::SouthEast);
+        assert_eq!(
+            Maze::parse(input2()).unwrap().start_tile(),
+            Tile::SouthEast
+        );
+        assert_eq!(
+            Maze::parse(input4()).unwrap().start_tile(),
+            Tile::SouthEast
+        );
+        assert_eq!(
+            Maze::parse(input5()).unwrap().start_tile(),
+            Tile::SouthWest
+        );
+
+        // Three neighbours point at `S`, but only one pair closes a loop.
+        let maze = Maze::parse("FS7\n|||\nL-J").unwrap();
+        assert_eq!(maze.start_tile(), Tile::Horizontal);
+    }
+
+    #[test]
+    fn invalid_mazes() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("S7\nLX"),
+            "day 10, line 2: column 2, expected one of `|-LJ7F.S`"
+        );
+        assert_eq!(
+            error("S7\nL"),
+            "day 10, line 2: column 1, expected 2 tiles"
+        );
+        assert_eq!(error("F7\nLJ"), "day 10: no start tile");
+        assert_eq!(error("S7\nLS"), "day 10: more than one start tile");
+        assert_eq!(error("S-\n.."), "day 10: the start tile isn't on a loop");
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            Maze::parse(input3()).unwrap().render(),
+            "\
+OOOOOOOOOOO
+O┌───────┐O
+O│┌─────┐│O
+O││OOOOO││O
+O││OOOOO││O
+O│└─┐O┌─┘│O
+O│II│O│II│O
+O└──┘O└──┘O
+OOOOOOOOOOO
+"
+        );
+
+        for input in [input3(), input4(), input5()] {
+            let maze = Maze::parse(input).unwrap();
+            let inside = maze.render().chars().filter(|&c| c == 'I').count();
+
+            assert_eq!(inside, maze.enclosed_area());
+        }
     }
 }
@@ -1,5 +1,9 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 11;
+
 type Galaxy = usize;
 type Location = (usize, usize);
 
@@ -81,9 +85,27 @@ impl Image {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let image = parse(input).expand(1);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the image is a rectangle of `#` and `.`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let image = parse(input)?.expand(1);
     let mut total = 0;
 
     for from in 1..=image.galaxy_count {
@@ -92,16 +114,21 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    total
+    Ok(total)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     part2_with_expansion_factor(input, 1000000 - 1)
 }
 
-fn part2_with_expansion_factor(input: &str, expansion_factor: usize) -> usize {
-    let image = parse(input).expand(expansion_factor);
+fn part2_with_expansion_factor(
+    input: &str,
+    expansion_factor: usize,
+) -> Result<usize, PuzzleError> {
+    let image = parse(input)?.expand(expansion_factor);
     let mut total = 0;
 
     for from in 1..=image.galaxy_count {
@@ -110,34 +137,53 @@ fn part2_with_expansion_factor(input: &str, expansion_factor: usize) -> usize {
         }
     }
 
-    total
+    Ok(total)
 }
 
-fn parse(input: &str) -> Image {
-    let max_x = input.lines().next().unwrap().chars().count();
-    let max_y = input.lines().count();
+fn parse(input: &str) -> Result<Image, PuzzleError> {
+    let lines = parsing::lines(input);
+    let max_x = lines.first().map_or(0, |line| line.text.chars().count());
+    let max_y = lines.len();
+
+    if max_x == 0 {
+        return Err(PuzzleError::new(DAY, "expected an image"));
+    }
 
     let mut next_galaxy_number = 1;
     let mut map: HashMap<Location, Galaxy> = HashMap::new();
     let mut reverse_map: HashMap<Galaxy, Location> = HashMap::new();
 
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            if ch == '#' {
-                map.insert((x, y), next_galaxy_number);
-                reverse_map.insert(next_galaxy_number, (x, y));
-                next_galaxy_number += 1;
+    for (y, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != max_x {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {max_x} pixels")),
+            ));
+        }
+
+        for (x, (index, ch)) in line.text.char_indices().enumerate() {
+            match ch {
+                '#' => {
+                    map.insert((x, y), next_galaxy_number);
+                    reverse_map.insert(next_galaxy_number, (x, y));
+                    next_galaxy_number += 1;
+                }
+                '.' => {}
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(
+                        line.error(&line.text[index..], "expected `#` or `.`"),
+                    ))
+                }
             }
         }
     }
 
-    Image {
+    Ok(Image {
         max_x,
         max_y,
         map,
         reverse_map,
         galaxy_count: next_galaxy_number - 1,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -165,11 +211,25 @@ mod tests {
 
     #[test]
     fn part2_example1() {
-        assert_eq!(part2_with_expansion_factor(input(), 9), 1030)
+        assert_eq!(part2_with_expansion_factor(input(), 9).unwrap(), 1030)
     }
 
     #[test]
     fn part2_example2() {
-        assert_eq!(part2_with_expansion_factor(input(), 99), 8410)
+        assert_eq!(part2_with_expansion_factor(input(), 99).unwrap(), 8410)
+    }
+
+    #[test]
+    fn invalid_images() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("#..\n.*.\n"),
+            "day 11, line 2: column 2, expected `#` or `.`"
+        );
+        assert_eq!(
+            error("#..\n..\n"),
+            "day 11, line 2: column 1, expected 3 pixels"
+        );
     }
 }
@@ -1,10 +1,34 @@
-#[derive(Clone, Debug, PartialEq)]
-enum Condition {
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Condition {
     Operational,
     Damaged,
     Unknown,
 }
 
+impl Condition {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '?' => Some(Condition::Unknown),
+            '.' => Some(Condition::Operational),
+            '#' => Some(Condition::Damaged),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Condition::Operational => '.',
+            Condition::Damaged => '#',
+            Condition::Unknown => '?',
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ConditionRecord {
     conditions: Vec<Condition>,
@@ -12,148 +36,259 @@ struct ConditionRecord {
 }
 
 impl ConditionRecord {
-    fn unfold(&mut self) {
+    fn unfold(&self) -> Self {
         let mut conditions_with_joiner = self.conditions.clone();
         conditions_with_joiner.push(Condition::Unknown);
 
-        self.conditions = conditions_with_joiner
-            .iter()
-            .cycle()
-            .take(self.conditions.len() * 5 + 4)
-            .cloned()
-            .collect();
-        self.counts = self
-            .counts
-            .iter()
-            .cycle()
-            .take(self.counts.len() * 5)
-            .cloned()
-            .collect();
+        Self {
+            conditions: conditions_with_joiner
+                .iter()
+                .cycle()
+                .take(self.conditions.len() * 5 + 4)
+                .copied()
+                .collect(),
+            counts: self
+                .counts
+                .iter()
+                .cycle()
+                .take(self.counts.len() * 5)
+                .copied()
+                .collect(),
+        }
     }
 
     fn possibilities(&self) -> usize {
-        self.unknown_patterns()
-            .iter()
-            .filter(|pattern| {
-                self.group_count_with_pattern(&pattern) == self.counts
-            })
-            .count()
+        arrangements(&self.conditions, &self.counts)
     }
+}
 
-    fn unknown_patterns(&self) -> Vec<Vec<usize>> {
-        let size = Self::unknown_count(&self.conditions);
+// How many ways the unknowns can be filled in so that the runs of damaged
+// cells are exactly `counts`, in order. Scans left to right keeping, for
+// every (groups completed, length of the current run) state, the number of
+// ways to reach it, so the work is O(cells * groups * longest group).
+#[must_use]
+pub fn arrangements(conditions: &[Condition], counts: &[usize]) -> usize {
+    let longest = counts.iter().copied().max().unwrap_or(0);
+    let mut ways = vec![vec![0; longest + 1]; counts.len() + 1];
+    ways[0][0] = 1;
 
-        (0..2_usize.pow(size as u32))
-            .map(|value| {
-                format!("{:0>width$b}", value, width = size)
-                    .chars()
-                    .map(|c| if c == '0' { 0 } else { 1 })
-                    .collect()
-            })
-            .collect()
+    for &condition in conditions {
+        let mut next = vec![vec![0; longest + 1]; counts.len() + 1];
+
+        for (group, runs) in ways.iter().enumerate() {
+            for (run, &count) in runs.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+
+                if condition != Condition::Damaged {
+                    if run == 0 {
+                        next[group][0] += count;
+                    } else if run == counts[group] {
+                        next[group + 1][0] += count;
+                    }
+                }
+
+                if condition != Condition::Operational
+                    && group < counts.len()
+                    && run < counts[group]
+                {
+                    next[group][run + 1] += count;
+                }
+            }
+        }
+
+        ways = next;
     }
 
-    fn group_count_with_pattern(&self, pattern: &Vec<usize>) -> Vec<usize> {
-        let mut pattern_iter = pattern.iter();
-
-        let conditions: Vec<Condition> = self
-            .conditions
-            .iter()
-            .map(|condition| match condition {
-                Condition::Operational => Condition::Operational,
-                Condition::Damaged => Condition::Damaged,
-                Condition::Unknown => match pattern_iter.next() {
-                    Some(1) => Condition::Damaged,
-                    Some(0) => Condition::Operational,
-                    _ => panic!("shouldn't get here"),
-                },
-            })
-            .collect();
+    let finished = ways[counts.len()][0];
+    let finishing = counts
+        .last()
+        .map_or(0, |&last| ways[counts.len() - 1][last]);
 
-        Self::group_count(&conditions)
+    finished + finishing
+}
+
+// Fixes every unknown cell that takes the same value in all arrangements.
+// Returns `None` when no arrangement fits, otherwise whether anything
+// changed.
+fn solve_line(line: &mut [Condition], counts: &[usize]) -> Option<bool> {
+    if arrangements(line, counts) == 0 {
+        return None;
     }
 
-    fn unknown_count(conditions: &[Condition]) -> usize {
-        conditions
-            .iter()
-            .filter(|c| *c == &Condition::Unknown)
-            .count()
+    let mut changed = false;
+    for index in 0..line.len() {
+        if line[index] != Condition::Unknown {
+            continue;
+        }
+
+        line[index] = Condition::Damaged;
+        let damaged = arrangements(line, counts);
+        line[index] = Condition::Operational;
+        let operational = arrangements(line, counts);
+
+        line[index] = match (damaged, operational) {
+            (0, _) => Condition::Operational,
+            (_, 0) => Condition::Damaged,
+            _ => Condition::Unknown,
+        };
+        changed |= line[index] != Condition::Unknown;
     }
 
-    fn group_count(conditions: &Vec<Condition>) -> Vec<usize> {
-        conditions
-            .split(|c| c == &Condition::Operational)
-            .map(|cs| cs.len())
-            .filter(|s| s > &0)
-            .collect()
+    Some(changed)
+}
+
+pub type Grid = Vec<Vec<Condition>>;
+
+#[must_use]
+pub fn render(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| row.iter().map(|c| c.to_char()).collect::<String>() + "\n")
+        .collect()
+}
+
+// A picture puzzle: each row and column lists the lengths of its runs of
+// filled (damaged) cells.
+#[derive(Clone, Debug)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    #[must_use]
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Self {
+        Self { rows, columns }
+    }
+
+    // Line solving until nothing changes, then guessing a cell when that
+    // stalls. Returns the first solution found.
+    #[must_use]
+    pub fn solve(&self) -> Option<Grid> {
+        let grid =
+            vec![vec![Condition::Unknown; self.columns.len()]; self.rows.len()];
+
+        self.search(grid)
     }
 
-    #[allow(dead_code)]
-    fn debug(conditions: &[Condition]) {
-        for condition in conditions {
-            let ch = match condition {
-                Condition::Operational => '.',
-                Condition::Damaged => '#',
-                Condition::Unknown => '?',
-            };
-            print!("{}", ch);
+    fn search(&self, mut grid: Grid) -> Option<Grid> {
+        self.propagate(&mut grid)?;
+
+        let Some((y, x)) = grid.iter().enumerate().find_map(|(y, row)| {
+            row.iter()
+                .position(|&c| c == Condition::Unknown)
+                .map(|x| (y, x))
+        }) else {
+            return Some(grid);
+        };
+
+        [Condition::Damaged, Condition::Operational]
+            .into_iter()
+            .find_map(|guess| {
+                let mut grid = grid.clone();
+                grid[y][x] = guess;
+                self.search(grid)
+            })
+    }
+
+    fn propagate(&self, grid: &mut Grid) -> Option<()> {
+        loop {
+            let mut changed = false;
+
+            for (row, counts) in grid.iter_mut().zip(&self.rows) {
+                changed |= solve_line(row, counts)?;
+            }
+
+            for (x, counts) in self.columns.iter().enumerate() {
+                let mut column: Vec<Condition> =
+                    grid.iter().map(|row| row[x]).collect();
+                changed |= solve_line(&mut column, counts)?;
+                for (row, condition) in grid.iter_mut().zip(column) {
+                    row[x] = condition;
+                }
+            }
+
+            if !changed {
+                return Some(());
+            }
         }
-        println!("");
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let condition_records = parse(input);
-
-    condition_records
-        .iter()
-        .map(|condition_record| condition_record.possibilities())
-        .sum()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let mut condition_records = parse(input);
-
-    condition_records
-        .iter_mut()
-        .map(|condition_record| {
-            dbg!(&condition_record);
-            condition_record.unfold();
-            dbg!(&condition_record);
-            condition_record.possibilities()
-        })
-        .sum()
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a row of `?`, `.` and `#` followed by
+/// comma-separated counts.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let condition_records = parse(input)?;
+
+    Ok(condition_records
+        .iter()
+        .map(ConditionRecord::possibilities)
+        .sum())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let condition_records = parse(input)?;
+
+    Ok(condition_records
+        .iter()
+        .map(|condition_record| condition_record.unfold().possibilities())
+        .sum())
 }
 
-fn parse(input: &str) -> Vec<ConditionRecord> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<ConditionRecord>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
             let (conditions_string, counts_string) =
-                line.split_once(' ').unwrap();
+                line.split_once(line.text, " ")?;
             let conditions = conditions_string
-                .chars()
-                .map(|ch| match ch {
-                    '?' => Condition::Unknown,
-                    '.' => Condition::Operational,
-                    '#' => Condition::Damaged,
-                    invalid => panic!("invalid condition: {:?}", invalid),
+                .char_indices()
+                .map(|(index, ch)| {
+                    Condition::from_char(ch).ok_or_else(|| {
+                        line.error(
+                            &conditions_string[index..],
+                            "expected `?`, `.` or `#`",
+                        )
+                    })
                 })
-                .collect();
+                .collect::<parsing::Result<_>>()?;
             let counts: Vec<usize> = counts_string
                 .split(',')
-                .map(|c| c.parse().unwrap())
-                .collect();
-            ConditionRecord { conditions, counts }
+                .map(|c| line.parse(c))
+                .collect::<parsing::Result<_>>()?;
+
+            Ok(ConditionRecord { conditions, counts })
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Rng;
 
     fn edge1() -> &'static str {
         "?????#?#.???.?.?###? 1,4,1,5"
@@ -173,6 +308,13 @@ mod tests {
 ?###???????? 3,2,1"
     }
 
+    fn runs(line: &[Condition]) -> Vec<usize> {
+        line.split(|c| *c == Condition::Operational)
+            .map(<[Condition]>::len)
+            .filter(|&len| len > 0)
+            .collect()
+    }
+
     #[test]
     fn part1_example() {
         assert_eq!(part1(edge2()), 1);
@@ -182,6 +324,95 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        //        assert_eq!(part2(input()), 525152)
+        assert_eq!(part2(input()), 525152)
+    }
+
+    #[test]
+    fn invalid_records() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("???.### 1,1,3\n??x 1"),
+            "day 12, line 2: column 3, expected `?`, `.` or `#`"
+        );
+        assert_eq!(
+            error("???.### 1,,3"),
+            "day 12, line 1: column 11, invalid value ``"
+        );
+        assert_eq!(error("???.###"), "day 12, line 1: column 1, expected ` `");
+    }
+
+    #[test]
+    fn arrangements_match_brute_force() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..300 {
+            let line: Vec<Condition> = (0..=rng.below(12))
+                .map(|_| match rng.below(3) {
+                    0 => Condition::Operational,
+                    1 => Condition::Damaged,
+                    _ => Condition::Unknown,
+                })
+                .collect();
+            let counts: Vec<usize> =
+                (0..rng.below(4)).map(|_| 1 + rng.below(3)).collect();
+
+            let unknowns: Vec<usize> = (0..line.len())
+                .filter(|&i| line[i] == Condition::Unknown)
+                .collect();
+            let brute_force = (0..1_usize << unknowns.len())
+                .filter(|mask| {
+                    let mut filled = line.clone();
+                    for (bit, &i) in unknowns.iter().enumerate() {
+                        filled[i] = if mask >> bit & 1 == 1 {
+                            Condition::Damaged
+                        } else {
+                            Condition::Operational
+                        };
+                    }
+                    runs(&filled) == counts
+                })
+                .count();
+
+            assert_eq!(arrangements(&line, &counts), brute_force);
+        }
+    }
+
+    #[test]
+    fn nonogram() {
+        let picture = "\
+.###.
+##.##
+#####
+#...#
+.###.
+";
+        let grid: Grid = picture
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| Condition::from_char(ch).unwrap())
+                    .collect()
+            })
+            .collect();
+        let rows = grid.iter().map(|row| runs(row)).collect();
+        let columns = (0..5)
+            .map(|x| runs(&grid.iter().map(|row| row[x]).collect::<Vec<_>>()))
+            .collect();
+
+        let solution = Nonogram::new(rows, columns).solve().unwrap();
+        assert_eq!(render(&solution), picture);
+
+        let impossible = Nonogram::new(vec![vec![2], vec![]], vec![vec![1]; 3]);
+        assert_eq!(impossible.solve(), None);
+    }
+
+    #[test]
+    fn nonogram_needing_a_guess() {
+        // Two diagonals fit these clues; line solving alone fixes nothing.
+        let puzzle = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1]; 2]);
+        let solution = puzzle.solve().unwrap();
+
+        assert_eq!(render(&solution), "#.\n.#\n");
     }
 }
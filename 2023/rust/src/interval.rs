@@ -0,0 +1,275 @@
+use std::ops::Range;
+
+// A set of values stored as sorted, disjoint, non-adjacent half-open ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<usize>>,
+}
+
+impl IntervalSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: usize) -> bool {
+        let index = self.ranges.partition_point(|range| range.end <= value);
+
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    #[must_use]
+    pub fn coverage(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Option<usize> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Option<usize> {
+        self.ranges.last().map(|range| range.end - 1)
+    }
+
+    pub fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+
+        let merged = if first < last {
+            self.ranges[first].start.min(range.start)
+                ..self.ranges[last - 1].end.max(range.end)
+        } else {
+            range
+        };
+
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+
+        for range in &other.ranges {
+            result.insert(range.clone());
+        }
+
+        result
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = &self.ranges[i];
+            let b = &other.ranges[j];
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+
+        for range in &self.ranges {
+            let mut start = range.start;
+
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                let cut = &other.ranges[k];
+
+                if start < cut.start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        Self { ranges }
+    }
+}
+
+impl From<Range<usize>> for IntervalSet {
+    fn from(range: Range<usize>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl FromIterator<Range<usize>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<usize>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Range<usize>> for IntervalSet {
+    fn extend<I: IntoIterator<Item = Range<usize>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+// A piecewise-linear map: each source range is shifted onto a destination
+// start, and values outside every source range map to themselves. When
+// source ranges overlap, the piece added first wins.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<(Range<usize>, usize)>,
+}
+
+impl PiecewiseMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, source: Range<usize>, destination_start: usize) {
+        self.pieces.push((source, destination_start));
+    }
+
+    #[must_use]
+    pub fn pieces(&self) -> &[(Range<usize>, usize)] {
+        &self.pieces
+    }
+
+    #[must_use]
+    pub fn apply(&self, value: usize) -> usize {
+        for (source, destination_start) in &self.pieces {
+            if source.contains(&value) {
+                return destination_start + (value - source.start);
+            }
+        }
+
+        value
+    }
+
+    #[must_use]
+    pub fn map(&self, set: &IntervalSet) -> IntervalSet {
+        let mut remaining = set.clone();
+        let mut result = IntervalSet::new();
+
+        for (source, destination_start) in &self.pieces {
+            let source_set = IntervalSet::from(source.clone());
+
+            for range in remaining.intersection(&source_set).ranges() {
+                let start = destination_start + (range.start - source.start);
+                result.insert(start..start + (range.end - range.start));
+            }
+            remaining = remaining.difference(&source_set);
+        }
+
+        result.union(&remaining)
+    }
+}
+
+impl FromIterator<(Range<usize>, usize)> for PiecewiseMap {
+    fn from_iter<I: IntoIterator<Item = (Range<usize>, usize)>>(
+        iter: I,
+    ) -> Self {
+        Self {
+            pieces: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<usize>]) -> IntervalSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_adjacent_ranges() {
+        let intervals = set(&[10..15, 1..3, 3..5, 12..20, 30..30, 25..26]);
+
+        assert_eq!(intervals.ranges(), &[1..5, 10..20, 25..26]);
+        assert_eq!(intervals.coverage(), 15);
+        assert_eq!(intervals.min(), Some(1));
+        assert_eq!(intervals.max(), Some(25));
+        assert!(intervals.contains(19));
+        assert!(!intervals.contains(20));
+        assert!(!intervals.contains(0));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 40..50]);
+
+        assert_eq!(a.union(&b).ranges(), &[0..30, 40..50]);
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..30]);
+        assert_eq!(b.difference(&a).ranges(), &[10..20, 40..50]);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn map_through_pieces() {
+        let map: PiecewiseMap =
+            [(98..100, 50), (50..98, 52)].into_iter().collect();
+
+        assert_eq!(map.apply(79), 81);
+        assert_eq!(map.apply(99), 51);
+        assert_eq!(map.apply(10), 10);
+        assert_eq!(
+            map.map(&set(&[45..55, 97..101])).ranges(),
+            &[45..57, 99..101]
+        );
+        assert_eq!(
+            map.map(&IntervalSet::from(79..93)),
+            IntervalSet::from(81..95)
+        );
+    }
+}
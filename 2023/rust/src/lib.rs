@@ -13,3 +13,9 @@ pub mod day10;
 pub mod day11;
 pub mod day12;
 pub mod day13;
+pub mod interval;
+pub mod parsing;
+pub mod puzzle;
+
+#[cfg(test)]
+mod testing;
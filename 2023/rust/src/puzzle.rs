@@ -0,0 +1,84 @@
+// Mirrored as src/puzzle.rs in each year's crate: the years share no library,
+// so change every copy together.
+
+use crate::parsing::ParseError;
+use std::fmt;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PuzzleError {
+    pub day: u8,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl PuzzleError {
+    #[must_use]
+    pub fn new(day: u8, message: impl Into<String>) -> Self {
+        Self {
+            day,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    pub fn on_day(day: u8) -> impl Fn(ParseError) -> Self {
+        move |error| Self {
+            day,
+            line: Some(error.line),
+            message: format!("column {}, {}", error.column, error.message),
+        }
+    }
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "day {:02}, line {}: ", self.day, line)?,
+            None => write!(f, "day {:02}: ", self.day)?,
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+// Shown by `unwrap()` in the infallible `part1`/`part2` wrappers, so keep it
+// as readable as `Display`.
+impl fmt::Debug for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let error = PuzzleError::on_day(4)(ParseError {
+            line: 3,
+            column: 5,
+            message: "expected `-`".into(),
+        });
+
+        assert_eq!(error.to_string(), "day 04, line 3: column 5, expected `-`");
+        assert_eq!(
+            PuzzleError::new(12, "no path to the summit").to_string(),
+            "day 12: no path to the summit"
+        );
+        assert_eq!(
+            PuzzleError::new(19, "no blank line").at_line(7).to_string(),
+            "day 19, line 7: no blank line"
+        );
+    }
+}
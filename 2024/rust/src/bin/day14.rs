@@ -1,10 +1,15 @@
 use aoc2024::day14::*;
+use aoc2024::visualize;
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input: String = std::fs::read_to_string("../inputs/14")?;
 
-    println!("part 1: {}", part1(&input));
-    println!("part 2: {}", part2(&input));
+    println!("part 1: {}", try_part1(&input)?);
+    println!("part 2: {}", try_part2(&input)?);
+
+    if visualize::requested() {
+        visualize(&input)?;
+    }
 
     Ok(())
 }
@@ -1,10 +1,20 @@
 use aoc2024::day24::*;
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input: String = std::fs::read_to_string("../inputs/24")?;
 
-    println!("part 1: {}", part1(&input));
-    println!("part 2: {}", part2(&input));
+    // Print a diagram of the gates instead, e.g. to regenerate 24.mmd.
+    if std::env::args().any(|arg| arg == "--mermaid") {
+        print!("{}", to_mermaid(&input)?);
+        return Ok(());
+    }
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", to_dot(&input)?);
+        return Ok(());
+    }
+
+    println!("part 1: {}", try_part1(&input)?);
+    println!("part 2: {}", try_part2(&input)?);
 
     Ok(())
 }
@@ -1,11 +1,30 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 1;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let pairs = input
-        .lines()
-        .map(parse_ints)
-        .collect::<Vec<(usize, usize)>>();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a pair of location IDs.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let (mut lefts, mut rights): (Vec<usize>, Vec<usize>) =
         pairs.into_iter().unzip();
 
@@ -14,27 +33,26 @@ pub fn part1(input: &str) -> usize {
 
     let sorted_pairs = lefts.into_iter().zip(rights);
 
-    sorted_pairs.into_iter().map(|(a, b)| a.abs_diff(b)).sum()
+    Ok(sorted_pairs.into_iter().map(|(a, b)| a.abs_diff(b)).sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let pairs = input
-        .lines()
-        .map(parse_ints)
-        .collect::<Vec<(usize, usize)>>();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let pairs = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let (lefts, rights): (Vec<usize>, Vec<usize>) = pairs.into_iter().unzip();
 
     let lefts_with_counts = unique_values_with_counts(lefts);
     let rights_with_counts = unique_values_with_counts(rights);
 
-    lefts_with_counts
+    Ok(lefts_with_counts
         .into_iter()
         .map(|(value, left_count)| match rights_with_counts.get(&value) {
             Some(right_count) => value * left_count * right_count,
             None => 0,
         })
-        .sum()
+        .sum())
 }
 
 fn unique_values_with_counts(values: Vec<usize>) -> HashMap<usize, usize> {
@@ -47,15 +65,14 @@ fn unique_values_with_counts(values: Vec<usize>) -> HashMap<usize, usize> {
     map
 }
 
-fn parse_ints(line: &str) -> (usize, usize) {
-    let ints = line
-        .splitn(2, ' ')
-        .map(|part| part.trim().parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
+fn parse(input: &str) -> parsing::Result<Vec<(usize, usize)>> {
+    parsing::lines(input).iter().map(parse_pair).collect()
+}
 
-    match ints.as_slice() {
-        [a, b] => (*a, *b),
-        _ => panic!("invalid input: {line}"),
+fn parse_pair(line: &SourceLine) -> parsing::Result<(usize, usize)> {
+    match line.integers()?.as_slice() {
+        [a, b] => Ok((*a, *b)),
+        _ => Err(line.error(line.text, "expected two location IDs")),
     }
 }
 
@@ -82,4 +99,12 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 31)
     }
+
+    #[test]
+    fn invalid_pairs() {
+        assert_eq!(
+            try_part1("3   4\n4\n").unwrap_err().to_string(),
+            "day 01, line 2: column 1, expected two location IDs"
+        );
+    }
 }
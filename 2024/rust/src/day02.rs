@@ -1,21 +1,46 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 2;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let reports = parse(input);
-
-    reports.into_iter().filter(is_safe).count()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let reports = parse(input);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a report of levels.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let reports = parse(input)?;
+
+    Ok(reports.into_iter().filter(is_safe).count())
+}
 
-    reports
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let reports = parse(input)?;
+
+    Ok(reports
         .into_iter()
         .filter(|report| {
             is_safe(report)
                 || permutations_holding_one_out(report).iter().any(is_safe)
         })
-        .count()
+        .count())
 }
 
 fn permutations_holding_one_out(report: &[usize]) -> Vec<Vec<usize>> {
@@ -34,9 +59,12 @@ fn is_safe(report: &Vec<usize>) -> bool {
     all_increasing_or_decreasing(&derivative) && within_range(&derivative)
 }
 
+// A single level has no neighbours to disagree with.
 fn all_increasing_or_decreasing(values: &[isize]) -> bool {
-    let min = *values.iter().min().unwrap();
-    let max = *values.iter().max().unwrap();
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max())
+    else {
+        return true;
+    };
 
     (min > 0isize && max > 0isize) || (min < 0isize && max < 0isize)
 }
@@ -58,15 +86,21 @@ fn derivative(values: &[usize]) -> Vec<isize> {
         .collect()
 }
 
-fn parse(input: &str) -> Vec<Vec<usize>> {
-    input
-        .lines()
+fn parse(input: &str) -> Result<Vec<Vec<usize>>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
         .map(|line| {
-            line.split(' ')
-                .map(|ch| ch.parse::<usize>().unwrap())
+            if line.text.trim().is_empty() {
+                return Err(line.error(line.text, "expected a report"));
+            }
+
+            line.text
+                .split_whitespace()
+                .map(|level| line.parse(level))
                 .collect()
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -97,4 +131,18 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 9)
     }
+
+    #[test]
+    fn short_and_invalid_reports() {
+        assert_eq!(part1("7\n1 5\n"), 1);
+        assert_eq!(part2("7\n1 5\n"), 2);
+        assert_eq!(
+            try_part1("7 6 4\n\n").unwrap_err().to_string(),
+            "day 02, line 2: column 1, expected a report"
+        );
+        assert_eq!(
+            try_part1("7 6 x\n").unwrap_err().to_string(),
+            "day 02, line 1: column 5, invalid value `x`"
+        );
+    }
 }
@@ -1,4 +1,7 @@
-use regex::RegexBuilder;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 3;
 
 #[derive(Debug)]
 enum Operation {
@@ -7,16 +10,38 @@ enum Operation {
     Multiply(usize, usize),
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let operations: Vec<Operation> = parse(input);
-    evaluate(operations)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let operations: Vec<Operation> = parse(input);
-    evaluate(operations)
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a `mul` operand doesn't fit in a `usize`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let operations: Vec<Operation> =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
+    Ok(evaluate(operations))
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let operations: Vec<Operation> =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
+    Ok(evaluate(operations))
 }
 
 fn evaluate(operations: Vec<Operation>) -> usize {
@@ -38,25 +63,67 @@ fn evaluate(operations: Vec<Operation>) -> usize {
     total
 }
 
-fn parse(input: &str) -> Vec<Operation> {
-    let regex = RegexBuilder::new(r"(do[n't]*)(\(\))|mul\((\d+),(\d+)\)")
-        .multi_line(true)
-        .build()
-        .unwrap();
-    regex
-        .captures_iter(input)
-        .map(|capture| match capture.extract() {
-            (_, ["do", _]) => Operation::Enable,
-            (_, ["don't", _]) => Operation::Disable,
-            (_, [first, second]) => {
-                Operation::Multiply(parse_int(first), parse_int(second))
+// Scans the corrupted memory for `do()`, `don't()` and `mul(a,b)`, skipping
+// everything else.
+fn parse(input: &str) -> parsing::Result<Vec<Operation>> {
+    let mut operations = vec![];
+
+    for line in parsing::lines(input) {
+        let mut rest = line.text;
+
+        while let Some(index) = rest.find(['d', 'm']) {
+            rest = &rest[index..];
+
+            if let Some(after) = rest.strip_prefix("do()") {
+                operations.push(Operation::Enable);
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("don't()") {
+                operations.push(Operation::Disable);
+                rest = after;
+            } else if let Some((operation, after)) = multiply(&line, rest)? {
+                operations.push(operation);
+                rest = after;
+            } else {
+                rest = &rest[1..];
             }
-        })
-        .collect()
+        }
+    }
+
+    Ok(operations)
 }
 
-fn parse_int(input: &str) -> usize {
-    input.parse::<usize>().unwrap()
+fn multiply<'a>(
+    line: &SourceLine<'a>,
+    text: &'a str,
+) -> parsing::Result<Option<(Operation, &'a str)>> {
+    let Some(rest) = text.strip_prefix("mul(") else {
+        return Ok(None);
+    };
+    let (first, rest) = digits(rest);
+    let Some(rest) = rest.strip_prefix(',') else {
+        return Ok(None);
+    };
+    let (second, rest) = digits(rest);
+    let Some(rest) = rest.strip_prefix(')') else {
+        return Ok(None);
+    };
+
+    if first.is_empty() || second.is_empty() {
+        return Ok(None);
+    }
+
+    let operation =
+        Operation::Multiply(line.parse(first)?, line.parse(second)?);
+
+    Ok(Some((operation, rest)))
+}
+
+fn digits(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(text.len());
+
+    text.split_at(end)
 }
 
 #[cfg(test)]
@@ -79,4 +146,23 @@ xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)
         assert_eq!(part2("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"), 48);
         assert_eq!(part2("don't()xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)\nundo()?mul(8,5))"), 40);
     }
+
+    #[test]
+    fn parse_skips_incomplete_instructions() {
+        let operations = parse("mul(1,2mul(,3)mul(4,5)dont()mul(6,7").unwrap();
+
+        assert_eq!(operations.len(), 1);
+        assert_eq!(
+            parse("mul(2,99999999999999999999999)")
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 7: invalid value `99999999999999999999999`"
+        );
+        assert_eq!(
+            try_part1("mul(1,2)\nmul(99999999999999999999999,2)")
+                .unwrap_err()
+                .to_string(),
+            "day 03, line 2: column 5, invalid value `99999999999999999999999`"
+        );
+    }
 }
@@ -1,7 +1,29 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 4;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let chars: Vec<Vec<char>> =
-        input.lines().map(|line| line.chars().collect()).collect();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the word search is a rectangle of letters.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let chars: Vec<Vec<char>> = parse(input)?;
     let mut count: usize = 0;
 
     for row in 0..chars.len() {
@@ -21,13 +43,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let chars: Vec<Vec<char>> =
-        input.lines().map(|line| line.chars().collect()).collect();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let chars: Vec<Vec<char>> = parse(input)?;
     let mut count: usize = 0;
 
     for row in 0..chars.len() {
@@ -39,7 +62,24 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
+}
+
+fn parse(input: &str) -> Result<Vec<Vec<char>>, PuzzleError> {
+    let lines = parsing::lines(input);
+    let width = lines.first().map_or(0, |line| line.text.chars().count());
+
+    lines
+        .iter()
+        .map(|line| {
+            if line.text.chars().count() == width {
+                Ok(line.text.chars().collect())
+            } else {
+                Err(line.error(line.text, format!("expected {width} letters")))
+            }
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 fn x_mas(chars: &[Vec<char>], row: usize, col: usize) -> bool {
@@ -148,4 +188,12 @@ MXMXAXMASX"
     fn part2_example() {
         assert_eq!(part2(input()), 9)
     }
+
+    #[test]
+    fn ragged_word_search() {
+        assert_eq!(
+            try_part2("MMS\nMA\nMMS").unwrap_err().to_string(),
+            "day 04, line 2: column 1, expected 3 letters"
+        );
+    }
 }
@@ -1,21 +1,49 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 5;
+
 type Rules = HashMap<usize, Vec<usize>>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (_must_precede, cant_succeed, updates) = parse(input);
+    try_part1(input).unwrap()
+}
 
-    updates
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is `a|b` rules, a blank line, then updates of
+/// comma-separated pages.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (_must_precede, cant_succeed, updates) =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
+
+    Ok(updates
         .into_iter()
         .filter(|update| is_valid(update, &cant_succeed))
         .map(|update| update[update.len() / 2])
-        .sum()
+        .sum())
 }
 
+/// # Panics
+///
+/// If the rules and updates aren't separated by a blank line, or a page
+/// isn't a number.
 #[must_use]
 pub fn part2_new(input: &str) -> usize {
-    let (must_precede, cant_succeed, updates) = parse(input);
+    let (must_precede, cant_succeed, updates) = parse(input).unwrap();
     //    for (key, values) in &must_precede {
     //        dbg!((key, values.len()));
     //    }
@@ -50,17 +78,23 @@ fn index(value: usize, ordering: &[usize]) -> Option<usize> {
     ordering.iter().position(|v| *v == value)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (must_precede, cant_succeed, updates) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the rules leave no
+/// place for a page of an update.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (must_precede, cant_succeed, updates) =
+        parse(input).map_err(PuzzleError::on_day(DAY))?;
 
     updates
         .into_iter()
         .filter(|update| !is_valid(update, &cant_succeed))
         .map(|update| {
             let corrected_update =
-                correct(&update, &cant_succeed, &must_precede);
-            corrected_update[corrected_update.len() / 2]
+                correct(&update, &cant_succeed, &must_precede).ok_or_else(
+                    || PuzzleError::new(DAY, "an update can't be reordered"),
+                )?;
+            Ok(corrected_update[corrected_update.len() / 2])
         })
         .sum()
 }
@@ -98,24 +132,26 @@ fn order(must_precede: &Rules, cant_succeed: &Rules) -> Vec<usize> {
     ordering
 }
 
+// Inserts each page at the first place the rules allow, or gives up when
+// there's none.
 fn correct(
     update: &Vec<usize>,
     _must_precede: &Rules,
     cant_succeed: &Rules,
-) -> Vec<usize> {
+) -> Option<Vec<usize>> {
     let mut corrected: Vec<usize> = Vec::new();
 
     for page in update {
-        for index in 0..=corrected.len() {
+        let index = (0..=corrected.len()).find(|&index| {
             corrected.insert(index, *page);
-            if is_valid(&corrected, cant_succeed) {
-                break;
-            }
+            let valid = is_valid(&corrected, cant_succeed);
             corrected.remove(index);
-        }
+            valid
+        })?;
+        corrected.insert(index, *page);
     }
 
-    corrected
+    Some(corrected)
 }
 
 fn is_valid(update: &Vec<usize>, rules: &Rules) -> bool {
@@ -133,15 +169,22 @@ fn is_valid(update: &Vec<usize>, rules: &Rules) -> bool {
     true
 }
 
-fn parse(input: &str) -> (Rules, Rules, Vec<Vec<usize>>) {
-    let (rule_strings, update_strings) = input.split_once("\n\n").unwrap();
+fn parse(input: &str) -> parsing::Result<(Rules, Rules, Vec<Vec<usize>>)> {
+    let blocks = parsing::blocks(input);
+    let [rule_lines, update_lines] = &blocks[..] else {
+        return Err(parsing::ParseError {
+            line: 1,
+            column: 1,
+            message: "expected a blank line after the rules".into(),
+        });
+    };
     let mut must_precede: Rules = HashMap::new();
     let mut cant_succeed: Rules = HashMap::new();
 
-    for line in rule_strings.lines() {
-        let (left_string, right_string) = line.split_once('|').unwrap();
-        let left = parse_usize(left_string);
-        let right = parse_usize(right_string);
+    for line in rule_lines {
+        let (left_string, right_string) = line.split_once(line.text, "|")?;
+        let left = line.parse(left_string)?;
+        let right = line.parse(right_string)?;
 
         must_precede
             .entry(left)
@@ -154,16 +197,16 @@ fn parse(input: &str) -> (Rules, Rules, Vec<Vec<usize>>) {
             .or_insert(vec![left]);
     }
 
-    let updates: Vec<Vec<usize>> = update_strings
-        .lines()
-        .map(|line| line.split(',').map(parse_usize).collect())
-        .collect();
+    let updates = update_lines
+        .iter()
+        .map(parse_update)
+        .collect::<parsing::Result<_>>()?;
 
-    (must_precede, cant_succeed, updates)
+    Ok((must_precede, cant_succeed, updates))
 }
 
-fn parse_usize(s: &str) -> usize {
-    s.parse::<usize>().unwrap()
+fn parse_update(line: &SourceLine) -> parsing::Result<Vec<usize>> {
+    line.text.split(',').map(|page| line.parse(page)).collect()
 }
 
 #[cfg(test)]
@@ -204,7 +247,7 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        let (_must_precede, cant_succeed, _updates) = parse(input());
+        let (_must_precede, cant_succeed, _updates) = parse(input()).unwrap();
 
         assert_eq!(is_valid(&vec![75, 47, 61, 53, 29], &cant_succeed), true);
         assert_eq!(is_valid(&vec![97, 61, 53, 29, 13], &cant_succeed), true);
@@ -218,26 +261,38 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let (must_precede, cant_succeed, _updates) = parse(input());
+        let (must_precede, cant_succeed, _updates) = parse(input()).unwrap();
 
         assert_eq!(
             correct(&vec![75, 97, 47, 61, 53], &must_precede, &cant_succeed),
-            vec![97, 75, 47, 61, 53]
+            Some(vec![97, 75, 47, 61, 53])
         );
         assert_eq!(
             correct(&vec![61, 13, 29], &must_precede, &cant_succeed),
-            vec![61, 29, 13]
+            Some(vec![61, 29, 13])
         );
         assert_eq!(
             correct(&vec![97, 13, 75, 29, 47], &must_precede, &cant_succeed),
-            vec![97, 75, 47, 29, 13]
+            Some(vec![97, 75, 47, 29, 13])
         );
         assert_eq!(part2(input()), 123)
     }
 
+    #[test]
+    fn invalid_input() {
+        assert_eq!(
+            try_part1("47|53\n\n75,4x,61").unwrap_err().to_string(),
+            "day 05, line 3: column 4, invalid value `4x`"
+        );
+        assert_eq!(
+            try_part2("1|2\n2|1\n\n2,1").unwrap_err().to_string(),
+            "day 05: an update can't be reordered"
+        );
+    }
+
     #[test]
     fn order_example() {
-        let (must_precede, cant_succeed, _updates) = parse(input());
+        let (must_precede, cant_succeed, _updates) = parse(input()).unwrap();
         assert_eq!(
             order(&must_precede, &cant_succeed),
             vec![97, 75, 47, 61, 53, 29, 13]
@@ -246,7 +301,7 @@ mod tests {
 
     #[test]
     fn correct_with_ordering_example() {
-        let (must_precede, cant_succeed, _updates) = parse(input());
+        let (must_precede, cant_succeed, _updates) = parse(input()).unwrap();
         let ordering = order(&must_precede, &cant_succeed);
 
         let test_cases: Vec<Vec<usize>> = vec![
@@ -257,7 +312,7 @@ mod tests {
 
         for test_case in test_cases {
             assert_eq!(
-                correct_with_ordering(&test_case, &ordering),
+                Some(correct_with_ordering(&test_case, &ordering)),
                 correct(&test_case, &must_precede, &cant_succeed)
             );
         }
@@ -1,8 +1,14 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use crate::visualize::{self, Canvas, GRAY, GREEN, YELLOW};
 use std::{
     collections::{HashMap, HashSet},
+    error::Error,
     ops::Range,
 };
 
+const DAY: u8 = 6;
+
 type Position = (usize, usize);
 
 #[derive(PartialEq, Clone)]
@@ -13,16 +19,32 @@ enum Direction {
     West,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (bounds, starting_position, obstacles) = parse(input);
+    try_part1(input).unwrap()
+}
 
-    if let Some(positions) = walk(&bounds, starting_position, &obstacles) {
-        //        print_map(&bounds, &obstacles, &positions);
-        positions.len()
-    } else {
-        0
-    }
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the map is a rectangle of `.` and `#` with a single `^`,
+/// or when the guard walks in a loop.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (bounds, starting_position, obstacles) = parse(input)?;
+
+    walk(&bounds, starting_position, &obstacles)
+        .map(|positions| positions.len())
+        .ok_or_else(|| PuzzleError::new(DAY, "the guard walks in a loop"))
 }
 
 fn walk(
@@ -57,24 +79,39 @@ fn walk(
     Some(positions)
 }
 
-#[allow(dead_code)]
-fn print_map(
+fn render(
     bounds: &(Range<usize>, Range<usize>),
+    starting_position: Position,
     obstacles: &[Position],
     positions: &HashSet<Position>,
-) {
-    for row in bounds.0.start..bounds.0.end {
-        for col in bounds.1.start..bounds.1.end {
-            if obstacles.contains(&(row, col)) {
-                print!("#");
-            } else if positions.contains(&(row, col)) {
-                print!("X");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
+) -> Canvas {
+    // Positions are 1-based so the walk can step off the top-left edge.
+    let cell = |&(row, col): &Position| (col - 1, row - 1);
+    let mut canvas = Canvas::new(bounds.1.len(), bounds.0.len(), 6);
+
+    canvas.plot(positions.iter().map(cell), YELLOW);
+    canvas.plot(obstacles.iter().map(cell), GRAY);
+    let (x, y) = cell(&starting_position);
+    canvas.fill(x, y, GREEN);
+
+    canvas
+}
+
+/// Saves the guard's patrol route.
+///
+/// # Errors
+///
+/// Fails on input [`try_part1`] can't parse, or when an image can't be
+/// written.
+pub fn visualize(input: &str) -> Result<(), Box<dyn Error>> {
+    let (bounds, starting_position, obstacles) = parse(input)?;
+    let positions =
+        walk(&bounds, starting_position, &obstacles).unwrap_or_default();
+
+    render(&bounds, starting_position, &obstacles, &positions)
+        .save_png(&visualize::output_path(6, "patrol.png")?)?;
+
+    Ok(())
 }
 
 fn turn_right(direction: &Direction) -> Direction {
@@ -95,9 +132,11 @@ fn step(direction: &Direction, position: &Position) -> Position {
     }
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (bounds, starting_position, obstacles) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`] apart from a looping guard.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (bounds, starting_position, obstacles) = parse(input)?;
     let mut updated_obstacles: Vec<Position>;
     let mut loops: usize = 0;
 
@@ -116,29 +155,45 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    loops
+    Ok(loops)
 }
 
-fn parse(
-    input: &str,
-) -> ((Range<usize>, Range<usize>), Position, Vec<Position>) {
+type Lab = ((Range<usize>, Range<usize>), Position, Vec<Position>);
+
+fn parse(input: &str) -> Result<Lab, PuzzleError> {
     let mut obstacles: Vec<Position> = Vec::new();
-    let mut starting_position: Position = (1, 1);
-    let rows = input.lines().count();
-    let mut cols: usize = 1;
+    let mut starting_positions: Vec<Position> = Vec::new();
+    let lines = parsing::lines(input);
+    let rows = lines.len();
+    let cols = lines.first().map_or(0, |line| line.text.chars().count());
+
+    for (row, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != cols {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {cols} positions")),
+            ));
+        }
 
-    for (row, line) in input.lines().enumerate() {
-        cols = line.chars().count();
-        for (col, char) in line.chars().enumerate() {
+        for (col, (index, char)) in line.text.char_indices().enumerate() {
             match char {
                 '#' => obstacles.push((row + 1, col + 1)),
-                '^' => starting_position = (row + 1, col + 1),
-                _ => {}
+                '^' => starting_positions.push((row + 1, col + 1)),
+                '.' => {}
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `.`, `#` or `^`",
+                    )))
+                }
             }
         }
     }
 
-    ((1..rows + 1, 1..cols + 1), starting_position, obstacles)
+    let [starting_position] = starting_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single guard `^`"));
+    };
+
+    Ok(((1..rows + 1, 1..cols + 1), starting_position, obstacles))
 }
 
 #[cfg(test)]
@@ -168,4 +223,23 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 6)
     }
+
+    #[test]
+    fn invalid_maps() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("..#\n.^\n"),
+            "day 06, line 2: column 1, expected 3 positions"
+        );
+        assert_eq!(
+            error("..#\n.^>\n"),
+            "day 06, line 2: column 3, expected `.`, `#` or `^`"
+        );
+        assert_eq!(error("..#\n...\n"), "day 06: expected a single guard `^`");
+        assert_eq!(
+            error(".#...\n....#\n.....\n#^...\n...#.\n"),
+            "day 06: the guard walks in a loop"
+        );
+    }
 }
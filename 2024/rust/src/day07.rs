@@ -1,8 +1,32 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 7;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let equations = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on a line that isn't a test value, a colon and the numbers to
+/// combine.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let equations = parse(input).map_err(PuzzleError::on_day(DAY))?;
 
-    equations
+    Ok(equations
         .iter()
         .filter(|(total, values)| {
             let (init, rest) = values.split_at(1);
@@ -10,7 +34,7 @@ pub fn part1(input: &str) -> usize {
             is_possible(*total, init[0], rest)
         })
         .map(|(total, _)| total)
-        .sum()
+        .sum())
 }
 
 fn is_possible(desired: usize, current: usize, values: &[usize]) -> bool {
@@ -27,11 +51,13 @@ fn is_possible(desired: usize, current: usize, values: &[usize]) -> bool {
         || is_possible(desired, current * next_value, rest)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let equations = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let equations = parse(input).map_err(PuzzleError::on_day(DAY))?;
 
-    equations
+    Ok(equations
         .iter()
         .filter(|(total, values)| {
             let (init, rest) = values.split_at(1);
@@ -39,7 +65,7 @@ pub fn part2(input: &str) -> usize {
             is_possible_with_concatenation(*total, init[0], rest)
         })
         .map(|(total, _)| total)
-        .sum()
+        .sum())
 }
 
 fn is_possible_with_concatenation(
@@ -76,21 +102,19 @@ fn digits(value: usize) -> u32 {
     1 + digits(value / 10)
 }
 
-fn parse(input: &str) -> Vec<(usize, Vec<usize>)> {
-    input
-        .lines()
-        .map(|line| {
-            let (left, right) = line.split_once(':').unwrap();
-            let total = parse_usize(left);
-            let values = right.trim().split(" ").map(parse_usize).collect();
-
-            (total, values)
-        })
-        .collect()
+fn parse(input: &str) -> parsing::Result<Vec<(usize, Vec<usize>)>> {
+    parsing::lines(input).iter().map(parse_equation).collect()
 }
 
-fn parse_usize(value: &str) -> usize {
-    value.parse::<usize>().unwrap()
+fn parse_equation(line: &SourceLine) -> parsing::Result<(usize, Vec<usize>)> {
+    let (left, right) = line.key_value()?;
+    let total = line.parse(left)?;
+    let values = right
+        .split(' ')
+        .map(|value| line.parse(value))
+        .collect::<parsing::Result<_>>()?;
+
+    Ok((total, values))
 }
 
 #[cfg(test)]
@@ -129,4 +153,14 @@ mod tests {
         assert_eq!(digits(582), 3);
         assert_eq!(concatenate(11, 22), 1122);
     }
+
+    #[test]
+    fn invalid_equations() {
+        assert_eq!(
+            try_part1("190: 10 19\n3267 81 40\n")
+                .unwrap_err()
+                .to_string(),
+            "day 07, line 2: column 1, expected `:`"
+        );
+    }
 }
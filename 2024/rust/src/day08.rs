@@ -1,13 +1,35 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::{collections::HashMap, ops::Range};
 
+const DAY: u8 = 8;
+
 type Bounds = (Range<usize>, Range<usize>);
 type Position = (usize, usize);
 type Map = HashMap<Position, char>;
 type Antennas = HashMap<char, Vec<Position>>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (bounds, _map, antennas) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the map is a rectangle of `.` and alphanumeric antennas.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (bounds, _map, antennas) = parse(input)?;
     let mut antinodes: HashMap<Position, char> = HashMap::new();
 
     for (frequency, positions) in antennas {
@@ -18,12 +40,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    antinodes.len()
+    Ok(antinodes.len())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (bounds, _map, antennas) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (bounds, _map, antennas) = parse(input)?;
     let mut antinodes: HashMap<Position, char> = HashMap::new();
 
     for (frequency, positions) in antennas {
@@ -35,7 +59,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    antinodes.len()
+    Ok(antinodes.len())
 }
 
 fn position_pairs(positions: &[Position]) -> Vec<(&Position, &Position)> {
@@ -123,27 +147,43 @@ fn in_bounds(position: &Position, bounds: &Bounds) -> bool {
     bounds.0.contains(&position.0) && bounds.1.contains(&position.1)
 }
 
-fn parse(input: &str) -> (Bounds, Map, Antennas) {
-    let bounds: Bounds = (
-        0..input.lines().count(),
-        0..input.lines().next().unwrap().len(),
-    );
+fn parse(input: &str) -> Result<(Bounds, Map, Antennas), PuzzleError> {
+    let lines = parsing::lines(input);
+    let width = lines.first().map_or(0, |line| line.text.chars().count());
+    let bounds: Bounds = (0..lines.len(), 0..width);
+
+    if width == 0 {
+        return Err(PuzzleError::new(DAY, "expected a map"));
+    }
+
     let mut map: Map = HashMap::new();
     let mut antennas: Antennas = HashMap::new();
 
-    for (row, line) in input.lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
+    for (row, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != width {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {width} positions")),
+            ));
+        }
+
+        for (col, (index, char)) in line.text.char_indices().enumerate() {
             match char {
                 '.' => {}
-                ch => {
+                ch if ch.is_ascii_alphanumeric() => {
                     map.insert((row, col), ch);
                     antennas.entry(char).or_default().push((row, col));
                 }
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `.` or an antenna",
+                    )))
+                }
             }
         }
     }
 
-    (bounds, map, antennas)
+    Ok((bounds, map, antennas))
 }
 
 #[cfg(test)]
@@ -185,7 +225,7 @@ mod tests {
 ..........
 ..........";
 
-        let (_bounds, _map, antennas) = parse(simple_input);
+        let (_bounds, _map, antennas) = parse(simple_input).unwrap();
         let a_positions = antennas.get(&'a').unwrap();
 
         assert_eq!(position_pairs(a_positions), vec![(&(3, 4), &(5, 5))]);
@@ -235,4 +275,18 @@ T.........
         assert_eq!(part2(simple_input), 9);
         assert_eq!(part2(input()), 34)
     }
+
+    #[test]
+    fn invalid_maps() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("..a\n.#.\n"),
+            "day 08, line 2: column 2, expected `.` or an antenna"
+        );
+        assert_eq!(
+            error("..a\n.a\n"),
+            "day 08, line 2: column 1, expected 3 positions"
+        );
+    }
 }
@@ -1,12 +1,34 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 use std::ops::RangeInclusive;
 
+const DAY: u8 = 9;
+
 type Disk = Vec<Option<usize>>;
 type DiskMap = HashMap<usize, (Option<usize>, usize)>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (_disk_map, mut disk): (DiskMap, Disk) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when the disk map is empty or holds anything but digits.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (_disk_map, mut disk): (DiskMap, Disk) = parse(input)?;
     let mut free_index: usize = 0;
     let mut used_index: usize = disk.len() - 1;
 
@@ -31,18 +53,21 @@ pub fn part1(input: &str) -> usize {
 
     //    print_disk(&disk);
 
-    disk.iter()
+    Ok(disk
+        .iter()
         .enumerate()
         .map(|(index, block)| match block {
             Some(id) => index * id,
             None => 0,
         })
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (disk_map, _disk): (DiskMap, Disk) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (disk_map, _disk): (DiskMap, Disk) = parse(input)?;
     let mut free_list: Vec<Option<RangeInclusive<usize>>> = Vec::new();
     let mut used_list: Vec<(usize, RangeInclusive<usize>)> = Vec::new();
 
@@ -85,7 +110,7 @@ pub fn part2(input: &str) -> usize {
         index = *range.end() + 1;
     }
 
-    total
+    Ok(total)
 }
 
 fn range_size(range: &RangeInclusive<usize>) -> usize {
@@ -110,24 +135,32 @@ fn print_disk(disk: &Disk) {
     );
 }
 
-fn parse(input: &str) -> (DiskMap, Disk) {
+fn parse(input: &str) -> Result<(DiskMap, Disk), PuzzleError> {
     let mut disk_map = HashMap::new();
     let mut id: usize = 0;
     let mut index: usize = 0;
     let mut expecting_block: bool = true;
 
-    for char in input.trim().chars() {
-        let size: usize = parse_usize(char);
+    for line in parsing::lines(input.trim()) {
+        for (at, char) in line.text.char_indices() {
+            let size: usize = line
+                .parse(&line.text[at..at + char.len_utf8()])
+                .map_err(PuzzleError::on_day(DAY))?;
+
+            if expecting_block {
+                disk_map.insert(index, (Some(id), size));
+                id += 1;
+            } else if size > 0 {
+                disk_map.insert(index, (None, size));
+            }
 
-        if expecting_block {
-            disk_map.insert(index, (Some(id), size));
-            id += 1;
-        } else if size > 0 {
-            disk_map.insert(index, (None, size));
+            index += size;
+            expecting_block = !expecting_block;
         }
+    }
 
-        index += size;
-        expecting_block = !expecting_block;
+    if disk_map.is_empty() {
+        return Err(PuzzleError::new(DAY, "expected a disk map"));
     }
 
     let mut output: Disk = Vec::new();
@@ -144,25 +177,7 @@ fn parse(input: &str) -> (DiskMap, Disk) {
         }
     }
 
-    (disk_map, output)
-}
-
-fn parse_usize(ch: char) -> usize {
-    match ch {
-        '0' => 0,
-        '1' => 1,
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        non_digit_char => {
-            panic!("non-digit character seen: {non_digit_char:?}")
-        }
-    }
+    Ok((disk_map, output))
 }
 
 #[cfg(test)]
@@ -182,4 +197,15 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 2858)
     }
+
+    #[test]
+    fn invalid_disk_maps() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("23x3"),
+            "day 09, line 1: column 3, invalid value `x`"
+        );
+        assert_eq!(error("\n"), "day 09: expected a disk map");
+    }
 }
@@ -1,13 +1,35 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 10;
+
 type Position = (usize, usize);
 type Height = usize;
 type Map = HashMap<Position, Height>;
 type Path = Vec<(Position, Height)>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (map, trail_heads) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when the map holds anything but digit heights.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (map, trail_heads) = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut score: usize = 0;
 
     for trail_head in trail_heads {
@@ -29,12 +51,14 @@ pub fn part1(input: &str) -> usize {
         score += end_positions.len();
     }
 
-    score
+    Ok(score)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (map, trail_heads) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (map, trail_heads) = parse(input).map_err(PuzzleError::on_day(DAY))?;
     let mut score: usize = 0;
 
     for trail_head in trail_heads {
@@ -42,7 +66,7 @@ pub fn part2(input: &str) -> usize {
         score += paths.len();
     }
 
-    score
+    Ok(score)
 }
 
 fn walk(path: &Path, mut visited: Vec<Position>, map: &Map) -> Vec<Path> {
@@ -99,13 +123,13 @@ fn print_path(path: &Path) {
     println!();
 }
 
-fn parse(input: &str) -> (Map, Vec<Position>) {
+fn parse(input: &str) -> parsing::Result<(Map, Vec<Position>)> {
     let mut map = HashMap::new();
     let mut trail_heads = Vec::new();
 
-    for (row, line) in input.lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
-            let height = parse_usize(char);
+    for (row, line) in parsing::lines(input).into_iter().enumerate() {
+        for (col, (at, char)) in line.text.char_indices().enumerate() {
+            let height = line.parse(&line.text[at..at + char.len_utf8()])?;
             map.insert((row, col), height);
             if height == 0 {
                 trail_heads.push((row, col));
@@ -113,25 +137,7 @@ fn parse(input: &str) -> (Map, Vec<Position>) {
         }
     }
 
-    (map, trail_heads)
-}
-
-fn parse_usize(ch: char) -> usize {
-    match ch {
-        '0' => 0,
-        '1' => 1,
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        non_digit_char => {
-            panic!("non-digit character seen: {non_digit_char:?}")
-        }
-    }
+    Ok((map, trail_heads))
 }
 
 #[cfg(test)]
@@ -159,4 +165,12 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 81)
     }
+
+    #[test]
+    fn invalid_maps() {
+        assert_eq!(
+            try_part1("0123\n1.34\n").unwrap_err().to_string(),
+            "day 10, line 2: column 2, invalid value `.`"
+        );
+    }
 }
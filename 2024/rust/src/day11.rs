@@ -1,15 +1,39 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 11;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let stones = parse(input);
-    stone_count_after_blinks(stones, 25)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let stones = parse(input);
-    stone_count_after_blinks(stones, 75)
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the stones are whitespace-separated numbers.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let stones = parse(input)?;
+    Ok(stone_count_after_blinks(stones, 25))
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let stones = parse(input)?;
+    Ok(stone_count_after_blinks(stones, 75))
 }
 
 fn stone_count_after_blinks(stones: Vec<usize>, blinks: usize) -> usize {
@@ -79,12 +103,14 @@ fn split(stone: usize) -> Option<(usize, usize)> {
     }
 }
 
-fn parse(input: &str) -> Vec<usize> {
-    input
-        .trim()
-        .split(' ')
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect()
+fn parse(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .flat_map(|line| {
+            line.text.split_whitespace().map(|stone| line.parse(stone))
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -114,4 +140,12 @@ mod tests {
     fn stone_count_after_blinks_example() {
         assert_eq!(stone_count_after_blinks(vec![0], 75), 22938365706844)
     }
+
+    #[test]
+    fn invalid_stones() {
+        assert_eq!(
+            try_part1("125 -17").unwrap_err().to_string(),
+            "day 11, line 1: column 5, invalid value `-17`"
+        );
+    }
 }
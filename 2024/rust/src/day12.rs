@@ -1,16 +1,38 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 12;
+
 type Plant = char;
 type Position = (usize, usize);
 type PlotMap = HashMap<Plant, Vec<Position>>;
 type PositionMap = HashMap<Position, Plant>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (plot_map, position_map) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when the garden holds anything but plant letters.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (plot_map, position_map) = parse(input)?;
     let contiguous_plots: Vec<(char, Vec<Position>)> = segment(&plot_map);
 
-    contiguous_plots
+    Ok(contiguous_plots
         .into_iter()
         .map(|(plant, plant_positions)| {
             let perimeter = perimeter(&plant, &plant_positions, &position_map);
@@ -18,15 +40,17 @@ pub fn part1(input: &str) -> usize {
 
             perimeter * area
         })
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (plot_map, position_map) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (plot_map, position_map) = parse(input)?;
     let contiguous_plots: Vec<(char, Vec<Position>)> = segment(&plot_map);
 
-    contiguous_plots
+    Ok(contiguous_plots
         .into_iter()
         .map(|(plant, plant_positions)| {
             let sides = sides(&plant, &plant_positions, &position_map);
@@ -36,7 +60,7 @@ pub fn part2(input: &str) -> usize {
 
             sides * area
         })
-        .sum()
+        .sum())
 }
 
 fn sides(
@@ -204,18 +228,24 @@ fn neighboring_plants(
         .collect()
 }
 
-fn parse(input: &str) -> (PlotMap, PositionMap) {
+fn parse(input: &str) -> Result<(PlotMap, PositionMap), PuzzleError> {
     let mut plots: HashMap<Plant, Vec<Position>> = HashMap::new();
     let mut positions: HashMap<Position, Plant> = HashMap::new();
 
-    for (row, line) in input.lines().enumerate() {
-        for (col, plant) in line.chars().enumerate() {
+    for (row, line) in parsing::lines(input).iter().enumerate() {
+        for (col, (index, plant)) in line.text.char_indices().enumerate() {
+            if !plant.is_ascii_alphabetic() {
+                return Err(PuzzleError::on_day(DAY)(
+                    line.error(&line.text[index..], "expected a plant letter"),
+                ));
+            }
+
             plots.entry(plant).or_default().push((row, col));
             positions.insert((row, col), plant);
         }
     }
 
-    (plots, positions)
+    Ok((plots, positions))
 }
 
 #[cfg(test)]
@@ -294,4 +324,12 @@ AAAAAA"
         assert_eq!(part2(mobius_input()), 368);
         assert_eq!(part2(large_input()), 1206);
     }
+
+    #[test]
+    fn invalid_gardens() {
+        assert_eq!(
+            try_part1("AAB\nA.B\n").unwrap_err().to_string(),
+            "day 12, line 2: column 2, expected a plant letter"
+        );
+    }
 }
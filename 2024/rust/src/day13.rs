@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::fmt::Debug;
 
+const DAY: u8 = 13;
+
 struct Position(usize, usize);
 
 impl Debug for Position {
@@ -15,27 +19,49 @@ struct Game {
     prize: Position,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let games = parse(input, 0);
+    try_part1(input).unwrap()
+}
 
-    games
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless each claw machine is described by its two buttons and a
+/// prize, with the buttons moving in different directions.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let games = parse(input, 0).map_err(PuzzleError::on_day(DAY))?;
+
+    Ok(games
         .iter()
         .map(|game| minimum_tokens_to_win(game).unwrap_or(0))
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let games = parse(input, 10_000_000_000_000);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let games =
+        parse(input, 10_000_000_000_000).map_err(PuzzleError::on_day(DAY))?;
 
-    games
+    Ok(games
         .iter()
         .map(|game| match minimum_tokens_to_win(&game) {
             Some(tokens) => tokens,
             None => 0,
         })
-        .sum()
+        .sum())
 }
 
 fn minimum_tokens_to_win(game: &Game) -> Option<usize> {
@@ -52,7 +78,11 @@ fn minimum_tokens_to_win(game: &Game) -> Option<usize> {
     let py = game.prize.1 as isize;
 
     let b = (ay * px - ax * py) / (bx * ay - by * ax);
-    let a = (px - b * bx) / ax;
+    let a = if ax == 0 {
+        (py - b * by) / ay
+    } else {
+        (px - b * bx) / ax
+    };
 
     if (px as isize == a * ax + b * bx) && (py as isize == a * ay + b * by) {
         Some((3 * a + b) as usize)
@@ -61,36 +91,44 @@ fn minimum_tokens_to_win(game: &Game) -> Option<usize> {
     }
 }
 
-fn parse(input: &str, add: usize) -> Vec<Game> {
-    input
-        .split("\n\n")
-        .map(|game_string| {
-            let mut lines = game_string.lines();
-            Game {
-                a: parse_position(lines.next().unwrap(), 0),
-                b: parse_position(lines.next().unwrap(), 0),
-                prize: parse_position(lines.next().unwrap(), add),
+fn parse(input: &str, add: usize) -> parsing::Result<Vec<Game>> {
+    parsing::blocks(input)
+        .iter()
+        .map(|lines| match &lines[..] {
+            [a_line, b_line, prize] => {
+                let a = parse_position(a_line, "Button A: X+{}, Y+{}", 0)?;
+                let b = parse_position(b_line, "Button B: X+{}, Y+{}", 0)?;
+
+                // `minimum_tokens_to_win` solves for the one way to reach
+                // the prize, which buttons moving along a line don't have.
+                if a.0 * b.1 == a.1 * b.0 {
+                    return Err(b_line.error(
+                        b_line.text,
+                        "expected button B to move at an angle to button A",
+                    ));
+                }
+
+                Ok(Game {
+                    a,
+                    b,
+                    prize: parse_position(prize, "Prize: X={}, Y={}", add)?,
+                })
             }
+            _ => Err(lines[0].error(lines[0].text, "expected three lines")),
         })
         .collect()
 }
 
-fn parse_position(input: &str, add: usize) -> Position {
-    let position_string = input.split(": ").skip(1).next().unwrap();
-    let mut iter = position_string.split(", ");
+fn parse_position(
+    line: &SourceLine<'_>,
+    pattern: &str,
+    add: usize,
+) -> parsing::Result<Position> {
+    let fields = line.fields(pattern)?;
+    let x: usize = line.parse(fields[0])?;
+    let y: usize = line.parse(fields[1])?;
 
-    Position(
-        parse_coordinate(iter.next().unwrap()) + add,
-        parse_coordinate(iter.next().unwrap()) + add,
-    )
-}
-
-fn parse_coordinate(input: &str) -> usize {
-    parse_usize(input.split(&['+', '=']).skip(1).next().unwrap())
-}
-
-fn parse_usize(input: &str) -> usize {
-    input.parse::<usize>().unwrap()
+    Ok(Position(x + add, y + add))
 }
 
 #[cfg(test)]
@@ -120,4 +158,19 @@ Prize: X=18641, Y=10279"
     fn part1_example() {
         assert_eq!(part1(input()), 480)
     }
+
+    #[test]
+    fn invalid_machines() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("Button A: X+1, Y+2\nButton B: X+2, Y+4\nPrize: X=3, Y=6"),
+            "day 13, line 2: column 1, expected button B to move at an angle \
+             to button A"
+        );
+        assert_eq!(
+            error("Button A: X+1, Y+2\nPrize: X=3, Y=6"),
+            "day 13, line 1: column 1, expected three lines"
+        );
+    }
 }
@@ -1,6 +1,12 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use crate::visualize::{self, Canvas, GREEN};
 use std::collections::HashMap;
+use std::error::Error;
 use std::fmt::Debug;
 
+const DAY: u8 = 14;
+
 #[derive(PartialEq, Eq, Hash)]
 struct Position {
     x: usize,
@@ -44,29 +50,52 @@ impl Robot {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    part1_with_params(input, 101, 103, 100)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a robot's `p=x,y v=dx,dy`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    part1_with_params(input, 101, 103, 100)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when no step within
+/// 100,000 looks like a Christmas tree.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    part2_with_params(input, 101, 103)
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a robot's `p=x,y v=dx,dy`.
 pub fn part1_with_params(
     input: &str,
     width: usize,
     height: usize,
     steps: usize,
-) -> usize {
-    let mut robots = parse(input);
-
-    //    print_map(&robots, width, height, 0);
+) -> Result<usize, PuzzleError> {
+    let mut robots = parse(input)?;
 
     for _step in 1..=steps {
         for robot in robots.iter_mut() {
             robot.step(width, height);
         }
-        if possible_tree(&robots, width, height) {
-            //            print_map(&robots, width, height, step);
-        }
     }
 
     let left_half = 0..width / 2;
@@ -91,20 +120,19 @@ pub fn part1_with_params(
         }
     }
 
-    totals.iter().fold(1, |acc, total| acc * total)
+    Ok(totals.iter().fold(1, |acc, total| acc * total))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    part2_with_params(input, 101, 103)
-}
-
-#[must_use]
-pub fn part2_with_params(input: &str, width: usize, height: usize) -> usize {
-    let mut robots = parse(input);
-    let mut answer: usize = 0;
-
-    //    print_map(&robots, width, height, 0);
+/// # Errors
+///
+/// Fails on the same input as [`part1_with_params`], or when no step within
+/// 100,000 looks like a Christmas tree.
+pub fn part2_with_params(
+    input: &str,
+    width: usize,
+    height: usize,
+) -> Result<usize, PuzzleError> {
+    let mut robots = parse(input)?;
 
     for step in 1..=100_000 {
         for robot in &mut robots {
@@ -112,13 +140,11 @@ pub fn part2_with_params(input: &str, width: usize, height: usize) -> usize {
         }
 
         if possible_tree(&robots, width, height) {
-            answer = step;
-            //            print_map(&robots, width, height, step);
-            break;
+            return Ok(step);
         }
     }
 
-    answer
+    Err(PuzzleError::new(DAY, "no step looks like a Christmas tree"))
 }
 
 fn possible_tree(robots: &Vec<Robot>, width: usize, height: usize) -> bool {
@@ -160,74 +186,61 @@ fn possible_tree(robots: &Vec<Robot>, width: usize, height: usize) -> bool {
     }
 }
 
-#[allow(dead_code)]
-fn print_map(robots: &Vec<Robot>, width: usize, height: usize, steps: usize) {
-    print!("\x1B[2J\x1B[1;1H");
-    println!("STEP {}", steps);
-    for y in 0..height {
-        for x in 0..width {
-            let count = robots
-                .iter()
-                .filter(|robot| robot.position == Position { x, y })
-                .count();
-            if count > 0 {
-                print!("{}", count)
-            } else {
-                print!(".")
-            }
-        }
-        println!()
-    }
-    println!("\n");
-}
-
-fn parse(input: &str) -> Vec<Robot> {
-    input
-        .lines()
-        .map(|line| {
-            let (position_string, velocity_string) =
-                line.split_once(" ").unwrap();
+fn render(robots: &[Robot], width: usize, height: usize) -> Canvas {
+    let mut canvas = Canvas::new(width, height, 4);
+    canvas.plot(
+        robots
+            .iter()
+            .map(|robot| (robot.position.x, robot.position.y)),
+        GREEN,
+    );
 
-            Robot {
-                position: parse_position(position_string),
-                velocity: parse_velocity(velocity_string),
-            }
-        })
-        .collect()
+    canvas
 }
 
-fn parse_position(input: &str) -> Position {
-    let (_, pair) = input.split_once("=").unwrap();
-    let (x_string, y_string) = pair.split_once(",").unwrap();
-
-    Position {
-        x: parse_usize(x_string),
-        y: parse_usize(y_string),
+/// Saves the frame the part 2 heuristic picks out as the Christmas tree.
+///
+/// # Errors
+///
+/// Fails on input [`try_part2`] rejects, or when an image can't be written.
+pub fn visualize(input: &str) -> Result<(), Box<dyn Error>> {
+    let (width, height) = (101, 103);
+    let steps = part2_with_params(input, width, height)?;
+    let mut robots = parse(input)?;
+
+    for _step in 0..steps {
+        for robot in &mut robots {
+            robot.step(width, height);
+        }
     }
-}
 
-fn parse_velocity(input: &str) -> Velocity {
-    let (_, pair) = input.split_once("=").unwrap();
-    let (x_string, y_string) = pair.split_once(",").unwrap();
+    render(&robots, width, height)
+        .save_png(&visualize::output_path(14, "tree.png")?)?;
 
-    Velocity {
-        x: parse_isize(x_string),
-        y: parse_isize(y_string),
-    }
+    Ok(())
 }
 
-fn parse_usize(input: &str) -> usize {
-    match input.parse::<usize>() {
-        Ok(value) => value,
-        Err(_error) => panic!("invalid digit: {}", input),
-    }
+fn parse(input: &str) -> Result<Vec<Robot>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(parse_robot)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
-fn parse_isize(input: &str) -> isize {
-    match input.parse::<isize>() {
-        Ok(value) => value,
-        Err(_error) => panic!("invalid digit: {}", input),
-    }
+fn parse_robot(line: &SourceLine<'_>) -> parsing::Result<Robot> {
+    let fields = line.fields("p={},{} v={},{}")?;
+
+    Ok(Robot {
+        position: Position {
+            x: line.parse(fields[0])?,
+            y: line.parse(fields[1])?,
+        },
+        velocity: Velocity {
+            x: line.parse(fields[2])?,
+            y: line.parse(fields[3])?,
+        },
+    })
 }
 
 #[cfg(test)]
@@ -264,6 +277,16 @@ p=9,5 v=-3,-3"
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1_with_params(input(), 11, 7, 100), 12);
+        assert_eq!(part1_with_params(input(), 11, 7, 100).unwrap(), 12);
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_field() {
+        let error = parse("p=0,4 v=3,-3\np=-6,3 v=-1,-3").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "day 14, line 2: column 3, invalid value `-6`"
+        );
     }
 }
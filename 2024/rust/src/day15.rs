@@ -1,4 +1,10 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use crate::visualize::{self, Animation, Canvas, GRAY, RED, YELLOW};
 use std::collections::HashMap;
+use std::error::Error;
+
+const DAY: u8 = 15;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 struct Position {
@@ -145,26 +151,19 @@ impl Warehouse {
         };
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for row in 0..self.bounds.0 {
-            for col in 0..self.bounds.1 {
-                let position = Position { row, col };
-                match self.map.get(&position) {
-                    Some(Cell::Wall) => print!("#"),
-                    Some(Cell::Box(_)) => print!("O"),
-                    None => {
-                        if position == self.robot_position {
-                            print!("@")
-                        } else {
-                            print!(".")
-                        }
-                    }
-                }
-            }
-            println!();
+    fn render(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.bounds.1, self.bounds.0, 4);
+
+        for (position, cell) in &self.map {
+            let color = match cell {
+                Cell::Wall => GRAY,
+                Cell::Box(_) => YELLOW,
+            };
+            canvas.fill(position.col, position.row, color);
         }
-        println!();
+        canvas.fill(self.robot_position.col, self.robot_position.row, RED);
+
+        canvas
     }
 
     fn shift(
@@ -192,43 +191,54 @@ impl Warehouse {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (mut warehouse, moves) = parse(input);
+    try_part1(input).unwrap()
+}
 
-    //    println!("Initial state:");
-    //    warehouse.print();
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a rectangular map of `#`, `O`, `.` and a single
+/// robot `@`, closed in by walls, followed by `^`, `v`, `<` and `>` moves.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (mut warehouse, moves) = parse(input)?;
 
     for direction in moves {
-        //        println!("Direction {}:", &direction);
         warehouse.move_robot(direction);
-        //        warehouse.print();
     }
 
-    warehouse
+    Ok(warehouse
         .map
         .iter()
         .map(|(position, cell)| match cell {
             Cell::Box(_) => position.row * 100 + position.col,
             Cell::Wall => 0,
         })
-        .sum()
+        .sum())
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (mut warehouse, moves) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (mut warehouse, moves) = parse(input)?;
     let mut box_scores: HashMap<usize, usize> = HashMap::new();
 
     warehouse.widen();
 
-    //    println!("Initial state:");
-    //    warehouse.print();
-
     for direction in moves {
-        //        println!("Direction {}:", &direction);
         warehouse.move_robot(direction);
-        //        warehouse.print();
     }
 
     for (position, cell) in warehouse.map {
@@ -245,21 +255,95 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    box_scores.values().sum()
+    Ok(box_scores.values().sum())
+}
+
+/// Animates the robot through both the narrow and the widened warehouse,
+/// sampling moves so each GIF stays around a few hundred frames.
+///
+/// # Errors
+///
+/// Fails on input [`try_part1`] rejects, or when an image can't be written.
+pub fn visualize(input: &str) -> Result<(), Box<dyn Error>> {
+    for (name, widen) in [("narrow.gif", false), ("wide.gif", true)] {
+        let (mut warehouse, moves) = parse(input)?;
+        if widen {
+            warehouse.widen();
+        }
+
+        let every = moves.len() / 300 + 1;
+        let mut animation = Animation::new(40);
+        animation.push(&warehouse.render());
+
+        for (index, direction) in moves.into_iter().enumerate() {
+            warehouse.move_robot(direction);
+
+            if (index + 1) % every == 0 {
+                animation.push(&warehouse.render());
+            }
+        }
+        animation.push(&warehouse.render());
+
+        animation.save_gif(&visualize::output_path(15, name)?)?;
+    }
+
+    Ok(())
+}
+
+fn parse(input: &str) -> Result<(Warehouse, Vec<Direction>), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [map_lines, move_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(DAY, "expected a map followed by moves"));
+    };
+
+    let warehouse = warehouse(map_lines)?;
+    let moves = move_lines
+        .iter()
+        .flat_map(|line| {
+            line.text.char_indices().map(|(index, ch)| match ch {
+                '^' => Ok(Direction::Up),
+                'v' => Ok(Direction::Down),
+                '<' => Ok(Direction::Left),
+                '>' => Ok(Direction::Right),
+                _ => Err(line.error(
+                    &line.text[index..],
+                    "expected `^`, `v`, `<` or `>`",
+                )),
+            })
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((warehouse, moves))
 }
 
-fn parse(input: &str) -> (Warehouse, Vec<Direction>) {
-    let (map_string, moves_string) = input.split_once("\n\n").unwrap();
+// The robot never leaves the map, so walls have to close it in.
+fn warehouse(lines: &[SourceLine<'_>]) -> Result<Warehouse, PuzzleError> {
     let mut map: HashMap<Position, Cell> = HashMap::new();
-    let mut robot_position: Position = Position { row: 0, col: 0 };
-    let mut rows: usize = 0;
-    let mut cols: usize = 0;
+    let mut robot_positions: Vec<Position> = Vec::new();
+    let rows = lines.len();
+    let cols = lines[0].text.chars().count();
     let mut boxes: usize = 0;
 
-    for (row, line) in map_string.lines().enumerate() {
-        cols = 0;
-        for (col, ch) in line.chars().enumerate() {
+    for (row, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != cols {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {cols} cells")),
+            ));
+        }
+
+        for (col, (index, ch)) in line.text.char_indices().enumerate() {
             let position = Position { row, col };
+            let edge =
+                row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+
+            if edge && ch != '#' {
+                return Err(PuzzleError::on_day(DAY)(line.error(
+                    &line.text[index..],
+                    "expected walls `#` around the warehouse",
+                )));
+            }
+
             match ch {
                 '#' => {
                     map.insert(position, Cell::Wall);
@@ -268,37 +352,27 @@ fn parse(input: &str) -> (Warehouse, Vec<Direction>) {
                     map.insert(position, Cell::Box(boxes));
                     boxes += 1;
                 }
-                '@' => {
-                    robot_position = position;
-                }
+                '@' => robot_positions.push(position),
                 '.' => (),
-                _ => panic!("invalid map cell: {}", ch),
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `#`, `O`, `@` or `.`",
+                    )))
+                }
             };
-            cols += 1;
         }
-        rows += 1;
     }
 
-    let moves: Vec<Direction> = moves_string
-        .replace("\n", "")
-        .chars()
-        .map(|ch| match ch {
-            '^' => Direction::Up,
-            'v' => Direction::Down,
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            _ => panic!("invalid move: {}", ch),
-        })
-        .collect();
-
-    (
-        Warehouse {
-            map,
-            bounds: (rows, cols),
-            robot_position,
-        },
-        moves,
-    )
+    let [robot_position] = &robot_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single robot `@`"));
+    };
+
+    Ok(Warehouse {
+        map,
+        bounds: (rows, cols),
+        robot_position: robot_position.clone(),
+    })
 }
 
 #[cfg(test)]
@@ -369,4 +443,34 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^"
         //        assert_eq!(part2(part2_small_input()), 0);
         assert_eq!(part2(input()), 9021);
     }
+
+    #[test]
+    fn invalid_warehouses() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("####\n#@.#\n####\n"),
+            "day 15: expected a map followed by moves"
+        );
+        assert_eq!(
+            error("####\n#@.#\n###\n\n<>"),
+            "day 15, line 3: column 1, expected 4 cells"
+        );
+        assert_eq!(
+            error("####\n#@..\n####\n\n<>"),
+            "day 15, line 2: column 4, expected walls `#` around the warehouse"
+        );
+        assert_eq!(
+            error("####\n#@x#\n####\n\n<>"),
+            "day 15, line 2: column 3, expected `#`, `O`, `@` or `.`"
+        );
+        assert_eq!(
+            error("####\n#@@#\n####\n\n<>"),
+            "day 15: expected a single robot `@`"
+        );
+        assert_eq!(
+            error("####\n#@.#\n####\n\n<>\n^x"),
+            "day 15, line 6: column 2, expected `^`, `v`, `<` or `>`"
+        );
+    }
 }
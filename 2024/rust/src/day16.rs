@@ -1,9 +1,13 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use priority_queue::PriorityQueue;
 use std::{
     cmp::{Ordering, Reverse},
     collections::{HashMap, HashSet, VecDeque},
 };
 
+const DAY: u8 = 16;
+
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Position {
     row: usize,
@@ -42,17 +46,38 @@ impl std::fmt::Display for Direction {
 type Path = Vec<Position>;
 type Map = HashMap<Position, Tile>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (map, start_position, target_position) = parse(input);
-
-    best_path_cost(&start_position, &target_position, &map)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let (map, start_position, target_position) = parse(input);
-    let best_cost = best_path_cost(&start_position, &target_position, &map);
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the maze is a rectangle of `#` and `.` with a single start
+/// `S` and end `E`, closed in by walls, or when no path reaches the end.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (map, start_position, target_position) = parse(input)?;
+
+    best_path_cost(&start_position, &target_position, &map)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (map, start_position, target_position) = parse(input)?;
+    let best_cost = best_path_cost(&start_position, &target_position, &map)?;
 
     let best_positions: HashSet<Position> =
         best_paths(&start_position, &target_position, &map, best_cost)
@@ -60,7 +85,7 @@ pub fn part2(input: &str) -> usize {
             .flat_map(|path| path.into_iter())
             .collect();
 
-    best_positions.len()
+    Ok(best_positions.len())
 }
 
 fn best_paths(
@@ -120,7 +145,7 @@ fn best_path_cost(
     start_position: &Position,
     target_position: &Position,
     map: &Map,
-) -> usize {
+) -> Result<usize, PuzzleError> {
     let mut frontier: PriorityQueue<Position, Reverse<usize>> =
         PriorityQueue::new();
     let mut came_from: HashMap<Position, Position> = HashMap::new();
@@ -158,8 +183,10 @@ fn best_path_cost(
         }
     }
 
-    let (_, cost) = cost_so_far.get(&target_position).unwrap();
-    *cost
+    cost_so_far
+        .get(target_position)
+        .map(|(_, cost)| *cost)
+        .ok_or_else(|| PuzzleError::new(DAY, "no path reaches the end `E`"))
 }
 
 fn neighbors(position: &Position, map: &Map) -> Vec<Position> {
@@ -218,30 +245,58 @@ fn rotation_cost(from: &Direction, to: &Direction) -> usize {
     }
 }
 
-fn parse(input: &str) -> (HashMap<Position, Tile>, Position, Position) {
+// Paths only ever stay inside the maze when walls close it in.
+fn parse(input: &str) -> Result<(Map, Position, Position), PuzzleError> {
     let mut map = HashMap::new();
-    let mut start_position: Option<Position> = None;
-    let mut end_position: Option<Position> = None;
+    let mut start_positions: Vec<Position> = Vec::new();
+    let mut end_positions: Vec<Position> = Vec::new();
+    let lines = parsing::lines(input);
+    let rows = lines.len();
+    let cols = lines.first().map_or(0, |line| line.text.chars().count());
+
+    for (row, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != cols {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {cols} tiles")),
+            ));
+        }
+
+        for (col, (index, char)) in line.text.char_indices().enumerate() {
+            let edge =
+                row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+
+            if edge && char != '#' {
+                return Err(PuzzleError::on_day(DAY)(line.error(
+                    &line.text[index..],
+                    "expected walls `#` around the maze",
+                )));
+            }
 
-    for (row, line) in input.lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
             match char {
                 '#' => {
                     map.insert(Position { row, col }, Tile::Wall);
                 }
                 '.' => {}
-                'S' => {
-                    start_position = Some(Position { row, col });
-                }
-                'E' => {
-                    end_position = Some(Position { row, col });
+                'S' => start_positions.push(Position { row, col }),
+                'E' => end_positions.push(Position { row, col }),
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `#`, `.`, `S` or `E`",
+                    )))
                 }
-                _ => panic!("invalid character seen: {}", char),
             }
         }
     }
 
-    (map, start_position.unwrap(), end_position.unwrap())
+    let [start_position] = &start_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single start `S`"));
+    };
+    let [end_position] = &end_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single end `E`"));
+    };
+
+    Ok((map, start_position.clone(), end_position.clone()))
 }
 
 #[cfg(test)]
@@ -319,4 +374,30 @@ mod tests {
         assert_eq!(part2(example_input()), 45);
         assert_eq!(part2(second_example_input()), 64);
     }
+
+    #[test]
+    fn invalid_mazes() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("#####\n#S.E#\n####\n"),
+            "day 16, line 3: column 1, expected 5 tiles"
+        );
+        assert_eq!(
+            error("#####\n#S.E.\n#####\n"),
+            "day 16, line 2: column 5, expected walls `#` around the maze"
+        );
+        assert_eq!(
+            error("#####\n#SxE#\n#####\n"),
+            "day 16, line 2: column 3, expected `#`, `.`, `S` or `E`"
+        );
+        assert_eq!(
+            error("#####\n#S.S#\n#####\n"),
+            "day 16: expected a single start `S`"
+        );
+        assert_eq!(
+            error("#####\n#S#E#\n#####\n"),
+            "day 16: no path reaches the end `E`"
+        );
+    }
 }
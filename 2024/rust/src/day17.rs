@@ -1,7 +1,8 @@
-use std::{
-    collections::HashMap,
-    ops::{BitXor, Shl, Shr},
-};
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::ops::{BitXor, Shl, Shr};
+
+const DAY: u8 = 17;
 
 #[derive(Clone, Debug)]
 struct Computer {
@@ -257,16 +258,37 @@ impl Computer {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> String {
-    let mut computer = parse(input);
-    computer.run();
-
-    computer.output_string()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is registers A, B and C followed by a program of
+/// comma-separated 3-bit numbers.
+pub fn try_part1(input: &str) -> Result<String, PuzzleError> {
+    let mut computer = parse(input)?;
+    computer.run();
+
+    Ok(computer.output_string())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     // [0] bst :: B = A % 8 = 63687530 % 8 = 2
     // [2] bxl :: B = xor(B, 3) = 1
     // [4] cdv :: C = A / 2^B :: 63687530 / 2^1 = 31843765
@@ -306,7 +328,7 @@ pub fn part2(input: &str) -> usize {
     //0bxxxxxxxx011x111 => 2
     //0bxxxxx   x   xxx => 4
 
-    let computer = parse(input);
+    let computer = parse(input)?;
     let mut start: usize = 0b000;
 
     for size in 1..=16 {
@@ -341,38 +363,48 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    start.shr(3)
+    Ok(start.shr(3))
 }
 
-fn parse(input: &str) -> Computer {
-    let (computer_string, program_string) =
-        input.trim_end().split_once("\n\n").unwrap();
-    let mut registers: HashMap<&str, usize> = HashMap::new();
-
-    for line in computer_string.lines() {
-        let (register_string, value_string) = line.split_once(": ").unwrap();
-        let (_, name) = register_string.split_once(" ").unwrap();
+fn parse(input: &str) -> Result<Computer, PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [register_lines, program_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected registers followed by a program",
+        ));
+    };
+    let [a, b, c] = &register_lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected registers A, B and C"));
+    };
+    let [program] = &program_lines[..] else {
+        return Err(PuzzleError::new(DAY, "expected the program on one line"));
+    };
+
+    Ok(Computer {
+        register_a: register(a, "Register A: {}")
+            .map_err(PuzzleError::on_day(DAY))?,
+        register_b: register(b, "Register B: {}")
+            .map_err(PuzzleError::on_day(DAY))?,
+        register_c: register(c, "Register C: {}")
+            .map_err(PuzzleError::on_day(DAY))?,
+        memory: instructions(program).map_err(PuzzleError::on_day(DAY))?,
+        ..Computer::default()
+    })
+}
 
-        registers.insert(name, value_string.parse::<usize>().unwrap());
-    }
+fn register(line: &SourceLine<'_>, pattern: &str) -> parsing::Result<usize> {
+    line.parse(line.fields(pattern)?[0])
+}
 
-    let (_program, instructions_string) =
-        program_string.split_once(": ").unwrap();
-    let instructions: Vec<u8> = instructions_string
-        .split(",")
-        .map(|s| s.parse::<u8>().expect("instruction"))
-        .collect();
-
-    Computer {
-        register_a: *registers.get("A").unwrap(),
-        register_b: *registers.get("B").unwrap(),
-        register_c: *registers.get("C").unwrap(),
-        memory: instructions,
-        instruction_pointer: 0,
-        outputs: Vec::new(),
-        running: true,
-        debug: false,
-    }
+fn instructions(line: &SourceLine<'_>) -> parsing::Result<Vec<u8>> {
+    line.strip_prefix(line.text, "Program: ")?
+        .split(',')
+        .map(|field| match line.parse(field)? {
+            instruction @ 0..=7 => Ok(instruction),
+            _ => Err(line.error(field, "expected a 3-bit number")),
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -446,4 +478,26 @@ Program: 0,1,5,4,3,0"
     fn part2_example() {
         assert_eq!(part2(input()), 117440);
     }
+
+    #[test]
+    fn invalid_computers() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error(
+                "Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 5,8"
+            ),
+            "day 17, line 5: column 12, expected a 3-bit number"
+        );
+        assert_eq!(
+            error(
+                "Register A: 1\nRegister C: 0\nRegister B: 0\n\nProgram: 5,4"
+            ),
+            "day 17, line 2: column 1, expected `Register B: `"
+        );
+        assert_eq!(
+            error("Register A: 1\nRegister B: 0\n\nProgram: 5,4"),
+            "day 17: expected registers A, B and C"
+        );
+    }
 }
@@ -1,5 +1,10 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use crate::visualize::{self, Animation, Canvas, GRAY, GREEN, RED};
 use priority_queue::PriorityQueue;
-use std::{cmp::Reverse, collections::HashMap};
+use std::{cmp::Reverse, collections::HashMap, error::Error};
+
+const DAY: u8 = 18;
 
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Position {
@@ -16,13 +21,48 @@ impl std::fmt::Debug for Position {
 type Path = Vec<Position>;
 type Map = HashMap<Position, usize>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a byte's `x,y` position, or when the first
+/// kilobyte cuts off the exit.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     part1_with_params(input, 70, 1024)
 }
 
-pub fn part1_with_params(input: &str, bounds: usize, time: usize) -> usize {
-    let map: Map = parse(input);
+/// # Errors
+///
+/// Fails when a line isn't a byte's `x,y` position, or when no byte cuts
+/// off the exit.
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    part2_with_params(input, 70)
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a byte's `x,y` position, or when the bytes that
+/// have fallen by `time` cut off the exit.
+pub fn part1_with_params(
+    input: &str,
+    bounds: usize,
+    time: usize,
+) -> Result<usize, PuzzleError> {
+    let map: Map = parse(input)?;
     let start_position = Position { x: 0, y: 0 };
     let target_position = Position {
         x: bounds,
@@ -31,23 +71,43 @@ pub fn part1_with_params(input: &str, bounds: usize, time: usize) -> usize {
     let path =
         shortest_path(&start_position, &target_position, &map, bounds, time);
 
-    //    print(&map, &path, bounds, time);
+    if path.first() != Some(&start_position) {
+        return Err(PuzzleError::new(
+            DAY,
+            format!("no path reaches the exit after {time} bytes"),
+        ));
+    }
 
-    path.len() - 1
+    Ok(path.len() - 1)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> String {
-    part2_with_params(input, 70)
-}
-
-pub fn part2_with_params(input: &str, bounds: usize) -> String {
-    let map: Map = parse(input);
+/// # Errors
+///
+/// Fails when a line isn't a byte's `x,y` position, or when no byte cuts
+/// off the exit.
+pub fn part2_with_params(
+    input: &str,
+    bounds: usize,
+) -> Result<String, PuzzleError> {
+    let map: Map = parse(input)?;
     let start_position = Position { x: 0, y: 0 };
     let target_position = Position {
         x: bounds,
         y: bounds,
     };
+    let no_blocking_byte =
+        || PuzzleError::new(DAY, "no byte cuts off the exit");
+
+    let path = shortest_path(
+        &start_position,
+        &target_position,
+        &map,
+        bounds,
+        map.len(),
+    );
+    if path.first() == Some(&start_position) {
+        return Err(no_blocking_byte());
+    }
 
     let blocked_time: usize = find_blocked_time(
         &start_position,
@@ -60,10 +120,10 @@ pub fn part2_with_params(input: &str, bounds: usize) -> String {
 
     let (position, _time) = map
         .into_iter()
-        .find(|(_position, fall_time)| *fall_time == blocked_time - 1)
-        .unwrap();
+        .find(|(_position, fall_time)| *fall_time + 1 == blocked_time)
+        .ok_or_else(no_blocking_byte)?;
 
-    format!("{},{}", position.x, position.y)
+    Ok(format!("{},{}", position.x, position.y))
 }
 
 fn find_blocked_time(
@@ -126,9 +186,6 @@ fn shortest_path(
             break;
         }
 
-        //    let path = calculate_path(&came_from, &current);
-        //    print(&map, &path, bounds, time);
-
         for next in neighbors(&current, map, bounds, time) {
             let cost = cost_so_far.get(&current).unwrap();
             let new_cost = cost + 1;
@@ -186,41 +243,95 @@ fn neighbors(
     .collect()
 }
 
-#[allow(dead_code)]
-fn print(map: &Map, path: &Path, bounds: usize, time: usize) {
-    println!("time={}", time);
-
-    for y in 0..=bounds {
-        for x in 0..=bounds {
-            if path.contains(&Position { x, y }) {
-                print!(" () ")
-            } else if let Some(fall_time) = map.get(&Position { x, y }) {
-                if time < *fall_time {
-                    print!(" .. ")
-                } else {
-                    print!("{:03} ", fall_time)
-                }
-            } else {
-                print!(" .. ")
-            }
-        }
-        println!();
+fn render(map: &Map, path: &Path, bounds: usize, time: usize) -> Canvas {
+    let mut canvas = Canvas::new(bounds + 1, bounds + 1, 6);
+
+    canvas.plot(
+        map.iter()
+            .filter(|(_position, fall_time)| **fall_time < time)
+            .map(|(position, _fall_time)| (position.x, position.y)),
+        GRAY,
+    );
+    canvas.plot(path.iter().map(|position| (position.x, position.y)), GREEN);
+
+    canvas
+}
+
+/// Saves the part 1 path, then animates bytes falling until the exit is cut
+/// off, with the byte that blocks it marked in red.
+///
+/// # Errors
+///
+/// Fails on input [`try_part1`] can't parse, or when an image can't be
+/// written.
+pub fn visualize(input: &str) -> Result<(), Box<dyn Error>> {
+    let bounds = 70;
+    let map: Map = parse(input)?;
+    let start_position = Position { x: 0, y: 0 };
+    let target_position = Position {
+        x: bounds,
+        y: bounds,
+    };
+    let frame = |time: usize| {
+        let path = shortest_path(
+            &start_position,
+            &target_position,
+            &map,
+            bounds,
+            time,
+        );
+        render(&map, &path, bounds, time)
+    };
+
+    frame(1024).save_png(&visualize::output_path(18, "path.png")?)?;
+
+    let blocked_time = find_blocked_time(
+        &start_position,
+        &target_position,
+        &map,
+        bounds,
+        0,
+        map.len(),
+    );
+    let mut animation = Animation::new(60);
+
+    for time in (0..blocked_time).step_by(32) {
+        animation.push(&frame(time));
+    }
+
+    let mut canvas = frame(blocked_time);
+    if let Some((position, _time)) = map
+        .iter()
+        .find(|(_position, fall_time)| **fall_time + 1 == blocked_time)
+    {
+        canvas.fill(position.x, position.y, RED);
     }
+    animation.push(&canvas);
+
+    animation.save_gif(&visualize::output_path(18, "falling.gif")?)?;
+
+    Ok(())
 }
 
-fn parse(input: &str) -> Map {
+fn parse(input: &str) -> Result<Map, PuzzleError> {
     let mut map: Map = HashMap::new();
 
-    for (time, line) in input.trim_end().lines().enumerate() {
-        let (x_string, y_string) =
-            line.split_once(",").expect("x,y coordinate");
-        let x = x_string.parse::<usize>().expect("integral x coordinate");
-        let y = y_string.parse::<usize>().expect("integral x coordinate");
+    for (time, line) in parsing::lines(input.trim_end()).iter().enumerate() {
+        let position = position(line).map_err(PuzzleError::on_day(DAY))?;
 
-        map.insert(Position { x, y }, time);
+        map.insert(position, time);
     }
 
-    map
+    Ok(map)
+}
+
+fn position(line: &SourceLine<'_>) -> parsing::Result<Position> {
+    let (x, y) = line.split_once(line.text, ",")?;
+
+    Ok(Position {
+        x: line.parse(x)?,
+        y: line.parse(y)?,
+    })
 }
 
 #[cfg(test)]
@@ -258,11 +369,31 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1_with_params(input(), 6, 12), 22);
+        assert_eq!(part1_with_params(input(), 6, 12).unwrap(), 22);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2_with_params(input(), 6), "6,1");
+        assert_eq!(part2_with_params(input(), 6).unwrap(), "6,1");
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        assert_eq!(
+            part1_with_params("1,1\n2;2\n", 6, 12)
+                .unwrap_err()
+                .to_string(),
+            "day 18, line 2: column 1, expected `,`"
+        );
+        assert_eq!(
+            part1_with_params("0,1\n1,0\n", 6, 2)
+                .unwrap_err()
+                .to_string(),
+            "day 18: no path reaches the exit after 2 bytes"
+        );
+        assert_eq!(
+            part2_with_params("1,1\n2,2\n", 6).unwrap_err().to_string(),
+            "day 18: no byte cuts off the exit"
+        );
     }
 }
@@ -1,11 +1,34 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, VecDeque};
 
+const DAY: u8 = 19;
+
 type Pattern = String;
 type Design = String;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (all_patterns, designs) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is a line of comma-separated towel patterns
+/// followed by designs.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (all_patterns, designs) = parse(input)?;
     let available_patterns = all_patterns
         .into_iter()
         .filter(|pattern| designs.iter().any(|design| design.contains(pattern)))
@@ -20,7 +43,7 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
 fn create(
@@ -56,9 +79,11 @@ fn create(
     None
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let (all_patterns, designs) = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let (all_patterns, designs) = parse(input)?;
 
     let available_patterns: Vec<Pattern> = all_patterns
         .into_iter()
@@ -72,7 +97,7 @@ pub fn part2(input: &str) -> usize {
         count += arrangement_count(design, &available_patterns, &mut cache);
     }
 
-    count
+    Ok(count)
 }
 
 fn arrangement_count(
@@ -115,16 +140,39 @@ fn arrangement_count(
         .sum()
 }
 
-fn parse(input: &str) -> (Vec<Pattern>, Vec<Design>) {
-    let (pattern_strings, design_strings) =
-        input.trim_end().split_once("\n\n").unwrap();
-    let mut patterns: Vec<Pattern> =
-        pattern_strings.split(", ").map(|s| s.into()).collect();
-    let designs = design_strings.lines().map(|s| s.into()).collect();
+fn parse(input: &str) -> Result<(Vec<Pattern>, Vec<Design>), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [pattern_lines, design_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected towel patterns followed by designs",
+        ));
+    };
+    let [pattern_line] = &pattern_lines[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected the towel patterns on one line",
+        ));
+    };
+
+    // An empty pattern would match forever without using up the design.
+    let mut patterns: Vec<Pattern> = pattern_line
+        .text
+        .split(',')
+        .map(|field| match field.trim() {
+            "" => Err(pattern_line.error(field, "expected a towel pattern")),
+            pattern => Ok(pattern.into()),
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    let designs = design_lines
+        .iter()
+        .map(|line| line.text.trim().into())
+        .collect();
 
     patterns.sort_by_key(|p| std::cmp::Reverse(p.len()));
 
-    (patterns, designs)
+    Ok((patterns, designs))
 }
 
 #[cfg(test)]
@@ -151,7 +199,7 @@ bbrgwb\n"
 
     #[test]
     fn part1_example() {
-        let (patterns, _) = parse(input());
+        let (patterns, _) = parse(input()).unwrap();
 
         // brwrr can be made with a br towel, then a wr towel, and then finally an r towel.
         assert_eq!(
@@ -204,7 +252,8 @@ bbrgwb\n"
 gwrgb, rwwr, rrgwbgg, rwgw, wwgu, rrwuww, wgrw, wbgw, gwrg, ugbr, uuru, bgu, brrur, wggbrb, rurrggw, rggb, wgww, bu, ubwug, bruuu, u, ggbrwgb, burgbub, wrbwguuu, wgb, burugbu, wwgr, uwb, grr, ugu, ggbuu, gbrb, guw, buuguu, rrbrrg, wbgurw, wurr, rgbr, uubu, gwr, gbrwu, ugrru, ugbur, urrrwgw, wrbgrw, rruwr, uwwurwu, wbgbbrb, rbwugu, wrr, wub, rggwg, rgw, ugwgbbr, gggg, uwuuwbww, wwwgw, uub, ugurwwgw, ubrg, rwww, grgu, uwu, gbu, gru, rwrwb, wrw, ubugrb, ruu, grw, wbrggg, wbwwr, wruburw, wurb, wwg, bwburg, gwrbg, guwgr, gwu, wwwuw, wwbrw, gruw, uwbggr, uww, gwb, wurrgg, uuww, rwgwgr, wubg, uuur, uwrrrwu, bbww, wgbwb, rrgg, grggwr, bb, ug, wbwr, rw, rbbru, bwbbub, bur, ggbg, gww, buruggrr, bgbw, rru, rur, brbbr, urug, ggrburu, rgb, bwrwrubu, uuugw, uwgwwb, wgu, wguwuww, ruw, gub, bbwb, ubbgrr, ggr, rug, brg, rwg, ubgwrgr, bwwbw, rww, bgruwuwu, uuw, guwr, urg, urr, gggbwu, brub, gruwub, gbbr, uru, wwur, grrrwbg, rr, guwwuw, ruwr, bubw, bgru, bbggb, wuwbrrg, w, rgr, rguu, wugr, rrbubrgg, wugrr, bru, ggguubr, brwgw, gwbbgbgr, bbu, brr, rubgu, burgrr, wwgw, rwu, rrgubgu, uwr, bguwbr, gw, gur, ruurb, wbur, urub, wrwbubg, ur, uggwr, bbbb, rrg, rgwg, rguw, rbwgr, uugb, grguwwgr, bgubw, bbg, rgbw, wgwrubrr, gwug, ubrugwr, uuwgur, bwurw, ggu, gubb, gbbub, gwg, gugw, r, wgg, wwbb, buubg, rgbwubug, urbb, bg, wwrur, rwr, ugug, bwg, rbb, bbwgru, gbr, urgwb, rbuwub, gbb, rwwgw, grb, wrbw, rrbbwr, rrrubb, rrggr, uugug, rwbr, rguwuuw, wwu, wruggur, wurbb, urrg, urgwrrr, uuu, wrggbg, www, rwrgg, wbguru, bwgr, wu, bbgr, guu, wbrgurw, rbg, ubgrrg, uggrw, wbbwgwbg, uur, brbrbggb, uwbrr, gwrr, ugw, wg, wrrurrb, uggb, bw, urgwg, wwrbwu, bwwbguw, bwb, brguu, ugrbr, bwrg, bbw, bugwu, gb, gugu, gwwurwu, rbw, wuuw, guug, ubb, wrg, uguw, brw, bgg, wrgr, wbrugb, gwgug, uwuurug, rrgu, brrrwu, wbr, uu, rwrru, uwrgugw, gwugu, gugrg, wgr, ggurb, wrur, wgwrug, bwuw, bbrguu, grru, bwur, grgbgbgr, rggub, ggb, wbb, uubbrw, wubb, rubrwb, gggrg, bwbb, uw, rbgwrg, wbgr, bggbb, rwb, buw, ubr, gg, rurrgu, gr, rrb, ugb, gbrrb, rub, rgubbbg, wuwg, wrgu, wbu, wbwgb, ruwbguu, gbbrgbu, urgbgb, bgr, buu, wbg, uruuw, bwuwugw, wwbrr, wguu, rubru, bbb, urb, bug, wgrbw, bwu, bgur, gu, ubgg, gbbw, uguu, rwrb, wru, bubrwu, wwr, wuwub, bbr, uug, burw, uwg, rurwbbr, wbw, wbwb, wuw, wur, brbwr, wr, wbrwug, ugrgwu, wb, ubu, ggg, brb, wwb, ubg, gug, urbuwg, rgg, b, br, wrrbbw, ggbbg, rrggw, rbu, brbrw, rbwrgb, ubw, wuu, gbwur, gbg, bbbggubb, wguuub, grrbwu, gwwgb, rbwru, ub, gwbub, burgu, wgw, uurbwbw, brrw, rb, brrbbbu, bggg, rugr, ugg, ggw, gwgwr, grurw, bwrgbw, bbgwuuw, rgbwwu, rrrbg, bggu, brwb, bbbgur, ruubrb, wubgrru, brrg, uubbr, ubbrugrw, gubrurwr, rrw, wrb, rgwbw, uubgru, wrwg, bgw, ruuw, gbwrg, ww, rrrbw, gurbrr, ruuug, wrrr, ugr, wubbrwbg, gbwrwrgb, bugr, gruuwg, rrr, bubg, bgbrwr, wug, rgu, buugwb, grubgr, grur, gbw, wwrgb, bgwub, rbr, rrwb, gwuwrb, rwwbbrr, grurbbgr, wrwubb, urbrwug, bbrw, ubbb, bub
 
 unused
-");
+")
+        .unwrap();
 
         assert_eq!(
             create(
@@ -219,7 +268,7 @@ unused
 
     #[test]
     fn part2_example() {
-        let (patterns, _) = parse(input());
+        let (patterns, _) = parse(input()).unwrap();
         let cache = &mut HashMap::new();
 
         assert_eq!(arrangement_count("brwrr".into(), &patterns, cache), 2);
@@ -235,7 +284,8 @@ unused
 gwrgb, rwwr, rrgwbgg, rwgw, wwgu, rrwuww, wgrw, wbgw, gwrg, ugbr, uuru, bgu, brrur, wggbrb, rurrggw, rggb, wgww, bu, ubwug, bruuu, u, ggbrwgb, burgbub, wrbwguuu, wgb, burugbu, wwgr, uwb, grr, ugu, ggbuu, gbrb, guw, buuguu, rrbrrg, wbgurw, wurr, rgbr, uubu, gwr, gbrwu, ugrru, ugbur, urrrwgw, wrbgrw, rruwr, uwwurwu, wbgbbrb, rbwugu, wrr, wub, rggwg, rgw, ugwgbbr, gggg, uwuuwbww, wwwgw, uub, ugurwwgw, ubrg, rwww, grgu, uwu, gbu, gru, rwrwb, wrw, ubugrb, ruu, grw, wbrggg, wbwwr, wruburw, wurb, wwg, bwburg, gwrbg, guwgr, gwu, wwwuw, wwbrw, gruw, uwbggr, uww, gwb, wurrgg, uuww, rwgwgr, wubg, uuur, uwrrrwu, bbww, wgbwb, rrgg, grggwr, bb, ug, wbwr, rw, rbbru, bwbbub, bur, ggbg, gww, buruggrr, bgbw, rru, rur, brbbr, urug, ggrburu, rgb, bwrwrubu, uuugw, uwgwwb, wgu, wguwuww, ruw, gub, bbwb, ubbgrr, ggr, rug, brg, rwg, ubgwrgr, bwwbw, rww, bgruwuwu, uuw, guwr, urg, urr, gggbwu, brub, gruwub, gbbr, uru, wwur, grrrwbg, rr, guwwuw, ruwr, bubw, bgru, bbggb, wuwbrrg, w, rgr, rguu, wugr, rrbubrgg, wugrr, bru, ggguubr, brwgw, gwbbgbgr, bbu, brr, rubgu, burgrr, wwgw, rwu, rrgubgu, uwr, bguwbr, gw, gur, ruurb, wbur, urub, wrwbubg, ur, uggwr, bbbb, rrg, rgwg, rguw, rbwgr, uugb, grguwwgr, bgubw, bbg, rgbw, wgwrubrr, gwug, ubrugwr, uuwgur, bwurw, ggu, gubb, gbbub, gwg, gugw, r, wgg, wwbb, buubg, rgbwubug, urbb, bg, wwrur, rwr, ugug, bwg, rbb, bbwgru, gbr, urgwb, rbuwub, gbb, rwwgw, grb, wrbw, rrbbwr, rrrubb, rrggr, uugug, rwbr, rguwuuw, wwu, wruggur, wurbb, urrg, urgwrrr, uuu, wrggbg, www, rwrgg, wbguru, bwgr, wu, bbgr, guu, wbrgurw, rbg, ubgrrg, uggrw, wbbwgwbg, uur, brbrbggb, uwbrr, gwrr, ugw, wg, wrrurrb, uggb, bw, urgwg, wwrbwu, bwwbguw, bwb, brguu, ugrbr, bwrg, bbw, bugwu, gb, gugu, gwwurwu, rbw, wuuw, guug, ubb, wrg, uguw, brw, bgg, wrgr, wbrugb, gwgug, uwuurug, rrgu, brrrwu, wbr, uu, rwrru, uwrgugw, gwugu, gugrg, wgr, ggurb, wrur, wgwrug, bwuw, bbrguu, grru, bwur, grgbgbgr, rggub, ggb, wbb, uubbrw, wubb, rubrwb, gggrg, bwbb, uw, rbgwrg, wbgr, bggbb, rwb, buw, ubr, gg, rurrgu, gr, rrb, ugb, gbrrb, rub, rgubbbg, wuwg, wrgu, wbu, wbwgb, ruwbguu, gbbrgbu, urgbgb, bgr, buu, wbg, uruuw, bwuwugw, wwbrr, wguu, rubru, bbb, urb, bug, wgrbw, bwu, bgur, gu, ubgg, gbbw, uguu, rwrb, wru, bubrwu, wwr, wuwub, bbr, uug, burw, uwg, rurwbbr, wbw, wbwb, wuw, wur, brbwr, wr, wbrwug, ugrgwu, wb, ubu, ggg, brb, wwb, ubg, gug, urbuwg, rgg, b, br, wrrbbw, ggbbg, rrggw, rbu, brbrw, rbwrgb, ubw, wuu, gbwur, gbg, bbbggubb, wguuub, grrbwu, gwwgb, rbwru, ub, gwbub, burgu, wgw, uurbwbw, brrw, rb, brrbbbu, bggg, rugr, ugg, ggw, gwgwr, grurw, bwrgbw, bbgwuuw, rgbwwu, rrrbg, bggu, brwb, bbbgur, ruubrb, wubgrru, brrg, uubbr, ubbrugrw, gubrurwr, rrw, wrb, rgwbw, uubgru, wrwg, bgw, ruuw, gbwrg, ww, rrrbw, gurbrr, ruuug, wrrr, ugr, wubbrwbg, gbwrwrgb, bugr, gruuwg, rrr, bubg, bgbrwr, wug, rgu, buugwb, grubgr, grur, gbw, wwrgb, bgwub, rbr, rrwb, gwuwrb, rwwbbrr, grurbbgr, wrwubb, urbrwug, bbrw, ubbb, bub
 
 unused
-");
+")
+        .unwrap();
         assert_eq!(
             arrangement_count(
                 "wuwgbububbrgbbuurbrrrbbrwgbwgbubrubbbubwgwubbwbbrrgrwbrwgrg"
@@ -248,4 +298,18 @@ unused
 
         assert_eq!(part2(input()), 16);
     }
+
+    #[test]
+    fn invalid_towels() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("r, wr, , b\n\nbwr\n"),
+            "day 19, line 1: column 7, expected a towel pattern"
+        );
+        assert_eq!(
+            error("r, wr, b\nbwr\n"),
+            "day 19: expected towel patterns followed by designs"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use priority_queue::PriorityQueue;
 use std::{
     cmp::Reverse,
@@ -19,19 +21,54 @@ impl std::fmt::Debug for Position {
 type Path = Vec<Position>;
 type Walls = HashSet<Position>;
 
+const DAY: u8 = 20;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the racetrack is a rectangle of `#` and `.` with a single
+/// start `S` and end `E`, closed in by walls, or when no path reaches the
+/// end.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     part1_with_params(input, 100)
 }
 
-pub fn part1_with_params(input: &str, minimum_savings: usize) -> usize {
-    let (walls, start_position, target_position) = parse(input);
-    let path = shortest_path(&start_position, &target_position, &walls);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    part2_with_params(input, 100)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn part1_with_params(
+    input: &str,
+    minimum_savings: usize,
+) -> Result<usize, PuzzleError> {
+    let (walls, start_position, target_position) = parse(input)?;
+    let path = shortest_path(&start_position, &target_position, &walls)?;
     let mut count: usize = 0;
 
     for (index, position) in path.iter().enumerate() {
         for target_index in (index + 1)..path.len() {
-            let target = path.get(target_index).unwrap();
+            let target = &path[target_index];
             let distance = position.col.abs_diff(target.col)
                 + position.row.abs_diff(target.row);
 
@@ -50,23 +87,21 @@ pub fn part1_with_params(input: &str, minimum_savings: usize) -> usize {
         }
     }
 
-    count
-}
-
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    part2_with_params(input, 100)
+    Ok(count)
 }
 
-fn part2_with_params(input: &str, minimum_savings: usize) -> usize {
-    let (walls, start_position, target_position) = parse(input);
-    let path = shortest_path(&start_position, &target_position, &walls);
+fn part2_with_params(
+    input: &str,
+    minimum_savings: usize,
+) -> Result<usize, PuzzleError> {
+    let (walls, start_position, target_position) = parse(input)?;
+    let path = shortest_path(&start_position, &target_position, &walls)?;
     let mut count: usize = 0;
     let mut count_by_time_saved: HashMap<usize, usize> = HashMap::new();
 
     for (index, position) in path.iter().enumerate() {
         for target_index in (index + 1)..path.len() {
-            let target = path.get(target_index).unwrap();
+            let target = &path[target_index];
             let distance = position.col.abs_diff(target.col)
                 + position.row.abs_diff(target.row);
 
@@ -88,14 +123,14 @@ fn part2_with_params(input: &str, minimum_savings: usize) -> usize {
 
     //    dbg!(&count_by_time_saved);
 
-    count
+    Ok(count)
 }
 
 fn shortest_path(
     start_position: &Position,
     target_position: &Position,
     walls: &Walls,
-) -> Path {
+) -> Result<Path, PuzzleError> {
     let mut frontier: PriorityQueue<Position, Reverse<usize>> =
         PriorityQueue::new();
     let mut came_from: HashMap<Position, Position> = HashMap::new();
@@ -131,7 +166,11 @@ fn shortest_path(
         current_position = previous_position;
     }
 
-    positions.into_iter().rev().collect()
+    if current_position != start_position {
+        return Err(PuzzleError::new(DAY, "no path reaches the end `E`"));
+    }
+
+    Ok(positions.into_iter().rev().collect())
 }
 
 fn neighbors(
@@ -174,34 +213,58 @@ fn print(walls: &Walls, path: &Path) {
     println!();
 }
 
-fn parse(input: &str) -> (HashSet<Position>, Position, Position) {
+// Paths only ever stay inside the racetrack when walls close it in.
+fn parse(input: &str) -> Result<(Walls, Position, Position), PuzzleError> {
     let mut walls = HashSet::new();
-    let mut start_position: Option<Position> = None;
-    let mut end_position: Option<Position> = None;
+    let mut start_positions: Vec<Position> = Vec::new();
+    let mut end_positions: Vec<Position> = Vec::new();
+    let lines = parsing::lines(input);
+    let rows = lines.len();
+    let cols = lines.first().map_or(0, |line| line.text.chars().count());
+
+    for (row, line) in lines.iter().enumerate() {
+        if line.text.chars().count() != cols {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {cols} positions")),
+            ));
+        }
+
+        for (col, (index, char)) in line.text.char_indices().enumerate() {
+            let edge =
+                row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+
+            if edge && char != '#' {
+                return Err(PuzzleError::on_day(DAY)(line.error(
+                    &line.text[index..],
+                    "expected walls `#` around the racetrack",
+                )));
+            }
 
-    for (row, line) in input.lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
             match char {
                 '#' => {
                     walls.insert(Position { row, col });
                 }
                 '.' => {}
-                'S' => {
-                    start_position = Some(Position { row, col });
-                }
-                'E' => {
-                    end_position = Some(Position { row, col });
+                'S' => start_positions.push(Position { row, col }),
+                'E' => end_positions.push(Position { row, col }),
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `#`, `.`, `S` or `E`",
+                    )))
                 }
-                _ => panic!("invalid character seen: {}", char),
             }
         }
     }
 
-    (
-        walls,
-        start_position.expect("start position"),
-        end_position.expect("end position"),
-    )
+    let [start_position] = &start_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single start `S`"));
+    };
+    let [end_position] = &end_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single end `E`"));
+    };
+
+    Ok((walls, start_position.clone(), end_position.clone()))
 }
 
 #[cfg(test)]
@@ -229,11 +292,37 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1_with_params(input(), 1), 44);
+        assert_eq!(part1_with_params(input(), 1).unwrap(), 44);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2_with_params(input(), 50), 285);
+        assert_eq!(part2_with_params(input(), 50).unwrap(), 285);
+    }
+
+    #[test]
+    fn invalid_racetracks() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("#####\n#S.E#\n####\n"),
+            "day 20, line 3: column 1, expected 5 positions"
+        );
+        assert_eq!(
+            error("#####\n#S.E.\n#####\n"),
+            "day 20, line 2: column 5, expected walls `#` around the racetrack"
+        );
+        assert_eq!(
+            error("#####\n#SxE#\n#####\n"),
+            "day 20, line 2: column 3, expected `#`, `.`, `S` or `E`"
+        );
+        assert_eq!(
+            error("#####\n#E.E#\n#####\n"),
+            "day 20: expected a single start `S`"
+        );
+        assert_eq!(
+            error("#####\n#S#E#\n#####\n"),
+            "day 20: no path reaches the end `E`"
+        );
     }
 }
@@ -1,7 +1,11 @@
-use std::{collections::HashMap, io::Write};
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+use std::collections::HashMap;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum KeypadButton {
+const DAY: u8 = 21;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum KeypadButton {
     ONE,
     TWO,
     THREE,
@@ -35,32 +39,6 @@ impl std::fmt::Display for KeypadButton {
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct KeypadSequence(Vec<KeypadButton>);
-
-impl From<String> for KeypadSequence {
-    fn from(input: String) -> Self {
-        Self(input.chars().map(|ch| KeypadButton::from(ch)).collect())
-    }
-}
-
-impl From<&str> for KeypadSequence {
-    fn from(input: &str) -> Self {
-        Self(input.chars().map(|ch| KeypadButton::from(ch)).collect())
-    }
-}
-
-impl Iterator for KeypadSequence {
-    type Item = KeypadButton;
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.0.is_empty() {
-            Some(self.0.remove(0))
-        } else {
-            None
-        }
-    }
-}
-
 impl From<char> for KeypadButton {
     fn from(ch: char) -> Self {
         match ch {
@@ -75,13 +53,13 @@ impl From<char> for KeypadButton {
             '8' => KeypadButton::EIGHT,
             '9' => KeypadButton::NINE,
             'A' => KeypadButton::APPLY,
-            _ => panic!("Invalid keypad button: {}", ch),
+            _ => panic!("Invalid keypad button: {ch}"),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum DirectionButton {
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DirectionButton {
     UP,
     DOWN,
     LEFT,
@@ -103,421 +81,297 @@ impl std::fmt::Display for DirectionButton {
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct DirectionSequence(Vec<DirectionButton>);
-
-impl std::fmt::Display for DirectionSequence {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.0
-                .iter()
-                .map(|direction_button| format!("{}", direction_button))
-                .collect::<Vec<String>>()
-                .join("")
-        )
+impl From<char> for DirectionButton {
+    fn from(ch: char) -> Self {
+        match ch {
+            '^' => DirectionButton::UP,
+            'v' => DirectionButton::DOWN,
+            '<' => DirectionButton::LEFT,
+            '>' => DirectionButton::RIGHT,
+            'A' => DirectionButton::APPLY,
+            _ => panic!("Invalid direction button: {ch}"),
+        }
     }
 }
 
-impl From<&str> for DirectionSequence {
-    fn from(input: &str) -> Self {
-        Self(
-            input
-                .chars()
-                .map(|ch| match ch {
-                    '^' => DirectionButton::UP,
-                    'v' => DirectionButton::DOWN,
-                    '<' => DirectionButton::LEFT,
-                    '>' => DirectionButton::RIGHT,
-                    'A' => DirectionButton::APPLY,
-                    _ => panic!("Invalid direction button: {}", ch),
-                })
-                .collect(),
-        )
-    }
+// A pad of buttons laid out on a grid, with (row, column) positions and one
+// empty square that robot arms must never pass over.
+pub trait Pad: Copy + Eq + std::hash::Hash {
+    const GAP: (i8, i8);
+
+    fn position(self) -> (i8, i8);
 }
 
-impl From<KeypadSequence> for DirectionSequence {
-    fn from(keypad_sequence: KeypadSequence) -> Self {
-        let mut current_button = KeypadButton::APPLY;
-        let mut moves: Vec<DirectionButton> = Vec::new();
+// +---+---+---+
+// | 7 | 8 | 9 |
+// +---+---+---+
+// | 4 | 5 | 6 |
+// +---+---+---+
+// | 1 | 2 | 3 |
+// +---+---+---+
+//     | 0 | A |
+//     +---+---+
+impl Pad for KeypadButton {
+    const GAP: (i8, i8) = (3, 0);
+
+    fn position(self) -> (i8, i8) {
+        use KeypadButton::{
+            APPLY, EIGHT, FIVE, FOUR, NINE, ONE, SEVEN, SIX, THREE, TWO, ZERO,
+        };
 
-        for keypad_button in keypad_sequence.into_iter() {
-            moves.append(&mut keypad_path(&current_button, &keypad_button));
-            moves.push(DirectionButton::APPLY);
-            current_button = keypad_button;
+        match self {
+            SEVEN => (0, 0),
+            EIGHT => (0, 1),
+            NINE => (0, 2),
+            FOUR => (1, 0),
+            FIVE => (1, 1),
+            SIX => (1, 2),
+            ONE => (2, 0),
+            TWO => (2, 1),
+            THREE => (2, 2),
+            ZERO => (3, 1),
+            APPLY => (3, 2),
         }
-
-        Self(moves)
     }
 }
 
-impl Iterator for DirectionSequence {
-    type Item = DirectionButton;
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.0.is_empty() {
-            Some(self.0.remove(0))
-        } else {
-            None
-        }
-    }
-}
+//     +---+---+
+//     | ^ | A |
+// +---+---+---+
+// | < | v | > |
+// +---+---+---+
+impl Pad for DirectionButton {
+    const GAP: (i8, i8) = (0, 0);
+
+    fn position(self) -> (i8, i8) {
+        use DirectionButton::{APPLY, DOWN, LEFT, RIGHT, UP};
 
-impl DirectionSequence {
-    fn len(&self) -> usize {
-        self.0.len()
+        match self {
+            UP => (0, 1),
+            APPLY => (0, 2),
+            LEFT => (1, 0),
+            DOWN => (1, 1),
+            RIGHT => (1, 2),
+        }
     }
 }
 
-#[must_use]
-pub fn part1(input: &str) -> usize {
-    let codes: Vec<String> = parse(input);
-
-    //    let buttons = [
-    //        KeypadButton::ZERO,
-    //        KeypadButton::ONE,
-    //        KeypadButton::TWO,
-    //        KeypadButton::THREE,
-    //        KeypadButton::FOUR,
-    //        KeypadButton::FIVE,
-    //        KeypadButton::SIX,
-    //        KeypadButton::SEVEN,
-    //        KeypadButton::EIGHT,
-    //        KeypadButton::NINE,
-    //        KeypadButton::APPLY,
-    //    ];
-
-    //    use itertools::Itertools;
-    //
-    //    for from_button in &buttons {
-    //        for to_button in &buttons {
-    //            let path = keypad_path(from_button, to_button);
-    //            let baseline = foo(path.clone());
-    //
-    //            for iteration in path.iter().permutations(path.len()) {
-    //                let test =
-    //                    foo(iteration.clone().into_iter().cloned().collect());
-    //                if test < baseline {
-    //                    println!(
-    //                        "{:?} to {:?} is shorter via {:?} ({} vs {})",
-    //                        from_button, to_button, iteration, baseline, test
-    //                    );
-    //                }
-    //            }
-    //        }
-    //    }
-
-    codes
-        .iter()
-        .map(|code| {
-            let encoded = encode(code);
-            encoded.len() * code[0..3].parse::<usize>().unwrap()
-        })
-        .sum()
-}
+// The only moves worth considering between two buttons: all horizontal
+// presses then all vertical ones, or the other way round, skipping an order
+// that would sweep over the gap. Each ends with APPLY.
+fn paths<B: Pad>(from: B, to: B) -> Vec<Vec<DirectionButton>> {
+    use DirectionButton::{APPLY, DOWN, LEFT, RIGHT, UP};
 
-fn parse(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_owned()).collect()
-}
+    let ((from_row, from_column), (to_row, to_column)) =
+        (from.position(), to.position());
+    let vertical = if to_row > from_row { DOWN } else { UP };
+    let horizontal = if to_column > from_column { RIGHT } else { LEFT };
+    let vertical_moves = vec![vertical; from_row.abs_diff(to_row).into()];
+    let horizontal_moves =
+        vec![horizontal; from_column.abs_diff(to_column).into()];
 
-#[allow(dead_code)]
-fn foo(mut moves: Vec<DirectionButton>, times: usize) -> usize {
-    moves.push(DirectionButton::APPLY);
-    let mut iteration = DirectionSequence(moves);
+    let mut paths = vec![];
 
-    for _ in 0..times {
-        iteration = iterate(iteration);
+    if (from_row, to_column) != B::GAP {
+        paths.push([&horizontal_moves[..], &vertical_moves, &[APPLY]].concat());
+    }
+    if (to_row, from_column) != B::GAP {
+        paths.push([&vertical_moves[..], &horizontal_moves, &[APPLY]].concat());
     }
+    paths.dedup();
 
-    iteration.len()
+    paths
 }
 
-fn encode(code: &str) -> DirectionSequence {
-    //    println!("encode: {}", code);
-
-    let keypad_sequence = KeypadSequence::from(code);
-    //    println!("keypad_sequence = {:?}", &keypad_sequence);
+// Counts the buttons a human presses when `robots` robots on direction pads
+// stand between them and the door's numeric keypad. Costs of moving between
+// two direction buttons are memoized by depth, so any chain length is cheap.
+#[derive(Default)]
+pub struct CostModel {
+    cache: HashMap<(DirectionButton, DirectionButton, usize), usize>,
+}
 
-    let first_iteration = DirectionSequence::from(keypad_sequence);
-    //    println!("first_iteration = {}", &first_iteration);
+impl CostModel {
+    // Presses needed for the pad `depth` levels above the human to move from
+    // `from` to `to` and press it. The human's own pad is depth zero.
+    pub fn direction_cost(
+        &mut self,
+        from: DirectionButton,
+        to: DirectionButton,
+        depth: usize,
+    ) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        if let Some(&cost) = self.cache.get(&(from, to, depth)) {
+            return cost;
+        }
 
-    let second_iteration = iterate(first_iteration);
-    //    println!("second_iteration = {}", &second_iteration);
+        let cost = paths(from, to)
+            .iter()
+            .map(|path| self.sequence_cost(path, depth - 1))
+            .fold(usize::MAX, usize::min);
+        self.cache.insert((from, to, depth), cost);
 
-    let final_iteration = iterate(second_iteration);
-    //    println!("final_iteration = {}", &final_iteration);
+        cost
+    }
 
-    final_iteration
-}
+    // Presses needed to type `sequence` on the pad at `depth`, whose arm
+    // starts and (since every sequence ends in APPLY) finishes on APPLY.
+    pub fn sequence_cost(
+        &mut self,
+        sequence: &[DirectionButton],
+        depth: usize,
+    ) -> usize {
+        let mut current = DirectionButton::APPLY;
+
+        sequence
+            .iter()
+            .map(|&button| {
+                let cost = self.direction_cost(current, button, depth);
+                current = button;
+                cost
+            })
+            .sum()
+    }
 
-fn encode_extra(
-    code: &str,
-    cache: &HashMap<KeypadSequence, usize>,
-) -> DirectionSequence {
-    let keypad_sequence = KeypadSequence::from(code);
-    let mut iteration = DirectionSequence::from(keypad_sequence);
+    pub fn keypad_cost(
+        &mut self,
+        from: KeypadButton,
+        to: KeypadButton,
+        robots: usize,
+    ) -> usize {
+        paths(from, to)
+            .iter()
+            .map(|path| self.sequence_cost(path, robots))
+            .fold(usize::MAX, usize::min)
+    }
 
-    for _ in 0..25 {
-        iteration = iterate(iteration);
+    pub fn code_cost(&mut self, code: &str, robots: usize) -> usize {
+        let mut current = KeypadButton::APPLY;
+
+        code.chars()
+            .map(|ch| {
+                let button = KeypadButton::from(ch);
+                let cost = self.keypad_cost(current, button, robots);
+                current = button;
+                cost
+            })
+            .sum()
     }
 
-    iteration
-}
+    // An optimal sequence of presses for the human to type `code`. Its length
+    // is `code_cost`, which grows exponentially with `robots`, so this is for
+    // small chains only.
+    pub fn reconstruct(&mut self, code: &str, robots: usize) -> String {
+        let mut current = KeypadButton::APPLY;
+        let mut presses = vec![];
 
-fn iterate(direction_sequence: DirectionSequence) -> DirectionSequence {
-    let mut current_button = DirectionButton::APPLY;
-    let mut moves: Vec<DirectionButton> = Vec::new();
+        for ch in code.chars() {
+            let button = KeypadButton::from(ch);
+            let path = self.cheapest(paths(current, button), robots);
 
-    //    println!("{:?}\n", direction_sequence);
+            presses.extend(self.expand(&path, robots));
+            current = button;
+        }
 
-    for direction_button in direction_sequence.into_iter() {
-        let mut path = direction_path(&current_button, &direction_button);
+        presses.iter().map(ToString::to_string).collect()
+    }
 
-        //        println!("{:?} => {:?}: {:?}", current_button, direction_button, path);
+    fn expand(
+        &mut self,
+        sequence: &[DirectionButton],
+        depth: usize,
+    ) -> Vec<DirectionButton> {
+        if depth == 0 {
+            return sequence.to_vec();
+        }
 
-        moves.append(&mut path);
-        moves.push(DirectionButton::APPLY);
+        let mut current = DirectionButton::APPLY;
+        let mut presses = vec![];
 
-        current_button = direction_button;
-    }
+        for &button in sequence {
+            let path = self.cheapest(paths(current, button), depth - 1);
 
-    //    println!("\n");
+            presses.extend(self.expand(&path, depth - 1));
+            current = button;
+        }
 
-    DirectionSequence(moves)
-}
+        presses
+    }
 
-fn keypad_path(from: &KeypadButton, to: &KeypadButton) -> Vec<DirectionButton> {
-    use DirectionButton::{DOWN, LEFT, RIGHT, UP};
-    use KeypadButton::*;
-
-    // +---+---+---+
-    // | 7 | 8 | 9 |
-    // +---+---+---+
-    // | 4 | 5 | 6 |
-    // +---+---+---+
-    // | 1 | 2 | 3 |
-    // +---+---+---+
-    //     | 0 | A |
-    //     +---+---+
-
-    match (from, to) {
-        (ZERO, APPLY) => vec![RIGHT],
-        (ZERO, ONE) => vec![UP, LEFT],
-        (ZERO, TWO) => vec![UP],
-        (ZERO, THREE) => vec![UP, RIGHT],
-        (ZERO, FOUR) => vec![UP, UP, LEFT],
-        (ZERO, FIVE) => vec![UP, UP],
-        (ZERO, SIX) => vec![UP, UP, RIGHT],
-        (ZERO, SEVEN) => vec![UP, UP, UP, LEFT],
-        (ZERO, EIGHT) => vec![UP, UP, UP],
-        (ZERO, NINE) => vec![UP, UP, UP, RIGHT],
-
-        (APPLY, ZERO) => vec![LEFT],
-        (APPLY, ONE) => vec![UP, LEFT, LEFT],
-        (APPLY, TWO) => vec![LEFT, UP],
-        (APPLY, THREE) => vec![UP],
-        (APPLY, FOUR) => vec![UP, UP, LEFT, LEFT],
-        (APPLY, FIVE) => vec![LEFT, UP, UP],
-        (APPLY, SIX) => vec![UP, UP],
-        (APPLY, SEVEN) => vec![UP, UP, UP, LEFT, LEFT],
-        (APPLY, EIGHT) => vec![LEFT, UP, UP, UP],
-        (APPLY, NINE) => vec![UP, UP, UP],
-
-        (ONE, APPLY) => vec![DOWN, RIGHT, RIGHT],
-        (ONE, ZERO) => vec![DOWN, RIGHT],
-        (ONE, TWO) => vec![RIGHT],
-        (ONE, THREE) => vec![RIGHT, RIGHT],
-        (ONE, FOUR) => vec![UP],
-        (ONE, FIVE) => vec![UP, RIGHT],
-        (ONE, SIX) => vec![UP, RIGHT, RIGHT],
-        (ONE, SEVEN) => vec![UP, UP],
-        (ONE, EIGHT) => vec![UP, UP, RIGHT],
-        (ONE, NINE) => vec![UP, UP, RIGHT, RIGHT],
-
-        (TWO, APPLY) => vec![DOWN, RIGHT],
-        (TWO, ZERO) => vec![DOWN],
-        (TWO, ONE) => vec![LEFT],
-        (TWO, THREE) => vec![RIGHT],
-        (TWO, FOUR) => vec![LEFT, UP],
-        (TWO, FIVE) => vec![UP],
-        (TWO, SIX) => vec![UP, RIGHT],
-        (TWO, SEVEN) => vec![LEFT, UP, UP],
-        (TWO, EIGHT) => vec![UP, UP],
-        (TWO, NINE) => vec![RIGHT, UP, UP],
-
-        (THREE, APPLY) => vec![DOWN],
-        (THREE, ZERO) => vec![LEFT, DOWN],
-        (THREE, ONE) => vec![LEFT, LEFT],
-        (THREE, TWO) => vec![LEFT],
-        (THREE, FOUR) => vec![LEFT, LEFT, UP],
-        (THREE, FIVE) => vec![LEFT, UP],
-        (THREE, SIX) => vec![UP],
-        (THREE, SEVEN) => vec![LEFT, LEFT, UP, UP],
-        (THREE, EIGHT) => vec![LEFT, UP, UP],
-        (THREE, NINE) => vec![UP, UP],
-
-        (FOUR, APPLY) => vec![RIGHT, RIGHT, DOWN, DOWN],
-        (FOUR, ZERO) => vec![DOWN, DOWN, RIGHT],
-        (FOUR, ONE) => vec![DOWN],
-        (FOUR, TWO) => vec![DOWN, RIGHT],
-        (FOUR, THREE) => vec![DOWN, RIGHT, RIGHT],
-        (FOUR, FIVE) => vec![RIGHT],
-        (FOUR, SIX) => vec![RIGHT, RIGHT],
-        (FOUR, SEVEN) => vec![UP],
-        (FOUR, EIGHT) => vec![UP, RIGHT],
-        (FOUR, NINE) => vec![UP, RIGHT, RIGHT],
-
-        (FIVE, APPLY) => vec![DOWN, DOWN, RIGHT],
-        (FIVE, ZERO) => vec![DOWN, DOWN],
-        (FIVE, ONE) => vec![LEFT, DOWN],
-        (FIVE, TWO) => vec![DOWN],
-        (FIVE, THREE) => vec![DOWN, RIGHT],
-        (FIVE, FOUR) => vec![LEFT],
-        (FIVE, SIX) => vec![RIGHT],
-        (FIVE, SEVEN) => vec![LEFT, UP],
-        (FIVE, EIGHT) => vec![UP],
-        (FIVE, NINE) => vec![RIGHT, UP],
-
-        (SIX, APPLY) => vec![DOWN, DOWN],
-        (SIX, ZERO) => vec![LEFT, DOWN, DOWN],
-        (SIX, ONE) => vec![LEFT, LEFT, DOWN],
-        (SIX, TWO) => vec![LEFT, DOWN],
-        (SIX, THREE) => vec![DOWN],
-        (SIX, FOUR) => vec![LEFT, LEFT],
-        (SIX, FIVE) => vec![LEFT],
-        (SIX, SEVEN) => vec![LEFT, LEFT, UP],
-        (SIX, EIGHT) => vec![LEFT, UP],
-        (SIX, NINE) => vec![UP],
-
-        (SEVEN, APPLY) => vec![RIGHT, RIGHT, DOWN, DOWN, DOWN],
-        (SEVEN, ZERO) => vec![RIGHT, DOWN, DOWN, DOWN],
-        (SEVEN, ONE) => vec![DOWN, DOWN],
-        (SEVEN, TWO) => vec![DOWN, DOWN, RIGHT],
-        (SEVEN, THREE) => vec![DOWN, DOWN, RIGHT, RIGHT],
-        (SEVEN, FOUR) => vec![DOWN],
-        (SEVEN, FIVE) => vec![DOWN, RIGHT],
-        (SEVEN, SIX) => vec![DOWN, RIGHT, RIGHT],
-        (SEVEN, EIGHT) => vec![RIGHT],
-        (SEVEN, NINE) => vec![RIGHT, RIGHT],
-
-        (EIGHT, APPLY) => vec![DOWN, DOWN, DOWN, RIGHT],
-        (EIGHT, ZERO) => vec![DOWN, DOWN, DOWN],
-        (EIGHT, ONE) => vec![LEFT, DOWN, DOWN],
-        (EIGHT, TWO) => vec![DOWN, DOWN],
-        (EIGHT, THREE) => vec![DOWN, DOWN, RIGHT],
-        (EIGHT, FOUR) => vec![LEFT, DOWN],
-        (EIGHT, FIVE) => vec![DOWN],
-        (EIGHT, SIX) => vec![DOWN, RIGHT],
-        (EIGHT, SEVEN) => vec![LEFT],
-        (EIGHT, NINE) => vec![RIGHT],
-
-        (NINE, APPLY) => vec![DOWN, DOWN, DOWN],
-        (NINE, ZERO) => vec![LEFT, DOWN, DOWN, DOWN],
-        (NINE, ONE) => vec![LEFT, LEFT, DOWN, DOWN],
-        (NINE, TWO) => vec![LEFT, DOWN, DOWN],
-        (NINE, THREE) => vec![DOWN, DOWN],
-        (NINE, FOUR) => vec![LEFT, LEFT, DOWN],
-        (NINE, FIVE) => vec![LEFT, DOWN],
-        (NINE, SIX) => vec![DOWN],
-        (NINE, SEVEN) => vec![LEFT, LEFT],
-        (NINE, EIGHT) => vec![LEFT],
-
-        (from, to) if from == to => vec![],
-
-        _ => panic!("Invalid transition from {:?} to {:?}", from, to),
+    fn cheapest(
+        &mut self,
+        paths: Vec<Vec<DirectionButton>>,
+        depth: usize,
+    ) -> Vec<DirectionButton> {
+        paths
+            .into_iter()
+            .min_by_key(|path| self.sequence_cost(path, depth))
+            .unwrap_or_default()
     }
 }
 
-fn direction_path(
-    from: &DirectionButton,
-    to: &DirectionButton,
-) -> Vec<DirectionButton> {
-    use DirectionButton::*;
-
-    //     +---+---+
-    //     | ^ | A |
-    // +---+---+---+
-    // | < | v | > |
-    // +---+---+---+
-
-    match (from, to) {
-        (APPLY, UP) => vec![LEFT],
-        (APPLY, DOWN) => vec![LEFT, DOWN],
-        (APPLY, LEFT) => vec![DOWN, LEFT, LEFT],
-        (APPLY, RIGHT) => vec![DOWN],
-
-        (UP, APPLY) => vec![RIGHT],
-        (UP, DOWN) => vec![DOWN],
-        (UP, LEFT) => vec![DOWN, LEFT],
-        (UP, RIGHT) => vec![DOWN, RIGHT],
-
-        (LEFT, APPLY) => vec![RIGHT, RIGHT, UP],
-        (LEFT, UP) => vec![RIGHT, UP],
-        (LEFT, DOWN) => vec![RIGHT],
-        (LEFT, RIGHT) => vec![RIGHT, RIGHT],
-
-        (DOWN, APPLY) => vec![RIGHT, UP],
-        (DOWN, UP) => vec![UP],
-        (DOWN, LEFT) => vec![LEFT],
-        (DOWN, RIGHT) => vec![RIGHT],
-
-        (RIGHT, APPLY) => vec![UP],
-        (RIGHT, UP) => vec![LEFT, UP],
-        (RIGHT, DOWN) => vec![LEFT],
-        (RIGHT, LEFT) => vec![LEFT, LEFT],
-
-        (from, to) if from == to => vec![],
-
-        _ => panic!("Invalid transition from {:?} to {:?}", from, to),
-    }
+/// # Errors
+///
+/// Fails unless every code is a number followed by `A`.
+pub fn complexity(input: &str, robots: usize) -> Result<usize, PuzzleError> {
+    let mut model = CostModel::default();
+
+    Ok(parse(input)?
+        .iter()
+        .map(|(code, numeric)| model.code_cost(code, robots) * numeric)
+        .sum())
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
-pub fn part2(input: &str) -> usize {
-    let codes: Vec<String> = parse(input);
-
-    let buttons = [
-        DirectionButton::UP,
-        DirectionButton::DOWN,
-        DirectionButton::LEFT,
-        DirectionButton::RIGHT,
-        DirectionButton::APPLY,
-    ];
-
-    let mut cache: HashMap<(DirectionButton, DirectionButton), usize> =
-        HashMap::new();
-
-    for from_button in &buttons {
-        for to_button in &buttons {
-            if from_button == to_button {
-                continue;
-            }
+pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
 
-            let mut path = direction_path(from_button, to_button);
-            path.push(DirectionButton::APPLY);
-            let count = path.len();
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
 
-            println!("{} -> {}: {} {:?}", from_button, to_button, count, path);
-            cache.insert((from_button.clone(), to_button.clone()), count);
-        }
-    }
+/// # Errors
+///
+/// Fails unless every code is a number followed by `A`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    complexity(input, 2)
+}
 
-    //    codes
-    //        .iter()
-    //        .map(|code| {
-    //            let encoded = encode_extra(code);
-    //            encoded.len() * code[0..3].parse::<usize>().unwrap()
-    //        })
-    //        .sum()
-    0
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    complexity(input, 25)
 }
 
-fn encoding_length(
-    from_button: &DirectionButton,
-    to_button: &DirectionButton,
-    iterations: usize,
-) -> usize {
+// Each code with its numeric part, which excludes the final `A`.
+fn parse(input: &str) -> Result<Vec<(&str, usize)>, PuzzleError> {
+    parsing::lines(input)
+        .iter()
+        .map(|line| {
+            let code = line.text.trim();
+            let digits = code.strip_suffix('A').ok_or_else(|| {
+                line.error(code, "expected a code ending in `A`")
+            })?;
+
+            if let Some(index) = digits.find(|ch: char| !ch.is_ascii_digit()) {
+                return Err(line.error(&digits[index..], "expected a digit"));
+            }
+
+            Ok((code, line.parse(digits)?))
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -533,46 +387,95 @@ mod tests {
 379A\n"
     }
 
+    // Runs presses through a direction pad, returning the buttons its arm
+    // pressed on the pad below and panicking if it crosses the gap.
+    fn press<B: Pad>(presses: &str, buttons: &[B]) -> Vec<B> {
+        let find = |position| {
+            buttons
+                .iter()
+                .copied()
+                .find(|button| button.position() == position)
+                .unwrap()
+        };
+        let (mut row, mut column) = buttons.last().copied().unwrap().position();
+        let mut output = vec![];
+
+        for ch in presses.chars() {
+            match ch {
+                '^' => row -= 1,
+                'v' => row += 1,
+                '<' => column -= 1,
+                '>' => column += 1,
+                _ => output.push(find((row, column))),
+            }
+            assert_ne!((row, column), B::GAP);
+        }
+
+        output
+    }
+
+    fn direction_buttons() -> Vec<DirectionButton> {
+        "^<v>A".chars().map(DirectionButton::from).collect()
+    }
+
     #[test]
     fn part1_example() {
-        //        assert_eq!(part1(input()), 0);
-        assert_eq!(
-            DirectionSequence::from(KeypadSequence::from("029A")),
-            "<A^A>^^AvvvA".into()
-        );
-        assert_eq!(
-            iterate("<A^A>^^AvvvA".into()),
-            "v<<A>>^A<A>AvA<^AA>A<vAAA>^A".into()
-        );
-        assert_eq!(
-            iterate(iterate(DirectionSequence::from(KeypadSequence::from("029A")))),
-            DirectionSequence::from("<vA<AA>>^AvAA<^A>Av<<A>>^AvA^A<vA>^Av<<A>^A>AAvA^Av<<A>A>^AAAvA<^A>A")
-        );
+        let mut model = CostModel::default();
 
-        assert_eq!(
-            encode("980A"),
-            "v<<A>>^AAAvA^A<vA<AA>>^AvAA<^A>Av<<A>A>^AAAvA<^A>A<vA>^A<A>A"
-                .into()
-        );
-        assert_eq!(encode("179A"), "v<<A>>^A<vA<A>>^AAvAA<^A>Av<<A>>^AAvA^A<vA>^AA<A>Av<<A>A>^AAAvA<^A>A".into());
-
-        assert_eq!(
-            encode("456A"),
-            "v<<A>>^AA<vA<A>>^AAvAA<^A>A<vA>^A<A>A<vA>^A<A>Av<<A>A>^AAvA<^A>A"
-                .into()
-        );
+        assert_eq!(model.code_cost("029A", 0), "<A^A>^^AvvvA".len());
+        assert_eq!(model.code_cost("029A", 1), 28);
+        assert_eq!(model.code_cost("029A", 2), 68);
+        assert_eq!(model.code_cost("980A", 2), 60);
+        assert_eq!(model.code_cost("179A", 2), 68);
+        assert_eq!(model.code_cost("456A", 2), 64);
+        assert_eq!(model.code_cost("379A", 2), 64);
 
-        assert_eq!(
-            encode("379A"),
-            "v<<A>>^AvA^A<vA<AA>>^AAvA<^A>AAvA^A<vA>^AA<A>Av<<A>A>^AAAvA<^A>A"
-                .into()
-        );
+        assert_eq!(part1(input()), 126_384);
+    }
 
-        assert_eq!(part1(input()), 126384);
+    #[test]
+    fn reconstruction() {
+        let mut model = CostModel::default();
+        let keypad: Vec<KeypadButton> =
+            "0123456789A".chars().map(KeypadButton::from).collect();
+
+        for code in input().lines() {
+            for robots in 0..=3 {
+                let mut presses = model.reconstruct(code, robots);
+                assert_eq!(presses.len(), model.code_cost(code, robots));
+
+                for _ in 0..robots {
+                    presses = press(&presses, &direction_buttons())
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect();
+                }
+
+                let typed: String = press(&presses, &keypad)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                assert_eq!(typed, code);
+            }
+        }
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(input()), 5);
+        assert_eq!(part2(input()), 154_115_708_116_294);
+    }
+
+    #[test]
+    fn invalid_codes() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("029A\n980\n"),
+            "day 21, line 2: column 1, expected a code ending in `A`"
+        );
+        assert_eq!(
+            error("029A\n9+0A\n"),
+            "day 21, line 2: column 2, expected a digit"
+        );
     }
 }
@@ -1,20 +1,44 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::{collections::HashMap, collections::HashSet, ops::BitXor};
 
+const DAY: u8 = 22;
+
 type ID = [isize; 4];
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    parse(input)
-        .into_iter()
-        .map(|x| evolve_times(x, 2000))
-        .sum()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a secret number.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    Ok(parse(input)?
+        .into_iter()
+        .map(|x| evolve_times(x, 2000))
+        .sum())
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut maps: HashMap<usize, HashMap<ID, usize>> = HashMap::new();
 
-    for x in parse(input).into_iter() {
+    for x in parse(input)?.into_iter() {
         let mut map: HashMap<ID, usize> = HashMap::new();
 
         for window in evolve_prices_with_derivatives(x, 2000).windows(4) {
@@ -50,7 +74,7 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    best_sum
+    Ok(best_sum)
 }
 
 fn evolve_times(mut x: usize, iterations: usize) -> usize {
@@ -102,12 +126,12 @@ fn prune(x: usize) -> usize {
     x % 16_777_216
 }
 
-fn parse(input: &str) -> Vec<usize> {
-    input
-        .trim_end()
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect()
+fn parse(input: &str) -> Result<Vec<usize>, PuzzleError> {
+    parsing::lines(input.trim_end())
+        .iter()
+        .map(|line| line.parse(line.text))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -170,4 +194,12 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(part2_input()), 23);
     }
+
+    #[test]
+    fn invalid_secrets() {
+        assert_eq!(
+            try_part1("1\n10\nx\n").unwrap_err().to_string(),
+            "day 22, line 3: column 1, invalid value `x`"
+        );
+    }
 }
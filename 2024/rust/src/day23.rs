@@ -1,8 +1,30 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 23;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let connections = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a connection `a-b`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let connections = parse(input)?;
     let mut groups: HashSet<Vec<&str>> = HashSet::new();
 
     for (computer, connected_computers) in &connections {
@@ -13,10 +35,10 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    groups
+    Ok(groups
         .iter()
         .filter(|group| group.iter().any(|c| c.starts_with('t')))
-        .count()
+        .count())
 }
 
 fn connected_pairs<'a>(
@@ -46,9 +68,12 @@ fn is_connected(
         .map_or(false, |cs| cs.contains(&computer_b))
 }
 
-#[must_use]
-pub fn part2(input: &str) -> String {
-    let connections = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when no three computers are
+/// all connected.
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    let connections = parse(input)?;
 
     let mut groups: HashSet<Vec<&str>> = HashSet::new();
 
@@ -76,26 +101,30 @@ pub fn part2(input: &str) -> String {
     let mut largest_set: Vec<&str> = sets
         .into_iter()
         .max_by_key(HashSet::len)
-        .unwrap()
+        .ok_or_else(|| {
+            PuzzleError::new(DAY, "no three computers are all connected")
+        })?
         .into_iter()
         .collect();
 
     largest_set.sort_unstable();
 
-    largest_set.into_iter().collect::<Vec<&str>>().join(",")
+    Ok(largest_set.into_iter().collect::<Vec<&str>>().join(","))
 }
 
-fn parse(input: &str) -> HashMap<&str, HashSet<&str>> {
+fn parse(input: &str) -> Result<HashMap<&str, HashSet<&str>>, PuzzleError> {
     let mut map: HashMap<&str, HashSet<&str>> = HashMap::new();
 
-    for line in input.lines() {
-        let (left, right) = line.split_once('-').unwrap();
+    for line in parsing::lines(input) {
+        let (left, right) = line
+            .split_once(line.text, "-")
+            .map_err(PuzzleError::on_day(DAY))?;
 
         map.entry(left).or_default().insert(right);
         map.entry(right).or_default().insert(left);
     }
 
-    map
+    Ok(map)
 }
 
 #[cfg(test)]
@@ -147,4 +176,16 @@ td-yn\n"
     fn part2_example() {
         assert_eq!(part2(input()), "co,de,ka,ta".to_owned());
     }
+
+    #[test]
+    fn invalid_networks() {
+        assert_eq!(
+            try_part1("kh-tc\nqp+kh\n").unwrap_err().to_string(),
+            "day 23, line 2: column 1, expected `-`"
+        );
+        assert_eq!(
+            try_part2("kh-tc\nqp-kh\n").unwrap_err().to_string(),
+            "day 23: no three computers are all connected"
+        );
+    }
 }
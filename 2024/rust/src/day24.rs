@@ -1,6 +1,11 @@
-use std::collections::HashMap;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
 use std::ops::{BitAnd, BitOr, BitXor};
 
+const DAY: u8 = 24;
+
 type System<'a> = HashMap<&'a str, u8>;
 
 #[derive(Debug, Clone)]
@@ -11,20 +16,40 @@ struct Gate<'a> {
     output: &'a str,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Op {
     AND,
     XOR,
     OR,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (mut system, gates, _deps) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> String {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is `wire: 0` or `wire: 1` initial values followed
+/// by `a AND b -> c` gates, with `OR` and `XOR` too, whose inputs are all
+/// known wires.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (mut system, gates) = parse(input)?;
 
     execute(&mut system, &gates);
 
-    get_value('z', &system)
+    Ok(get_value('z', &system))
 }
 
 fn execute<'a>(system: &mut System<'a>, gates: &Vec<Gate<'a>>) {
@@ -81,96 +106,237 @@ fn get_value(name: char, system: &System) -> usize {
     value
 }
 
-fn put_value(system: &mut System, name: char, value: usize) {
-    let mut vs: Vec<&str> = system
-        .keys()
-        .filter(|wire| wire.starts_with(name))
-        .cloned()
-        .collect();
-    vs.sort();
-
-    let mut bits: Vec<u8> = format!("{:046b}", value)
-        .chars()
-        .map(|c| format!("{}", c).parse::<u8>().unwrap())
-        .collect();
-    bits.reverse();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when no set of output swaps
+/// turns the gates into a working adder.
+pub fn try_part2(input: &str) -> Result<String, PuzzleError> {
+    let (_system, gates) = parse(input)?;
+    let circuit = Circuit::new(gates);
+    let swaps =
+        repair(&circuit, &circuit.suspicious_wires()).ok_or_else(|| {
+            PuzzleError::new(DAY, "no set of output swaps repairs the adder")
+        })?;
+
+    let mut wires: Vec<&str> =
+        swaps.into_iter().flat_map(|(a, b)| [a, b]).collect();
+    wires.sort_unstable();
+    Ok(wires.join(","))
+}
 
-    for (name, bit) in vs.iter().zip(bits.iter()) {
-        system.insert(name, *bit);
-    }
+#[derive(Clone)]
+struct Circuit<'a> {
+    gates: Vec<Gate<'a>>,
+    bits: usize,
 }
 
-#[must_use]
-pub fn part2(input: &str) -> String {
-    let (system, gates, deps) = parse(input);
+impl<'a> Circuit<'a> {
+    fn new(gates: Vec<Gate<'a>>) -> Self {
+        let bits = gates
+            .iter()
+            .flat_map(|gate| [gate.left, gate.right])
+            .filter(|wire| wire.starts_with('x'))
+            .collect::<HashSet<_>>()
+            .len();
 
-    let mut x = get_value('x', &system);
-    let mut y = get_value('y', &system);
+        Self { gates, bits }
+    }
 
-    let mut s = system.clone();
-    let mut g = gates.clone();
+    // Checks every gate against the role it plays in a ripple-carry adder:
+    //
+    //   z(i)     = x(i) XOR y(i) XOR carry(i-1)
+    //   carry(i) = (x(i) AND y(i)) OR ((x(i) XOR y(i)) AND carry(i-1))
+    //
+    // and returns the outputs of gates that break the pattern.
+    fn suspicious_wires(&self) -> Vec<&'a str> {
+        let last_z = format!("z{:02}", self.bits);
+        let feeds = |output: &str, op: Op| {
+            self.gates.iter().any(|gate| {
+                gate.op == op && (gate.left == output || gate.right == output)
+            })
+        };
+        let mut wires: Vec<&str> = self
+            .gates
+            .iter()
+            .filter(|gate| {
+                let from_inputs = is_input(gate.left) && is_input(gate.right);
+                let first_bit = gate.left.ends_with("00");
+
+                match gate.op {
+                    _ if gate.output == last_z => gate.op != Op::OR,
+                    Op::XOR if gate.output.starts_with('z') => {
+                        from_inputs && !first_bit
+                    }
+                    Op::XOR => !from_inputs || !feeds(gate.output, Op::XOR),
+                    _ if gate.output.starts_with('z') => true,
+                    Op::AND => !first_bit && !feeds(gate.output, Op::OR),
+                    Op::OR => false,
+                }
+            })
+            .map(|gate| gate.output)
+            .collect();
 
-    execute(&mut s, &g);
+        wires.sort_unstable();
+        wires
+    }
 
-    let mut z = get_value('z', &s);
+    // Gate indices in evaluation order, or None if the wiring has a cycle.
+    fn order(&self) -> Option<Vec<usize>> {
+        let producers: HashMap<&str, usize> = self
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(index, gate)| (gate.output, index))
+            .collect();
+        let mut pending: Vec<usize> = vec![0; self.gates.len()];
+        let mut consumers: Vec<Vec<usize>> = vec![vec![]; self.gates.len()];
+
+        for (index, gate) in self.gates.iter().enumerate() {
+            for wire in [gate.left, gate.right] {
+                if let Some(&producer) = producers.get(wire) {
+                    pending[index] += 1;
+                    consumers[producer].push(index);
+                }
+            }
+        }
 
-    for i in 0..45 {
-        x = 1usize << i;
-        y = 1usize << i;
+        let mut ready: Vec<usize> =
+            (0..self.gates.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(self.gates.len());
 
-        s = system.clone();
-        g = gates.clone();
+        while let Some(index) = ready.pop() {
+            order.push(index);
+
+            for &consumer in &consumers[index] {
+                pending[consumer] -= 1;
+                if pending[consumer] == 0 {
+                    ready.push(consumer);
+                }
+            }
+        }
 
-        put_value(&mut s, 'x', x);
-        put_value(&mut s, 'y', y);
+        (order.len() == self.gates.len()).then_some(order)
+    }
 
-        execute(&mut s, &g);
+    fn add(&self, order: &[usize], x: usize, y: usize) -> usize {
+        let mut values: HashMap<&str, u8> = HashMap::new();
+        let mut z = 0;
+
+        for &index in order {
+            let gate = &self.gates[index];
+            let bit_of = |value: usize, bit: &str| {
+                u8::from(value >> bit.parse::<usize>().unwrap() & 1 == 1)
+            };
+            let value = |wire: &str| match wire.split_at(1) {
+                ("x", bit) => bit_of(x, bit),
+                ("y", bit) => bit_of(y, bit),
+                _ => values[wire],
+            };
+            let (left, right) = (value(gate.left), value(gate.right));
+            let result = match gate.op {
+                Op::AND => left.bitand(right),
+                Op::OR => left.bitor(right),
+                Op::XOR => left.bitxor(right),
+            };
+
+            if let Some(bit) = gate.output.strip_prefix('z') {
+                z |= usize::from(result) << bit.parse::<usize>().unwrap();
+            }
+            values.insert(gate.output, result);
+        }
 
-        x = get_value('x', &s);
-        y = get_value('y', &s);
-        z = get_value('z', &s);
+        z
+    }
 
-        //        println!(
-        //            " {:b}\n+{:b}\n={:b}\nbit {} matching: {}\n\n",
-        //            x,
-        //            y,
-        //            z,
-        //            i,
-        //            x + y == z
-        //        );
-        if x + y != z {
-            let binary: String =
-                format!("{:b}", z.bitxor(x + y)).chars().collect();
-            for (index, bit) in binary.chars().rev().enumerate() {
-                if bit == '1' {
-                    println!("mismatch at bit {}", index);
+    // The adder bit each wire belongs to: x/y inputs carry their own bit and
+    // every gate output takes the highest bit among its inputs.
+    fn bit_positions(&self) -> HashMap<&'a str, usize> {
+        let mut bits: HashMap<&str, usize> = HashMap::new();
+
+        for gate in &self.gates {
+            for wire in [gate.left, gate.right] {
+                if let Some(Ok(bit)) =
+                    wire.strip_prefix(['x', 'y']).map(str::parse::<usize>)
+                {
+                    bits.insert(wire, bit);
                 }
             }
         }
-    }
 
-    let mut gate_pairs = Vec::new();
-    for i in 0..gates.len() {
-        for j in (i + 1)..gates.len() {
-            gate_pairs.push((gates[i].output, gates[j].output));
+        for index in self.order().unwrap_or_default() {
+            let gate = &self.gates[index];
+            let bit = [gate.left, gate.right]
+                .iter()
+                .filter_map(|wire| bits.get(wire))
+                .max()
+                .copied();
+
+            if let Some(bit) = bit {
+                bits.insert(gate.output, bit);
+            }
         }
+
+        bits
     }
 
-    dbg!(&deps, &x, &y, &z);
+    // Probes every bit on its own and with a carry, then adds random pairs.
+    fn verify(&self) -> bool {
+        let Some(order) = self.order() else {
+            return false;
+        };
+        let mask = (1 << self.bits) - 1;
+        let mut state: usize = 0x2545_f491_4f6c_dd1d;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state & mask
+        };
 
-    // # outputs should be XORed
-    // swap z09 and nnf
-    // swap z20 and nhs
-    // swap z34 and wrc
-    //
-    // # inputs ANDed should be ORed
-    // # inputs XORed should be ANDed
-    // swap kqh and ddn
+        let probes: Vec<(usize, usize)> = (0..self.bits)
+            .flat_map(|bit| {
+                [(1 << bit, 0), (0, 1 << bit), (1 << bit, 1 << bit)]
+            })
+            .chain((0..100).map(|_| (random(), random())))
+            .collect();
+
+        probes
+            .into_iter()
+            .all(|(x, y)| self.add(&order, x, y) == x + y)
+    }
+}
+
+fn is_input(wire: &str) -> bool {
+    wire.starts_with('x') || wire.starts_with('y')
+}
 
-    "ddn,kqh,nhs,nnf,wrc,z09,z20,z34".into()
+// Tries every way of pairing up the suspicious wires, keeping the first set
+// of swaps that leaves a well-formed adder which also adds correctly.
+fn repair<'a>(
+    circuit: &Circuit<'a>,
+    wires: &[&'a str],
+) -> Option<Vec<(&'a str, &'a str)>> {
+    let Some((&first, rest)) = wires.split_first() else {
+        return (circuit.suspicious_wires().is_empty() && circuit.verify())
+            .then(Vec::new);
+    };
+
+    for (index, &second) in rest.iter().enumerate() {
+        let mut swapped = circuit.clone();
+        swap_outputs(&mut swapped.gates, (first, second));
+
+        let mut remaining = rest.to_vec();
+        remaining.remove(index);
+
+        if let Some(mut swaps) = repair(&swapped, &remaining) {
+            swaps.push((first, second));
+            return Some(swaps);
+        }
+    }
+
+    None
 }
 
-#[allow(dead_code)]
 fn swap_outputs(gates: &mut Vec<Gate>, (a_name, b_name): (&str, &str)) {
     let a = gates.iter().position(|g| g.output == a_name).unwrap();
     let b = gates.iter().position(|g| g.output == b_name).unwrap();
@@ -180,58 +346,169 @@ fn swap_outputs(gates: &mut Vec<Gate>, (a_name, b_name): (&str, &str)) {
     gates[b].output = tmp;
 }
 
-#[allow(dead_code)]
-fn swap_outputs_by_index(gates: &mut Vec<Gate>, (a, b): (usize, usize)) {
-    let tmp = gates[a].output;
-    gates[a].output = gates[b].output;
-    gates[b].output = tmp;
+// Groups wires by adder bit, in bit order, for the diagram exporters.
+fn clusters<'a>(circuit: &Circuit<'a>) -> BTreeMap<usize, Vec<&'a str>> {
+    let mut clusters: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+
+    for (wire, bit) in circuit.bit_positions() {
+        clusters.entry(bit).or_default().push(wire);
+    }
+    for wires in clusters.values_mut() {
+        wires.sort_unstable();
+    }
+
+    clusters
 }
 
-#[allow(dead_code)]
-fn matching_bit_count(a: usize, b: usize) -> usize {
-    let xor = a ^ b;
-    (0..46).filter(|i| (xor & (1 << i)) == 0).count()
+/// A Mermaid flowchart of the gates, one subgraph per adder bit, with gates
+/// that don't fit the adder pattern highlighted.
+///
+/// # Errors
+///
+/// Fails on input [`try_part1`] rejects.
+pub fn to_mermaid(input: &str) -> Result<String, PuzzleError> {
+    let (_system, gates) = parse(input)?;
+    let circuit = Circuit::new(gates);
+    let mut out = String::from("flowchart LR\n");
+
+    for (bit, wires) in clusters(&circuit) {
+        writeln!(out, "    subgraph bit{bit:02} [\"bit {bit:02}\"]").unwrap();
+        for wire in wires {
+            writeln!(out, "        {wire}").unwrap();
+        }
+        writeln!(out, "    end").unwrap();
+    }
+    out.push('\n');
+
+    for gate in &circuit.gates {
+        writeln!(
+            out,
+            "    {} & {} --> |{:?}| {}",
+            gate.left, gate.right, gate.op, gate.output
+        )
+        .unwrap();
+    }
+
+    let suspicious = circuit.suspicious_wires();
+    if !suspicious.is_empty() {
+        out.push_str("\n    classDef suspicious fill:#f96,stroke:#c00\n");
+        writeln!(out, "    class {} suspicious", suspicious.join(",")).unwrap();
+    }
+
+    Ok(out)
 }
 
-fn parse(input: &str) -> (System, Vec<Gate>, HashMap<&str, Vec<&str>>) {
-    let mut system: System = HashMap::new();
-    let mut gates: Vec<Gate> = Vec::new();
-    let mut deps: HashMap<&str, Vec<&str>> = HashMap::new();
+/// The same diagram in Graphviz DOT, one cluster per adder bit.
+///
+/// # Errors
+///
+/// Fails on input [`try_part1`] rejects.
+pub fn to_dot(input: &str) -> Result<String, PuzzleError> {
+    let (_system, gates) = parse(input)?;
+    let circuit = Circuit::new(gates);
+    let mut out = String::from("digraph adder {\n    rankdir=LR;\n");
+
+    for (bit, wires) in clusters(&circuit) {
+        writeln!(out, "    subgraph cluster_{bit:02} {{").unwrap();
+        writeln!(out, "        label=\"bit {bit:02}\";").unwrap();
+        writeln!(out, "        {};", wires.join("; ")).unwrap();
+        writeln!(out, "    }}").unwrap();
+    }
+
+    for gate in &circuit.gates {
+        for input in [gate.left, gate.right] {
+            writeln!(
+                out,
+                "    {input} -> {} [label=\"{:?}\"];",
+                gate.output, gate.op
+            )
+            .unwrap();
+        }
+    }
 
-    let (initial_values_string, gates_string) =
-        input.split_once("\n\n").unwrap();
+    for wire in circuit.suspicious_wires() {
+        writeln!(
+            out,
+            "    {wire} [style=filled, fillcolor=\"#ff9966\", color=\"#cc0000\"];"
+        )
+        .unwrap();
+    }
 
-    for line in initial_values_string.lines() {
-        let (name, value_string) = line.split_once(": ").unwrap();
-        let value = if value_string == "0" { 0 } else { 1 };
+    out.push_str("}\n");
+    Ok(out)
+}
 
-        system.insert(name, value);
+fn parse(input: &str) -> Result<(System<'_>, Vec<Gate<'_>>), PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [value_lines, gate_lines] = &blocks[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected initial values followed by gates",
+        ));
+    };
+
+    let system: System = value_lines
+        .iter()
+        .map(initial_value)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+    let gates: Vec<Gate> = gate_lines
+        .iter()
+        .map(gate)
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    let outputs: HashSet<&str> = gates.iter().map(|gate| gate.output).collect();
+    for (line, gate) in gate_lines.iter().zip(&gates) {
+        if let Some(unknown) = [gate.left, gate.right]
+            .into_iter()
+            .find(|wire| !system.contains_key(wire) && !outputs.contains(wire))
+        {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(unknown, format!("unknown wire `{unknown}`")),
+            ));
+        }
     }
 
-    for line in gates_string.lines() {
-        let (lhs, output) = line.split_once(" -> ").unwrap();
-        let eq: Vec<&str> = lhs.splitn(3, " ").collect();
-        let op: Op = match eq[1] {
-            "AND" => Op::AND,
-            "XOR" => Op::XOR,
-            "OR" => Op::OR,
-            _ => panic!("unexpected op found"),
-        };
-        let left = eq[0];
-        let right = eq[2];
-
-        gates.push(Gate {
-            op,
-            left,
-            right,
-            output,
-        });
-        let deps_entry = deps.entry(output).or_insert(Vec::new());
-        deps_entry.push(left);
-        deps_entry.push(right);
+    Ok((system, gates))
+}
+
+fn initial_value<'a>(line: &SourceLine<'a>) -> parsing::Result<(&'a str, u8)> {
+    let (name, value) = line.key_value()?;
+
+    match value {
+        "0" => Ok((wire(line, name)?, 0)),
+        "1" => Ok((wire(line, name)?, 1)),
+        _ => Err(line.error(value, "expected `0` or `1`")),
     }
+}
+
+fn gate<'a>(line: &SourceLine<'a>) -> parsing::Result<Gate<'a>> {
+    let fields = line.fields("{} {} {} -> {}")?;
+    let op = match fields[1] {
+        "AND" => Op::AND,
+        "XOR" => Op::XOR,
+        "OR" => Op::OR,
+        op => return Err(line.error(op, "expected `AND`, `OR` or `XOR`")),
+    };
+
+    Ok(Gate {
+        op,
+        left: wire(line, fields[0])?,
+        right: wire(line, fields[2])?,
+        output: wire(line, fields[3])?,
+    })
+}
 
-    (system, gates, deps)
+// Input and output wires are numbered by the bit they carry.
+fn wire<'a>(line: &SourceLine<'a>, name: &'a str) -> parsing::Result<&'a str> {
+    match name.strip_prefix(['x', 'y', 'z']) {
+        Some(bit) if bit.parse::<u8>().is_err() => Err(line.error(
+            name,
+            format!("expected a bit number after `{}`", &name[..1]),
+        )),
+        _ => Ok(name),
+    }
 }
 
 #[cfg(test)]
@@ -309,6 +586,92 @@ tnw OR pbm -> gnj\n"
         assert_eq!(part1(larger_example()), 2024);
     }
 
+    // A correct ripple-carry adder over `bits` bits, one gate per line.
+    fn adder(bits: usize) -> String {
+        let mut lines = vec![
+            "x00 XOR y00 -> z00".to_string(),
+            "x00 AND y00 -> c00".to_string(),
+        ];
+
+        for i in 1..bits {
+            let carry_in = format!("c{:02}", i - 1);
+            let carry_out = if i + 1 == bits {
+                format!("z{:02}", bits)
+            } else {
+                format!("c{i:02}")
+            };
+
+            lines.push(format!("x{i:02} XOR y{i:02} -> s{i:02}"));
+            lines.push(format!("x{i:02} AND y{i:02} -> a{i:02}"));
+            lines.push(format!("s{i:02} XOR {carry_in} -> z{i:02}"));
+            lines.push(format!("s{i:02} AND {carry_in} -> t{i:02}"));
+            lines.push(format!("a{i:02} OR t{i:02} -> {carry_out}"));
+        }
+
+        let inputs: String = (0..bits)
+            .flat_map(|i| [format!("x{i:02}: 0\n"), format!("y{i:02}: 0\n")])
+            .collect();
+
+        format!("{inputs}\n{}\n", lines.join("\n"))
+    }
+
+    fn swap(input: &str, a: &str, b: &str) -> String {
+        input
+            .replace(&format!("-> {a}"), "-> TMP")
+            .replace(&format!("-> {b}"), &format!("-> {a}"))
+            .replace("-> TMP", &format!("-> {b}"))
+    }
+
+    #[test]
+    fn diagram_export() {
+        let input = swap(&adder(3), "z01", "t01");
+        let mermaid = to_mermaid(&input).unwrap();
+        let dot = to_dot(&input).unwrap();
+
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    subgraph bit01 [\"bit 01\"]\n"));
+        assert!(mermaid.contains("    s01 & c00 --> |XOR| t01\n"));
+        assert!(mermaid.contains("    class t01,z01 suspicious\n"));
+
+        assert!(dot.contains("    subgraph cluster_02 {\n"));
+        assert!(dot.contains("    c00 -> t01 [label=\"XOR\"];\n"));
+        assert!(dot.contains("    z01 [style=filled"));
+    }
+
     #[test]
-    fn part2_example() {}
+    fn part2_example() {
+        let input = adder(8);
+        assert_eq!(part2(&input), "");
+
+        let input = swap(&swap(&input, "z03", "t03"), "s06", "a06");
+        assert_eq!(part2(&input), "a06,s06,t03,z03");
+    }
+
+    #[test]
+    fn invalid_gates() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("x00: 1\ny00: 2\n\nx00 AND y00 -> z00"),
+            "day 24, line 2: column 6, expected `0` or `1`"
+        );
+        assert_eq!(
+            error("x00: 1\ny00: 0\n\nx00 NAND y00 -> z00"),
+            "day 24, line 4: column 5, expected `AND`, `OR` or `XOR`"
+        );
+        assert_eq!(
+            error("x00: 1\ny00: 0\n\nx00 AND abc -> z00"),
+            "day 24, line 4: column 9, unknown wire `abc`"
+        );
+        assert_eq!(
+            error("x00: 1\ny00: 0\n\nx00 AND y00 -> zed"),
+            "day 24, line 4: column 16, expected a bit number after `z`"
+        );
+        assert_eq!(
+            try_part2("x00: 1\ny00: 0\n\nx00 AND y00 -> z00")
+                .unwrap_err()
+                .to_string(),
+            "day 24: no set of output swaps repairs the adder"
+        );
+    }
 }
@@ -1,9 +1,32 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 25;
+
 type Lock = Vec<usize>;
 type Key = Vec<usize>;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (locks, keys) = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless every schematic is 7 rows of 5 `#` or `.` columns.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (locks, keys) = parse(input)?;
     let mut count: usize = 0;
 
     for lock in &locks {
@@ -14,7 +37,7 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    count
+    Ok(count)
 }
 
 fn fits(lock: &Lock, key: &Key) -> bool {
@@ -24,35 +47,55 @@ fn fits(lock: &Lock, key: &Key) -> bool {
         .all(|s| s <= 7)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    input.lines().count()
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    Ok(input.lines().count())
 }
 
-fn parse(input: &str) -> (Vec<Lock>, Vec<Key>) {
-    let groups: Vec<&str> = input.trim_end().split("\n\n").collect();
+fn parse(input: &str) -> Result<(Vec<Lock>, Vec<Key>), PuzzleError> {
     let mut locks: Vec<Lock> = Vec::new();
     let mut keys: Vec<Key> = Vec::new();
 
-    for group in groups {
+    for block in parsing::blocks(input) {
         let mut column_counts: Vec<usize> = vec![0, 0, 0, 0, 0];
 
-        for line in group.lines() {
-            for (row, ch) in line.chars().enumerate() {
-                if ch == '#' {
-                    column_counts[row] += 1;
+        if block.len() != 7 {
+            return Err(PuzzleError::on_day(DAY)(
+                block[0].error(block[0].text, "expected 7 rows"),
+            ));
+        }
+
+        for line in &block {
+            if line.text.chars().count() != 5 {
+                return Err(PuzzleError::on_day(DAY)(
+                    line.error(line.text, "expected 5 columns"),
+                ));
+            }
+
+            for (row, (index, ch)) in line.text.char_indices().enumerate() {
+                match ch {
+                    '#' => column_counts[row] += 1,
+                    '.' => {}
+                    _ => {
+                        return Err(PuzzleError::on_day(DAY)(line.error(
+                            &line.text[index..],
+                            "expected `#` or `.`",
+                        )))
+                    }
                 }
             }
         }
 
-        if group.starts_with("#####") {
+        if block[0].text.starts_with("#####") {
             locks.push(column_counts);
         } else {
             keys.push(column_counts);
         }
     }
 
-    (locks, keys)
+    Ok((locks, keys))
 }
 
 #[cfg(test)]
@@ -111,4 +154,22 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(input()), 39);
     }
+
+    #[test]
+    fn invalid_schematics() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("#####\n.####\n.####\n.####\n.#.#.\n.#...\n"),
+            "day 25, line 1: column 1, expected 7 rows"
+        );
+        assert_eq!(
+            error("#####\n.####\n.####\n.####\n.#.#\n.#...\n.....\n"),
+            "day 25, line 5: column 1, expected 5 columns"
+        );
+        assert_eq!(
+            error("#####\n.####\n.####\n.####\n.#.#x\n.#...\n.....\n"),
+            "day 25, line 5: column 5, expected `#` or `.`"
+        );
+    }
 }
@@ -25,3 +25,6 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+pub mod parsing;
+pub mod puzzle;
+pub mod visualize;
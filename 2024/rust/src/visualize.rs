@@ -0,0 +1,220 @@
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, DynamicImage, Frame, ImageResult, Rgb, RgbImage,
+};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+pub type Color = Rgb<u8>;
+
+pub const BLACK: Color = Rgb([16, 16, 24]);
+pub const GRAY: Color = Rgb([96, 96, 104]);
+pub const RED: Color = Rgb([220, 50, 47]);
+pub const GREEN: Color = Rgb([80, 200, 90]);
+pub const YELLOW: Color = Rgb([240, 200, 60]);
+
+// Days opt in to rendering when their binary is run with `--visualize`.
+#[must_use]
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == "--visualize")
+}
+
+/// Where a day's renders go, e.g. `../visualizations/day14-tree.png`.
+///
+/// # Errors
+///
+/// Fails when the directory can't be created.
+pub fn output_path(day: u8, name: &str) -> std::io::Result<PathBuf> {
+    let directory = Path::new("../visualizations");
+    fs::create_dir_all(directory)?;
+
+    Ok(directory.join(format!("day{day:02}-{name}")))
+}
+
+// A grid of cells, each drawn as a `scale` x `scale` pixel square.
+#[derive(Clone)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    scale: u32,
+    image: RgbImage,
+}
+
+impl Canvas {
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(width: usize, height: usize, scale: u32) -> Self {
+        let (pixel_width, pixel_height) =
+            (width as u32 * scale, height as u32 * scale);
+
+        Self {
+            width,
+            height,
+            scale,
+            image: RgbImage::from_pixel(pixel_width, pixel_height, BLACK),
+        }
+    }
+
+    // Colors each character of a text grid, e.g. a puzzle map.
+    pub fn from_grid<F>(grid: &str, scale: u32, palette: F) -> Self
+    where
+        F: Fn(char) -> Color,
+    {
+        let height = grid.lines().count();
+        let width = grid
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut canvas = Self::new(width, height, scale);
+
+        for (y, line) in grid.lines().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                canvas.fill(x, y, palette(ch));
+            }
+        }
+
+        canvas
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn image(&self) -> &RgbImage {
+        &self.image
+    }
+
+    // Cells outside the canvas are ignored.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn fill(&mut self, x: usize, y: usize, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let (left, top) = (x as u32 * self.scale, y as u32 * self.scale);
+
+        for dy in 0..self.scale {
+            for dx in 0..self.scale {
+                self.image.put_pixel(left + dx, top + dy, color);
+            }
+        }
+    }
+
+    pub fn plot<I>(&mut self, points: I, color: Color)
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        for (x, y) in points {
+            self.fill(x, y, color);
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Fails when the file can't be written.
+    pub fn save_png(&self, path: &Path) -> ImageResult<()> {
+        self.image.save(path)
+    }
+}
+
+// A looping GIF built up one canvas at a time.
+pub struct Animation {
+    frames: Vec<RgbImage>,
+    delay_ms: u32,
+}
+
+impl Animation {
+    #[must_use]
+    pub fn new(delay_ms: u32) -> Self {
+        Self {
+            frames: vec![],
+            delay_ms,
+        }
+    }
+
+    pub fn push(&mut self, canvas: &Canvas) {
+        self.frames.push(canvas.image.clone());
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// # Errors
+    ///
+    /// Fails when encoding or writing a frame fails.
+    pub fn write_gif<W: Write>(&self, writer: W) -> ImageResult<()> {
+        let mut encoder = GifEncoder::new_with_speed(writer, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        encoder.encode_frames(self.frames.iter().map(|image| {
+            let rgba = DynamicImage::ImageRgb8(image.clone()).into_rgba8();
+            Frame::from_parts(
+                rgba,
+                0,
+                0,
+                Delay::from_numer_denom_ms(self.delay_ms, 1),
+            )
+        }))
+    }
+
+    /// # Errors
+    ///
+    /// Fails when the file can't be created or written.
+    pub fn save_gif(&self, path: &Path) -> ImageResult<()> {
+        self.write_gif(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canvas_scales_cells() {
+        let canvas = Canvas::from_grid("#.\n.#\n", 3, |ch| match ch {
+            '#' => GREEN,
+            _ => BLACK,
+        });
+
+        assert_eq!((canvas.width(), canvas.height()), (2, 2));
+        assert_eq!(canvas.image().dimensions(), (6, 6));
+        assert_eq!(*canvas.image().get_pixel(2, 2), GREEN);
+        assert_eq!(*canvas.image().get_pixel(3, 2), BLACK);
+        assert_eq!(*canvas.image().get_pixel(5, 5), GREEN);
+    }
+
+    #[test]
+    fn animation_encodes_gif() {
+        let mut canvas = Canvas::new(4, 4, 2);
+        let mut animation = Animation::new(100);
+
+        for step in 0..4 {
+            canvas.plot([(step, step)], RED);
+            animation.push(&canvas);
+        }
+
+        let mut bytes = vec![];
+        animation.write_gif(&mut bytes).unwrap();
+
+        assert_eq!(animation.len(), 4);
+        assert!(bytes.starts_with(b"GIF89a"));
+    }
+}
@@ -1,20 +1,45 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 1;
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a rotation like `L68` or `R48`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let mut dial = 50;
     let mut zero_count = 0;
 
-    for line in input.trim().lines() {
-        let (direction, amount_string) = line.trim().split_at(1);
-        let amount: usize = amount_string.trim().parse().unwrap();
-
+    for (direction, amount) in parse(input)? {
         match direction {
-            "L" => {
+            Direction::Left => {
                 dial = (dial + amount) % 100;
             }
-            "R" => {
+            Direction::Right => {
                 dial = (dial + 100 - amount) % 100;
             }
-            _ => panic!("Invalid direction: {direction}"),
         }
 
         if dial == 0 {
@@ -22,20 +47,19 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    zero_count
+    Ok(zero_count)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut dial = 50;
     let mut zero_count = 0;
 
-    for line in input.trim().lines() {
-        let (direction, amount_string) = line.trim().split_at(1);
-        let amount: usize = amount_string.trim().parse().unwrap();
-
+    for (direction, amount) in parse(input)? {
         match direction {
-            "L" => {
+            Direction::Left => {
                 zero_count += if dial == 0 {
                     amount / 100
                 } else {
@@ -43,15 +67,31 @@ pub fn part2(input: &str) -> usize {
                 };
                 dial = (dial + 100 - (amount % 100)) % 100;
             }
-            "R" => {
+            Direction::Right => {
                 zero_count += (dial + amount) / 100;
                 dial = (dial + amount) % 100;
             }
-            _ => panic!("Invalid direction: {direction}"),
         }
     }
 
-    zero_count
+    Ok(zero_count)
+}
+
+fn parse(input: &str) -> Result<Vec<(Direction, usize)>, PuzzleError> {
+    parsing::lines(input.trim())
+        .iter()
+        .map(|line| {
+            let text = line.text.trim();
+            let direction = match text.get(..1) {
+                Some("L") => Direction::Left,
+                Some("R") => Direction::Right,
+                _ => return Err(line.error(text, "expected `L` or `R`")),
+            };
+
+            Ok((direction, line.parse(&text[1..])?))
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -80,4 +120,18 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 6);
     }
+
+    #[test]
+    fn test_invalid_rotations() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("L68\nX30\n"),
+            "day 01, line 2: column 1, expected `L` or `R`"
+        );
+        assert_eq!(
+            error("L68\nR4x\n"),
+            "day 01, line 2: column 2, invalid value `4x`"
+        );
+    }
 }
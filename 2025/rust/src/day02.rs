@@ -1,31 +1,54 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashSet;
 
+const DAY: u8 = 2;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is comma-separated `start-end` ranges of IDs that
+/// don't end before they start.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let mut total = 0;
 
-    for range in input.split(',') {
-        let (start, end) = range.trim().split_once('-').unwrap();
+    for (start, end) in parse(input)? {
         for repeat in find_single_repeats(start, end) {
             total += repeat.parse::<usize>().unwrap();
         }
     }
 
-    total
+    Ok(total)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut total = 0;
 
-    for range in input.split(',') {
-        let (start, end) = range.trim().split_once('-').unwrap();
+    for (start, end) in parse(input)? {
         for repeat in find_all_repeats(start, end) {
             total += repeat.parse::<usize>().unwrap();
         }
     }
 
-    total
+    Ok(total)
 }
 
 fn find_single_repeats(start: &str, end: &str) -> Vec<String> {
@@ -52,7 +75,6 @@ fn find_single_repeats(start: &str, end: &str) -> Vec<String> {
             }
         }
     } else {
-        assert!(end.len() - start.len() == 1);
         let left_end = "9".repeat(size);
         let right_start = format!("1{}", "0".repeat(size));
 
@@ -95,7 +117,6 @@ fn find_all_repeats(start: &str, end: &str) -> Vec<String> {
             }
         }
     } else {
-        assert!(end.len() - start.len() == 1);
         let left_end = "9".repeat(size);
         let right_start = format!("1{}", "0".repeat(size));
 
@@ -110,6 +131,43 @@ fn find_all_repeats(start: &str, end: &str) -> Vec<String> {
     sorted_repeats
 }
 
+fn parse(input: &str) -> Result<Vec<(&str, &str)>, PuzzleError> {
+    let mut ranges = Vec::new();
+
+    for line in parsing::lines(input) {
+        for range in line.text.split(',').map(str::trim) {
+            if !range.is_empty() {
+                ranges.push(
+                    id_range(&line, range).map_err(PuzzleError::on_day(DAY))?,
+                );
+            }
+        }
+    }
+
+    Ok(ranges)
+}
+
+fn id_range<'a>(
+    line: &SourceLine<'a>,
+    range: &'a str,
+) -> parsing::Result<(&'a str, &'a str)> {
+    let (start, end) = line.split_once(range, "-")?;
+
+    for id in [start, end] {
+        if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(line.error(id, "expected an ID of digits"));
+        }
+    }
+
+    if line.parse::<usize>(start)? > line.parse::<usize>(end)? {
+        return Err(
+            line.error(range, "expected the range not to end before it starts")
+        );
+    }
+
+    Ok((start, end))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +247,24 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 4174379265);
     }
+
+    #[test]
+    fn test_invalid_ranges() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("11-22,95+115"),
+            "day 02, line 1: column 7, expected `-`"
+        );
+        assert_eq!(
+            error("11-22,\n95-1x5"),
+            "day 02, line 2: column 4, expected an ID of digits"
+        );
+        assert_eq!(
+            error("11-22,115-95"),
+            "day 02, line 1: column 7, \
+             expected the range not to end before it starts"
+        );
+        assert_eq!(part1("5-1111"), part1("5-9,10-99,100-999,1000-1111"));
+    }
 }
@@ -1,17 +1,35 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 3;
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let lines: Vec<&str> = input.trim().lines().collect();
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless every bank is a line of at least two digit joltages.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
     let mut total_joltage = 0usize;
 
-    for line in lines {
-        let joltages: Vec<usize> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as usize)
-            .collect();
+    for joltages in parse(input, 2)? {
         total_joltage += maximum_two_digit_joltage(&joltages);
     }
 
-    total_joltage
+    Ok(total_joltage)
 }
 
 fn maximum_two_digit_joltage(joltages: &[usize]) -> usize {
@@ -34,20 +52,17 @@ fn maximum_two_digit_joltage(joltages: &[usize]) -> usize {
     largest_digit * 10 + second_largest_digit
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let lines: Vec<&str> = input.trim().lines().collect();
+/// # Errors
+///
+/// Fails unless every bank is a line of at least twelve digit joltages.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
     let mut total_joltage = 0usize;
 
-    for line in lines {
-        let joltages: Vec<usize> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as usize)
-            .collect();
+    for joltages in parse(input, 12)? {
         total_joltage += maximum_twelve_digit_joltage(&joltages);
     }
 
-    total_joltage
+    Ok(total_joltage)
 }
 
 fn maximum_twelve_digit_joltage(joltages: &[usize]) -> usize {
@@ -77,6 +92,43 @@ fn maximum_twelve_digit_joltage(joltages: &[usize]) -> usize {
         .unwrap()
 }
 
+// Each bank's joltages, which must number at least `batteries` so there are
+// enough to turn on.
+fn parse(
+    input: &str,
+    batteries: usize,
+) -> Result<Vec<Vec<usize>>, PuzzleError> {
+    parsing::lines(input.trim())
+        .iter()
+        .map(|line| {
+            let text = line.text.trim();
+            let joltages: Vec<usize> = text
+                .char_indices()
+                .map(|(index, c)| {
+                    c.to_digit(10).map(|digit| digit as usize).ok_or_else(
+                        || {
+                            line.error(
+                                &text[index..],
+                                "expected a digit joltage",
+                            )
+                        },
+                    )
+                })
+                .collect::<parsing::Result<_>>()?;
+
+            if joltages.len() < batteries {
+                return Err(line.error(
+                    text,
+                    format!("expected at least {batteries} batteries"),
+                ));
+            }
+
+            Ok(joltages)
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +205,18 @@ mod tests {
             888911112111
         );
     }
+
+    #[test]
+    fn test_invalid_banks() {
+        assert_eq!(
+            try_part1("987\n81x\n").unwrap_err().to_string(),
+            "day 03, line 2: column 3, expected a digit joltage"
+        );
+        assert_eq!(
+            try_part2("987654321111111\n81111\n")
+                .unwrap_err()
+                .to_string(),
+            "day 03, line 2: column 1, expected at least 12 batteries"
+        );
+    }
 }
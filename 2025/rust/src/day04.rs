@@ -1,5 +1,9 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::collections::HashMap;
 
+const DAY: u8 = 4;
+
 type Position = (usize, usize);
 
 #[derive(PartialEq, Eq)]
@@ -75,9 +79,27 @@ impl Map {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let map = parse_map(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the map is a rectangle of `@` rolls of paper and `.`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let map = parse_map(input)?;
     let mut accessible_rolls = Vec::<Position>::new();
 
     for row in 0..map.rows {
@@ -90,16 +112,25 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    accessible_rolls.len()
+    Ok(accessible_rolls.len())
 }
 
-fn parse_map(input: &str) -> Map {
+fn parse_map(input: &str) -> Result<Map, PuzzleError> {
     let mut tiles = HashMap::new();
-    let rows = input.trim().lines().count();
-    let cols = input.lines().next().unwrap().trim().chars().count();
+    let lines = parsing::lines(input.trim());
+    let rows = lines.len();
+    let cols = lines
+        .first()
+        .map_or(0, |line| line.trim().text.chars().count());
+
+    for (row, line) in lines.into_iter().map(SourceLine::trim).enumerate() {
+        if line.text.chars().count() != cols {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(line.text, format!("expected {cols} tiles")),
+            ));
+        }
 
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, ch) in line.trim().chars().enumerate() {
+        for (col, (index, ch)) in line.text.char_indices().enumerate() {
             match ch {
                 '@' => {
                     tiles.insert((row, col), Tile::RollOfPaper);
@@ -108,18 +139,22 @@ fn parse_map(input: &str) -> Map {
                     tiles.insert((row, col), Tile::Open);
                 }
                 _ => {
-                    panic!("Unexpected character in map: {ch}");
+                    return Err(PuzzleError::on_day(DAY)(
+                        line.error(&line.text[index..], "expected `@` or `.`"),
+                    ));
                 }
             }
         }
     }
 
-    Map { tiles, rows, cols }
+    Ok(Map { tiles, rows, cols })
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let mut map = parse_map(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let mut map = parse_map(input)?;
     let mut accessible_roll_count = 0usize;
     let mut accessible_rolls = Vec::<Position>::new();
 
@@ -144,7 +179,7 @@ pub fn part2(input: &str) -> usize {
         accessible_rolls.clear();
     }
 
-    accessible_roll_count
+    Ok(accessible_roll_count)
 }
 
 #[cfg(test)]
@@ -173,4 +208,16 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 43);
     }
+
+    #[test]
+    fn test_invalid_maps() {
+        assert_eq!(
+            try_part1("..@\n.@\n").unwrap_err().to_string(),
+            "day 04, line 2: column 1, expected 3 tiles"
+        );
+        assert_eq!(
+            try_part2("..@\n.#@\n").unwrap_err().to_string(),
+            "day 04, line 2: column 2, expected `@` or `.`"
+        );
+    }
 }
@@ -1,13 +1,36 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
 use std::ops::RangeInclusive;
 
+const DAY: u8 = 5;
+
 struct Inventory {
     ranges: Vec<RangeInclusive<usize>>,
     ids: Vec<usize>,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let inventory = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the input is `start-end` ranges of IDs, a blank line, then
+/// one ID per line.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let inventory = parse(input)?;
     let mut fresh_count = 0;
 
     for id in inventory.ids {
@@ -16,38 +39,58 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    fresh_count
+    Ok(fresh_count)
 }
 
-fn parse(input: &str) -> Inventory {
-    let (ranges_input, ids_input) = input.trim().split_once("\n\n").unwrap();
+fn parse(input: &str) -> Result<Inventory, PuzzleError> {
+    let blocks = parsing::blocks(input);
+    let [ranges_input, ids_input] = &blocks[..] else {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected ranges and IDs separated by a blank line",
+        ));
+    };
     let mut ranges = Vec::<RangeInclusive<usize>>::new();
     let mut ids = Vec::<usize>::new();
 
-    for line in ranges_input.lines() {
-        let (start, end) = line.trim().split_once('-').unwrap();
-        ranges.push(RangeInclusive::new(
-            start.parse().unwrap(),
-            end.parse().unwrap(),
-        ));
+    for line in ranges_input.iter().map(|line| line.trim()) {
+        ranges.push(parse_range(&line).map_err(PuzzleError::on_day(DAY))?);
     }
 
-    for line in ids_input.lines() {
-        ids.push(line.trim().parse().unwrap());
+    for line in ids_input.iter().map(|line| line.trim()) {
+        ids.push(line.parse(line.text).map_err(PuzzleError::on_day(DAY))?);
     }
 
-    Inventory { ranges, ids }
+    Ok(Inventory { ranges, ids })
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let inventory = parse(input);
+fn parse_range(
+    line: &SourceLine<'_>,
+) -> parsing::Result<RangeInclusive<usize>> {
+    let (start, end) = line.split_once(line.text, "-")?;
+    let (start, end): (usize, usize) = (line.parse(start)?, line.parse(end)?);
+
+    if start > end {
+        return Err(line.error(
+            line.text,
+            "expected the range not to end before it starts",
+        ));
+    }
+
+    Ok(RangeInclusive::new(start, end))
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let inventory = parse(input)?;
     let ranges = unify(inventory.ranges);
 
-    ranges
+    Ok(ranges
         .iter()
         .map(|range| range.end() - range.start() + 1)
-        .sum()
+        .sum())
 }
 
 fn unify(ranges: Vec<RangeInclusive<usize>>) -> Vec<RangeInclusive<usize>> {
@@ -112,4 +155,27 @@ mod tests {
             vec![RangeInclusive::new(3, 5), RangeInclusive::new(10, 20),]
         );
     }
+
+    #[test]
+    fn test_invalid_inventories() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("3-5\n10-14\n"),
+            "day 05: expected ranges and IDs separated by a blank line"
+        );
+        assert_eq!(
+            error("3-5\n10:14\n\n1\n"),
+            "day 05, line 2: column 1, expected `-`"
+        );
+        assert_eq!(
+            error("3-5\n14-10\n\n1\n"),
+            "day 05, line 2: column 1, \
+             expected the range not to end before it starts"
+        );
+        assert_eq!(
+            error("3-5\n\n1\nx\n"),
+            "day 05, line 4: column 1, invalid value `x`"
+        );
+    }
 }
@@ -1,3 +1,8 @@
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+
+const DAY: u8 = 6;
+
 #[derive(Debug)]
 enum Operation {
     Add,
@@ -24,74 +29,81 @@ struct Homework {
     problems: Vec<Problem>,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let homework = parse(input);
-
-    homework.problems.iter().map(Problem::solve).sum()
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
 pub fn part2(input: &str) -> usize {
-    let homework = parse_right_to_left(input);
-
-    homework.problems.iter().map(Problem::solve).sum()
+    try_part2(input).unwrap()
 }
 
-fn parse(input: &str) -> Homework {
-    let lines: Vec<&str> = input.trim().lines().collect();
+/// # Errors
+///
+/// Fails unless the worksheet is lines of numbers over a line of `*` and `+`
+/// operations, one per problem.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let homework = parse(input)?;
 
-    let number_lines = &lines[..lines.len() - 1];
-    let operation_line = &lines[lines.len() - 1];
+    Ok(homework.problems.iter().map(Problem::solve).sum())
+}
 
-    let mut problems: Vec<Problem> = operation_line
-        .split_whitespace()
-        .map(|c| match c {
-            "*" => Problem {
-                numbers: Vec::new(),
-                operation: Operation::Multiply,
-            },
-            "+" => Problem {
-                numbers: Vec::new(),
-                operation: Operation::Add,
-            },
-            _ => panic!("Unexpected operation: {c}"),
-        })
-        .collect();
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or when the digits don't line up
+/// in columns.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let homework = parse_right_to_left(input)?;
 
-    for line in number_lines {
-        for (i, number_string) in line.split_whitespace().enumerate() {
-            problems[i].numbers.push(number_string.parse().unwrap());
+    Ok(homework.problems.iter().map(Problem::solve).sum())
+}
+
+fn parse(input: &str) -> Result<Homework, PuzzleError> {
+    let (number_lines, mut problems) = parse_sheet(input)?;
+
+    for line in &number_lines {
+        for (i, number_string) in line.text.split_whitespace().enumerate() {
+            let Some(problem) = problems.get_mut(i) else {
+                return Err(PuzzleError::on_day(DAY)(line.error(
+                    number_string,
+                    format!("expected {} numbers", problems.len()),
+                )));
+            };
+            problem.numbers.push(
+                line.parse(number_string)
+                    .map_err(PuzzleError::on_day(DAY))?,
+            );
         }
     }
 
-    Homework { problems }
+    Ok(Homework { problems })
 }
 
-fn parse_right_to_left(input: &str) -> Homework {
-    let lines: Vec<&str> = input.trim().lines().collect();
-
-    let number_lines = &lines[..lines.len() - 1];
-    let operation_line = &lines[lines.len() - 1];
-
-    let mut problems: Vec<Problem> = operation_line
-        .split_whitespace()
-        .map(|c| match c {
-            "*" => Problem {
-                numbers: Vec::new(),
-                operation: Operation::Multiply,
-            },
-            "+" => Problem {
-                numbers: Vec::new(),
-                operation: Operation::Add,
-            },
-            _ => panic!("Unexpected operation: {c}"),
-        })
-        .collect();
+fn parse_right_to_left(input: &str) -> Result<Homework, PuzzleError> {
+    let (number_lines, mut problems) = parse_sheet(input)?;
+
+    for line in &number_lines {
+        if let Some((index, _)) = line
+            .text
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_digit() && !c.is_whitespace())
+        {
+            return Err(PuzzleError::on_day(DAY)(
+                line.error(&line.text[index..], "expected a digit"),
+            ));
+        }
+    }
 
     let number_chars: Vec<Vec<char>> = number_lines
         .iter()
-        .map(|line| line.chars().collect())
+        .map(|line| line.text.chars().collect())
         .collect();
 
     let rows = number_chars.len();
@@ -102,16 +114,29 @@ fn parse_right_to_left(input: &str) -> Homework {
 
     for (row, number_row) in number_chars.iter().enumerate() {
         for (col, number_char) in number_row.iter().enumerate() {
-            number_chars_transposed[col][row] = *number_char;
+            let Some(column) = number_chars_transposed.get_mut(col) else {
+                return Err(PuzzleError::new(
+                    DAY,
+                    format!("expected {cols} columns"),
+                )
+                .at_line(number_lines[row].number));
+            };
+            column[row] = *number_char;
         }
     }
 
     let mut problem_number = 0;
     let mut numbers: Vec<usize> = Vec::new();
 
-    for number_vec in &number_chars_transposed {
+    for (col, number_vec) in number_chars_transposed.iter().enumerate() {
         if number_vec.iter().all(|c| c.is_whitespace()) {
-            problems[problem_number].numbers = numbers;
+            let Some(problem) = problems.get_mut(problem_number) else {
+                return Err(PuzzleError::new(
+                    DAY,
+                    format!("expected {} problems", problems.len()),
+                ));
+            };
+            problem.numbers = numbers;
             problem_number += 1;
             numbers = Vec::new();
         } else {
@@ -121,13 +146,48 @@ fn parse_right_to_left(input: &str) -> Homework {
                     .collect::<String>()
                     .trim()
                     .parse()
-                    .unwrap(),
+                    .map_err(|_| {
+                        PuzzleError::new(
+                            DAY,
+                            format!("column {} isn't a number", col + 1),
+                        )
+                    })?,
             );
         }
     }
     problems.last_mut().unwrap().numbers = numbers;
 
-    Homework { problems }
+    Ok(Homework { problems })
+}
+
+// Splits the worksheet into its lines of numbers and one empty problem per
+// operation on the last line.
+fn parse_sheet(
+    input: &str,
+) -> Result<(Vec<SourceLine<'_>>, Vec<Problem>), PuzzleError> {
+    let mut lines = parsing::lines(input.trim());
+    let Some(operation_line) = lines.pop() else {
+        return Err(PuzzleError::new(DAY, "expected a line of operations"));
+    };
+
+    let problems = operation_line
+        .text
+        .split_whitespace()
+        .map(|c| match c {
+            "*" => Ok(Problem {
+                numbers: Vec::new(),
+                operation: Operation::Multiply,
+            }),
+            "+" => Ok(Problem {
+                numbers: Vec::new(),
+                operation: Operation::Add,
+            }),
+            _ => Err(operation_line.error(c, "expected `*` or `+`")),
+        })
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((lines, problems))
 }
 
 #[cfg(test)]
@@ -165,4 +225,16 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 3263827);
     }
+
+    #[test]
+    fn test_invalid_worksheets() {
+        assert_eq!(
+            try_part1("1 2\n3 4 5\n* +\n").unwrap_err().to_string(),
+            "day 06, line 2: column 5, expected 2 numbers"
+        );
+        assert_eq!(
+            try_part2("1 2\n3 4\n* -\n").unwrap_err().to_string(),
+            "day 06, line 3: column 3, expected `*` or `+`"
+        );
+    }
 }
@@ -1,5 +1,9 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 7;
+
 type Position = (usize, usize);
 
 #[derive(Debug)]
@@ -9,9 +13,28 @@ struct Manifold {
     height: usize,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let manifold = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless the manifold is `.` and splitters `^` with a single start
+/// `S`, and no splitter on the left edge.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let manifold = parse(input)?;
     let mut beam_positions = HashSet::<usize>::new();
     let mut splits = 0usize;
 
@@ -33,12 +56,14 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    splits
+    Ok(splits)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let manifold = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let manifold = parse(input)?;
     let mut beams: HashMap<usize, usize> = HashMap::new();
 
     beams.insert(manifold.start_position.1, 1);
@@ -75,32 +100,51 @@ pub fn part2(input: &str) -> usize {
         beams = new_beams;
     }
 
-    beams.values().sum()
+    Ok(beams.values().sum())
 }
 
-fn parse(input: &str) -> Manifold {
-    let mut start_position = (0, 0);
+fn parse(input: &str) -> Result<Manifold, PuzzleError> {
+    let mut start_positions = Vec::new();
     let mut splitter_positions = Vec::new();
+    let lines = parsing::lines(input.trim());
 
-    for (row, line) in input.trim().lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
+    for (row, line) in lines.iter().enumerate() {
+        for (col, (index, char)) in line.text.char_indices().enumerate() {
             match char {
                 'S' => {
-                    start_position = (row, col);
+                    start_positions.push((row, col));
+                }
+                // A splitter sends beams to both sides, so none can sit on
+                // the left edge.
+                '^' if col == 0 => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected no splitter `^` on the left edge",
+                    )));
                 }
                 '^' => {
                     splitter_positions.push((row, col));
                 }
-                _ => {}
+                '.' => {}
+                _ => {
+                    return Err(PuzzleError::on_day(DAY)(line.error(
+                        &line.text[index..],
+                        "expected `.`, `S` or `^`",
+                    )));
+                }
             }
         }
     }
 
-    Manifold {
+    let [start_position] = start_positions[..] else {
+        return Err(PuzzleError::new(DAY, "expected a single start `S`"));
+    };
+
+    Ok(Manifold {
         start_position,
         splitter_positions,
-        height: input.trim().lines().count(),
-    }
+        height: lines.len(),
+    })
 }
 
 #[cfg(test)]
@@ -135,4 +179,23 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 40);
     }
+
+    #[test]
+    fn test_invalid_manifolds() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error(".....\n.....\n"),
+            "day 07: expected a single start `S`"
+        );
+        assert_eq!(
+            error("..S..\n..x..\n"),
+            "day 07, line 2: column 3, expected `.`, `S` or `^`"
+        );
+        assert_eq!(
+            error("..S..\n^....\n"),
+            "day 07, line 2: column 1, \
+             expected no splitter `^` on the left edge"
+        );
+    }
 }
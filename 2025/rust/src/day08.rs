@@ -1,9 +1,13 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::{
     cmp::Reverse,
     collections::HashSet,
     fmt::{self, Debug, Formatter},
 };
 
+const DAY: u8 = 8;
+
 #[derive(PartialEq, Eq, Hash)]
 struct Position {
     x: usize,
@@ -17,14 +21,37 @@ impl Debug for Position {
     }
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    part1_with_count(input, 1000)
+    try_part1(input).unwrap()
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
 #[must_use]
-pub fn part1_with_count(input: &str, count: usize) -> usize {
-    let box_positions = parse(input);
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a junction box position like `162,817,812`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    part1_with_count(input, 1000)
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn part1_with_count(
+    input: &str,
+    count: usize,
+) -> Result<usize, PuzzleError> {
+    let box_positions = parse(input)?;
     let distances = all_pairs_distances(&box_positions);
     let mut circuits: Vec<HashSet<&Position>> = Vec::new();
 
@@ -57,11 +84,11 @@ pub fn part1_with_count(input: &str, count: usize) -> usize {
     }
 
     circuits.sort_unstable_by_key(|nodes| Reverse(nodes.len()));
-    circuits
+    Ok(circuits
         .iter()
         .take(3)
         .map(std::collections::HashSet::len)
-        .product()
+        .product())
 }
 
 fn all_pairs_distances(
@@ -83,9 +110,11 @@ fn all_pairs_distances(
     pair_distances
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let box_positions = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let box_positions = parse(input)?;
     let distances = all_pairs_distances(&box_positions);
     let mut circuits: Vec<HashSet<&Position>> = Vec::new();
     let mut max_circuit_size = 0usize;
@@ -131,26 +160,24 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    answer
+    Ok(answer)
 }
 
-fn parse(input: &str) -> Vec<Position> {
-    input
-        .trim()
-        .lines()
+fn parse(input: &str) -> Result<Vec<Position>, PuzzleError> {
+    parsing::lines(input.trim())
+        .into_iter()
         .map(|line| {
-            let coords: Vec<usize> = line
-                .trim()
-                .split(',')
-                .map(|num| num.parse::<usize>().unwrap())
-                .collect();
-            Position {
-                x: coords[0],
-                y: coords[1],
-                z: coords[2],
-            }
+            let line = line.trim();
+            let coords = line.fields("{},{},{}")?;
+
+            Ok(Position {
+                x: line.parse(coords[0])?,
+                y: line.parse(coords[1])?,
+                z: line.parse(coords[2])?,
+            })
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -192,12 +219,12 @@ mod tests {
 
     #[test]
     fn test_part1_with_example_input() {
-        assert_eq!(part1_with_count(EXAMPLE_INPUT, 10), 40);
+        assert_eq!(part1_with_count(EXAMPLE_INPUT, 10).unwrap(), 40);
     }
 
     #[test]
     fn test_all_pairs_distances() {
-        let positions = parse(EXAMPLE_INPUT);
+        let positions = parse(EXAMPLE_INPUT).unwrap();
         let distances = all_pairs_distances(&positions);
 
         assert!(matches!(
@@ -222,4 +249,18 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 25272);
     }
+
+    #[test]
+    fn test_invalid_positions() {
+        assert_eq!(
+            try_part1("162,817,812\n57,618\n").unwrap_err().to_string(),
+            "day 08, line 2: column 4, expected `,`"
+        );
+        assert_eq!(
+            try_part2("162,817,812\n57,-618,57\n")
+                .unwrap_err()
+                .to_string(),
+            "day 08, line 2: column 4, invalid value `-618`"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use image::{ImageBuffer, Rgb, RgbImage};
 use imageproc::drawing::{draw_filled_circle_mut, draw_line_segment_mut};
 use std::{
@@ -5,6 +7,8 @@ use std::{
     fmt::{self, Debug, Formatter},
 };
 
+const DAY: u8 = 9;
+
 #[derive(PartialEq, Eq, Clone)]
 struct Position {
     x: usize,
@@ -25,9 +29,27 @@ enum Tile {
     Green,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let tiles = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a red tile position like `7,1`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let tiles = parse(input)?;
     let mut max_area = 0usize;
 
     for tile in &tiles {
@@ -48,12 +70,16 @@ pub fn part1(input: &str) -> usize {
         }
     }
 
-    max_area
+    Ok(max_area)
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let tiles = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`try_part1`], or unless the red tiles trace a
+/// loop along rows and columns that encloses an area.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let tiles = parse(input)?;
+    check_loop(&tiles)?;
     let (mut grid, x_mapping, y_mapping) = compress(&tiles);
     add_edges(&mut grid, &tiles, &x_mapping, &y_mapping);
     flood_fill(&mut grid);
@@ -84,7 +110,38 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    max_area
+    Ok(max_area)
+}
+
+// Part 2 needs the red tiles to trace out a loop whose every edge runs along a
+// row or column, and that loop to enclose some area for the flood fill.
+fn check_loop(tiles: &[Position]) -> Result<(), PuzzleError> {
+    for (index, tile) in tiles.iter().enumerate() {
+        let previous = &tiles[(index + tiles.len() - 1) % tiles.len()];
+
+        if tile.x != previous.x && tile.y != previous.y {
+            return Err(PuzzleError::new(
+                DAY,
+                "expected a tile in the same row or column as the one before",
+            )
+            .at_line(index + 1));
+        }
+    }
+
+    let distinct = |axis: fn(&Position) -> usize| {
+        let mut values: Vec<usize> = tiles.iter().map(axis).collect();
+        values.sort_unstable();
+        values.dedup();
+        values.len()
+    };
+    if distinct(|p| p.x) < 2 || distinct(|p| p.y) < 2 {
+        return Err(PuzzleError::new(
+            DAY,
+            "expected the red tiles to enclose an area",
+        ));
+    }
+
+    Ok(())
 }
 
 #[allow(clippy::needless_range_loop)]
@@ -312,19 +369,22 @@ fn draw_tiles_png(tiles: &[Position], filename: &str) {
     img.save(filename).unwrap();
 }
 
-fn parse(input: &str) -> Vec<Position> {
-    input
-        .trim()
-        .lines()
+// Line numbers count from the first tile, which is also how `check_loop`
+// reports them.
+fn parse(input: &str) -> Result<Vec<Position>, PuzzleError> {
+    parsing::lines(input.trim())
+        .into_iter()
         .map(|line| {
-            let (x_string, y_string) = line.split_once(',').unwrap();
+            let line = line.trim();
+            let (x_string, y_string) = line.split_once(line.text, ",")?;
 
-            Position {
-                x: x_string.parse().unwrap(),
-                y: y_string.parse().unwrap(),
-            }
+            Ok(Position {
+                x: line.parse(x_string)?,
+                y: line.parse(y_string)?,
+            })
         })
-        .collect()
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
 #[cfg(test)]
@@ -351,4 +411,23 @@ mod tests {
     fn test_part2_with_example_input() {
         assert_eq!(part2(EXAMPLE_INPUT), 24);
     }
+
+    #[test]
+    fn test_invalid_tiles() {
+        assert_eq!(
+            try_part1("7,1\n11;1\n").unwrap_err().to_string(),
+            "day 09, line 2: column 1, expected `,`"
+        );
+        assert_eq!(
+            try_part2("7,1\n11,1\n11,7\n9,5\n9,1\n")
+                .unwrap_err()
+                .to_string(),
+            "day 09, line 4: \
+             expected a tile in the same row or column as the one before"
+        );
+        assert_eq!(
+            try_part2("7,1\n11,1\n").unwrap_err().to_string(),
+            "day 09: expected the red tiles to enclose an area"
+        );
+    }
 }
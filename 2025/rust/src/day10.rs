@@ -1,6 +1,12 @@
-use itertools::Itertools;
+use crate::gf2::{BitMatrix, BitVector};
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+#[cfg(feature = "z3")]
 use z3::ast::{Ast, Int};
-use z3::{Config, Context, Optimize};
+#[cfg(feature = "z3")]
+use z3::{Config, Context, Optimize, SatResult};
+
+const DAY: u8 = 10;
 
 #[derive(Clone, Debug, PartialEq)]
 enum State {
@@ -17,32 +23,89 @@ struct Machine {
     target_joltage: Vec<usize>,
 }
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let machines = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails unless each line is a machine like `[.##.] (3) (1,3) {3,5}` whose
+/// buttons and joltages match its lights, or when no presses light a machine
+/// up.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let machines = parse(input)?;
 
     machines
         .iter()
-        .map(|machine| {
+        .map(|(line, machine)| {
             find_minimum_button_presses(&machine.target_state, &machine.buttons)
-                .len()
+                .map(|presses| presses.len())
+                .ok_or_else(|| {
+                    PuzzleError::new(DAY, "the lights can't be reached")
+                        .at_line(*line)
+                })
         })
         .sum()
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let machines = parse(input);
+/// # Errors
+///
+/// Fails on the same input as [`part2_with`].
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    part2_with(input, Backend::Builtin)
+}
+
+// How part 2's integer program gets solved. The z3 backend needs a system
+// libz3, so it is only built with `--features z3`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Builtin,
+    #[cfg(feature = "z3")]
+    Z3,
+}
+
+/// # Errors
+///
+/// Fails on the machines [`try_part1`] can't parse, or when no presses reach
+/// a machine's joltages without overflow.
+pub fn part2_with(input: &str, backend: Backend) -> Result<usize, PuzzleError> {
+    let machines = parse(input)?;
 
     machines
         .iter()
-        .map(|machine| {
-            minimum_presses_z3(&machine.target_joltage, &machine.buttons)
+        .map(|(line, machine)| {
+            let (target, buttons) = (&machine.target_joltage, &machine.buttons);
+
+            match backend {
+                Backend::Builtin => minimum_presses(target, buttons),
+                #[cfg(feature = "z3")]
+                Backend::Z3 => minimum_presses_z3(target, buttons),
+            }
+            .ok_or_else(|| {
+                PuzzleError::new(DAY, "the joltages can't be reached")
+                    .at_line(*line)
+            })
         })
         .sum()
 }
 
-fn minimum_presses_z3(target: &[usize], buttons: &[Vec<usize>]) -> usize {
+#[cfg(feature = "z3")]
+fn minimum_presses_z3(
+    target: &[usize],
+    buttons: &[Vec<usize>],
+) -> Option<usize> {
     let cfg = Config::new();
     let ctx = Context::new(&cfg);
     let opt = Optimize::new(&ctx);
@@ -75,182 +138,314 @@ fn minimum_presses_z3(target: &[usize], buttons: &[Vec<usize>]) -> usize {
     opt.minimize(&total);
 
     // Solve
-    opt.check(&[]);
+    if opt.check(&[]) != SatResult::Sat {
+        return None;
+    }
 
     // Extract solution
-    let model = opt.get_model().unwrap();
+    let model = opt.get_model()?;
     button_vars
         .iter()
-        .map(|var| {
-            usize::try_from(model.eval(var, true).unwrap().as_u64().unwrap())
-                .unwrap()
-        })
+        .map(|var| usize::try_from(model.eval(var, true)?.as_u64()?).ok())
         .sum()
 }
 
+// Pressing a button twice undoes it, so each button is pressed at most once
+// and the presses form a solution of a linear system over GF(2). None when no
+// presses reach the target.
 fn find_minimum_button_presses<'a>(
     target_state: &'a [State],
     buttons: &'a [Button],
-) -> Vec<&'a Button> {
-    let target_value =
-        target_state
-            .iter()
-            .enumerate()
-            .fold(0usize, |acc, (i, state)| match state {
-                State::On => acc | (1 << i),
-                State::Off => acc,
-            });
-    let button_values = buttons
-        .iter()
-        .map(|button| {
-            button.iter().fold(0usize, |acc, &index| acc | (1 << index))
-        })
-        .collect::<Vec<usize>>();
-    let mut optimal_button_presses = Vec::new();
-
-    let n = buttons.len();
-    for combination in 1..(1 << n) {
-        let mut combined_value = 0usize;
-        let mut button_presses = Vec::new();
-
-        for bit_position in 0..n {
-            if (combination & (1 << bit_position)) != 0 {
-                button_presses.push(&buttons[bit_position]);
-                combined_value ^= button_values[bit_position];
-            }
+) -> Option<Vec<&'a Button>> {
+    let lights = target_state.len();
+    let target = BitVector::from_ones(
+        lights,
+        (0..lights).filter(|&light| target_state[light] == State::On),
+    );
+
+    let presses = BitMatrix::from_columns(lights, buttons)
+        .solve(&target)?
+        .minimum_weight();
+
+    Some(presses.ones().map(|button| &buttons[button]).collect())
+}
+
+// Exact rational arithmetic for the elimination below; the denominator is
+// always positive and coprime with the numerator. Each operation works in
+// i128 and returns None when the reduced result doesn't fit back in an i64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    const fn integer(value: i64) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
         }
-        if combined_value == target_value
-            && (optimal_button_presses.is_empty()
-                || button_presses.len() < optimal_button_presses.len())
-        {
-            optimal_button_presses.clone_from(&button_presses);
+    }
+
+    fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+
+        Some(Self {
+            numerator: i64::try_from(numerator / divisor).ok()?,
+            denominator: i64::try_from(denominator / divisor).ok()?,
+        })
+    }
+
+    fn count(value: usize) -> Option<Self> {
+        i64::try_from(value).ok().map(Self::integer)
+    }
+
+    fn is_zero(self) -> bool {
+        self.numerator == 0
+    }
+
+    fn to_integer(self) -> Option<i64> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+
+    fn wide(self) -> (i128, i128) {
+        (i128::from(self.numerator), i128::from(self.denominator))
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        let ((a, b), (c, d)) = (self.wide(), other.wide());
+
+        Self::new((a * d).checked_sub(c * b)?, b * d)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        let ((a, b), (c, d)) = (self.wide(), other.wide());
+
+        Self::new(a * c, b * d)
+    }
+
+    fn checked_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
         }
+        let ((a, b), (c, d)) = (self.wide(), other.wide());
+
+        Self::new(a * d, b * c)
     }
+}
 
-    optimal_button_presses
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
 }
 
-// Solve part 2 via brute-force search (too slow)
-#[allow(dead_code)]
-fn minimum_presses(target_joltages: &[usize], button_list: &[Button]) -> usize {
-    let n = target_joltages.len();
-    let button_values = button_list
+// The fewest total presses, if any, that make every counter hit its target
+// exactly. Gaussian elimination writes the pivot buttons' press counts in
+// terms of the free buttons, and the free buttons are then searched within
+// the bounds the targets put on them (no button can be pressed more often
+// than the smallest target among the counters it bumps). None also covers
+// overflow in the elimination.
+fn minimum_presses(target: &[usize], buttons: &[Button]) -> Option<usize> {
+    let width = buttons.len();
+    let mut rows: Vec<Vec<Rational>> = target
         .iter()
-        .map(|button| {
-            let mut value = vec![0usize; n];
-            for index in button {
-                value[*index] = 1;
-            }
-            value
+        .enumerate()
+        .map(|(counter, &value)| {
+            let row: Vec<Rational> = buttons
+                .iter()
+                .map(|button| {
+                    Rational::integer(button.contains(&counter).into())
+                })
+                .collect();
+            Some([row, vec![Rational::count(value)?]].concat())
         })
-        .collect::<Vec<Vec<usize>>>();
+        .collect::<Option<_>>()?;
+
+    let mut pivots: Vec<usize> = vec![];
+
+    for column in 0..width {
+        let rank = pivots.len();
+        let Some(pivot) =
+            (rank..rows.len()).find(|&r| !rows[r][column].is_zero())
+        else {
+            continue;
+        };
+        rows.swap(rank, pivot);
 
-    let multiple_button_values: Vec<Vec<Vec<usize>>> = button_values
+        let scale = rows[rank][column];
+        for value in &mut rows[rank] {
+            *value = value.checked_div(scale)?;
+        }
+
+        let pivot_row = rows[rank].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if index != rank && !factor.is_zero() {
+                for (value, &pivot_value) in row.iter_mut().zip(&pivot_row) {
+                    *value =
+                        value.checked_sub(factor.checked_mul(pivot_value)?)?;
+                }
+            }
+        }
+
+        pivots.push(column);
+    }
+
+    if rows[pivots.len()..].iter().any(|row| !row[width].is_zero()) {
+        return None;
+    }
+
+    let free: Vec<usize> = (0..width)
+        .filter(|column| !pivots.contains(column))
+        .collect();
+    let bounds: Vec<usize> = free
         .iter()
-        .map(|button_value| {
-            let max_multiples = target_joltages
+        .map(|&column| {
+            buttons[column]
                 .iter()
-                .zip(button_value.iter())
-                .map(|(&target, &button)| {
-                    if button == 0 {
-                        usize::MAX
-                    } else {
-                        target / button
-                    }
-                })
+                .map(|&counter| target[counter])
                 .min()
-                .unwrap();
-
-            (0..=max_multiples)
-                .rev()
-                .map(|multiple| {
-                    button_value
-                        .iter()
-                        .map(|&v| v * multiple)
-                        .collect::<Vec<usize>>()
-                })
-                .collect()
+                .unwrap_or(0)
         })
         .collect();
 
-    let mut minimum_presses = usize::MAX;
+    let mut best = None;
+    search_free(
+        &rows[..pivots.len()],
+        &free,
+        &bounds,
+        &mut vec![],
+        &mut best,
+    )?;
 
-    for combination in multiple_button_values.iter().multi_cartesian_product() {
-        let sum = sum_joltages(&combination);
+    best
+}
 
-        if sum == target_joltages {
-            let presses = combination
-                .iter()
-                .map(|value| value.iter().max().unwrap())
-                .sum();
-            if presses < minimum_presses {
-                minimum_presses = presses;
-            }
+// Keeps the fewest presses found so far in `best`; None on overflow.
+fn search_free(
+    rows: &[Vec<Rational>],
+    free: &[usize],
+    bounds: &[usize],
+    presses: &mut Vec<usize>,
+    best: &mut Option<usize>,
+) -> Option<()> {
+    let free_total: usize = presses.iter().sum();
+    if best.is_some_and(|best| free_total >= best) {
+        return Some(());
+    }
+
+    if presses.len() < free.len() {
+        for count in 0..=bounds[presses.len()] {
+            presses.push(count);
+            search_free(rows, free, bounds, presses, best)?;
+            presses.pop();
         }
+        return Some(());
     }
 
-    minimum_presses
-}
+    let width = rows.first().map_or(0, |row| row.len() - 1);
+    let mut pivot_total = Some(0);
+    for row in rows {
+        let mut value = row[width];
+        for (&column, &count) in free.iter().zip(presses.iter()) {
+            let used = row[column].checked_mul(Rational::count(count)?)?;
+            value = value.checked_sub(used)?;
+        }
+
+        // Pivot buttons need a whole, non-negative number of presses.
+        let presses = value.to_integer().and_then(|v| usize::try_from(v).ok());
+        pivot_total = pivot_total.zip(presses).map(|(total, p)| total + p);
+    }
+
+    if let Some(pivot_total) = pivot_total {
+        let total = free_total + pivot_total;
+        if best.is_none_or(|best| total < best) {
+            *best = Some(total);
+        }
+    }
 
-#[allow(dead_code)]
-fn sum_joltages(joltages: &[&Vec<usize>]) -> Vec<usize> {
-    let length = joltages[0].len();
+    Some(())
+}
 
-    joltages
+// Each machine along with the line it came from.
+fn parse(input: &str) -> Result<Vec<(usize, Machine)>, PuzzleError> {
+    parsing::lines(input.trim())
         .iter()
-        .fold(vec![0usize; length], |joltages, button| {
-            joltages
-                .iter()
-                .zip(button.iter())
-                .map(|(&a, &b)| a + b)
-                .collect::<Vec<usize>>()
-        })
+        .map(|line| Ok((line.number, parse_machine(line)?)))
+        .collect::<parsing::Result<_>>()
+        .map_err(PuzzleError::on_day(DAY))
 }
 
-fn parse(input: &str) -> Vec<Machine> {
-    let mut machines = Vec::new();
-
-    for line in input.trim().lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        let lights_str = parts[0];
-        let buttons_strs = &parts[1..parts.len() - 1];
-        let joltages_str = parts.last().unwrap();
-
-        let target_state: Vec<State> = lights_str
-            .trim_matches(&['[', ']'][..])
-            .chars()
-            .map(|c| if c == '#' { State::On } else { State::Off })
-            .collect();
-
-        let buttons: Vec<Vec<usize>> = buttons_strs
-            .iter()
-            .map(|&s| {
-                s.trim_matches(&['(', ')'][..])
-                    .split(',')
-                    .map(|num_str| num_str.parse::<usize>().unwrap())
-                    .collect()
-            })
-            .collect();
-
-        let target_joltage: Vec<usize> = joltages_str
-            .trim_matches(&['{', '}'][..])
-            .split(',')
-            .map(|num_str| num_str.parse::<usize>().unwrap())
-            .collect();
-
-        machines.push(Machine {
-            target_state,
-            buttons,
-            target_joltage,
-        });
+fn parse_machine(line: &SourceLine<'_>) -> parsing::Result<Machine> {
+    let parts: Vec<&str> = line.text.split_whitespace().collect();
+    let [lights_str, buttons_strs @ .., joltages_str] = &parts[..] else {
+        return Err(line.error(line.text, "expected lights and joltages"));
+    };
+
+    let target_state: Vec<State> = bracketed(line, lights_str, "[", "]")?
+        .char_indices()
+        .map(|(index, c)| match c {
+            '#' => Ok(State::On),
+            '.' => Ok(State::Off),
+            _ => {
+                Err(line.error(&lights_str[index + 1..], "expected `#` or `.`"))
+            }
+        })
+        .collect::<parsing::Result<_>>()?;
+    let lights = target_state.len();
+
+    let buttons: Vec<Vec<usize>> = buttons_strs
+        .iter()
+        .map(|s| {
+            bracketed(line, s, "(", ")")?
+                .split(',')
+                .map(|num_str| {
+                    let light = line.parse(num_str)?;
+                    if light < lights {
+                        Ok(light)
+                    } else {
+                        Err(line.error(
+                            num_str,
+                            format!("expected a light below {lights}"),
+                        ))
+                    }
+                })
+                .collect()
+        })
+        .collect::<parsing::Result<_>>()?;
+
+    let target_joltage: Vec<usize> = bracketed(line, joltages_str, "{", "}")?
+        .split(',')
+        .map(|num_str| line.parse(num_str))
+        .collect::<parsing::Result<_>>()?;
+    if target_joltage.len() != lights {
+        return Err(
+            line.error(joltages_str, format!("expected {lights} joltages"))
+        );
     }
 
-    machines
+    Ok(Machine {
+        target_state,
+        buttons,
+        target_joltage,
+    })
+}
+
+// `field` with its `open` and `close` brackets removed.
+fn bracketed<'a>(
+    line: &SourceLine<'a>,
+    field: &'a str,
+    open: &str,
+    close: &str,
+) -> parsing::Result<&'a str> {
+    line.strip_prefix(field, open)?
+        .strip_suffix(close)
+        .ok_or_else(|| line.error(field, format!("expected `{close}`")))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Rng;
 
     const EXAMPLE_INPUT: &str = "\
 [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
@@ -312,6 +507,7 @@ mod tests {
                 &machine1.target_state,
                 &machine1.buttons
             )
+            .unwrap()
             .len(),
             2
         );
@@ -320,6 +516,7 @@ mod tests {
                 &machine2.target_state,
                 &machine2.buttons
             )
+            .unwrap()
             .len(),
             3
         );
@@ -328,6 +525,7 @@ mod tests {
                 &machine3.target_state,
                 &machine3.buttons
             )
+            .unwrap()
             .len(),
             2
         );
@@ -336,15 +534,15 @@ mod tests {
 
         assert_eq!(
             minimum_presses(&machine1.target_joltage, &machine1.buttons),
-            10
+            Some(10)
         );
         assert_eq!(
             minimum_presses(&machine2.target_joltage, &machine2.buttons),
-            12
+            Some(12)
         );
         assert_eq!(
             minimum_presses(&machine3.target_joltage, &machine3.buttons),
-            11
+            Some(11)
         );
 
         assert_eq!(part2(EXAMPLE_INPUT), 33);
@@ -357,4 +555,129 @@ mod tests {
         ";
         assert_eq!(part2(input), 252);
     }
+
+    fn random_machines(count: usize) -> Vec<(Vec<usize>, Vec<Button>)> {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        let mut next = |bound: usize| rng.below(bound);
+
+        (0..count)
+            .map(|_| {
+                let counters = 2 + next(4);
+                let buttons: Vec<Button> = (0..2 + next(5))
+                    .map(|_| (0..counters).filter(|_| next(2) == 0).collect())
+                    .collect();
+                let mut target = vec![0; counters];
+
+                for button in &buttons {
+                    let presses = next(6);
+                    for &counter in button {
+                        target[counter] += presses;
+                    }
+                }
+
+                (target, buttons)
+            })
+            .collect()
+    }
+
+    // Tries every press count up to the largest target.
+    fn exhaustive(target: &[usize], buttons: &[Button]) -> Option<usize> {
+        let limit = target.iter().max().copied().unwrap_or(0);
+        let mut presses = vec![0; buttons.len()];
+        let mut best = None;
+
+        loop {
+            let mut counters = vec![0; target.len()];
+            for (button, &count) in buttons.iter().zip(&presses) {
+                for &counter in button {
+                    counters[counter] += count;
+                }
+            }
+            if counters == target {
+                let total = presses.iter().sum();
+                best = Some(best.map_or(total, |best: usize| best.min(total)));
+            }
+
+            let Some(index) = presses.iter().position(|&count| count < limit)
+            else {
+                return best;
+            };
+            presses[..index].fill(0);
+            presses[index] += 1;
+        }
+    }
+
+    #[test]
+    fn test_builtin_matches_exhaustive_search() {
+        for (target, buttons) in random_machines(40) {
+            if buttons.len() <= 5 {
+                assert_eq!(
+                    minimum_presses(&target, &buttons),
+                    exhaustive(&target, &buttons),
+                    "{target:?} {buttons:?}"
+                );
+            }
+        }
+        assert_eq!(minimum_presses(&[1, 2], &[vec![0, 1]]), None);
+    }
+
+    #[test]
+    fn test_overflow_is_none() {
+        let big = Rational::integer(i64::MAX);
+
+        assert_eq!(big.checked_mul(Rational::integer(2)), None);
+        assert_eq!(big.checked_sub(Rational::integer(-1)), None);
+        assert_eq!(
+            big.checked_div(Rational::integer(2)),
+            Rational::new(i128::from(i64::MAX), 2)
+        );
+        assert_eq!(big.checked_div(Rational::integer(0)), None);
+        assert_eq!(minimum_presses(&[usize::MAX], &[vec![0]]), None);
+    }
+
+    #[cfg(feature = "z3")]
+    #[test]
+    fn test_backends_agree() {
+        for (target, buttons) in random_machines(200) {
+            assert_eq!(
+                minimum_presses(&target, &buttons),
+                minimum_presses_z3(&target, &buttons),
+                "{target:?} {buttons:?}"
+            );
+        }
+        assert_eq!(
+            part2_with(EXAMPLE_INPUT, Backend::Builtin).unwrap(),
+            part2_with(EXAMPLE_INPUT, Backend::Z3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_machines() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("[.##.] (3) (1,3) {3,5,4,7}\n[.#x] (0,1) {1,2,3}\n"),
+            "day 10, line 2: column 4, expected `#` or `.`"
+        );
+        assert_eq!(
+            error("[.##.] (3) (1,4) {3,5,4,7}\n"),
+            "day 10, line 1: column 15, expected a light below 4"
+        );
+        assert_eq!(
+            error("[.##.] (3) (1,3) {3,5,4}\n"),
+            "day 10, line 1: column 18, expected 4 joltages"
+        );
+        assert_eq!(
+            error("[.##.] (3) (1,3) 3,5,4,7\n"),
+            "day 10, line 1: column 18, expected `{`"
+        );
+        assert_eq!(
+            error("[.#] (0) {0,1}\n"),
+            "day 10, line 1: the lights can't be reached"
+        );
+        assert_eq!(
+            try_part2("[.#] (0,1) {1,2}\n").unwrap_err().to_string(),
+            "day 10, line 1: the joltages can't be reached"
+        );
+    }
 }
@@ -1,16 +1,42 @@
+use crate::parsing;
+use crate::puzzle::PuzzleError;
 use std::collections::{HashMap, HashSet};
 
+const DAY: u8 = 11;
+
 type Device = String;
 
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let map = parse(input);
+    try_part1(input).unwrap()
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails when a line isn't a device and its outputs like `aaa: you hhh`, or
+/// there's no device `you`.
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let map = parse(input)?;
+    if !map.contains_key("you") {
+        return Err(PuzzleError::new(DAY, "expected a device `you`"));
+    }
     let paths = bfs("you".to_string(), &map);
 
-    paths
+    Ok(paths
         .iter()
         .filter(|path| path.last().unwrap() == "out")
-        .count()
+        .count())
 }
 
 fn bfs(start: Device, map: &HashMap<Device, Vec<Device>>) -> Vec<Vec<Device>> {
@@ -55,11 +81,21 @@ fn bfs(start: Device, map: &HashMap<Device, Vec<Device>>) -> Vec<Vec<Device>> {
     paths
 }
 
-#[must_use]
-pub fn part2(input: &str) -> usize {
-    let map = parse(input);
+/// # Errors
+///
+/// Fails when a line isn't a device and its outputs like `aaa: you hhh`, or
+/// there's no server rack `svr`.
+pub fn try_part2(input: &str) -> Result<usize, PuzzleError> {
+    let map = parse(input)?;
+    if !map.contains_key("svr") {
+        return Err(PuzzleError::new(DAY, "expected a server rack `svr`"));
+    }
     let required = vec!["dac".to_string(), "fft".to_string()];
-    count_paths_through_devices("svr".to_string(), required, &map)
+    Ok(count_paths_through_devices(
+        "svr".to_string(),
+        required,
+        &map,
+    ))
 }
 
 fn count_paths_through_devices(
@@ -133,11 +169,13 @@ fn count_paths_dfs(
     count
 }
 
-fn parse(input: &str) -> HashMap<Device, Vec<Device>> {
+fn parse(input: &str) -> Result<HashMap<Device, Vec<Device>>, PuzzleError> {
     let mut map = HashMap::new();
 
-    for line in input.trim().lines() {
-        let (device, connections_string) = line.split_once(": ").unwrap();
+    for line in parsing::lines(input.trim()) {
+        let (device, connections_string) = line
+            .split_once(line.text, ": ")
+            .map_err(PuzzleError::on_day(DAY))?;
         let connections = connections_string
             .trim()
             .split_whitespace()
@@ -147,7 +185,7 @@ fn parse(input: &str) -> HashMap<Device, Vec<Device>> {
         map.insert(device.to_string(), connections);
     }
 
-    map
+    Ok(map)
 }
 
 #[cfg(test)]
@@ -191,4 +229,22 @@ hhh: out
     fn test_part2_with_example_input() {
         assert_eq!(part2(PART_2_EXAMPLE_INPUT), 2);
     }
+
+    #[test]
+    fn test_invalid_devices() {
+        assert_eq!(
+            try_part1("aaa: you hhh\nyou bbb\n")
+                .unwrap_err()
+                .to_string(),
+            "day 11, line 2: column 1, expected `: `"
+        );
+        assert_eq!(
+            try_part1(PART_2_EXAMPLE_INPUT).unwrap_err().to_string(),
+            "day 11: expected a device `you`"
+        );
+        assert_eq!(
+            try_part2(PART_1_EXAMPLE_INPUT).unwrap_err().to_string(),
+            "day 11: expected a server rack `svr`"
+        );
+    }
 }
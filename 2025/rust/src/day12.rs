@@ -1,85 +1,415 @@
-use std::collections::HashMap;
+use crate::parsing::{self, SourceLine};
+use crate::puzzle::PuzzleError;
+use std::collections::{BTreeSet, HashMap};
 
-type Shape = Vec<Vec<bool>>;
+const DAY: u8 = 12;
+
+type Cell = (usize, usize);
+
+// Every distinct rotation and reflection of a present, as (x, y) cell
+// offsets. Each orientation is shifted so its first cell in reading order is
+// at (0, 0), which is the cell the search anchors it on.
+#[derive(Debug)]
+pub struct Shape {
+    orientations: Vec<Vec<(isize, isize)>>,
+    width: usize,
+    height: usize,
+}
 
 #[derive(Debug)]
-struct Region {
+pub struct Region {
+    pub width: usize,
+    pub height: usize,
+    pub shape_counts: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub shape: usize,
+    pub cells: Vec<Cell>,
+}
+
+impl Shape {
+    fn new(grid: &[Vec<bool>]) -> Self {
+        let cells: Vec<(isize, isize)> =
+            grid.iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.iter().enumerate().filter(|&(_x, &filled)| filled).map(
+                        move |(x, _filled)| (x.cast_signed(), y.cast_signed()),
+                    )
+                })
+                .collect();
+
+        let orientations: BTreeSet<Vec<(isize, isize)>> = (0..8)
+            .map(|transform| {
+                let mut oriented: Vec<(isize, isize)> = cells
+                    .iter()
+                    .map(|&(x, y)| {
+                        let (x, y) =
+                            if transform & 4 == 0 { (x, y) } else { (-x, y) };
+                        match transform & 3 {
+                            0 => (x, y),
+                            1 => (-y, x),
+                            2 => (-x, -y),
+                            _ => (y, -x),
+                        }
+                    })
+                    .collect();
+                oriented.sort_unstable_by_key(|&(x, y)| (y, x));
+
+                let (anchor_x, anchor_y) = oriented[0];
+                oriented
+                    .iter()
+                    .map(|&(x, y)| (x - anchor_x, y - anchor_y))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            orientations: orientations.into_iter().collect(),
+            width: grid.iter().map(Vec::len).max().unwrap_or(0),
+            height: grid.len(),
+        }
+    }
+
+    #[must_use]
+    pub fn area(&self) -> usize {
+        self.orientations[0].len()
+    }
+}
+
+// The occupancy grid and remaining presents for one packing search.
+struct Packer<'a> {
+    shapes: &'a [Shape],
     width: usize,
     height: usize,
-    shape_counts: Vec<usize>,
+    filled: Vec<bool>,
+    remaining: Vec<usize>,
+    placements: Vec<Placement>,
 }
 
+impl Region {
+    // Packs every present into the region, returning where each one went, or
+    // `None` once every arrangement has been ruled out.
+    #[must_use]
+    pub fn pack(&self, shapes: &[Shape]) -> Option<Vec<Placement>> {
+        let area = self.width * self.height;
+        let present_area: usize = self
+            .shape_counts
+            .iter()
+            .zip(shapes)
+            .map(|(count, shape)| count * shape.area())
+            .sum();
+
+        if present_area > area {
+            return None;
+        }
+        if let Some(placements) = self.pack_in_slots(shapes) {
+            return Some(placements);
+        }
+
+        // Orientations include reflections, so searching the transposed
+        // region is equivalent; scanning along the short side keeps the
+        // frontier of unfilled cells narrow and dead ends show up sooner.
+        let transposed = self.width > self.height;
+        let mut packer = Packer {
+            shapes,
+            width: self.width.min(self.height),
+            height: self.width.max(self.height),
+            filled: vec![false; area],
+            remaining: self.shape_counts.clone(),
+            placements: vec![],
+        };
+
+        if !packer.search(0, area - present_area) {
+            return None;
+        }
+        if transposed {
+            for placement in &mut packer.placements {
+                for cell in &mut placement.cells {
+                    *cell = (cell.1, cell.0);
+                }
+            }
+        }
+
+        Some(packer.placements)
+    }
+
+    // When every present fits in its own bounding-box slot there is no need
+    // to search: lay them out in a grid of slots.
+    fn pack_in_slots(&self, shapes: &[Shape]) -> Option<Vec<Placement>> {
+        let slot = shapes
+            .iter()
+            .map(|shape| shape.width.max(shape.height))
+            .max()?
+            .max(1);
+        let columns = self.width / slot;
+        let slots = columns * (self.height / slot);
+        let presents = self.shape_counts.iter().flat_map(|&count| 0..count);
+
+        if presents.count() > slots {
+            return None;
+        }
+
+        let mut index = 0;
+        let mut placements = vec![];
+
+        for (shape, &count) in self.shape_counts.iter().enumerate() {
+            for _ in 0..count {
+                let cells = &shapes[shape].orientations[0];
+                let min_x = cells.iter().map(|&(x, _y)| x).min().unwrap_or(0);
+                let min_y = cells.iter().map(|&(_x, y)| y).min().unwrap_or(0);
+                let left = (index % columns * slot).cast_signed() - min_x;
+                let top = (index / columns * slot).cast_signed() - min_y;
+
+                placements.push(Placement {
+                    shape,
+                    cells: cells
+                        .iter()
+                        .map(|&(x, y)| {
+                            (
+                                (left + x).cast_unsigned(),
+                                (top + y).cast_unsigned(),
+                            )
+                        })
+                        .collect(),
+                });
+                index += 1;
+            }
+        }
+
+        Some(placements)
+    }
+}
+
+impl Packer<'_> {
+    // Algorithm X over the cells in reading order: the first unfilled cell
+    // is either covered by some orientation of a remaining present anchored
+    // there, or left empty, which spends one unit of `slack`. Identical
+    // presents are counted rather than labelled, so swapping two copies
+    // never shows up as a different branch.
+    fn search(&mut self, start: usize, slack: usize) -> bool {
+        if self.remaining.iter().all(|&count| count == 0) {
+            return true;
+        }
+
+        let Some(index) = (start..self.filled.len()).find(|&i| !self.filled[i])
+        else {
+            return false;
+        };
+        let (x, y) = (index % self.width, index / self.width);
+
+        for shape in 0..self.shapes.len() {
+            if self.remaining[shape] == 0 {
+                continue;
+            }
+
+            for orientation in 0..self.shapes[shape].orientations.len() {
+                let Some(cells) = self.fit(shape, orientation, x, y) else {
+                    continue;
+                };
+
+                self.set(&cells, true);
+                self.remaining[shape] -= 1;
+                self.placements.push(Placement { shape, cells });
+
+                if self.search(index + 1, slack) {
+                    return true;
+                }
+
+                let placement = self.placements.pop().unwrap();
+                self.remaining[shape] += 1;
+                self.set(&placement.cells, false);
+            }
+        }
+
+        if slack > 0 {
+            self.filled[index] = true;
+            let found = self.search(index + 1, slack - 1);
+            self.filled[index] = false;
+
+            return found;
+        }
+
+        false
+    }
+
+    fn fit(
+        &self,
+        shape: usize,
+        orientation: usize,
+        x: usize,
+        y: usize,
+    ) -> Option<Vec<Cell>> {
+        self.shapes[shape].orientations[orientation]
+            .iter()
+            .map(|&(dx, dy)| {
+                let cell_x = x.checked_add_signed(dx)?;
+                let cell_y = y.checked_add_signed(dy)?;
+
+                (cell_x < self.width
+                    && cell_y < self.height
+                    && !self.filled[cell_y * self.width + cell_x])
+                    .then_some((cell_x, cell_y))
+            })
+            .collect()
+    }
+
+    fn set(&mut self, cells: &[Cell], filled: bool) {
+        for &(x, y) in cells {
+            self.filled[y * self.width + x] = filled;
+        }
+    }
+}
+
+// Draws placements with a letter per present and `.` for empty cells.
+#[must_use]
+pub fn render(region: &Region, placements: &[Placement]) -> String {
+    let mut grid = vec![vec!['.'; region.width]; region.height];
+
+    for (index, placement) in placements.iter().enumerate() {
+        let letter = char::from(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ"[index % 26]);
+        for &(x, y) in &placement.cells {
+            grid[y][x] = letter;
+        }
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+// The quick check this puzzle is usually answered with: every present gets
+// a full 3x3 square. It is wrong for the example.
+#[must_use]
+pub fn fits_by_area(region: &Region) -> bool {
+    region.width * region.height
+        >= region.shape_counts.iter().sum::<usize>() * 9
+}
+
+/// # Panics
+///
+/// Panics on input [`try_part1`] rejects.
 #[must_use]
 pub fn part1(input: &str) -> usize {
-    let (_shapes, regions) = parse(input);
+    try_part1(input).unwrap()
+}
 
-    //    dbg!(&shapes);
-    //    dbg!(&regions);
+/// # Panics
+///
+/// Panics on input [`try_part2`] rejects.
+#[must_use]
+pub fn part2(input: &str) -> usize {
+    try_part2(input).unwrap()
+}
+
+/// # Errors
+///
+/// Fails on the same input as [`parse`].
+pub fn try_part1(input: &str) -> Result<usize, PuzzleError> {
+    let (shapes, regions) = parse(input)?;
 
-    regions
+    Ok(regions
         .iter()
-        .filter(|region| {
-            region.width * region.height
-                >= region.shape_counts.iter().sum::<usize>() * 9
-        })
-        .count()
+        .filter(|region| region.pack(&shapes).is_some())
+        .count())
 }
 
-#[must_use]
-pub fn part2(_input: &str) -> usize {
-    0
+/// # Errors
+///
+/// Never fails: the last day has no second puzzle.
+pub fn try_part2(_input: &str) -> Result<usize, PuzzleError> {
+    Ok(0)
 }
 
-fn parse(input: &str) -> (HashMap<usize, Shape>, Vec<Region>) {
+/// # Errors
+///
+/// Fails unless the input is shapes numbered from 0, each drawn in `#` and
+/// `.` with at least one `#`, followed by regions like `12x5: 1 0 1 0 2 2`
+/// with a count for every shape.
+pub fn parse(input: &str) -> Result<(Vec<Shape>, Vec<Region>), PuzzleError> {
     let mut shapes = HashMap::new();
 
-    let mut lines = input.split("\n\n").collect::<Vec<&str>>();
-    let regions_string = lines.pop().unwrap();
-    for line in lines.into_iter() {
-        let shape_lines = line.trim().lines().collect::<Vec<&str>>();
-        let id = shape_lines[0]
-            .trim_end_matches(':')
-            .parse::<usize>()
-            .unwrap();
-        let shape = shape_lines[1..]
-            .iter()
-            .map(|&l| {
-                l.chars()
-                    .map(|c| match c {
-                        '#' => true,
-                        '.' => false,
-                        _ => panic!("Unexpected character in shape"),
-                    })
-                    .collect::<Vec<bool>>()
-            })
-            .collect::<Vec<Vec<bool>>>();
-        shapes.insert(id, shape);
-    }
-
-    let regions = regions_string
-        .lines()
-        .map(|region_line| {
-            let (dimensions_string, shape_counts_string) =
-                region_line.split_once(':').unwrap();
-            let dimensions: Vec<usize> = dimensions_string
-                .split('x')
-                .map(|d| d.parse::<usize>().unwrap())
-                .collect();
-            let shape_counts: Vec<usize> = shape_counts_string
-                .trim()
-                .split_whitespace()
-                .map(|count| count.parse::<usize>().unwrap())
-                .collect();
+    let mut blocks = parsing::blocks(input);
+    let Some(regions_block) = blocks.pop() else {
+        return Err(PuzzleError::new(DAY, "expected shapes and regions"));
+    };
+    for block in blocks {
+        let (id, shape) =
+            parse_shape(&block).map_err(PuzzleError::on_day(DAY))?;
+        shapes.insert(id, Shape::new(&shape));
+    }
 
-            Region {
-                width: dimensions[0],
-                height: dimensions[1],
-                shape_counts,
-            }
-        })
-        .collect::<Vec<Region>>();
+    let mut shapes: Vec<(usize, Shape)> = shapes.into_iter().collect();
+    shapes.sort_unstable_by_key(|&(id, _)| id);
+    if let Some(index) =
+        (0..shapes.len()).find(|&index| shapes[index].0 != index)
+    {
+        return Err(PuzzleError::new(DAY, format!("expected a shape {index}")));
+    }
+
+    let regions = regions_block
+        .iter()
+        .map(|line| parse_region(line, shapes.len()))
+        .collect::<parsing::Result<Vec<Region>>>()
+        .map_err(PuzzleError::on_day(DAY))?;
+
+    Ok((
+        shapes.into_iter().map(|(_id, shape)| shape).collect(),
+        regions,
+    ))
+}
+
+fn parse_shape(
+    block: &[SourceLine<'_>],
+) -> parsing::Result<(usize, Vec<Vec<bool>>)> {
+    let header = block[0].trim();
+    let id = header.parse(header.fields("{}:")?[0])?;
+    let mut shape = vec![];
+
+    for line in &block[1..] {
+        let mut row = vec![];
+        for (index, c) in line.text.char_indices() {
+            row.push(match c {
+                '#' => true,
+                '.' => false,
+                _ => {
+                    return Err(
+                        line.error(&line.text[index..], "expected `#` or `.`")
+                    );
+                }
+            });
+        }
+        shape.push(row);
+    }
+
+    // Shapes are anchored on their first filled cell.
+    if !shape.iter().flatten().any(|&filled| filled) {
+        return Err(header.error(header.text, "expected a filled cell `#`"));
+    }
+
+    Ok((id, shape))
+}
+
+fn parse_region(
+    line: &SourceLine<'_>,
+    shapes: usize,
+) -> parsing::Result<Region> {
+    let fields = line.fields("{}x{}:{}")?;
+    let shape_counts: Vec<usize> = fields[2]
+        .split_whitespace()
+        .map(|count| line.parse(count))
+        .collect::<parsing::Result<_>>()?;
+    if shape_counts.len() != shapes {
+        return Err(line.error(fields[2], format!("expected {shapes} counts")));
+    }
 
-    (shapes, regions)
+    Ok(Region {
+        width: line.parse(fields[0])?,
+        height: line.parse(fields[1])?,
+        shape_counts,
+    })
 }
 
 #[cfg(test)]
@@ -126,4 +456,82 @@ mod tests {
     fn test_part1_with_example_input() {
         assert_eq!(part1(EXAMPLE_INPUT), 2);
     }
+
+    fn assert_valid(
+        region: &Region,
+        shapes: &[Shape],
+        placements: &[Placement],
+    ) {
+        let mut counts = vec![0; shapes.len()];
+        let mut cells = BTreeSet::new();
+
+        for placement in placements {
+            counts[placement.shape] += 1;
+            assert_eq!(placement.cells.len(), shapes[placement.shape].area());
+            for &(x, y) in &placement.cells {
+                assert!(x < region.width && y < region.height);
+                assert!(cells.insert((x, y)));
+            }
+        }
+        assert_eq!(counts, region.shape_counts);
+    }
+
+    #[test]
+    fn test_packings_are_valid() {
+        let (shapes, regions) = parse(EXAMPLE_INPUT).unwrap();
+
+        for region in &regions[..2] {
+            assert_valid(region, &shapes, &region.pack(&shapes).unwrap());
+        }
+
+        let placements = regions[0].pack(&shapes).unwrap();
+        assert_eq!(render(&regions[0], &placements).matches('.').count(), 2);
+    }
+
+    #[test]
+    fn test_area_heuristic_is_unsound_for_example() {
+        let (shapes, regions) = parse(EXAMPLE_INPUT).unwrap();
+        let exact: Vec<bool> = regions
+            .iter()
+            .map(|region| region.pack(&shapes).is_some())
+            .collect();
+        let heuristic: Vec<bool> = regions.iter().map(fits_by_area).collect();
+
+        assert_eq!(exact, vec![true, true, false]);
+        assert_eq!(heuristic, vec![false, true, false]);
+    }
+
+    #[test]
+    fn test_slots_need_no_search() {
+        let (shapes, _regions) = parse(EXAMPLE_INPUT).unwrap();
+        let region = Region {
+            width: 40,
+            height: 40,
+            shape_counts: vec![30, 30, 30, 30, 30, 19],
+        };
+
+        assert_valid(&region, &shapes, &region.pack(&shapes).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = |input| try_part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error("0:\n#\n\n1:\n..\n..\n\n2x2: 1 1\n"),
+            "day 12, line 4: column 1, expected a filled cell `#`"
+        );
+        assert_eq!(
+            error("0:\n#\n\n1:\n#x\n\n2x2: 1 1\n"),
+            "day 12, line 5: column 2, expected `#` or `.`"
+        );
+        assert_eq!(
+            error("0:\n#\n\n2:\n#\n\n2x2: 1 1\n"),
+            "day 12: expected a shape 1"
+        );
+        assert_eq!(
+            error("0:\n#\n\n1:\n#\n\n2x2: 1\n"),
+            "day 12, line 7: column 5, expected 2 counts"
+        );
+    }
 }
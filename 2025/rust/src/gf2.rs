@@ -0,0 +1,340 @@
+use std::ops::BitXorAssign;
+
+const WORD: usize = u64::BITS as usize;
+
+// A fixed-length vector over GF(2), packed 64 bits to a word.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVector {
+    #[must_use]
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    #[must_use]
+    pub fn from_ones<I>(len: usize, ones: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut vector = Self::zeros(len);
+        for index in ones {
+            vector.flip(index);
+        }
+        vector
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit {index} out of range");
+        self.words[index / WORD] >> (index % WORD) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        if self.get(index) != value {
+            self.flip(index);
+        }
+    }
+
+    pub fn flip(&mut self, index: usize) {
+        assert!(index < self.len, "bit {index} out of range");
+        self.words[index / WORD] ^= 1 << (index % WORD);
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    // The Hamming weight, i.e. how many entries are 1.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&index| self.get(index))
+    }
+}
+
+impl BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &BitVector) {
+        assert_eq!(self.len, other.len, "length mismatch");
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+}
+
+// A dense matrix over GF(2), stored as rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: Vec<BitVector>,
+    columns: usize,
+}
+
+impl BitMatrix {
+    #[must_use]
+    pub fn zeros(rows: usize, columns: usize) -> Self {
+        Self {
+            rows: vec![BitVector::zeros(columns); rows],
+            columns,
+        }
+    }
+
+    // Builds a matrix column by column, each given by the rows where it is
+    // 1. This is the natural shape for toggle puzzles: one column per
+    // button, listing the lights it flips.
+    #[must_use]
+    pub fn from_columns<C>(rows: usize, columns: &[C]) -> Self
+    where
+        C: AsRef<[usize]>,
+    {
+        let mut matrix = Self::zeros(rows, columns.len());
+        for (column, ones) in columns.iter().enumerate() {
+            for &row in ones.as_ref() {
+                matrix.rows[row].flip(column);
+            }
+        }
+        matrix
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[must_use]
+    pub fn get(&self, row: usize, column: usize) -> bool {
+        self.rows[row].get(column)
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, value: bool) {
+        self.rows[row].set(column, value);
+    }
+
+    #[must_use]
+    pub fn mul_vector(&self, vector: &BitVector) -> BitVector {
+        assert_eq!(vector.len(), self.columns, "length mismatch");
+        BitVector::from_ones(
+            self.rows(),
+            self.rows.iter().enumerate().filter_map(|(index, row)| {
+                let mut product = row.clone();
+                for (word, other) in product.words.iter_mut().zip(&vector.words)
+                {
+                    *word &= other;
+                }
+                (product.count_ones() % 2 == 1).then_some(index)
+            }),
+        )
+    }
+
+    // Gaussian elimination on `self * x = target`. Returns `None` when the
+    // system is inconsistent, otherwise every solution as a particular one
+    // plus the span of a null space basis.
+    #[must_use]
+    pub fn solve(&self, target: &BitVector) -> Option<Solutions> {
+        assert_eq!(target.len(), self.rows(), "length mismatch");
+        let mut rows = self.rows.clone();
+        let mut target = target.clone();
+        let mut pivots = vec![];
+
+        for column in 0..self.columns {
+            let rank = pivots.len();
+            let Some(pivot) = (rank..rows.len()).find(|&r| rows[r].get(column))
+            else {
+                continue;
+            };
+            rows.swap(rank, pivot);
+            let (pivot_value, rank_value) =
+                (target.get(pivot), target.get(rank));
+            target.set(rank, pivot_value);
+            target.set(pivot, rank_value);
+
+            let pivot_row = rows[rank].clone();
+            for (index, row) in rows.iter_mut().enumerate() {
+                if index != rank && row.get(column) {
+                    *row ^= &pivot_row;
+                    if pivot_value {
+                        target.flip(index);
+                    }
+                }
+            }
+
+            pivots.push(column);
+        }
+
+        if (pivots.len()..rows.len()).any(|row| target.get(row)) {
+            return None;
+        }
+
+        let mut particular = BitVector::zeros(self.columns);
+        for (row, &column) in pivots.iter().enumerate() {
+            particular.set(column, target.get(row));
+        }
+
+        let basis = (0..self.columns)
+            .filter(|column| !pivots.contains(column))
+            .map(|free| {
+                let mut vector = BitVector::zeros(self.columns);
+                vector.flip(free);
+                for (row, &column) in pivots.iter().enumerate() {
+                    vector.set(column, rows[row].get(free));
+                }
+                vector
+            })
+            .collect();
+
+        Some(Solutions { particular, basis })
+    }
+}
+
+// The affine space of solutions to a linear system over GF(2).
+#[derive(Clone, Debug)]
+pub struct Solutions {
+    pub particular: BitVector,
+    pub basis: Vec<BitVector>,
+}
+
+impl Solutions {
+    // How many solutions there are is 2 to this power.
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.basis.len()
+    }
+
+    // Visits every solution in Gray code order, so each step costs a single
+    // XOR with one basis vector.
+    pub fn for_each<F>(&self, mut visit: F)
+    where
+        F: FnMut(&BitVector),
+    {
+        assert!(self.dimension() < WORD, "too many solutions to enumerate");
+        let mut current = self.particular.clone();
+        visit(&current);
+
+        for step in 1..1u64 << self.dimension() {
+            current ^= &self.basis[step.trailing_zeros() as usize];
+            visit(&current);
+        }
+    }
+
+    // A solution with the fewest 1s, e.g. the fewest button presses.
+    #[must_use]
+    pub fn minimum_weight(&self) -> BitVector {
+        let mut best = self.particular.clone();
+        let mut best_weight = best.count_ones();
+
+        self.for_each(|solution| {
+            let weight = solution.count_ones();
+            if weight < best_weight {
+                best_weight = weight;
+                best.clone_from(solution);
+            }
+        });
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+
+    fn random_matrix(rng: &mut Rng, rows: usize, columns: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::zeros(rows, columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                matrix.set(row, column, rng.below(3) == 0);
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn vector_operations() {
+        let mut vector = BitVector::from_ones(70, [0, 3, 64, 69]);
+        vector ^= &BitVector::from_ones(70, [3, 65]);
+
+        assert_eq!(vector.ones().collect::<Vec<_>>(), vec![0, 64, 65, 69]);
+        assert_eq!(vector.count_ones(), 4);
+        assert!(!vector.is_zero());
+        assert!(BitVector::zeros(70).is_zero());
+    }
+
+    #[test]
+    fn solves_small_systems_exhaustively() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..200 {
+            let rows = 1 + rng.below(6);
+            let columns = 1 + rng.below(8);
+            let matrix = random_matrix(&mut rng, rows, columns);
+            let target = BitVector::from_ones(
+                rows,
+                (0..rows).filter(|_| rng.below(2) == 0),
+            );
+
+            let all: Vec<BitVector> = (0..1usize << columns)
+                .map(|mask| {
+                    BitVector::from_ones(
+                        columns,
+                        (0..columns).filter(|bit| mask >> bit & 1 == 1),
+                    )
+                })
+                .filter(|x| matrix.mul_vector(x) == target)
+                .collect();
+
+            match matrix.solve(&target) {
+                None => assert!(all.is_empty()),
+                Some(solutions) => {
+                    let mut found = vec![];
+                    solutions.for_each(|x| found.push(x.clone()));
+                    assert_eq!(found.len(), all.len());
+                    assert!(found.iter().all(|x| all.contains(x)));
+
+                    let fewest = all.iter().map(BitVector::count_ones).min();
+                    let best = solutions.minimum_weight();
+                    assert_eq!(Some(best.count_ones()), fewest);
+                    assert_eq!(matrix.mul_vector(&best), target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn many_columns() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let matrix = random_matrix(&mut rng, 12, 32);
+        let target = matrix.mul_vector(&BitVector::from_ones(32, [1, 7, 30]));
+        let best = matrix.solve(&target).unwrap().minimum_weight();
+
+        assert!(best.count_ones() <= 3);
+        assert_eq!(matrix.mul_vector(&best), target);
+    }
+}
@@ -13,3 +13,9 @@ pub mod day09;
 pub mod day10;
 pub mod day11;
 pub mod day12;
+pub mod gf2;
+pub mod parsing;
+pub mod puzzle;
+
+#[cfg(test)]
+mod testing;
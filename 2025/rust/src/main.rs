@@ -1,3 +1,4 @@
+use advent_of_code::puzzle::PuzzleError;
 use std::env;
 use std::fs;
 use std::process;
@@ -9,8 +10,8 @@ macro_rules! days {
                 $(
                     $day => {
                         use advent_of_code::$mod;
-                        println!("Part 1: {}", $mod::part1(input));
-                        println!("Part 2: {}", $mod::part2(input));
+                        println!("Part 1: {}", solve($mod::try_part1(input)));
+                        println!("Part 2: {}", solve($mod::try_part2(input)));
                     }
                 )*
                 _ => {
@@ -22,6 +23,15 @@ macro_rules! days {
     };
 }
 
+// Exits with the error rather than a panic when the input is truncated or
+// belongs to another day.
+fn solve<T>(answer: Result<T, PuzzleError>) -> T {
+    answer.unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    })
+}
+
 days!(
     1 => day01,
     2 => day02,